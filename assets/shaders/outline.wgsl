@@ -0,0 +1,34 @@
+#import bevy_pbr::mesh_view_bindings
+#import bevy_pbr::mesh_bindings
+#import bevy_pbr::mesh_functions
+
+struct OutlineMaterial {
+    color: vec4<f32>,
+    width: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: OutlineMaterial;
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+// Pushes the back faces out along their normals so they show as a shell around the mesh
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    let position = vertex.position + normalize(vertex.normal) * material.width;
+    out.clip_position = mesh_position_local_to_clip(mesh.model, vec4<f32>(position, 1.0));
+    return out;
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return material.color;
+}
@@ -0,0 +1,36 @@
+#import bevy_pbr::mesh_view_bindings
+
+struct SkyMaterial {
+    zenith: vec4<f32>,
+    horizon: vec4<f32>,
+    ground: vec4<f32>,
+    sun_color: vec4<f32>,
+    tint: vec4<f32>,
+    sun_direction: vec3<f32>,
+    sun_size: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: SkyMaterial;
+
+struct FragmentInput {
+    @builtin(front_facing) is_front: bool,
+    @builtin(position) frag_coord: vec4<f32>,
+    #import bevy_pbr::mesh_vertex_output
+};
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    let direction = normalize(in.world_position.xyz - view.world_position.xyz);
+    var rgb = mix(material.horizon.rgb, material.ground.rgb, sqrt(max(-direction.y, 0.0)));
+    if (direction.y > 0.0) {
+        rgb = mix(material.horizon.rgb, material.zenith.rgb, sqrt(direction.y));
+    }
+
+    // A hard edged disc for the sun with a soft glow spreading out from it
+    let facing = dot(direction, normalize(material.sun_direction));
+    let disc = smoothstep(cos(material.sun_size), cos(material.sun_size * 0.8), facing);
+    let glow = pow(max(facing, 0.0), 64.0) * 0.5;
+    rgb = rgb * material.tint.rgb + material.sun_color.rgb * (disc + glow);
+    return vec4<f32>(rgb, 1.0);
+}
@@ -0,0 +1,25 @@
+#import bevy_pbr::mesh_view_bindings
+
+struct SkyCubemapMaterial {
+    tint: vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> material: SkyCubemapMaterial;
+@group(1) @binding(1)
+var sky_texture: texture_cube<f32>;
+@group(1) @binding(2)
+var sky_sampler: sampler;
+
+struct FragmentInput {
+    @builtin(front_facing) is_front: bool,
+    @builtin(position) frag_coord: vec4<f32>,
+    #import bevy_pbr::mesh_vertex_output
+};
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    let direction = normalize(in.world_position.xyz - view.world_position.xyz);
+    let sky = textureSample(sky_texture, sky_sampler, direction);
+    return vec4<f32>(sky.rgb * material.tint.rgb, 1.0);
+}
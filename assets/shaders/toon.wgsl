@@ -0,0 +1,38 @@
+#import bevy_pbr::mesh_view_bindings
+
+struct ToonMaterial {
+    color: vec4<f32>,
+    shadow_color: vec4<f32>,
+    bands: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: ToonMaterial;
+
+struct FragmentInput {
+    @builtin(front_facing) is_front: bool,
+    @builtin(position) frag_coord: vec4<f32>,
+    #import bevy_pbr::mesh_vertex_output
+};
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    let normal = normalize(in.world_normal);
+    var light = 0.0;
+    var light_color = vec3<f32>(1.0);
+    if (lights.n_directional_lights > 0u) {
+        let sun = lights.directional_lights[0];
+        light = max(dot(normal, sun.direction_to_light), 0.0);
+        light_color = sun.color.rgb;
+    }
+
+    // Snap the diffuse term to a few flat bands, then a thin rim to pick the shape out
+    let banded = floor(light * material.bands + 0.5) / material.bands;
+    let view_direction = normalize(view.world_position.xyz - in.world_position.xyz);
+    let rim = smoothstep(0.6, 0.75, 1.0 - max(dot(normal, view_direction), 0.0)) * banded;
+
+    let lit = mix(material.shadow_color.rgb, material.color.rgb, banded);
+    let ambient = lights.ambient_color.rgb * material.color.rgb * 0.3;
+    let rgb = lit * min(light_color, vec3<f32>(1.0)) + ambient + vec3<f32>(rim * 0.25);
+    return vec4<f32>(rgb, material.color.a);
+}
@@ -0,0 +1,258 @@
+use bevy::{audio::AudioSinkPlayback, prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+
+use crate::{CeilingBonk, Footstep, MainCamera, SurfaceType};
+
+/// Distance between the listener's ears, how strongly sounds pan from side to side
+const EAR_GAP: f32 = 0.4;
+/// Volume left on a sound with a wall between it and the camera
+const OCCLUDED_VOLUME: f32 = 0.3;
+/// How quickly a sound muffles or clears as walls come between it and the camera, per second
+const OCCLUSION_SPEED: f32 = 4.0;
+/// Walls this close to the emitter don't count, so a sound sitting on the floor isn't blocked by it
+const OCCLUSION_MARGIN: f32 = 0.5;
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AudioUnlocked::default())
+            .insert_resource(EmitterSinks::default())
+            .add_startup_system(load_sound_effects)
+            .add_system(play_footsteps)
+            .add_system(play_bonks)
+            .add_system(update_sound_emitters);
+    }
+}
+
+/// Browsers keep audio silent until the page is first clicked or typed on. Sounds played before
+/// then would all burst out at once, so they're skipped instead
+#[derive(Resource, Debug)]
+pub struct AudioUnlocked(pub bool);
+
+impl Default for AudioUnlocked {
+    fn default() -> Self {
+        AudioUnlocked(!cfg!(target_arch = "wasm32"))
+    }
+}
+
+#[derive(Resource)]
+pub struct SoundEffects {
+    pub footstep_grass: Handle<AudioSource>,
+    pub footstep_ice: Handle<AudioSource>,
+    pub footstep_sand: Handle<AudioSource>,
+    pub footstep_metal: Handle<AudioSource>,
+    pub bonk: Handle<AudioSource>,
+    pub water: Handle<AudioSource>,
+    pub wind: Handle<AudioSource>,
+    pub machinery: Handle<AudioSource>,
+}
+
+impl SoundEffects {
+    pub fn footstep(&self, surface: SurfaceType) -> Handle<AudioSource> {
+        use SurfaceType::*;
+        match surface {
+            Grass => self.footstep_grass.clone(),
+            Ice => self.footstep_ice.clone(),
+            Sand => self.footstep_sand.clone(),
+            Metal => self.footstep_metal.clone(),
+        }
+    }
+
+    pub fn ambient(&self, sound: AmbientSound) -> Handle<AudioSource> {
+        match sound {
+            AmbientSound::Water => self.water.clone(),
+            AmbientSound::Wind => self.wind.clone(),
+            AmbientSound::Machinery => self.machinery.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AmbientSound {
+    Water,
+    Wind,
+    Machinery,
+}
+
+impl AmbientSound {
+    pub fn volume(&self) -> f32 {
+        match self {
+            AmbientSound::Water => 0.5,
+            AmbientSound::Wind => 0.4,
+            AmbientSound::Machinery => 0.35,
+        }
+    }
+
+    /// How far away it can still be heard
+    pub fn range(&self) -> f32 {
+        match self {
+            AmbientSound::Water => 18.0,
+            AmbientSound::Wind => 14.0,
+            AmbientSound::Machinery => 12.0,
+        }
+    }
+}
+
+/// Loops `sound` from where it is in the world, quieter with distance and with walls in the way
+#[derive(Component)]
+pub struct SoundEmitter {
+    pub sound: AmbientSound,
+    /// 0 when the camera can hear it clearly, 1 when it's fully behind a wall
+    pub occlusion: f32,
+}
+
+impl SoundEmitter {
+    pub fn new(sound: AmbientSound) -> Self {
+        SoundEmitter {
+            sound,
+            occlusion: 0.0,
+        }
+    }
+}
+
+/// The looping sink playing each emitter, stopped once the emitter is gone
+#[derive(Resource, Default)]
+pub struct EmitterSinks(HashMap<Entity, Handle<SpatialAudioSink>>);
+
+/// Fades linearly to nothing at the edge of `range`, then down to `OCCLUDED_VOLUME` of that as
+/// `occlusion` goes from 0 to 1
+pub fn emitter_volume(base: f32, distance: f32, range: f32, occlusion: f32) -> f32 {
+    let falloff = (1.0 - distance / range).clamp(0.0, 1.0);
+    base * falloff * (1.0 - occlusion * (1.0 - OCCLUDED_VOLUME))
+}
+
+fn load_sound_effects(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundEffects {
+        footstep_grass: asset_server.load("audio/footstep_grass.wav"),
+        footstep_ice: asset_server.load("audio/footstep_ice.wav"),
+        footstep_sand: asset_server.load("audio/footstep_sand.wav"),
+        footstep_metal: asset_server.load("audio/footstep_metal.wav"),
+        bonk: asset_server.load("audio/bonk.wav"),
+        water: asset_server.load("audio/water_loop.wav"),
+        wind: asset_server.load("audio/wind_loop.wav"),
+        machinery: asset_server.load("audio/machinery_loop.wav"),
+    });
+}
+
+fn play_footsteps(
+    audio: Res<Audio>,
+    unlocked: Res<AudioUnlocked>,
+    sound_effects: Res<SoundEffects>,
+    mut footsteps: EventReader<Footstep>,
+) {
+    for footstep in footsteps.iter() {
+        if !unlocked.0 {
+            continue;
+        }
+        audio.play_with_settings(
+            sound_effects.footstep(footstep.surface),
+            PlaybackSettings::ONCE.with_volume(0.4),
+        );
+    }
+}
+
+fn play_bonks(
+    audio: Res<Audio>,
+    unlocked: Res<AudioUnlocked>,
+    sound_effects: Res<SoundEffects>,
+    mut bonks: EventReader<CeilingBonk>,
+) {
+    for bonk in bonks.iter() {
+        if !unlocked.0 {
+            continue;
+        }
+        audio.play_with_settings(
+            sound_effects.bonk.clone(),
+            PlaybackSettings::ONCE.with_volume((bonk.speed / 20.0).clamp(0.3, 0.8)),
+        );
+    }
+}
+
+/// Starts a loop for each new emitter and keeps its position, distance falloff and occlusion up
+/// to date with the camera. bevy_audio can't filter a sink once it's playing, so a blocked sound
+/// is muffled by turning it down rather than low-passing it
+fn update_sound_emitters(
+    audio: Res<Audio>,
+    unlocked: Res<AudioUnlocked>,
+    time: Res<Time>,
+    sound_effects: Res<SoundEffects>,
+    rapier_context: Res<RapierContext>,
+    audio_sinks: Res<Assets<SpatialAudioSink>>,
+    mut sinks: ResMut<EmitterSinks>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut emitter_query: Query<(Entity, &GlobalTransform, &mut SoundEmitter)>,
+) {
+    sinks.0.retain(|entity, sink| {
+        let alive = emitter_query.contains(*entity);
+        if !alive {
+            if let Some(sink) = audio_sinks.get(sink) {
+                sink.stop();
+            }
+        }
+        alive
+    });
+    if !unlocked.0 {
+        return;
+    }
+    let Ok(camera_transform) = camera_query.get_single() else {return;};
+    let listener = camera_transform.compute_transform();
+
+    for (entity, transform, mut emitter) in &mut emitter_query {
+        let position = transform.translation();
+        let to_emitter = position - listener.translation;
+        let distance = to_emitter.length();
+
+        // Only level geometry blocks sound, not the player, items or trigger volumes
+        let blocked = distance > OCCLUSION_MARGIN
+            && rapier_context
+                .cast_ray(
+                    listener.translation,
+                    to_emitter / distance,
+                    distance - OCCLUSION_MARGIN,
+                    true,
+                    QueryFilter::only_fixed()
+                        .exclude_sensors()
+                        .exclude_collider(entity),
+                )
+                .is_some();
+        let target = if blocked { 1.0 } else { 0.0 };
+        let step = OCCLUSION_SPEED * time.delta_seconds();
+        emitter.occlusion += (target - emitter.occlusion).clamp(-step, step);
+
+        let volume = emitter_volume(
+            emitter.sound.volume(),
+            distance,
+            emitter.sound.range(),
+            emitter.occlusion,
+        );
+        let handle = sinks.0.entry(entity).or_insert_with(|| {
+            let sink = audio.play_spatial_with_settings(
+                sound_effects.ambient(emitter.sound),
+                PlaybackSettings::LOOP.with_volume(volume),
+                listener,
+                EAR_GAP,
+                position,
+            );
+            audio_sinks.get_handle(sink)
+        });
+        // The sink only exists once the audio output has picked the new sound up
+        let Some(sink) = audio_sinks.get(handle) else {continue;};
+        sink.set_listener_position(listener, EAR_GAP);
+        sink.set_emitter_position(position);
+        sink.set_volume(volume);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn emitters_fade_with_distance_and_walls() {
+        assert_eq!(emitter_volume(0.5, 0.0, 10.0, 0.0), 0.5);
+        assert_eq!(emitter_volume(0.5, 5.0, 10.0, 0.0), 0.25);
+        assert_eq!(emitter_volume(0.5, 20.0, 10.0, 0.0), 0.0);
+        assert!((emitter_volume(1.0, 0.0, 10.0, 1.0) - OCCLUDED_VOLUME).abs() < 1e-6);
+    }
+}
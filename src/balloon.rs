@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    Creation, CreationType, HeldItem, Player, PlayerAction, PlayerWallSensor, RespawnPlayer, Wall,
+};
+
+const BALLOON_RADIUS: f32 = 0.6;
+const BALLOON_REACH: f32 = 1.8;
+const BALLOON_HELD_HEIGHT: f32 = 2.6;
+const BALLOON_BOB_SPEED: f32 = 2.0;
+const BALLOON_BOB_HEIGHT: f32 = 0.15;
+const SLOW_FALL_GRAVITY: f32 = 0.25;
+const SLOW_FALL_SECONDS: f32 = 6.0;
+const BUOYANCY_DAMPING: f32 = 2.5;
+
+pub struct BalloonPlugin;
+
+impl Plugin for BalloonPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((bob_balloons, grab_balloons, pop_balloons).chain())
+            .add_system(float_buoyant);
+    }
+}
+
+/// A lone balloon bobbing in place, grabbing it lets the player drift down slowly
+#[derive(Component)]
+pub struct Balloon {
+    pub origin: Vec3,
+}
+
+/// The player is hanging on to `balloon`, which pops when the timer runs out or they hit a wall
+#[derive(Component)]
+pub struct SlowFalling {
+    pub balloon: Entity,
+    pub timer: Timer,
+}
+
+impl SlowFalling {
+    pub fn new(balloon: Entity) -> Self {
+        SlowFalling {
+            balloon,
+            timer: Timer::from_seconds(SLOW_FALL_SECONDS, TimerMode::Once),
+        }
+    }
+
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        self.timer.tick(delta);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.timer.finished()
+    }
+}
+
+/// Pushes a body up whenever it sinks below `float_height`, harder the deeper it goes
+#[derive(Component)]
+pub struct Buoyant {
+    pub float_height: f32,
+    pub strength: f32,
+}
+
+pub fn spawn_balloon(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::UVSphere {
+                radius: BALLOON_RADIUS,
+                ..default()
+            })),
+            material: materials.add(Color::PINK.into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(Balloon { origin: position })
+        .id()
+}
+
+/// The creation made from a cube and a balloon, a raft that floats at the height it was made
+pub fn spawn_floating_platform(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(3.0, 0.4, 3.0))),
+            material: materials.add(Color::PINK.into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::cuboid(1.5, 0.2, 1.5))
+        .insert(LockedAxes::ROTATION_LOCKED)
+        .insert(Velocity::default())
+        .insert(Buoyant {
+            float_height: position.y,
+            strength: 30.0,
+        })
+        .insert(CreationType::FloatingPlatform)
+        .insert(Creation)
+        .id()
+}
+
+pub fn bob_balloons(time: Res<Time>, mut balloon_query: Query<(&mut Transform, &Balloon)>) {
+    let bob = (time.elapsed_seconds() * BALLOON_BOB_SPEED).sin() * BALLOON_BOB_HEIGHT;
+    for (mut transform, balloon) in &mut balloon_query {
+        transform.translation = balloon.origin + Vec3::Y * bob;
+    }
+}
+
+pub fn grab_balloons(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut GravityScale,
+            &ActionState<PlayerAction>,
+        ),
+        (With<Player>, Without<HeldItem>, Without<SlowFalling>),
+    >,
+    balloon_query: Query<(Entity, &Transform), (With<Balloon>, Without<Player>)>,
+) {
+    let Ok(player) = player_query.get_single_mut() else {return;};
+    let (player_entity, player_transform, mut gravity_scale, action) = player;
+    if !action.just_pressed(PlayerAction::Grab) {
+        return;
+    }
+
+    for (balloon_entity, balloon_transform) in &balloon_query {
+        let reach = player_transform.translation + Vec3::Y * 0.5;
+        if reach.distance(balloon_transform.translation) > BALLOON_REACH {
+            continue;
+        }
+
+        gravity_scale.0 = SLOW_FALL_GRAVITY;
+        commands
+            .entity(balloon_entity)
+            .remove::<Balloon>()
+            .insert(Transform::from_xyz(0.0, BALLOON_HELD_HEIGHT, 0.0));
+        commands
+            .entity(player_entity)
+            .add_child(balloon_entity)
+            .insert(SlowFalling::new(balloon_entity));
+        break;
+    }
+}
+
+/// Whether `event` is the player's wall sensor starting to touch a wall
+pub fn sensor_touched_wall(
+    event: &CollisionEvent,
+    sensor: Entity,
+    is_wall: impl Fn(Entity) -> bool,
+) -> bool {
+    match event {
+        CollisionEvent::Started(e1, e2, _) => {
+            (*e1 == sensor && is_wall(*e2)) || (*e2 == sensor && is_wall(*e1))
+        }
+        CollisionEvent::Stopped(..) => false,
+    }
+}
+
+pub fn pop_balloons(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut respawn_events: EventReader<RespawnPlayer>,
+    mut player_query: Query<(Entity, &mut GravityScale, &mut SlowFalling), With<Player>>,
+    wall_sensor_query: Query<Entity, With<PlayerWallSensor>>,
+    wall_query: Query<(), With<Wall>>,
+) {
+    let Ok((player_entity, mut gravity_scale, mut slow_falling)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+    slow_falling.tick(time.delta());
+
+    // The player's body doesn't report collisions, its wall sensor does
+    let hit_wall = wall_sensor_query.get_single().map_or(false, |sensor| {
+        collision_events
+            .iter()
+            .any(|event| sensor_touched_wall(event, sensor, |entity| wall_query.contains(entity)))
+    });
+    let respawned = respawn_events.iter().count() > 0;
+
+    if slow_falling.finished() || hit_wall || respawned {
+        gravity_scale.0 = 1.0;
+        commands.entity(slow_falling.balloon).despawn_recursive();
+        commands.entity(player_entity).remove::<SlowFalling>();
+    }
+}
+
+pub fn float_buoyant(time: Res<Time>, mut query: Query<(&Transform, &mut Velocity, &Buoyant)>) {
+    for (transform, mut velocity, buoyant) in &mut query {
+        let depth = (buoyant.float_height - transform.translation.y).clamp(0.0, 1.0);
+        if depth <= 0.0 {
+            continue;
+        }
+        let lift = depth * buoyant.strength - velocity.linvel.y * BUOYANCY_DAMPING;
+        velocity.linvel.y += lift * time.delta_seconds();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_the_wall_sensor_touching_a_wall_pops_the_balloon() {
+        let sensor = Entity::from_raw(1);
+        let wall = Entity::from_raw(2);
+        let crate_entity = Entity::from_raw(3);
+        let is_wall = |entity: Entity| entity == wall;
+        let flags = CollisionEventFlags::SENSOR;
+
+        assert!(sensor_touched_wall(
+            &CollisionEvent::Started(sensor, wall, flags),
+            sensor,
+            is_wall
+        ));
+        assert!(sensor_touched_wall(
+            &CollisionEvent::Started(wall, sensor, flags),
+            sensor,
+            is_wall
+        ));
+        assert!(!sensor_touched_wall(
+            &CollisionEvent::Started(sensor, crate_entity, flags),
+            sensor,
+            is_wall
+        ));
+        assert!(!sensor_touched_wall(
+            &CollisionEvent::Stopped(sensor, wall, flags),
+            sensor,
+            is_wall
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    spawn_cart, spawn_chain, spawn_floating_platform, AddConsoleCommand, Cart, Creation,
+    CreationBuilt, CreationType, Idea, IdeaChanged, Player, PlayerIdeas,
+};
+
+/// How far in front of the player a new creation appears
+const BUILD_DISTANCE: f32 = 3.0;
+const BUILD_HEIGHT: f32 = 1.0;
+const HANGING_ROPE_LINKS: usize = 10;
+
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BuildCreation>()
+            .add_system(build_creations)
+            .add_console_command("build", "build <creation>", build_command);
+    }
+}
+
+/// Asks for a creation to be made in front of the player
+pub struct BuildCreation(pub CreationType);
+
+/// The ideas that went into a creation, given back a while after it breaks
+#[derive(Component, Default)]
+pub struct SpentIdeas(pub Vec<Idea>);
+
+pub fn spawn_creation(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    creation: CreationType,
+    position: Vec3,
+) -> Entity {
+    let (mesh, collider, color) = match creation {
+        CreationType::FloatingPlatform => {
+            return spawn_floating_platform(commands, meshes, materials, position);
+        }
+        CreationType::Cart => {
+            return spawn_cart(commands, meshes, materials, position);
+        }
+        // Without anchors picked, a rope hangs from a peg where it was made
+        CreationType::Rope => {
+            let rope = spawn_chain(commands, meshes, materials, position, HANGING_ROPE_LINKS);
+            commands.entity(rope).insert(creation).insert(Creation);
+            return rope;
+        }
+        CreationType::Crate => (
+            Mesh::from(shape::Box::new(2.0, 2.0, 2.0)),
+            Collider::cuboid(1.0, 1.0, 1.0),
+            Color::BEIGE,
+        ),
+        CreationType::Launcher => (
+            Mesh::from(shape::Box::new(2.0, 0.6, 2.0)),
+            Collider::cuboid(1.0, 0.3, 1.0),
+            Color::LIME_GREEN,
+        ),
+        CreationType::PogoStick => (
+            Mesh::from(shape::Capsule {
+                radius: 0.25,
+                depth: 1.2,
+                ..default()
+            }),
+            Collider::capsule_y(0.6, 0.25),
+            Color::ORANGE,
+        ),
+    };
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(mesh),
+            material: materials.add(color.into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(collider)
+        .insert(Velocity::default())
+        .insert(creation)
+        .insert(Creation)
+        .id()
+}
+
+/// Removes a creation along with any parts that aren't its children
+pub fn despawn_creation(commands: &mut Commands, entity: Entity, cart: Option<&Cart>) {
+    // A cart's wheels are bodies of their own rather than children
+    for wheel in cart.iter().flat_map(|cart| &cart.wheels) {
+        commands.entity(*wheel).despawn_recursive();
+    }
+    commands.entity(entity).despawn_recursive();
+}
+
+fn build_creations(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut player_ideas: ResMut<PlayerIdeas>,
+    mut build_events: EventReader<BuildCreation>,
+    mut built_events: EventWriter<CreationBuilt>,
+    mut idea_events: EventWriter<IdeaChanged>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {return;};
+    for BuildCreation(creation) in build_events.iter() {
+        // Ropes wait for their anchors to be picked first
+        if *creation == CreationType::Rope {
+            continue;
+        }
+        let position = player_transform.translation
+            + player_transform.forward() * BUILD_DISTANCE
+            + Vec3::Y * BUILD_HEIGHT;
+        let entity = spawn_creation(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            *creation,
+            position,
+        );
+        // Console builds with nothing loaded are free
+        let spent = player_ideas.spend_loaded_for(*creation);
+        if !spent.is_empty() {
+            idea_events.send(IdeaChanged::Spent);
+        }
+        commands.entity(entity).insert(SpentIdeas(spent));
+        built_events.send(CreationBuilt(*creation));
+    }
+}
+
+fn build_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [name] = args else {return Err("expected a creation".to_string());};
+    let creation: CreationType = name.parse()?;
+    world
+        .resource_mut::<Events<BuildCreation>>()
+        .send(BuildCreation(creation));
+    Ok(format!("building a {creation:?}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn building_a_creation_announces_it() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .insert_resource(PlayerIdeas::with_ideas(vec![Idea::Cube, Idea::Spring]))
+            .add_event::<BuildCreation>()
+            .add_event::<CreationBuilt>()
+            .add_event::<IdeaChanged>()
+            .add_system(build_creations);
+        app.world.spawn((Player, Transform::default()));
+
+        app.world
+            .resource_mut::<Events<BuildCreation>>()
+            .send(BuildCreation(CreationType::Launcher));
+        app.update();
+
+        let built: Vec<CreationType> = app
+            .world
+            .resource_mut::<Events<CreationBuilt>>()
+            .drain()
+            .map(|CreationBuilt(creation)| creation)
+            .collect();
+        assert_eq!(built, vec![CreationType::Launcher]);
+    }
+}
@@ -1,7 +1,16 @@
-use crate::{Momentum, Movement, Player, PlayerAction};
-use bevy::{core_pipeline::clear_color::ClearColorConfig, prelude::*};
+use crate::{
+    ActiveRoom, CurrentLevel, DebugProbes, GameConfig, GameSettings, LaunchOptions, LevelData,
+    Momentum, Movement, Player, PlayerAction, PredictedLanding, Probe, Room, PLAYER_HEAD_HEIGHT,
+};
+use bevy::{
+    core_pipeline::{bloom::BloomSettings, clear_color::ClearColorConfig},
+    prelude::*,
+    render::{camera::ScalingMode, view::RenderLayers},
+};
 use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+use std::mem::{discriminant, Discriminant};
 
 #[derive(Component)]
 pub struct MainCamera;
@@ -12,11 +21,224 @@ pub struct UiCamera;
 #[derive(Component)]
 pub struct IdeaUi;
 
+/// Where the loaded idea at this index shows its icon
+#[derive(Component)]
+pub struct IdeaSlot(pub usize);
+
+/// Where the held item shows its icon
+#[derive(Component)]
+pub struct HeldItemSlot;
+
+/// Render layer only the `UiCamera` sees
+pub const UI_LAYER: u8 = 1;
+/// Along the bottom left of the screen, relative to `IdeaUi`
+const IDEA_SLOT_POSITIONS: [Vec3; 3] = [
+    Vec3::new(-1.9, -0.95, 0.0),
+    Vec3::new(-1.5, -0.95, 0.0),
+    Vec3::new(-1.1, -0.95, 0.0),
+];
+/// Bottom right of the screen, above the held item text
+const HELD_ITEM_SLOT_POSITION: Vec3 = Vec3::new(1.9, -0.8, 0.0);
+
+const FIRST_PERSON_LOOK_SPEED: f32 = 120.0;
+const FIRST_PERSON_MAX_PITCH: f32 = 80.0;
+
+/// Vertical speed the camera starts making room for, launchers and bounce pads go well past it
+const VERTICAL_FRAMING_MIN_SPEED: f32 = 8.0;
+/// Vertical speed where the framing is as wide as it gets
+const VERTICAL_FRAMING_MAX_SPEED: f32 = 30.0;
+/// Extra height and distance the camera backs off by at full framing
+const VERTICAL_FRAMING_HEIGHT: f32 = 6.0;
+const VERTICAL_FRAMING_DISTANCE: f32 = 5.0;
+/// How far above or below the player the camera looks at full framing, towards where they're headed
+const VERTICAL_FRAMING_TILT: f32 = 4.0;
+/// Keeps the framing from snapping around at the top of an arc
+const VERTICAL_FRAMING_SMOOTHING: f32 = 5.0;
+/// The framing starts settling this long before a predicted landing, so it's level on touchdown
+const LANDING_PREFRAME_SECONDS: f32 = 0.4;
+
+/// Angle changes smaller than this are the idle orbit drifting, not a snap
+const ROTATION_SNAP_DEGREES: f32 = 10.0;
+/// A spring this close to its target with this little speed left counts as settled
+const SPRING_SETTLE_DISTANCE: f32 = 0.01;
+
+/// How far back the diorama camera sits, far enough that nothing in the room is behind it
+const DIORAMA_DISTANCE: f32 = 60.0;
+/// Looking down the diagonal of a cube, the classic isometric tilt
+const DIORAMA_PITCH: f32 = 35.264;
+/// How quickly the orthographic view grows or shrinks to fit, also while blending back to
+/// perspective
+const PROJECTION_BLEND_SPEED: f32 = 4.0;
+/// Close enough to the perspective view's size to swap back without a visible pop
+const PROJECTION_SNAP_HEIGHT: f32 = 0.05;
+
+#[derive(Reflect)]
 pub enum CameraMode {
     Normal,
     Fixed { position: Vec3, look_target: Vec3 },
+    FirstPerson,
+    /// Trails behind whatever the player is riding, `heading` is the way it faces
+    Chase { heading: Vec3 },
+    /// Looks down on a puzzle room from a fixed isometric angle with an orthographic view `size`
+    /// units tall
+    Diorama {
+        center: Vec3,
+        size: f32,
+    },
 }
+
+/// Set on a room to show it as a diorama while the player is inside
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DioramaView {
+    /// Height of the view in world units
+    pub size: f32,
+}
+
+/// Present on the camera while it shows a diorama room, keeps what to restore once the player
+/// leaves
+#[derive(Component)]
+pub struct DioramaCam {
+    pub previous_mode: CameraMode,
+}
+
+/// How the camera gets from one framing to the next
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum CameraEasing {
+    /// Closes a share of the gap every frame, same as the camera following the player
+    Lerp,
+    /// Starts and stops gently, arriving after exactly `seconds`
+    EaseInOut { seconds: f32 },
+    /// A critically damped spring, settles as fast as it can without overshooting, higher
+    /// `frequency` is snappier
+    Spring { frequency: f32 },
+}
+
+/// The changes that ease the camera instead of letting it lurch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraTransitionKind {
+    ModeSwitch,
+    RotationSnap,
+    /// Handing the view over to a fixed camera spot
+    ZoneEnter,
+}
+
+/// Present on the camera while it eases into a new framing, removed once it gets there
 #[derive(Component)]
+pub struct CameraTransition {
+    easing: CameraEasing,
+    elapsed: f32,
+    start: Vec3,
+    start_look: Vec3,
+    position: Vec3,
+    look: Vec3,
+    velocity: Vec3,
+    look_velocity: Vec3,
+}
+
+impl CameraTransition {
+    pub fn new(easing: CameraEasing, position: Vec3, look: Vec3) -> Self {
+        CameraTransition {
+            easing,
+            elapsed: 0.0,
+            start: position,
+            start_look: look,
+            position,
+            look,
+            velocity: Vec3::ZERO,
+            look_velocity: Vec3::ZERO,
+        }
+    }
+
+    /// Moves along the curve towards `target`, returning where the camera is and what it looks at
+    pub fn step(&mut self, delta: f32, target: Vec3, look_target: Vec3) -> (Vec3, Vec3) {
+        self.elapsed += delta;
+        match self.easing {
+            CameraEasing::Lerp => {
+                self.position = target;
+                self.look = look_target;
+            }
+            CameraEasing::EaseInOut { seconds } => {
+                let t = if seconds > 0.0 {
+                    ease_in_out(self.elapsed / seconds)
+                } else {
+                    1.0
+                };
+                self.position = self.start.lerp(target, t);
+                self.look = self.start_look.lerp(look_target, t);
+            }
+            CameraEasing::Spring { frequency } => {
+                (self.position, self.velocity) = critically_damped_spring(
+                    self.position,
+                    self.velocity,
+                    target,
+                    frequency,
+                    delta,
+                );
+                (self.look, self.look_velocity) = critically_damped_spring(
+                    self.look,
+                    self.look_velocity,
+                    look_target,
+                    frequency,
+                    delta,
+                );
+            }
+        }
+        (self.position, self.look)
+    }
+
+    pub fn is_finished(&self, target: Vec3) -> bool {
+        match self.easing {
+            CameraEasing::Lerp => true,
+            CameraEasing::EaseInOut { seconds } => self.elapsed >= seconds,
+            CameraEasing::Spring { frequency: _ } => {
+                self.position.distance(target) < SPRING_SETTLE_DISTANCE
+                    && self.velocity.length() < SPRING_SETTLE_DISTANCE
+            }
+        }
+    }
+}
+
+/// Smoothstep, slow at both ends of `t` from 0 to 1
+pub fn ease_in_out(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Steps a spring that's exactly damped enough to never overshoot, solved in closed form so it
+/// stays steady however long the frame was
+pub fn critically_damped_spring(
+    position: Vec3,
+    velocity: Vec3,
+    target: Vec3,
+    frequency: f32,
+    delta: f32,
+) -> (Vec3, Vec3) {
+    let offset = position - target;
+    let decay = (-frequency * delta).exp();
+    let drift = (velocity + offset * frequency) * delta;
+    let offset = (offset + drift) * decay;
+    let velocity = (velocity - drift * frequency) * decay;
+    (target + offset, velocity)
+}
+
+/// Degrees between two camera angles, the short way round
+fn angle_between(a: f32, b: f32) -> f32 {
+    let difference = (a - b).rem_euclid(360.0);
+    difference.min(360.0 - difference)
+}
+
+/// Present on the camera while looking through the player's eyes, keeps what to restore on the way
+/// out
+#[derive(Component)]
+pub struct FirstPersonLook {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub previous_mode: CameraMode,
+    pub previous_angle: f32,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct CameraController {
     pub z_distance: f32,
     pub y_distance: f32,
@@ -26,11 +248,17 @@ pub struct CameraController {
     pub player_position: Vec3,
     pub mode: CameraMode,
     pub blocked_by_a_wall: bool,
+    /// Keeps the camera from pulling back as the player picks up speed
+    pub reduced_motion: bool,
+    /// The player's vertical speed, smoothed so the framing eases in and out
+    pub vertical_speed: f32,
+    /// What the camera looked at last frame, where a transition's look starts from
+    pub looking_at: Vec3,
 }
 
 impl CameraController {
     pub fn desired_y_height(&self, momentum: f32) -> f32 {
-        if momentum < 5.0 {
+        if momentum < 5.0 || self.reduced_motion {
             self.y_distance / 2.0
         } else {
             self.y_distance
@@ -38,13 +266,48 @@ impl CameraController {
     }
 
     pub fn desired_z_distance(&self, momentum: f32) -> f32 {
-        if momentum < 10.0 {
+        if momentum < 10.0 || self.reduced_motion {
             self.z_distance
         } else {
             self.z_distance * 1.5
         }
     }
 
+    /// How much to make room for the player moving up or down fast, from -1 falling to 1 rising.
+    /// Reduced motion holds the camera still however fast they go
+    pub fn vertical_framing(&self) -> f32 {
+        if self.reduced_motion {
+            return 0.0;
+        }
+        let speed = self.vertical_speed.abs();
+        let amount = ((speed - VERTICAL_FRAMING_MIN_SPEED)
+            / (VERTICAL_FRAMING_MAX_SPEED - VERTICAL_FRAMING_MIN_SPEED))
+            .clamp(0.0, 1.0);
+        amount * self.vertical_speed.signum()
+    }
+
+    /// Where the camera looks, leading the player in the direction they're flying
+    pub fn look_target(&self) -> Vec3 {
+        self.player_position + Vec3::Y * self.vertical_framing() * VERTICAL_FRAMING_TILT
+    }
+
+    /// The nearest isometric heading to `angle`, so rotating the diorama steps between corners
+    pub fn diorama_yaw(&self) -> f32 {
+        ((self.angle - 45.0) / 90.0).round() * 90.0 + 45.0
+    }
+
+    /// Where the diorama camera sits to look down on `center`
+    pub fn diorama_position(&self, center: Vec3) -> Vec3 {
+        let yaw = self.diorama_yaw().to_radians();
+        let pitch = DIORAMA_PITCH.to_radians();
+        let direction = Vec3::new(
+            yaw.sin() * pitch.cos(),
+            pitch.sin(),
+            yaw.cos() * pitch.cos(),
+        );
+        center + direction * DIORAMA_DISTANCE
+    }
+
     pub fn desired_easing_speed(&self) -> f32 {
         match self.mode {
             CameraMode::Normal => {
@@ -58,6 +321,9 @@ impl CameraController {
                 position: _,
                 look_target: _,
             } => self.easing * 5.0,
+            CameraMode::FirstPerson => self.easing,
+            CameraMode::Chase { heading: _ } => self.easing * 2.0,
+            CameraMode::Diorama { center: _, size: _ } => self.easing * 2.0,
         }
     }
 }
@@ -71,12 +337,11 @@ impl Default for CameraController {
             easing: 4.0,
             target_position: Vec3::ZERO,
             player_position: Vec3::ZERO,
-            // mode: CameraMode::Normal,
-            mode: CameraMode::Fixed {
-                position: Vec3::new(0.0, 40.0, -23.0),
-                look_target: Vec3::ZERO,
-            },
+            mode: CameraMode::Normal,
             blocked_by_a_wall: false,
+            reduced_motion: false,
+            vertical_speed: 0.0,
+            looking_at: Vec3::ZERO,
         }
     }
 }
@@ -90,27 +355,49 @@ pub struct CameraControlPlugin;
 
 impl Plugin for CameraControlPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(spawn_main_camera)
-            .add_systems((update_camera_target_position, lerp_to_camera_position).chain())
-            .add_systems((rotate_camera, debug_change_camera_mode));
+        app.register_type::<CameraController>()
+            .register_type::<CameraMode>()
+            .add_event::<PredictedLanding>()
+            .add_startup_system(spawn_main_camera)
+            .add_systems(
+                (
+                    apply_camera_settings,
+                    update_camera_target_position,
+                    start_camera_transitions,
+                    lerp_to_camera_position,
+                )
+                    .chain(),
+            )
+            .add_systems((rotate_camera, debug_change_camera_mode))
+            .add_systems((enter_diorama_rooms, blend_camera_projection).chain())
+            .add_systems((toggle_first_person, first_person_look).chain());
     }
 }
-fn spawn_main_camera(mut commands: Commands) {
+fn spawn_main_camera(mut commands: Commands, options: Option<Res<LaunchOptions>>) {
+    let mut controller = CameraController::default();
+    if options.map_or(false, |options| options.fixed_camera) {
+        controller.mode = CameraMode::Fixed {
+            position: Vec3::new(0.0, 40.0, -23.0),
+            look_target: Vec3::ZERO,
+        };
+    }
     commands
         .spawn(Camera3dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
             transform: Transform::from_translation(Vec3::splat(10.0))
                 .looking_at(Vec3::ZERO, Vec3::Y),
             ..default()
         })
-        .insert(CameraController::default())
+        .insert(BloomSettings::default())
+        .insert(controller)
         .insert(MainCamera);
 }
 
-pub fn spawn_ui_camera(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
+/// Renders `UI_LAYER` over the main camera, far above the level so nothing else wanders into view
+pub fn spawn_ui_camera(mut commands: Commands) {
     commands
         .spawn(Camera3dBundle {
             camera_3d: Camera3d {
@@ -119,11 +406,15 @@ pub fn spawn_ui_camera(
             },
             camera: Camera {
                 order: 1,
+                // Shares the main camera's HDR target so it draws on top of it
+                hdr: true,
                 ..default()
             },
             transform: Transform::from_translation(Vec3::new(0.0, 500.0, 0.0)),
             ..default()
         })
+        .insert(UiCameraConfig { show_ui: false })
+        .insert(RenderLayers::layer(UI_LAYER))
         .insert(UiCamera);
 
     commands
@@ -133,28 +424,20 @@ pub fn spawn_ui_camera(
         })
         .insert(IdeaUi)
         .with_children(|parent| {
-            for i in 0..10 {
-                parent.spawn(PbrBundle {
-                    mesh: meshes.add(Mesh::from(
-                        TryInto::<Mesh>::try_into(shape::Icosphere {
-                            radius: 0.25,
-                            subdivisions: 2,
-                        })
-                        .unwrap(),
-                    )),
-                    material: materials.add(
-                        Color::Rgba {
-                            red: 1.0,
-                            green: 0.0,
-                            blue: 0.0,
-                            alpha: 0.5,
-                        }
-                        .into(),
-                    ),
-                    transform: circle_distribution(i, 0.85, 10.0),
-                    ..default()
-                });
+            for (index, position) in IDEA_SLOT_POSITIONS.iter().enumerate() {
+                parent
+                    .spawn(SpatialBundle {
+                        transform: Transform::from_translation(*position),
+                        ..default()
+                    })
+                    .insert(IdeaSlot(index));
             }
+            parent
+                .spawn(SpatialBundle {
+                    transform: Transform::from_translation(HELD_ITEM_SLOT_POSITION),
+                    ..default()
+                })
+                .insert(HeldItemSlot);
         });
 }
 
@@ -162,9 +445,15 @@ fn debug_change_camera_mode(
     mut camera_query: Query<&mut CameraController>,
     player_query: Query<&ActionState<PlayerAction>>,
 ) {
-    let mut camera = camera_query.single_mut();
+    let Ok(mut camera) = camera_query.get_single_mut() else {return;};
     let Ok(player_action) = player_query.get_single() else {println!("No Player to set camera mode"); return;};
     if player_action.just_pressed(PlayerAction::CameraMode) {
+        if let CameraMode::FirstPerson
+        | CameraMode::Chase { heading: _ }
+        | CameraMode::Diorama { center: _, size: _ } = camera.mode
+        {
+            return;
+        }
         if let CameraMode::Normal = camera.mode {
             camera.mode = CameraMode::Fixed {
                 position: Vec3::new(0.0, 30.0, -20.0),
@@ -175,22 +464,51 @@ fn debug_change_camera_mode(
         }
     }
 }
+fn apply_camera_settings(
+    settings: Option<Res<GameSettings>>,
+    mut camera_query: Query<&mut CameraController>,
+) {
+    let Some(settings) = settings else {return;};
+    for mut camera in &mut camera_query {
+        if camera.reduced_motion != settings.reduced_camera_motion {
+            camera.reduced_motion = settings.reduced_camera_motion;
+        }
+    }
+}
+
 fn update_camera_target_position(
+    time: Res<Time>,
     rapier_context: Res<RapierContext>,
     mut camera_query: Query<&mut CameraController>,
-    player_query: Query<(Entity, &Transform, &Momentum), With<Player>>,
+    player_query: Query<(Entity, &Transform, &Momentum, &Velocity), With<Player>>,
+    mut landing_events: EventReader<PredictedLanding>,
+    mut probes: Option<ResMut<DebugProbes>>,
 ) {
-    let mut camera = camera_query.single_mut();
-    let (player_entity, player_transform, player_momentum) = player_query.single();
+    let Ok(mut camera) = camera_query.get_single_mut() else {return;};
+    let Ok((player_entity, player_transform, player_momentum, player_velocity)) = player_query.get_single() else {return;};
+
+    let smoothing = (time.delta_seconds() * VERTICAL_FRAMING_SMOOTHING).min(1.0);
+    let settle = landing_events.iter().last().map_or(1.0, |landing| {
+        (landing.seconds / LANDING_PREFRAME_SECONDS).min(1.0)
+    });
+    let vertical_speed = player_velocity.linvel.y * settle;
+    camera.vertical_speed += (vertical_speed - camera.vertical_speed) * smoothing;
+    let framing = camera.vertical_framing().abs();
 
     let mut starting_transform = player_transform.clone();
     starting_transform.rotation = Quat::default();
     starting_transform.rotate_y(camera.angle.to_radians());
-    let dir = starting_transform.forward().normalize();
+    let dir = match camera.mode {
+        CameraMode::Chase { heading } if heading != Vec3::ZERO => -heading,
+        _ => starting_transform.forward().normalize(),
+    };
     camera.player_position = player_transform.translation;
-    let mut desired_position = starting_transform.translation
-        + (dir * camera.desired_z_distance(player_momentum.get()))
-        + (Vec3::Y * camera.desired_y_height(player_momentum.get()));
+    let z_distance =
+        camera.desired_z_distance(player_momentum.get()) + framing * VERTICAL_FRAMING_DISTANCE;
+    let y_height =
+        camera.desired_y_height(player_momentum.get()) + framing * VERTICAL_FRAMING_HEIGHT;
+    let mut desired_position =
+        starting_transform.translation + (dir * z_distance) + (Vec3::Y * y_height);
 
     let ray_pos = player_transform.translation;
     let ray_dir = (desired_position - player_transform.translation).normalize_or_zero();
@@ -200,42 +518,203 @@ fn update_camera_target_position(
         .exclude_sensors()
         .exclude_collider(player_entity);
 
-    if let Some((_, intersection)) =
-        rapier_context.cast_ray_and_get_normal(ray_pos, ray_dir, max_distance, solid, filter)
-    {
+    let occluder =
+        rapier_context.cast_ray_and_get_normal(ray_pos, ray_dir, max_distance, solid, filter);
+    if let Some(probes) = &mut probes {
+        let hit = occluder.map(|(_, intersection)| intersection.toi);
+        probes.camera = Some(Probe::new(ray_pos, ray_dir, max_distance, hit));
+    }
+    if let Some((_, intersection)) = occluder {
         desired_position = intersection.point;
     }
 
     camera.target_position = desired_position;
 }
 
+/// Picks the easing for whatever just changed about the camera and starts it from where the
+/// camera is now
+fn start_camera_transitions(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    camera_query: Query<(Entity, &Transform, &CameraController)>,
+    mut last_framing: Local<Option<(Discriminant<CameraMode>, f32)>>,
+) {
+    let Ok((camera_entity, transform, camera)) = camera_query.get_single() else {return;};
+    let framing = (discriminant(&camera.mode), camera.angle);
+    let Some((last_mode, last_angle)) = last_framing.replace(framing) else {return;};
+
+    let kind = if framing.0 != last_mode {
+        if let CameraMode::Fixed { .. } | CameraMode::Diorama { .. } = camera.mode {
+            CameraTransitionKind::ZoneEnter
+        } else {
+            CameraTransitionKind::ModeSwitch
+        }
+    } else if angle_between(last_angle, camera.angle) >= ROTATION_SNAP_DEGREES {
+        CameraTransitionKind::RotationSnap
+    } else {
+        return;
+    };
+
+    let easing = game_config.camera.easing(kind);
+    if easing == CameraEasing::Lerp {
+        commands.entity(camera_entity).remove::<CameraTransition>();
+    } else {
+        commands.entity(camera_entity).insert(CameraTransition::new(
+            easing,
+            transform.translation,
+            camera.looking_at,
+        ));
+    }
+}
+
 fn lerp_to_camera_position(
+    mut commands: Commands,
     time: Res<Time>,
-    mut camera_query: Query<(&mut Transform, &CameraController)>,
+    mut camera_query: Query<(
+        Entity,
+        &mut Transform,
+        &mut CameraController,
+        Option<&mut CameraTransition>,
+    )>,
 ) {
-    for (mut transform, camera) in &mut camera_query {
-        match camera.mode {
-            CameraMode::Normal => {
-                let lerped_position = transform.translation.lerp(
-                    camera.target_position,
-                    time.delta_seconds() * camera.desired_easing_speed(),
-                );
-                transform.translation = lerped_position;
-                transform.look_at(camera.player_position, Vec3::Y);
+    for (camera_entity, mut transform, mut camera, transition) in &mut camera_query {
+        let (target, look_target) = match camera.mode {
+            CameraMode::Normal | CameraMode::Chase { heading: _ } => {
+                (camera.target_position, camera.look_target())
             }
             CameraMode::Fixed {
                 position,
                 look_target,
-            } => {
-                let lerped_position = transform.translation.lerp(
-                    position,
-                    time.delta_seconds() * camera.desired_easing_speed(),
-                );
+            } => (position, look_target),
+            CameraMode::Diorama { center, size: _ } => (camera.diorama_position(center), center),
+            CameraMode::FirstPerson => {
+                if transition.is_some() {
+                    commands.entity(camera_entity).remove::<CameraTransition>();
+                }
+                continue;
+            }
+        };
 
-                transform.translation = lerped_position;
-                transform.look_at(look_target, Vec3::Y);
+        let look = if let Some(mut transition) = transition {
+            let (position, look) = transition.step(time.delta_seconds(), target, look_target);
+            if transition.is_finished(target) {
+                commands.entity(camera_entity).remove::<CameraTransition>();
             }
+            transform.translation = position;
+            look
+        } else {
+            let lerped_position = transform
+                .translation
+                .lerp(target, time.delta_seconds() * camera.desired_easing_speed());
+            transform.translation = lerped_position;
+            look_target
+        };
+        transform.look_at(look, Vec3::Y);
+        camera.looking_at = look;
+    }
+}
+
+/// Shows puzzle rooms as dioramas while the player is inside them, handing the view back on the
+/// way out
+fn enter_diorama_rooms(
+    mut commands: Commands,
+    active_room: Option<Res<ActiveRoom>>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Option<Res<Assets<LevelData>>>,
+    room_query: Query<&Room>,
+    mut camera_query: Query<
+        (Entity, &mut CameraController, Option<&mut DioramaCam>),
+        With<MainCamera>,
+    >,
+) {
+    let Some(active_room) = active_room else {return;};
+    let Some(levels) = levels else {return;};
+    if !active_room.is_changed() {
+        return;
+    }
+    let Ok((camera_entity, mut camera, diorama_cam)) = camera_query.get_single_mut() else {return;};
+    let level = current_level
+        .as_ref()
+        .and_then(|current_level| levels.get(&current_level.0));
+    let view = active_room
+        .0
+        .and_then(|room_entity| room_query.get(room_entity).ok())
+        .and_then(|room| Some((room.center, level?.rooms.get(room.index)?.diorama?)));
+
+    match (view, diorama_cam) {
+        (Some((center, view)), Some(_)) => {
+            if let CameraMode::Diorama { .. } = camera.mode {
+                camera.mode = CameraMode::Diorama {
+                    center,
+                    size: view.size,
+                };
+            }
+        }
+        (Some((center, view)), None) => {
+            // First person and riding keep their own view
+            if let CameraMode::FirstPerson | CameraMode::Chase { heading: _ } = camera.mode {
+                return;
+            }
+            let previous_mode = std::mem::replace(
+                &mut camera.mode,
+                CameraMode::Diorama {
+                    center,
+                    size: view.size,
+                },
+            );
+            commands
+                .entity(camera_entity)
+                .insert(DioramaCam { previous_mode });
+        }
+        (None, Some(mut diorama_cam)) => {
+            if let CameraMode::Diorama { .. } = camera.mode {
+                camera.mode = std::mem::replace(&mut diorama_cam.previous_mode, CameraMode::Normal);
+            }
+            commands.entity(camera_entity).remove::<DioramaCam>();
         }
+        (None, None) => {}
+    }
+}
+
+/// Height of what a perspective view shows `distance` away
+fn perspective_height(fov: f32, distance: f32) -> f32 {
+    2.0 * distance * (fov / 2.0).tan()
+}
+
+/// Swaps to an orthographic view the size of what the perspective one showed, then scales it to
+/// fit the diorama, and does the same in reverse on the way back
+fn blend_camera_projection(
+    time: Res<Time>,
+    mut camera_query: Query<(&Transform, &CameraController, &mut Projection), With<MainCamera>>,
+) {
+    let Ok((transform, camera, mut projection)) = camera_query.get_single_mut() else {return;};
+    let diorama_size = match camera.mode {
+        CameraMode::Diorama { center: _, size } => Some(size),
+        _ => None,
+    };
+    let focus_distance = transform.translation.distance(camera.looking_at);
+
+    if let Projection::Perspective(perspective) = &*projection {
+        if diorama_size.is_some() {
+            *projection = Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical(perspective_height(
+                    perspective.fov,
+                    focus_distance,
+                )),
+                ..default()
+            });
+        }
+        return;
+    }
+
+    let Projection::Orthographic(orthographic) = &mut *projection else {return;};
+    let ScalingMode::FixedVertical(height) = &mut orthographic.scaling_mode else {return;};
+    let target = diorama_size.unwrap_or_else(|| {
+        perspective_height(PerspectiveProjection::default().fov, focus_distance)
+    });
+    *height += (target - *height) * (time.delta_seconds() * PROJECTION_BLEND_SPEED).min(1.0);
+    if diorama_size.is_none() && (target - *height).abs() < PROJECTION_SNAP_HEIGHT {
+        *projection = Projection::Perspective(PerspectiveProjection::default());
     }
 }
 
@@ -243,7 +722,7 @@ fn rotate_camera(
     mut camera_query: Query<&mut CameraController>,
     player_query: Query<&ActionState<PlayerAction>>,
 ) {
-    let mut camera = camera_query.single_mut();
+    let Ok(mut camera) = camera_query.get_single_mut() else {return;};
     let Ok(player_action) = player_query.get_single() else {println!("No Player to rotate the camera"); return;};
     if player_action.just_pressed(PlayerAction::CameraLeft) {
         camera.angle -= 45.0;
@@ -260,3 +739,122 @@ fn rotate_camera(
         camera.angle += 360.0;
     }
 }
+
+fn toggle_first_person(
+    mut commands: Commands,
+    mut camera_query: Query<(Entity, &mut CameraController, Option<&mut FirstPersonLook>)>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Visibility,
+            &mut Movement,
+            &ActionState<PlayerAction>,
+        ),
+        With<Player>,
+    >,
+) {
+    let Ok((camera_entity, mut camera, first_person)) = camera_query.get_single_mut() else {return;};
+    let Ok(player) = player_query.get_single_mut() else {return;};
+    let (player_entity, player_transform, mut visibility, mut movement, action) = player;
+    if !action.just_pressed(PlayerAction::FirstPerson) {
+        return;
+    }
+
+    if let Some(mut first_person) = first_person {
+        camera.mode = std::mem::replace(&mut first_person.previous_mode, CameraMode::Normal);
+        camera.angle = first_person.previous_angle;
+        *visibility = Visibility::Inherited;
+        commands.entity(camera_entity).remove::<FirstPersonLook>();
+        commands.entity(player_entity).remove::<LookingAround>();
+    } else {
+        let forward = player_transform.forward();
+        let yaw = (-forward.x).atan2(-forward.z).to_degrees();
+        commands.entity(camera_entity).insert(FirstPersonLook {
+            yaw,
+            pitch: 0.0,
+            previous_mode: std::mem::replace(&mut camera.mode, CameraMode::FirstPerson),
+            previous_angle: camera.angle,
+        });
+        *visibility = Visibility::Hidden;
+        movement.0 = Vec3::ZERO;
+        commands.entity(player_entity).insert(LookingAround);
+    }
+}
+
+/// Marks the player while the camera is in first person, they stand still and turn with the view
+#[derive(Component)]
+pub struct LookingAround;
+
+fn first_person_look(
+    time: Res<Time>,
+    mut camera_query: Query<(&mut Transform, &mut FirstPersonLook), Without<Player>>,
+    mut player_query: Query<(&mut Transform, &ActionState<PlayerAction>), With<Player>>,
+) {
+    let Ok((mut camera_transform, mut look)) = camera_query.get_single_mut() else {return;};
+    let Ok((mut player_transform, action)) = player_query.get_single_mut() else {return;};
+
+    if let Some(axis_pair) = action.clamped_axis_pair(PlayerAction::Look) {
+        let step = FIRST_PERSON_LOOK_SPEED * time.delta_seconds();
+        look.yaw -= axis_pair.x() * step;
+        look.pitch = (look.pitch + axis_pair.y() * step)
+            .clamp(-FIRST_PERSON_MAX_PITCH, FIRST_PERSON_MAX_PITCH);
+    }
+
+    let yaw = Quat::from_rotation_y(look.yaw.to_radians());
+    player_transform.rotation = yaw;
+    camera_transform.translation = player_transform.translation + Vec3::Y * PLAYER_HEAD_HEIGHT;
+    camera_transform.rotation = yaw * Quat::from_rotation_x(look.pitch.to_radians());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn springs_settle_without_overshooting() {
+        let target = Vec3::new(0.0, 0.0, 10.0);
+        let mut position = Vec3::ZERO;
+        let mut velocity = Vec3::ZERO;
+        for _ in 0..120 {
+            (position, velocity) =
+                critically_damped_spring(position, velocity, target, 8.0, 1.0 / 60.0);
+            assert!(position.z <= target.z);
+        }
+        assert!(position.distance(target) < SPRING_SETTLE_DISTANCE);
+    }
+
+    #[test]
+    fn dioramas_only_look_from_isometric_corners() {
+        let mut camera = CameraController::default();
+        for angle in [-90.0, -45.0, 0.0, 45.0, 90.0, 180.0] {
+            camera.angle = angle;
+            assert_eq!((camera.diorama_yaw() - 45.0).rem_euclid(90.0), 0.0);
+        }
+        camera.angle = 45.0;
+        let offset = camera.diorama_position(Vec3::ZERO);
+        assert!((offset.x - offset.z).abs() < 0.001);
+        assert!((offset.length() - DIORAMA_DISTANCE).abs() < 0.001);
+    }
+
+    #[test]
+    fn reduced_motion_skips_vertical_framing() {
+        let mut camera = CameraController {
+            vertical_speed: -VERTICAL_FRAMING_MAX_SPEED,
+            ..default()
+        };
+        assert_eq!(camera.vertical_framing(), -1.0);
+        camera.reduced_motion = true;
+        assert_eq!(camera.vertical_framing(), 0.0);
+        assert_eq!(camera.look_target(), camera.player_position);
+    }
+
+    #[test]
+    fn rotation_snaps_measure_the_short_way_round() {
+        assert_eq!(angle_between(45.0, 405.0), 0.0);
+        assert_eq!(angle_between(-45.0, 45.0), 90.0);
+        assert_eq!(angle_between(350.0, 10.0), 20.0);
+        assert_eq!(ease_in_out(0.5), 0.5);
+        assert_eq!(ease_in_out(2.0), 1.0);
+    }
+}
@@ -0,0 +1,168 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{AppState, QuestLog, SaveData};
+
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<CampaignData>()
+            .init_asset_loader::<CampaignDataLoader>()
+            .insert_resource(PlayingLevel::default())
+            .add_startup_system(load_campaign)
+            .add_system(record_level_stars.in_set(OnUpdate(AppState::Playing)));
+    }
+}
+
+/// The levels in the order they're played, read from `assets/game.campaign.ron`
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "6e2c9a47-1d85-4f3b-a0c6-93b7e5d21f08"]
+pub struct CampaignData {
+    pub levels: Vec<CampaignLevel>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CampaignLevel {
+    /// What the level's progress is saved under, kept when the level is renamed or moved
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    /// Stars that have to be earned in the levels before this one to play it
+    #[serde(default)]
+    pub stars_required: u32,
+    /// Stars earned here are saved and shown, but don't go towards unlocking later levels
+    #[serde(default)]
+    pub practice: bool,
+}
+
+impl CampaignData {
+    /// Stars earned in every level before the one at `index`, leaving out practice levels
+    pub fn stars_before(&self, index: usize, progress: &BTreeMap<String, LevelProgress>) -> u32 {
+        self.levels
+            .iter()
+            .take(index)
+            .filter(|level| !level.practice)
+            .filter_map(|level| progress.get(&level.id))
+            .map(|level_progress| level_progress.stars)
+            .sum()
+    }
+
+    pub fn is_unlocked(&self, index: usize, progress: &BTreeMap<String, LevelProgress>) -> bool {
+        let Some(level) = self.levels.get(index) else {return false;};
+        self.stars_before(index, progress) >= level.stars_required
+    }
+}
+
+/// A level's best result on a profile, kept in its save
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct LevelProgress {
+    /// One for each of the level's quests finished in a single visit
+    pub stars: u32,
+}
+
+#[derive(Resource)]
+pub struct CampaignHandle(pub Handle<CampaignData>);
+
+/// The id of the campaign level being played, `None` for one picked with a launch flag
+#[derive(Resource, Default)]
+pub struct PlayingLevel(pub Option<String>);
+
+#[derive(Default)]
+pub struct CampaignDataLoader;
+
+impl AssetLoader for CampaignDataLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let campaign = ron::de::from_bytes::<CampaignData>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(campaign));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
+
+fn load_campaign(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CampaignHandle(asset_server.load("game.campaign.ron")));
+}
+
+/// Saves a level's stars whenever more of its quests are done than ever before
+fn record_level_stars(
+    quest_log: Res<QuestLog>,
+    playing_level: Res<PlayingLevel>,
+    mut save_data: ResMut<SaveData>,
+) {
+    if !quest_log.is_changed() {
+        return;
+    }
+    let Some(id) = &playing_level.0 else {return;};
+    let stars = quest_log.completed() as u32;
+    let best = save_data
+        .levels
+        .get(id)
+        .map_or(0, |progress| progress.stars);
+    if stars > best {
+        save_data.levels.insert(id.clone(), LevelProgress { stars });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn campaign() -> CampaignData {
+        let level = |id: &str, stars_required: u32| CampaignLevel {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("levels/{id}.level.ron"),
+            stars_required,
+            practice: false,
+        };
+        CampaignData {
+            levels: vec![level("first", 0), level("second", 2), level("third", 4)],
+        }
+    }
+
+    #[test]
+    fn levels_unlock_from_stars_earned_before_them() {
+        let campaign = campaign();
+        let mut progress = BTreeMap::new();
+        assert!(campaign.is_unlocked(0, &progress));
+        assert!(!campaign.is_unlocked(1, &progress));
+
+        progress.insert("first".to_string(), LevelProgress { stars: 3 });
+        assert!(campaign.is_unlocked(1, &progress));
+        assert!(!campaign.is_unlocked(2, &progress));
+
+        // Stars from the level itself or later ones don't count towards it
+        progress.insert("third".to_string(), LevelProgress { stars: 5 });
+        assert!(!campaign.is_unlocked(2, &progress));
+        progress.insert("second".to_string(), LevelProgress { stars: 1 });
+        assert!(campaign.is_unlocked(2, &progress));
+        assert!(!campaign.is_unlocked(3, &progress));
+    }
+
+    #[test]
+    fn practice_stars_dont_unlock_anything() {
+        let mut campaign = campaign();
+        campaign.levels[0].practice = true;
+        let mut progress = BTreeMap::new();
+        progress.insert("first".to_string(), LevelProgress { stars: 3 });
+        assert_eq!(campaign.stars_before(1, &progress), 0);
+        assert!(!campaign.is_unlocked(1, &progress));
+    }
+}
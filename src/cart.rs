@@ -0,0 +1,394 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    CameraController, CameraMode, Creation, CreationType, Drift, MainCamera, Momentum, PlacingRope,
+    Player, PlayerAction,
+};
+
+const CHASSIS_HALF_EXTENTS: Vec3 = Vec3::new(0.8, 0.4, 1.2);
+/// Where each wheel's axle sits on the chassis, the back two are driven
+const WHEEL_OFFSETS: [Vec3; 4] = [
+    Vec3::new(-0.95, -0.45, -0.8),
+    Vec3::new(0.95, -0.45, -0.8),
+    Vec3::new(-0.95, -0.45, 0.8),
+    Vec3::new(0.95, -0.45, 0.8),
+];
+const WHEEL_RADIUS: f32 = 0.35;
+const WHEEL_GRIP: f32 = 1.2;
+/// How fast the driven wheels spin at full throttle, in radians per second
+const CART_WHEEL_SPEED: f32 = 40.0;
+const CART_MOTOR_FACTOR: f32 = 2.0;
+const CART_BRAKE_FACTOR: f32 = 20.0;
+/// Yaw torque at full steer, scaled down when the cart is barely moving so it can't spin in place
+const CART_STEER_TORQUE: f32 = 25.0;
+const CART_STEER_FULL_SPEED: f32 = 4.0;
+const CART_MOUNT_REACH: f32 = 2.5;
+const CART_SEAT_OFFSET: Vec3 = Vec3::new(0.0, 1.4, 0.2);
+const CART_DISMOUNT_HOP: f32 = 6.0;
+
+pub struct CartPlugin;
+
+impl Plugin for CartPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                mount_carts,
+                drive_carts,
+                seat_riders,
+                dismount_carts,
+                chase_ridden_carts,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The chassis of a cart, `wheels` are the bodies jointed to it
+#[derive(Component)]
+pub struct Cart {
+    pub wheels: Vec<Entity>,
+}
+
+/// A wheel on a revolute joint to its cart's chassis
+#[derive(Component)]
+pub struct CartWheel {
+    pub driven: bool,
+}
+
+/// The player is sitting in `cart`, their input drives it instead of moving them
+#[derive(Component)]
+pub struct Riding {
+    pub cart: Entity,
+}
+
+/// Present on the camera while it follows behind a cart, keeps what to restore once the ride ends
+#[derive(Component)]
+pub struct ChaseCam {
+    pub previous_mode: CameraMode,
+}
+
+/// What the rider is asking the cart to do this frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CartControls {
+    /// Forward is positive, reversing is negative
+    pub throttle: f32,
+    /// Right is positive
+    pub steer: f32,
+    pub brake: bool,
+}
+
+impl CartControls {
+    pub fn from_action(action: &ActionState<PlayerAction>) -> Self {
+        let mut throttle = 0.0;
+        let mut steer = 0.0;
+        if action.pressed(PlayerAction::Up) {
+            throttle += 1.0;
+        }
+        if action.pressed(PlayerAction::Down) {
+            throttle -= 1.0;
+        }
+        if action.pressed(PlayerAction::Right) {
+            steer += 1.0;
+        }
+        if action.pressed(PlayerAction::Left) {
+            steer -= 1.0;
+        }
+        if let Some(axis_pair) = action.clamped_axis_pair(PlayerAction::Move) {
+            if action.pressed(PlayerAction::Move) {
+                throttle = axis_pair.y();
+                steer = axis_pair.x();
+            }
+        }
+        CartControls {
+            throttle,
+            steer,
+            brake: action.pressed(PlayerAction::Crouch),
+        }
+    }
+}
+
+/// The creation made from a cube and a wheel, a chassis rolling on four jointed wheels
+pub fn spawn_cart(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+) -> Entity {
+    let size = CHASSIS_HALF_EXTENTS * 2.0;
+    let chassis = commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(Color::MAROON.into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::cuboid(
+            CHASSIS_HALF_EXTENTS.x,
+            CHASSIS_HALF_EXTENTS.y,
+            CHASSIS_HALF_EXTENTS.z,
+        ))
+        .insert(Velocity::default())
+        .insert(ExternalForce::default())
+        .insert(CreationType::Cart)
+        .insert(Creation)
+        .id();
+
+    let wheel_mesh = meshes.add(Mesh::from(shape::UVSphere {
+        radius: WHEEL_RADIUS,
+        ..default()
+    }));
+    let wheel_material = materials.add(Color::DARK_GRAY.into());
+    let mut wheels = Vec::new();
+    for offset in &WHEEL_OFFSETS {
+        let driven = offset.z > 0.0;
+        let axle = RevoluteJointBuilder::new(Vec3::X)
+            .local_anchor1(*offset)
+            .local_anchor2(Vec3::ZERO);
+        let wheel = commands
+            .spawn(PbrBundle {
+                mesh: wheel_mesh.clone(),
+                material: wheel_material.clone(),
+                transform: Transform::from_translation(position + *offset),
+                ..default()
+            })
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(WHEEL_RADIUS))
+            .insert(Friction {
+                coefficient: WHEEL_GRIP,
+                combine_rule: CoefficientCombineRule::Max,
+            })
+            .insert(Velocity::default())
+            .insert(ImpulseJoint::new(chassis, axle))
+            .insert(CartWheel { driven })
+            .id();
+        wheels.push(wheel);
+    }
+    commands.entity(chassis).insert(Cart { wheels });
+    chassis
+}
+
+fn nearest_cart(
+    position: Vec3,
+    cart_query: &Query<(Entity, &GlobalTransform), With<Cart>>,
+) -> Option<Entity> {
+    cart_query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation().distance(position)))
+        .filter(|(_, distance)| *distance <= CART_MOUNT_REACH)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .map(|(entity, _)| entity)
+}
+
+fn mount_carts(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Momentum,
+            &mut Drift,
+            &mut GravityScale,
+            &ActionState<PlayerAction>,
+        ),
+        (With<Player>, Without<Riding>, Without<PlacingRope>),
+    >,
+    cart_query: Query<(Entity, &GlobalTransform), With<Cart>>,
+    mut camera_query: Query<(Entity, &mut CameraController), (With<MainCamera>, Without<ChaseCam>)>,
+) {
+    let Ok(player) = player_query.get_single_mut() else {return;};
+    let (player_entity, transform, mut momentum, mut drift, mut gravity_scale, action) = player;
+    if !action.just_pressed(PlayerAction::Interact) {
+        return;
+    }
+    let Some(cart) = nearest_cart(transform.translation, &cart_query) else {return;};
+
+    momentum.reset();
+    drift.reset();
+    gravity_scale.0 = 0.0;
+    commands
+        .entity(player_entity)
+        .insert(Riding { cart })
+        .insert(ColliderDisabled);
+
+    // First person keeps its own view, the chase only takes over from the follow cameras
+    let Ok((camera_entity, mut camera)) = camera_query.get_single_mut() else {return;};
+    if let CameraMode::FirstPerson = camera.mode {
+        return;
+    }
+    let previous_mode = std::mem::replace(
+        &mut camera.mode,
+        CameraMode::Chase {
+            heading: transform.forward(),
+        },
+    );
+    commands
+        .entity(camera_entity)
+        .insert(ChaseCam { previous_mode });
+}
+
+fn drive_carts(
+    rider_query: Query<(&Riding, &ActionState<PlayerAction>), With<Player>>,
+    mut cart_query: Query<(Entity, &Cart, &Transform, &Velocity, &mut ExternalForce)>,
+    mut wheel_query: Query<(&CartWheel, &mut ImpulseJoint)>,
+) {
+    let rider = rider_query.get_single().ok();
+    for (cart_entity, cart, transform, velocity, mut force) in &mut cart_query {
+        // Carts nobody is riding just roll free
+        let controls = match rider {
+            Some((riding, action)) if riding.cart == cart_entity => {
+                CartControls::from_action(action)
+            }
+            _ => CartControls::default(),
+        };
+
+        for wheel_entity in &cart.wheels {
+            let Ok((wheel, mut joint)) = wheel_query.get_mut(*wheel_entity) else {continue;};
+            let (target, factor) = if controls.brake {
+                (0.0, CART_BRAKE_FACTOR)
+            } else if wheel.driven && controls.throttle != 0.0 {
+                // Spinning backwards about the axle rolls the cart towards its forward, -Z
+                (-controls.throttle * CART_WHEEL_SPEED, CART_MOTOR_FACTOR)
+            } else {
+                (0.0, 0.0)
+            };
+            joint
+                .data
+                .set_motor_velocity(JointAxis::AngX, target, factor);
+        }
+
+        let speed = velocity.linvel.dot(transform.forward());
+        let grip = (speed.abs() / CART_STEER_FULL_SPEED).min(1.0);
+        // Reversing turns the nose the other way, like backing up anything on wheels
+        force.torque = -Vec3::Y * controls.steer * CART_STEER_TORQUE * grip * speed.signum();
+    }
+}
+
+fn seat_riders(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut GravityScale,
+            &Riding,
+        ),
+        With<Player>,
+    >,
+    cart_query: Query<(&Transform, &Velocity), (With<Cart>, Without<Player>)>,
+) {
+    for (player_entity, mut transform, mut velocity, mut gravity_scale, riding) in &mut player_query
+    {
+        let Ok((cart_transform, cart_velocity)) = cart_query.get(riding.cart) else {
+            gravity_scale.0 = 1.0;
+            commands
+                .entity(player_entity)
+                .remove::<Riding>()
+                .remove::<ColliderDisabled>();
+            continue;
+        };
+
+        let seat = cart_transform.transform_point(CART_SEAT_OFFSET);
+        transform.translation = seat;
+        let forward = cart_transform.forward();
+        let facing = Vec3::new(forward.x, 0.0, forward.z);
+        if facing.length_squared() > 0.0 {
+            transform.look_at(seat + facing, Vec3::Y);
+        }
+        velocity.linvel = cart_velocity.linvel;
+    }
+}
+
+fn dismount_carts(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut Momentum,
+            &mut GravityScale,
+            &ActionState<PlayerAction>,
+        ),
+        (With<Player>, With<Riding>),
+    >,
+) {
+    for (player_entity, mut transform, mut velocity, mut momentum, mut gravity_scale, action) in
+        &mut player_query
+    {
+        if !action.just_pressed(PlayerAction::Jump) && !action.just_pressed(PlayerAction::Interact)
+        {
+            continue;
+        }
+
+        // Keep the cart's speed and hop clear of it
+        velocity.linvel.y = velocity.linvel.y.max(0.0) + CART_DISMOUNT_HOP;
+        let flat_velocity = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+        momentum.set(flat_velocity.length());
+        if flat_velocity != Vec3::ZERO {
+            let position = transform.translation;
+            transform.look_at(position + flat_velocity, Vec3::Y);
+        }
+        gravity_scale.0 = 1.0;
+        commands
+            .entity(player_entity)
+            .remove::<Riding>()
+            .remove::<ColliderDisabled>();
+    }
+}
+
+/// Keeps the chase camera behind the ridden cart, handing the view back once nobody is riding
+fn chase_ridden_carts(
+    mut commands: Commands,
+    rider_query: Query<&Riding, With<Player>>,
+    cart_query: Query<&Transform, With<Cart>>,
+    mut camera_query: Query<(Entity, &mut CameraController, &mut ChaseCam)>,
+) {
+    let Ok((camera_entity, mut camera, mut chase)) = camera_query.get_single_mut() else {return;};
+    let ridden = rider_query
+        .get_single()
+        .ok()
+        .and_then(|riding| cart_query.get(riding.cart).ok());
+
+    match ridden {
+        Some(cart_transform) => {
+            if let CameraMode::Chase { heading } = &mut camera.mode {
+                let forward = cart_transform.forward();
+                *heading = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
+            }
+        }
+        None => {
+            camera.mode = std::mem::replace(&mut chase.previous_mode, CameraMode::Normal);
+            commands.entity(camera_entity).remove::<ChaseCam>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keys_map_to_throttle_steer_and_brake() {
+        let mut action = ActionState::<PlayerAction>::default();
+        action.press(PlayerAction::Down);
+        action.press(PlayerAction::Right);
+        action.press(PlayerAction::Crouch);
+        assert_eq!(
+            CartControls::from_action(&action),
+            CartControls {
+                throttle: -1.0,
+                steer: 1.0,
+                brake: true,
+            }
+        );
+
+        assert_eq!(
+            CartControls::from_action(&ActionState::default()),
+            CartControls::default()
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{Idea, IdeaChanged, Player, PlayerIdeas};
+
+const CHALLENGE_COIN_RADIUS: f32 = 0.4;
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CollectedStars::default())
+            .add_systems((start_challenges, collect_challenge_coins, tick_challenges).chain());
+    }
+}
+
+/// What the player earns for finishing a challenge in time
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum ChallengeReward {
+    Idea(Idea),
+    Star,
+}
+
+#[derive(Resource, Default)]
+pub struct CollectedStars(pub u32);
+
+/// Touching this starts a countdown and scatters coins at `coins`, collecting them all before time
+/// runs out earns `reward`
+#[derive(Component)]
+pub struct ChallengeTrigger {
+    pub coins: Vec<Vec3>,
+    pub seconds: f32,
+    pub reward: ChallengeReward,
+}
+
+/// Present on a trigger while its challenge is running
+#[derive(Component)]
+pub struct ActiveChallenge {
+    pub timer: Timer,
+    pub coins: Vec<Entity>,
+    pub total: usize,
+}
+
+impl ActiveChallenge {
+    pub fn collected(&self) -> usize {
+        self.total - self.coins.len()
+    }
+
+    pub fn seconds_left(&self) -> f32 {
+        self.timer.remaining_secs()
+    }
+}
+
+/// Present on a trigger once its challenge has been beaten, it can't be started again
+#[derive(Component)]
+pub struct ChallengeComplete;
+
+#[derive(Component)]
+pub struct ChallengeCoin {
+    pub challenge: Entity,
+}
+
+pub fn start_challenges(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut trigger_query: Query<
+        (Entity, &ChallengeTrigger, &mut Visibility),
+        (Without<ActiveChallenge>, Without<ChallengeComplete>),
+    >,
+    player_query: Query<(), With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let other_entity = if player_query.contains(*e1) {
+            *e2
+        } else if player_query.contains(*e2) {
+            *e1
+        } else {
+            continue;
+        };
+
+        let trigger_result = trigger_query.get_mut(other_entity);
+        let Ok((trigger_entity, trigger, mut visibility)) = trigger_result else {continue;};
+
+        let mesh = meshes.add(Mesh::from(shape::UVSphere {
+            radius: CHALLENGE_COIN_RADIUS,
+            ..default()
+        }));
+        let material = materials.add(Color::RED.into());
+        let coins = trigger
+            .coins
+            .iter()
+            .map(|position| {
+                commands
+                    .spawn(PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(*position),
+                        ..default()
+                    })
+                    .insert(Collider::ball(CHALLENGE_COIN_RADIUS))
+                    .insert(Sensor)
+                    .insert(ActiveEvents::COLLISION_EVENTS)
+                    .insert(RigidBody::Fixed)
+                    .insert(ChallengeCoin {
+                        challenge: trigger_entity,
+                    })
+                    .id()
+            })
+            .collect::<Vec<Entity>>();
+
+        *visibility = Visibility::Hidden;
+        commands.entity(trigger_entity).insert(ActiveChallenge {
+            timer: Timer::from_seconds(trigger.seconds, TimerMode::Once),
+            total: coins.len(),
+            coins,
+        });
+    }
+}
+
+pub fn collect_challenge_coins(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    coin_query: Query<&ChallengeCoin>,
+    mut challenge_query: Query<&mut ActiveChallenge>,
+    player_query: Query<(), With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let coin_entity = if player_query.contains(*e1) {
+            *e2
+        } else if player_query.contains(*e2) {
+            *e1
+        } else {
+            continue;
+        };
+
+        let Ok(coin) = coin_query.get(coin_entity) else {continue;};
+        if let Ok(mut challenge) = challenge_query.get_mut(coin.challenge) {
+            challenge.coins.retain(|entity| *entity != coin_entity);
+        }
+        commands.entity(coin_entity).despawn_recursive();
+    }
+}
+
+pub fn tick_challenges(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_ideas: ResMut<PlayerIdeas>,
+    mut idea_events: EventWriter<IdeaChanged>,
+    mut stars: ResMut<CollectedStars>,
+    mut challenge_query: Query<(
+        Entity,
+        &ChallengeTrigger,
+        &mut ActiveChallenge,
+        &mut Visibility,
+    )>,
+) {
+    for (entity, trigger, mut challenge, mut visibility) in &mut challenge_query {
+        if challenge.coins.is_empty() {
+            match trigger.reward {
+                ChallengeReward::Idea(idea) => {
+                    player_ideas.get_idea(idea);
+                    idea_events.send(IdeaChanged::Gained(idea));
+                }
+                ChallengeReward::Star => stars.0 += 1,
+            }
+            commands
+                .entity(entity)
+                .remove::<ActiveChallenge>()
+                .insert(ChallengeComplete);
+            continue;
+        }
+
+        challenge.timer.tick(time.delta());
+        if challenge.timer.finished() {
+            for coin in challenge.coins.iter() {
+                commands.entity(*coin).despawn_recursive();
+            }
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<ActiveChallenge>();
+        }
+    }
+}
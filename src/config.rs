@@ -0,0 +1,283 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use bevy_rapier3d::prelude::{RapierConfiguration, TimestepMode};
+use serde::Deserialize;
+
+use crate::{CameraEasing, CameraTransitionKind, MovementBackend};
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .insert_resource(GameConfig::default())
+            .add_startup_system(load_game_config)
+            .add_systems((apply_game_config_changes, apply_physics_step).chain());
+    }
+}
+
+/// Tuning values read from `assets/game.config.ron`, reloaded whenever the file changes on disk
+#[derive(Resource, Deserialize, TypeUuid, Clone, Default)]
+#[uuid = "3e7c4a61-52b9-4f0e-9d6c-8a1b7f2e4c90"]
+#[serde(default)]
+pub struct GameConfig {
+    pub sprint: SprintConfig,
+    pub movement_backend: MovementBackend,
+    pub step_up: StepUpConfig,
+    pub rewind: RewindConfig,
+    pub imagination: ImaginationConfig,
+    pub camera: CameraConfig,
+    pub fall_damage: FallDamageConfig,
+    pub physics: PhysicsStep,
+    /// Used in place of `physics` in the browser, which has a lot less to spare
+    pub web_physics: PhysicsStep,
+}
+
+impl GameConfig {
+    /// The physics step for the platform the game was built for
+    pub fn physics_step(&self) -> PhysicsStep {
+        if cfg!(target_arch = "wasm32") {
+            self.web_physics
+        } else {
+            self.physics
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct SprintConfig {
+    pub top_speed: f32,
+    pub use_stamina: bool,
+    pub max_stamina: f32,
+    pub drain_per_second: f32,
+    pub regen_per_second: f32,
+    pub exhaustion_cooldown: f32,
+}
+
+impl Default for SprintConfig {
+    fn default() -> Self {
+        SprintConfig {
+            top_speed: 22.0,
+            use_stamina: true,
+            max_stamina: 100.0,
+            drain_per_second: 25.0,
+            regen_per_second: 15.0,
+            exhaustion_cooldown: 2.0,
+        }
+    }
+}
+
+/// Curb height geometry the player walks onto without jumping
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct StepUpConfig {
+    pub max_height: f32,
+    /// How fast the player is lifted onto a step, in units per second
+    pub speed: f32,
+}
+
+impl Default for StepUpConfig {
+    fn default() -> Self {
+        StepUpConfig {
+            max_height: 0.4,
+            speed: 8.0,
+        }
+    }
+}
+
+/// How far back the player can rewind and what comes back with them
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct RewindConfig {
+    /// Length of the recorded history, and of a full meter
+    pub seconds: f32,
+    /// Meter seconds regained per second while not rewinding
+    pub recharge_per_second: f32,
+    /// Loose items are rewound along with the player
+    pub include_items: bool,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        RewindConfig {
+            seconds: 5.0,
+            recharge_per_second: 0.5,
+            include_items: false,
+        }
+    }
+}
+
+/// What it takes to keep creations materialized
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ImaginationConfig {
+    pub max: f32,
+    /// Drained per second for every idea that went into a creation still standing
+    pub upkeep_per_idea: f32,
+    /// Regained per second while standing still on the ground
+    pub regen_per_second: f32,
+    /// Seconds after a forced recall before running dry can recall another creation
+    pub recall_grace_seconds: f32,
+}
+
+impl Default for ImaginationConfig {
+    fn default() -> Self {
+        ImaginationConfig {
+            max: 100.0,
+            upkeep_per_idea: 2.0,
+            regen_per_second: 10.0,
+            recall_grace_seconds: 3.0,
+        }
+    }
+}
+
+/// The easing for each kind of camera transition
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct CameraConfig {
+    pub mode_switch: CameraEasing,
+    pub rotation_snap: CameraEasing,
+    pub zone_enter: CameraEasing,
+}
+
+impl CameraConfig {
+    pub fn easing(&self, kind: CameraTransitionKind) -> CameraEasing {
+        match kind {
+            CameraTransitionKind::ModeSwitch => self.mode_switch,
+            CameraTransitionKind::RotationSnap => self.rotation_snap,
+            CameraTransitionKind::ZoneEnter => self.zone_enter,
+        }
+    }
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        CameraConfig {
+            mode_switch: CameraEasing::Spring { frequency: 8.0 },
+            rotation_snap: CameraEasing::EaseInOut { seconds: 0.35 },
+            zone_enter: CameraEasing::EaseInOut { seconds: 0.8 },
+        }
+    }
+}
+
+/// How hard a landing has to be to hurt, turned off for a classic platformer fall
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct FallDamageConfig {
+    pub enabled: bool,
+    /// Landing slower than this never hurts, a little faster than the highest jump lands
+    pub min_speed: f32,
+    /// Each of this much landing speed past `min_speed` costs another point of health
+    pub speed_per_damage: f32,
+    /// How long before or after touching down pressing Crouch rolls out of the landing
+    pub roll_window_seconds: f32,
+}
+
+impl Default for FallDamageConfig {
+    fn default() -> Self {
+        FallDamageConfig {
+            enabled: true,
+            min_speed: 24.0,
+            speed_per_damage: 8.0,
+            roll_window_seconds: 0.2,
+        }
+    }
+}
+
+/// How each frame's physics step is cut up
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct PhysicsStep {
+    /// The longest a single step may be, frames slower than this run the world in slow motion
+    pub max_dt: f32,
+    pub substeps: usize,
+}
+
+impl PhysicsStep {
+    pub fn timestep_mode(&self) -> TimestepMode {
+        TimestepMode::Variable {
+            max_dt: self.max_dt,
+            // `TimeScale` already slows the `Time` this is stepped by
+            time_scale: 1.0,
+            substeps: self.substeps.max(1),
+        }
+    }
+}
+
+impl Default for PhysicsStep {
+    fn default() -> Self {
+        PhysicsStep {
+            max_dt: 1.0 / 60.0,
+            substeps: 1,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct GameConfigHandle(pub Handle<GameConfig>);
+
+#[derive(Default)]
+pub struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let config = ron::de::from_bytes::<GameConfig>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+fn load_game_config(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameConfigHandle(asset_server.load("game.config.ron")));
+}
+
+fn apply_game_config_changes(
+    mut asset_events: EventReader<AssetEvent<GameConfig>>,
+    config_assets: Res<Assets<GameConfig>>,
+    config_handle: Option<Res<GameConfigHandle>>,
+    mut game_config: ResMut<GameConfig>,
+) {
+    let Some(config_handle) = config_handle else {return;};
+    for event in asset_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                if *handle == config_handle.0 {
+                    if let Some(config) = config_assets.get(handle) {
+                        debug!("Applying game config");
+                        *game_config = config.clone();
+                    }
+                }
+            }
+            AssetEvent::Removed { handle: _ } => (),
+        }
+    }
+}
+
+fn apply_physics_step(
+    game_config: Res<GameConfig>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    if !game_config.is_changed() {
+        return;
+    }
+    let timestep_mode = game_config.physics_step().timestep_mode();
+    if rapier_config.timestep_mode != timestep_mode {
+        rapier_config.timestep_mode = timestep_mode;
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, window::ReceivedCharacter};
+use bevy_rapier3d::prelude::*;
+
+use crate::{AppState, AssetCollection, InputContext, Player, TimeScale};
+
+const CONSOLE_HISTORY: usize = 12;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleCommands>()
+            .insert_resource(Console::default())
+            .add_console_command("help", "help", list_commands)
+            .add_console_command("tp", "tp x y z", teleport_player)
+            .add_console_command("set", "set gravity y", set_value)
+            .add_console_command("timescale", "timescale speed", set_timescale)
+            .add_system(spawn_console.in_schedule(OnEnter(AppState::Playing)))
+            .add_systems(
+                (
+                    toggle_console,
+                    type_into_console,
+                    run_console_commands,
+                    update_console_text,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Runs a console command with the words typed after its name, the `Ok` text is echoed back
+pub type ConsoleHandler = fn(&mut World, &[&str]) -> Result<String, String>;
+
+#[derive(Clone, Copy)]
+pub struct ConsoleCommand {
+    pub usage: &'static str,
+    pub handler: ConsoleHandler,
+}
+
+/// Every command the console understands, plugins add their own with `add_console_command`
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(BTreeMap<String, ConsoleCommand>);
+
+impl ConsoleCommands {
+    pub fn add(&mut self, name: &str, usage: &'static str, handler: ConsoleHandler) {
+        self.0
+            .insert(name.to_lowercase(), ConsoleCommand { usage, handler });
+    }
+
+    pub fn get(&self, name: &str) -> Option<ConsoleCommand> {
+        self.0.get(&name.to_lowercase()).copied()
+    }
+
+    pub fn usages(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0.values().map(|command| command.usage)
+    }
+}
+
+pub trait AddConsoleCommand {
+    fn add_console_command(
+        &mut self,
+        name: &str,
+        usage: &'static str,
+        handler: ConsoleHandler,
+    ) -> &mut Self;
+}
+
+impl AddConsoleCommand for App {
+    fn add_console_command(
+        &mut self,
+        name: &str,
+        usage: &'static str,
+        handler: ConsoleHandler,
+    ) -> &mut Self {
+        self.init_resource::<ConsoleCommands>();
+        self.world
+            .resource_mut::<ConsoleCommands>()
+            .add(name, usage, handler);
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub history: Vec<String>,
+    submitted: Vec<String>,
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.history.push(line.into());
+        if self.history.len() > CONSOLE_HISTORY {
+            self.history.remove(0);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ConsoleUi;
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+/// Splits a typed line into the command name and its arguments
+pub fn split_command(line: &str) -> Option<(&str, Vec<&str>)> {
+    let mut words = line.split_whitespace();
+    let name = words.next()?;
+    Some((name, words.collect()))
+}
+
+/// Parses every argument as a float, naming the first one that isn't
+pub fn parse_floats(args: &[&str]) -> Result<Vec<f32>, String> {
+    args.iter()
+        .map(|arg| arg.parse().map_err(|_| format!("'{arg}' is not a number")))
+        .collect()
+}
+
+fn spawn_console(mut commands: Commands, assets: Res<AssetCollection>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(0.0),
+                    top: Val::Px(0.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(5),
+            ..default()
+        })
+        .insert(ConsoleUi)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(ConsoleText);
+        });
+}
+
+pub fn toggle_console(
+    keyboard: Res<Input<KeyCode>>,
+    mut console: ResMut<Console>,
+    mut input_context: ResMut<InputContext>,
+    mut ui_query: Query<&mut Visibility, With<ConsoleUi>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Grave) {
+        return;
+    }
+    console.open = !console.open;
+    console.input.clear();
+    // The player shouldn't run around while commands are typed
+    if console.open {
+        input_context.open_menu("console");
+    } else {
+        input_context.close_menu("console");
+    }
+    for mut visibility in &mut ui_query {
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+pub fn type_into_console(
+    keyboard: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+) {
+    if !console.open {
+        characters.clear();
+        return;
+    }
+    for character in characters.iter() {
+        if character.char != '`' && !character.char.is_control() {
+            console.input.push(character.char);
+        }
+    }
+    if keyboard.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keyboard.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.submitted.push(line);
+        }
+    }
+}
+
+/// Handlers get the whole world, so this runs exclusively and only when something was entered
+pub fn run_console_commands(world: &mut World) {
+    if world.resource::<Console>().submitted.is_empty() {
+        return;
+    }
+    let submitted = std::mem::take(&mut world.resource_mut::<Console>().submitted);
+    for line in submitted {
+        let Some((name, args)) = split_command(&line) else {continue;};
+        let command = world.resource::<ConsoleCommands>().get(name);
+        let output = match command {
+            Some(command) => (command.handler)(world, &args)
+                .unwrap_or_else(|error| format!("{error}\nusage: {}", command.usage)),
+            None => format!("unknown command '{name}', try 'help'"),
+        };
+        let mut console = world.resource_mut::<Console>();
+        console.print(format!("> {line}"));
+        if !output.is_empty() {
+            console.print(output);
+        }
+    }
+}
+
+pub fn update_console_text(
+    console: Res<Console>,
+    mut text_query: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        let mut value = console.history.join("\n");
+        value.push_str(&format!("\n> {}_", console.input));
+        text.sections[0].value = value;
+    }
+}
+
+fn list_commands(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let commands = world.resource::<ConsoleCommands>();
+    Ok(commands.usages().collect::<Vec<_>>().join("\n"))
+}
+
+fn teleport_player(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let coordinates = parse_floats(args)?;
+    let [x, y, z] = coordinates[..] else {return Err("expected 3 coordinates".to_string());};
+    let position = Vec3::new(x, y, z);
+    let mut query = world.query_filtered::<(&mut Transform, Option<&mut Velocity>), With<Player>>();
+    let Ok((mut transform, velocity)) = query.get_single_mut(world) else {return Err("no player to move".to_string());};
+    transform.translation = position;
+    if let Some(mut velocity) = velocity {
+        *velocity = Velocity::zero();
+    }
+    Ok(format!("moved to {position}"))
+}
+
+fn set_value(world: &mut World, args: &[&str]) -> Result<String, String> {
+    match args {
+        ["gravity", value] => {
+            let gravity = parse_floats(&[*value])?[0];
+            world.resource_mut::<RapierConfiguration>().gravity = Vec3::Y * gravity;
+            Ok(format!("gravity is {gravity}"))
+        }
+        _ => Err("unknown setting".to_string()),
+    }
+}
+
+fn set_timescale(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let speed = parse_floats(args)?;
+    let [speed] = speed[..] else {return Err("expected a speed".to_string());};
+    if speed < 0.0 {
+        return Err("speed can't be negative".to_string());
+    }
+    world.resource_mut::<TimeScale>().set(speed);
+    Ok(format!("timescale is {speed}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn echo(_world: &mut World, args: &[&str]) -> Result<String, String> {
+        Ok(args.join(" "))
+    }
+
+    #[test]
+    fn lines_split_into_name_and_args() {
+        assert_eq!(
+            split_command("  tp 1 2.5   -3 "),
+            Some(("tp", vec!["1", "2.5", "-3"]))
+        );
+        assert_eq!(split_command("   "), None);
+    }
+
+    #[test]
+    fn bad_numbers_are_reported() {
+        assert_eq!(parse_floats(&["1", "-2.5"]), Ok(vec![1.0, -2.5]));
+        assert!(parse_floats(&["1", "up"]).unwrap_err().contains("up"));
+    }
+
+    #[test]
+    fn registered_commands_run_against_the_world() {
+        let mut app = App::new();
+        app.add_console_command("Echo", "echo words", echo);
+        let command = app.world.resource::<ConsoleCommands>().get("echo").unwrap();
+        assert_eq!(
+            (command.handler)(&mut app.world, &["hello", "there"]),
+            Ok("hello there".to_string())
+        );
+        assert!(app
+            .world
+            .resource::<ConsoleCommands>()
+            .get("nope")
+            .is_none());
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{Localization, Player, SaveData};
+
+const COSTUME_RADIUS: f32 = 0.35;
+const COSTUME_HEIGHT: f32 = 0.3;
+const COSTUME_SPIN_SPEED: f32 = 1.5;
+
+pub struct CostumePlugin;
+
+impl Plugin for CostumePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((
+            hide_owned_costumes,
+            spin_costumes,
+            collect_costumes,
+            wear_equipped_costume,
+        ));
+    }
+}
+
+/// Something to wear, found in the level and kept in the save's wardrobe
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Costume {
+    SpringHat,
+    RunningShoes,
+    Umbrella,
+}
+
+impl Costume {
+    pub const ALL: [Costume; 3] = [Costume::SpringHat, Costume::RunningShoes, Costume::Umbrella];
+
+    pub fn name(&self, localization: &Localization) -> String {
+        localization.text(&format!("costume.{self:?}"))
+    }
+
+    pub fn modifiers(&self) -> CostumeModifiers {
+        match self {
+            Costume::SpringHat => CostumeModifiers {
+                jump: 1.2,
+                ..default()
+            },
+            Costume::RunningShoes => CostumeModifiers {
+                top_speed: 1.25,
+                ..default()
+            },
+            Costume::Umbrella => CostumeModifiers {
+                fall: 0.5,
+                ..default()
+            },
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Costume::SpringHat => Color::rgb(0.9, 0.3, 0.4),
+            Costume::RunningShoes => Color::rgb(0.3, 0.7, 0.9),
+            Costume::Umbrella => Color::rgb(0.95, 0.8, 0.3),
+        }
+    }
+}
+
+/// Multipliers from whatever the player is wearing, read by the movement systems on top of
+/// their usual tuning
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CostumeModifiers {
+    pub jump: f32,
+    /// Scales running speed, top speed included
+    pub top_speed: f32,
+    /// Scales how much gravity pulls while falling
+    pub fall: f32,
+}
+
+impl Default for CostumeModifiers {
+    fn default() -> Self {
+        CostumeModifiers {
+            jump: 1.0,
+            top_speed: 1.0,
+            fall: 1.0,
+        }
+    }
+}
+
+/// A costume lying in the level, touching it adds it to the wardrobe for good
+#[derive(Component)]
+pub struct CostumePickup(pub Costume);
+
+pub fn spawn_costume(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    costume: Costume,
+) -> Entity {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cylinder {
+                radius: COSTUME_RADIUS,
+                height: COSTUME_HEIGHT,
+                ..default()
+            })),
+            material: materials.add(StandardMaterial {
+                base_color: costume.color(),
+                emissive: costume.color() * 0.3,
+                ..default()
+            }),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(Collider::cylinder(COSTUME_HEIGHT / 2.0, COSTUME_RADIUS))
+        .insert(Sensor)
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(RigidBody::Fixed)
+        .insert(CostumePickup(costume))
+        .id()
+}
+
+/// Costumes already in the wardrobe don't come back when their room streams in again
+fn hide_owned_costumes(
+    mut commands: Commands,
+    save_data: Res<SaveData>,
+    query: Query<(Entity, &CostumePickup), Added<CostumePickup>>,
+) {
+    for (entity, pickup) in &query {
+        if save_data.has_costume(pickup.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn spin_costumes(time: Res<Time>, mut query: Query<&mut Transform, With<CostumePickup>>) {
+    for mut transform in &mut query {
+        transform.rotate_y(COSTUME_SPIN_SPEED * time.delta_seconds());
+    }
+}
+
+fn collect_costumes(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut save_data: ResMut<SaveData>,
+    costume_query: Query<&CostumePickup>,
+    player_query: Query<(), With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let costume_entity = if player_query.contains(*e1) {
+            *e2
+        } else if player_query.contains(*e2) {
+            *e1
+        } else {
+            continue;
+        };
+
+        let Ok(pickup) = costume_query.get(costume_entity) else {continue;};
+        save_data.add_costume(pickup.0);
+        commands.entity(costume_entity).despawn_recursive();
+    }
+}
+
+fn wear_equipped_costume(
+    save_data: Res<SaveData>,
+    mut player_query: Query<&mut CostumeModifiers, With<Player>>,
+) {
+    let wearing = save_data
+        .equipped_costume
+        .map(|costume| costume.modifiers())
+        .unwrap_or_default();
+    for mut modifiers in &mut player_query {
+        if *modifiers != wearing {
+            *modifiers = wearing;
+        }
+    }
+}
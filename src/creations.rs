@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{ChainAnchor, CreationType, Durability};
+
+pub struct CreationPlugin;
+
+impl Plugin for CreationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_creation_physics);
+    }
+}
+
+/// What a creation is made of, so built objects behave according to their recipe
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CreationPhysics {
+    pub restitution: f32,
+    pub restitution_combine: CoefficientCombineRule,
+    pub friction: f32,
+    pub friction_combine: CoefficientCombineRule,
+    pub density: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// How much impact and hazard damage it takes to break
+    pub durability: f32,
+}
+
+impl CreationPhysics {
+    pub fn of(creation: CreationType) -> Self {
+        use CoefficientCombineRule::*;
+        match creation {
+            // Dense enough to stand on and push around without skittering
+            CreationType::Crate => CreationPhysics {
+                restitution: 0.0,
+                restitution_combine: Min,
+                friction: 0.8,
+                friction_combine: Average,
+                density: 2.0,
+                linear_damping: 0.0,
+                angular_damping: 0.5,
+                durability: 40.0,
+            },
+            // Heavy so it stays put, and its bounce wins over whatever lands on it
+            CreationType::Launcher => CreationPhysics {
+                restitution: 0.95,
+                restitution_combine: Max,
+                friction: 0.5,
+                friction_combine: Average,
+                density: 4.0,
+                linear_damping: 0.0,
+                angular_damping: 1.0,
+                durability: 80.0,
+            },
+            CreationType::PogoStick => CreationPhysics {
+                restitution: 0.7,
+                restitution_combine: Max,
+                friction: 0.4,
+                friction_combine: Average,
+                density: 0.8,
+                linear_damping: 0.0,
+                angular_damping: 0.2,
+                durability: 30.0,
+            },
+            // Buoyancy does its own vertical damping, this only stops it drifting sideways
+            CreationType::FloatingPlatform => CreationPhysics {
+                restitution: 0.1,
+                restitution_combine: Average,
+                friction: 1.0,
+                friction_combine: Max,
+                density: 0.5,
+                linear_damping: 1.5,
+                angular_damping: 1.0,
+                durability: 50.0,
+            },
+            // Grippy and light, heavy damping keeps a walked rope from bucking the player off
+            CreationType::Rope => CreationPhysics {
+                restitution: 0.0,
+                restitution_combine: Min,
+                friction: 1.0,
+                friction_combine: Max,
+                density: 0.6,
+                linear_damping: 0.5,
+                angular_damping: 2.0,
+                durability: 60.0,
+            },
+            // The chassis slides off whatever it scrapes, its wheels do the gripping
+            CreationType::Cart => CreationPhysics {
+                restitution: 0.1,
+                restitution_combine: Average,
+                friction: 0.05,
+                friction_combine: Min,
+                density: 1.0,
+                linear_damping: 0.1,
+                angular_damping: 0.1,
+                durability: 60.0,
+            },
+        }
+    }
+
+    pub fn restitution(&self) -> Restitution {
+        Restitution {
+            coefficient: self.restitution,
+            combine_rule: self.restitution_combine,
+        }
+    }
+
+    pub fn friction(&self) -> Friction {
+        Friction {
+            coefficient: self.friction,
+            combine_rule: self.friction_combine,
+        }
+    }
+
+    pub fn damping(&self) -> Damping {
+        Damping {
+            linear_damping: self.linear_damping,
+            angular_damping: self.angular_damping,
+        }
+    }
+}
+
+/// Swaps Rapier's default material for the creation's own as soon as it is spawned
+fn apply_creation_physics(
+    mut commands: Commands,
+    creation_query: Query<(Entity, &CreationType), Added<CreationType>>,
+    chain_query: Query<&ChainAnchor>,
+) {
+    for (entity, creation) in &creation_query {
+        let physics = CreationPhysics::of(*creation);
+        // A rope's material belongs on its links, not the entity holding them
+        let bodies = chain_query
+            .get(entity)
+            .map_or(vec![entity], |chain| chain.links.clone());
+        for body in bodies {
+            commands
+                .entity(body)
+                .insert(physics.restitution())
+                .insert(physics.friction())
+                .insert(physics.damping())
+                .insert(ColliderMassProperties::Density(physics.density));
+        }
+        // Ropes hang off their anchors and don't wear out
+        if !chain_query.contains(entity) {
+            commands
+                .entity(entity)
+                .insert(Durability::new(physics.durability))
+                .insert(ActiveEvents::COLLISION_EVENTS);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn creations_keep_their_recipes_character() {
+        let launcher = CreationPhysics::of(CreationType::Launcher);
+        let crate_physics = CreationPhysics::of(CreationType::Crate);
+        let cart = CreationPhysics::of(CreationType::Cart);
+        assert!(launcher.restitution > crate_physics.restitution);
+        assert!(cart.friction < crate_physics.friction);
+        assert!(
+            CreationPhysics::of(CreationType::FloatingPlatform).density < crate_physics.density
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{Item, ItemId, Player};
+
+const CRITTER_RADIUS: f32 = 0.3;
+const CRITTER_HOP_SECONDS: f32 = 1.2;
+const CRITTER_HOP_SPEED: f32 = 2.5;
+const CRITTER_HOP_LIFT: f32 = 5.0;
+/// Critters hop away from the player once they come this close
+const CRITTER_SHY_DISTANCE: f32 = 4.0;
+/// How far a wandering critter turns between hops, an irrational fraction of a turn so its
+/// path doesn't repeat
+const CRITTER_WANDER_TURN: f32 = 2.4;
+
+pub struct CritterPlugin;
+
+impl Plugin for CritterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(hop_critters);
+    }
+}
+
+/// A small creature that hops around on its own, it can always be picked up like an item
+#[derive(Component)]
+pub struct Critter {
+    heading: Vec3,
+    hop_timer: Timer,
+}
+
+impl Default for Critter {
+    fn default() -> Self {
+        Critter {
+            heading: Vec3::Z,
+            hop_timer: Timer::from_seconds(CRITTER_HOP_SECONDS, TimerMode::Repeating),
+        }
+    }
+}
+
+pub fn spawn_critter(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::UVSphere {
+                radius: CRITTER_RADIUS,
+                ..default()
+            })),
+            material: materials.add(Color::GOLD.into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(ItemId::Critter.into_collider())
+        .insert(LockedAxes::ROTATION_LOCKED)
+        .insert(Velocity::default())
+        .insert(Item {
+            item_id: ItemId::Critter,
+        })
+        .insert(Critter::default())
+        .id()
+}
+
+/// Only loose critters hop, held ones are fixed in the player's hands
+fn hop_critters(
+    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    mut critter_query: Query<(&Transform, &RigidBody, &mut Velocity, &mut Critter)>,
+) {
+    let player_position = player_query
+        .get_single()
+        .map(|transform| transform.translation)
+        .ok();
+    for (transform, body, mut velocity, mut critter) in &mut critter_query {
+        critter.hop_timer.tick(time.delta());
+        if *body != RigidBody::Dynamic || !critter.hop_timer.just_finished() {
+            continue;
+        }
+
+        let mut away = player_position.map_or(Vec3::ZERO, |player| transform.translation - player);
+        away.y = 0.0;
+        critter.heading = if away != Vec3::ZERO && away.length() < CRITTER_SHY_DISTANCE {
+            away.normalize()
+        } else {
+            Quat::from_rotation_y(CRITTER_WANDER_TURN) * critter.heading
+        };
+        velocity.linvel = critter.heading * CRITTER_HOP_SPEED + Vec3::Y * CRITTER_HOP_LIFT;
+    }
+}
@@ -0,0 +1,346 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_rapier3d::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+use crate::{Footstep, Grounded, HeavyItem, Item, Player, Skidding, StandingOn, SurfaceType, Wet};
+
+/// Oldest marks are picked up and reused once this many are on the ground
+const MAX_DECALS: usize = 96;
+const DECAL_FADE_SECONDS: f32 = 8.0;
+/// Lifts marks just off the ground so they don't flicker into it
+const DECAL_LIFT: f32 = 0.02;
+/// How far apart the left and right footprints land
+const FOOTPRINT_STRIDE_WIDTH: f32 = 0.2;
+const SKID_MARK_SPACING: f32 = 0.3;
+const DRAG_MARK_SPACING: f32 = 0.4;
+/// Heavy items sliding slower than this are sitting still, not being pushed
+const DRAG_MIN_SPEED: f32 = 0.5;
+
+pub struct DecalPlugin;
+
+impl Plugin for DecalPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DecalPool::default())
+            .add_startup_system(load_decal_assets)
+            .add_systems((
+                leave_footprints,
+                leave_skid_marks,
+                leave_drag_marks,
+                fade_decals,
+            ));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecalKind {
+    Footprint,
+    SkidMark,
+    DragMark,
+}
+
+impl DecalKind {
+    /// Width then length, laid flat along the direction the mark was made in
+    pub fn size(&self) -> Vec2 {
+        match self {
+            DecalKind::Footprint => Vec2::new(0.2, 0.35),
+            DecalKind::SkidMark => Vec2::new(0.3, SKID_MARK_SPACING * 1.2),
+            DecalKind::DragMark => Vec2::new(1.6, DRAG_MARK_SPACING * 1.2),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            DecalKind::Footprint => Color::rgba(0.35, 0.27, 0.18, 0.6),
+            DecalKind::SkidMark => Color::rgba(0.3, 0.24, 0.16, 0.5),
+            DecalKind::DragMark => Color::rgba(0.28, 0.22, 0.15, 0.45),
+        }
+    }
+}
+
+/// A mark on the ground that fades out and is taken off once its lifetime runs out
+#[derive(Component)]
+pub struct Decal {
+    pub kind: DecalKind,
+    pub lifetime: Timer,
+}
+
+#[derive(Resource, Default)]
+pub struct DecalAssets {
+    pub mesh: Handle<Mesh>,
+}
+
+/// Every decal in the world, oldest first, so placing one past the cap moves the oldest instead
+/// of spawning another
+#[derive(Resource, Default)]
+pub struct DecalPool {
+    decals: VecDeque<Entity>,
+}
+
+impl DecalPool {
+    /// The oldest decal to reuse if the pool is full, it goes to the back as the newest
+    pub fn recycle(&mut self) -> Option<Entity> {
+        if self.decals.len() < MAX_DECALS {
+            return None;
+        }
+        let oldest = self.decals.pop_front()?;
+        self.decals.push_back(oldest);
+        Some(oldest)
+    }
+
+    pub fn add(&mut self, entity: Entity) {
+        self.decals.push_back(entity);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        self.decals.retain(|decal| *decal != entity);
+    }
+
+    pub fn len(&self) -> usize {
+        self.decals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decals.is_empty()
+    }
+}
+
+/// Whether the ground keeps marks, sand always does and anything else only while it's wet
+fn takes_marks(surface: Option<&SurfaceType>, wet: bool) -> bool {
+    wet || surface == Some(&SurfaceType::Sand)
+}
+
+fn load_decal_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(DecalAssets {
+        mesh: meshes.add(Mesh::from(shape::Plane::from_size(1.0))),
+    });
+}
+
+pub fn place_decal(
+    commands: &mut Commands,
+    pool: &mut DecalPool,
+    decal_assets: &DecalAssets,
+    materials: &mut Assets<StandardMaterial>,
+    kind: DecalKind,
+    position: Vec3,
+    facing: Vec3,
+) {
+    let facing = Vec3::new(facing.x, 0.0, facing.z);
+    let facing = if facing.length_squared() > 0.0 {
+        facing.normalize()
+    } else {
+        Vec3::NEG_Z
+    };
+    let size = kind.size();
+    let transform = Transform::from_translation(position + Vec3::Y * DECAL_LIFT)
+        .looking_to(facing, Vec3::Y)
+        .with_scale(Vec3::new(size.x, 1.0, size.y));
+    let bundle = (
+        PbrBundle {
+            mesh: decal_assets.mesh.clone(),
+            material: materials.add(StandardMaterial {
+                base_color: kind.color(),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+            transform,
+            ..default()
+        },
+        NotShadowCaster,
+        Decal {
+            kind,
+            lifetime: Timer::from_seconds(DECAL_FADE_SECONDS, TimerMode::Once),
+        },
+    );
+
+    // One despawned along with its level can't be reused, it makes room for a fresh one
+    let mut recycled = pool.recycle();
+    if let Some(stale) = recycled.filter(|entity| commands.get_entity(*entity).is_none()) {
+        pool.remove(stale);
+        recycled = None;
+    }
+    let entity = recycled.unwrap_or_else(|| {
+        let entity = commands.spawn_empty().id();
+        pool.add(entity);
+        entity
+    });
+    // Swapping the material handle on a reused decal lets the old one be freed
+    commands.entity(entity).insert(bundle);
+}
+
+fn leave_footprints(
+    mut commands: Commands,
+    mut pool: ResMut<DecalPool>,
+    decal_assets: Res<DecalAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut footsteps: EventReader<Footstep>,
+    player_query: Query<(&Transform, Option<&StandingOn>), With<Player>>,
+    wet_query: Query<(), With<Wet>>,
+    mut left_foot: Local<bool>,
+) {
+    let Ok((transform, standing_on)) = player_query.get_single() else {return;};
+    let wet = standing_on.map_or(false, |standing_on| wet_query.contains(standing_on.0));
+    for footstep in footsteps.iter() {
+        if !takes_marks(Some(&footstep.surface), wet) {
+            continue;
+        }
+        *left_foot = !*left_foot;
+        let side = if *left_foot { -1.0 } else { 1.0 };
+        let position = footstep.position + transform.right() * side * FOOTPRINT_STRIDE_WIDTH / 2.0;
+        place_decal(
+            &mut commands,
+            &mut pool,
+            &decal_assets,
+            &mut materials,
+            DecalKind::Footprint,
+            position,
+            transform.forward(),
+        );
+    }
+}
+
+fn leave_skid_marks(
+    mut commands: Commands,
+    mut pool: ResMut<DecalPool>,
+    decal_assets: Res<DecalAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_query: Query<
+        (&Transform, &Velocity, Option<&StandingOn>),
+        (With<Player>, With<Grounded>, With<Skidding>),
+    >,
+    surface_query: Query<(Option<&SurfaceType>, Option<&Wet>)>,
+    mut last_mark: Local<Option<Vec3>>,
+) {
+    // Stopping or reaching unmarked ground starts a fresh trail next time
+    let last = last_mark.take();
+    let Ok((transform, velocity, standing_on)) = player_query.get_single() else {return;};
+    let (surface, wet) = standing_on
+        .and_then(|standing_on| surface_query.get(standing_on.0).ok())
+        .unwrap_or_default();
+    if !takes_marks(surface, wet.is_some()) {
+        return;
+    }
+
+    let position = transform.translation - Vec3::Y;
+    *last_mark = last;
+    let far_enough = last.map_or(true, |last| last.distance(position) >= SKID_MARK_SPACING);
+    if far_enough {
+        *last_mark = Some(position);
+        place_decal(
+            &mut commands,
+            &mut pool,
+            &decal_assets,
+            &mut materials,
+            DecalKind::SkidMark,
+            position,
+            velocity.linvel,
+        );
+    }
+}
+
+/// Heavy items shoved along marked ground leave a trail behind them
+fn leave_drag_marks(
+    mut commands: Commands,
+    mut pool: ResMut<DecalPool>,
+    decal_assets: Res<DecalAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rapier_context: Res<RapierContext>,
+    item_query: Query<
+        (Entity, &GlobalTransform, &Velocity, &RigidBody, &Item),
+        (With<HeavyItem>, Without<Parent>),
+    >,
+    surface_query: Query<(Option<&SurfaceType>, Option<&Wet>)>,
+    mut last_marks: Local<HashMap<Entity, Vec3>>,
+) {
+    let mut dragging = HashMap::new();
+    for (entity, transform, velocity, body, item) in &item_query {
+        let sliding = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+        if *body != RigidBody::Dynamic || sliding.length() < DRAG_MIN_SPEED {
+            continue;
+        }
+        let half_height = item.item_id.height() / 2.0;
+        let filter = QueryFilter::exclude_dynamic()
+            .exclude_sensors()
+            .exclude_collider(entity);
+        let ground = rapier_context.cast_ray(
+            transform.translation(),
+            Vec3::NEG_Y,
+            half_height + 0.1,
+            true,
+            filter,
+        );
+        let Some((ground, _)) = ground else {continue;};
+        let (surface, wet) = surface_query.get(ground).unwrap_or_default();
+        if !takes_marks(surface, wet.is_some()) {
+            continue;
+        }
+
+        let position = transform.translation() - Vec3::Y * half_height;
+        let last = last_marks.get(&entity).copied();
+        if last.map_or(true, |last| last.distance(position) >= DRAG_MARK_SPACING) {
+            place_decal(
+                &mut commands,
+                &mut pool,
+                &decal_assets,
+                &mut materials,
+                DecalKind::DragMark,
+                position,
+                sliding,
+            );
+            dragging.insert(entity, position);
+        } else if let Some(last) = last {
+            dragging.insert(entity, last);
+        }
+    }
+    // Items that stopped or were picked up start a fresh trail next time
+    *last_marks = dragging;
+}
+
+fn fade_decals(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<DecalPool>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut decal_query: Query<(Entity, &mut Decal, &Handle<StandardMaterial>)>,
+) {
+    for (entity, mut decal, material) in &mut decal_query {
+        decal.lifetime.tick(time.delta());
+        if decal.lifetime.finished() {
+            pool.remove(entity);
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let Some(material) = materials.get_mut(material) else {continue;};
+        let alpha = decal.kind.color().a() * decal.lifetime.percent_left();
+        material.base_color.set_a(alpha);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn full_pool_recycles_the_oldest_decal() {
+        let mut pool = DecalPool::default();
+        for index in 0..MAX_DECALS as u32 {
+            assert_eq!(pool.recycle(), None);
+            pool.add(Entity::from_raw(index));
+        }
+        assert_eq!(pool.recycle(), Some(Entity::from_raw(0)));
+        assert_eq!(pool.recycle(), Some(Entity::from_raw(1)));
+        assert_eq!(pool.len(), MAX_DECALS);
+
+        pool.remove(Entity::from_raw(5));
+        assert_eq!(pool.recycle(), None);
+    }
+
+    #[test]
+    fn only_sand_and_wet_ground_take_marks() {
+        assert!(takes_marks(Some(&SurfaceType::Sand), false));
+        assert!(takes_marks(Some(&SurfaceType::Metal), true));
+        assert!(takes_marks(None, true));
+        assert!(!takes_marks(Some(&SurfaceType::Grass), false));
+        assert!(!takes_marks(None, false));
+    }
+}
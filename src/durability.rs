@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    despawn_creation, Cart, CreationType, Crusher, Idea, IdeaChanged, Pendulum, PlayerIdeas,
+    SpentIdeas, ToonMaterial,
+};
+
+/// Changes in speed gentler than this are just a creation being pushed around
+const IMPACT_SPEED_THRESHOLD: f32 = 8.0;
+const IMPACT_DAMAGE_PER_SPEED: f32 = 2.0;
+/// Moving further than this beyond what its velocity explains means it was teleported, not hit
+const TELEPORT_SLACK: f32 = 1.0;
+const HAZARD_DAMAGE: f32 = 25.0;
+/// Fractions of durability left where a creation shows another stage of cracks
+const CRACK_THRESHOLDS: [f32; 2] = [0.66, 0.33];
+const CRACK_DARKENING: f32 = 0.7;
+const IDEA_RETURN_SECONDS: f32 = 3.0;
+
+pub struct DurabilityPlugin;
+
+impl Plugin for DurabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CreationDestroyed>()
+            .insert_resource(IdeaReturns::default())
+            .add_systems(
+                (
+                    damage_from_impacts,
+                    damage_from_hazards,
+                    crack_creations,
+                    destroy_broken_creations,
+                    return_ideas,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// How much more punishment a creation can take before it breaks
+#[derive(Component)]
+pub struct Durability {
+    current: f32,
+    max: f32,
+    last_velocity: Vec3,
+    last_translation: Vec3,
+    /// The crack stage its material currently shows
+    cracks: usize,
+}
+
+impl Durability {
+    pub fn new(max: f32) -> Self {
+        Durability {
+            current: max,
+            max,
+            last_velocity: Vec3::ZERO,
+            last_translation: Vec3::ZERO,
+            cracks: 0,
+        }
+    }
+
+    pub fn damage(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.0);
+    }
+
+    pub fn percent(&self) -> f32 {
+        if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// How many of the `CRACK_THRESHOLDS` it has fallen past
+    pub fn crack_stage(&self) -> usize {
+        CRACK_THRESHOLDS
+            .iter()
+            .filter(|threshold| self.percent() <= **threshold)
+            .count()
+    }
+}
+
+/// Sent when a creation breaks apart
+pub struct CreationDestroyed(pub CreationType);
+
+/// Ideas from broken creations waiting out their cooldown before going back to the player
+#[derive(Resource, Default)]
+pub struct IdeaReturns(Vec<(Timer, Vec<Idea>)>);
+
+fn damage_from_impacts(
+    time: Res<Time>,
+    mut creation_query: Query<(&Transform, &Velocity, &mut Durability)>,
+) {
+    let delta = time.delta_seconds();
+    for (transform, velocity, mut durability) in &mut creation_query {
+        let expected_travel = durability.last_velocity.length() * delta + TELEPORT_SLACK;
+        let teleported =
+            transform.translation.distance(durability.last_translation) > expected_travel;
+        let speed_change = (velocity.linvel - durability.last_velocity).length();
+        if !teleported && speed_change > IMPACT_SPEED_THRESHOLD {
+            durability.damage((speed_change - IMPACT_SPEED_THRESHOLD) * IMPACT_DAMAGE_PER_SPEED);
+        }
+        durability.last_velocity = velocity.linvel;
+        durability.last_translation = transform.translation;
+    }
+}
+
+fn damage_from_hazards(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut creation_query: Query<&mut Durability>,
+    hazard_query: Query<(), Or<(With<Crusher>, With<Pendulum>)>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let creation_entity = if hazard_query.contains(*e2) {
+            *e1
+        } else if hazard_query.contains(*e1) {
+            *e2
+        } else {
+            continue;
+        };
+
+        if let Ok(mut durability) = creation_query.get_mut(creation_entity) {
+            durability.damage(HAZARD_DAMAGE);
+        }
+    }
+}
+
+/// Swaps in a darker copy of a creation's toon material each time it falls past a threshold
+fn crack_creations(
+    mut materials: ResMut<Assets<ToonMaterial>>,
+    mut creation_query: Query<(&mut Durability, &mut Handle<ToonMaterial>)>,
+) {
+    for (mut durability, mut material) in &mut creation_query {
+        let stage = durability.crack_stage();
+        if stage <= durability.cracks {
+            continue;
+        }
+        let Some(mut cracked) = materials.get(&material).cloned() else {continue;};
+        cracked.color = cracked.color * CRACK_DARKENING;
+        cracked.shadow_color = cracked.shadow_color * CRACK_DARKENING;
+        *material = materials.add(cracked);
+        durability.cracks = stage;
+    }
+}
+
+fn destroy_broken_creations(
+    mut commands: Commands,
+    mut returns: ResMut<IdeaReturns>,
+    mut destroyed_events: EventWriter<CreationDestroyed>,
+    creation_query: Query<(
+        Entity,
+        &Durability,
+        &CreationType,
+        Option<&SpentIdeas>,
+        Option<&Cart>,
+    )>,
+) {
+    for (entity, durability, creation, spent, cart) in &creation_query {
+        if !durability.is_broken() {
+            continue;
+        }
+
+        despawn_creation(&mut commands, entity, cart);
+        if let Some(SpentIdeas(ideas)) = spent {
+            if !ideas.is_empty() {
+                returns.0.push((
+                    Timer::from_seconds(IDEA_RETURN_SECONDS, TimerMode::Once),
+                    ideas.clone(),
+                ));
+            }
+        }
+        destroyed_events.send(CreationDestroyed(*creation));
+    }
+}
+
+fn return_ideas(
+    time: Res<Time>,
+    mut returns: ResMut<IdeaReturns>,
+    mut player_ideas: ResMut<PlayerIdeas>,
+    mut idea_events: EventWriter<IdeaChanged>,
+) {
+    for (timer, _) in &mut returns.0 {
+        timer.tick(time.delta());
+    }
+    let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut returns.0)
+        .into_iter()
+        .partition(|(timer, _)| timer.finished());
+    returns.0 = waiting;
+    for (_, ideas) in ready {
+        player_ideas.recall_ideas(ideas);
+        idea_events.send(IdeaChanged::Recalled);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cracks_show_as_durability_falls() {
+        let mut durability = Durability::new(90.0);
+        assert_eq!(durability.crack_stage(), 0);
+        durability.damage(40.0);
+        assert_eq!(durability.crack_stage(), 1);
+        durability.damage(40.0);
+        assert_eq!(durability.crack_stage(), 2);
+        assert!(!durability.is_broken());
+        durability.damage(40.0);
+        assert!(durability.is_broken());
+        assert_eq!(durability.percent(), 0.0);
+    }
+}
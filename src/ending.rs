@@ -0,0 +1,469 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::{
+    AppState, AssetCollection, Busy, CameraController, CameraMode, InputContext, InputDevice,
+    Localization, MainCamera, MenuAction, Player, SaveData, UiTheme,
+};
+
+/// How fast the credits roll up the screen, in pixels per second
+const CREDITS_SCROLL_SPEED: f32 = 60.0;
+const CREDITS_SECTION_GAP: f32 = 48.0;
+
+pub struct EndingPlugin;
+
+impl Plugin for EndingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<CreditsData>()
+            .init_asset_loader::<CreditsDataLoader>()
+            .insert_resource(EndingSequence::default())
+            .add_system(spawn_ending_ui.in_schedule(OnEnter(AppState::Playing)))
+            .add_systems(
+                (
+                    trigger_ending,
+                    skip_ending,
+                    play_cutscene,
+                    roll_credits,
+                    draw_ending,
+                    finish_ending,
+                )
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            );
+    }
+}
+
+/// The names rolled after the ending, read from `assets/game.credits.ron`
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "d3a5f1c8-4b62-4e97-8c0d-6f2b9e1a7c54"]
+pub struct CreditsData {
+    pub sections: Vec<CreditsSection>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct CreditsSection {
+    pub title: String,
+    pub names: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct CreditsDataLoader;
+
+impl AssetLoader for CreditsDataLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let credits = ron::de::from_bytes::<CreditsData>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(credits));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["credits.ron"]
+    }
+}
+
+/// One camera angle of the ending cutscene, held for `seconds` with `caption` along the bottom
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct CutsceneShot {
+    pub camera: [f32; 3],
+    pub look_at: [f32; 3],
+    pub seconds: f32,
+    #[serde(default)]
+    pub caption: String,
+}
+
+/// Walking into this box ends the game, playing `cutscene` before the credits
+#[derive(Component)]
+pub struct EndingTrigger {
+    pub half_extents: Vec3,
+    pub cutscene: Vec<CutsceneShot>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum EndingStage {
+    #[default]
+    NotReached,
+    Cutscene,
+    Credits,
+    /// Rolled all the way through, back to the menus next
+    Finished,
+}
+
+/// Where the player is in the ending, from the cutscene through to the credits
+#[derive(Resource, Default)]
+pub struct EndingSequence {
+    pub stage: EndingStage,
+    pub shots: Vec<CutsceneShot>,
+    pub shot: usize,
+    pub timer: Timer,
+    /// How far the credits have rolled, in pixels
+    pub scrolled: f32,
+}
+
+impl EndingSequence {
+    pub fn start(&mut self, shots: Vec<CutsceneShot>) {
+        self.shots = shots;
+        self.shot = 0;
+        self.scrolled = 0.0;
+        match self.shots.first() {
+            Some(first) => {
+                self.stage = EndingStage::Cutscene;
+                self.timer = Timer::from_seconds(first.seconds, TimerMode::Once);
+            }
+            None => self.stage = EndingStage::Credits,
+        }
+    }
+
+    pub fn current_shot(&self) -> Option<&CutsceneShot> {
+        if self.stage != EndingStage::Cutscene {
+            return None;
+        }
+        self.shots.get(self.shot)
+    }
+
+    /// Runs the current shot's timer, cutting to the next one or on to the credits once it runs
+    /// out. True if the shot changed
+    pub fn advance(&mut self, delta: Duration) -> bool {
+        if self.stage != EndingStage::Cutscene {
+            return false;
+        }
+        self.timer.tick(delta);
+        if !self.timer.finished() {
+            return false;
+        }
+        self.shot += 1;
+        match self.shots.get(self.shot) {
+            Some(next) => self.timer = Timer::from_seconds(next.seconds, TimerMode::Once),
+            None => self.stage = EndingStage::Credits,
+        }
+        true
+    }
+
+    /// Skips the rest of the cutscene, or the rest of the credits
+    pub fn skip(&mut self) {
+        self.stage = match self.stage {
+            EndingStage::Cutscene => EndingStage::Credits,
+            EndingStage::Credits => EndingStage::Finished,
+            stage => stage,
+        };
+    }
+}
+
+#[derive(Component)]
+pub struct CutsceneCaption;
+
+#[derive(Component)]
+pub struct CreditsRoot;
+
+/// The column of names that scrolls up through `CreditsRoot`
+#[derive(Component)]
+pub struct CreditsRoll;
+
+#[derive(Component)]
+pub struct EndingSkipHint;
+
+pub fn spawn_ending_trigger(
+    commands: &mut Commands,
+    position: Vec3,
+    half_extents: Vec3,
+    cutscene: Vec<CutsceneShot>,
+) -> Entity {
+    commands
+        .spawn(TransformBundle::from_transform(
+            Transform::from_translation(position),
+        ))
+        .insert(EndingTrigger {
+            half_extents,
+            cutscene,
+        })
+        .id()
+}
+
+fn spawn_ending_ui(
+    mut commands: Commands,
+    assets: Res<AssetCollection>,
+    asset_server: Res<AssetServer>,
+    credits: Res<Assets<CreditsData>>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+) {
+    let font = localization.font(&assets.font);
+    let style = |size: f32, color: Color| theme.text_style(&font, size, color);
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::FlexEnd,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(40.0)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(5),
+            ..default()
+        })
+        .insert(CutsceneCaption)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("", style(28.0, theme.text)));
+        });
+
+    let credits_handle = asset_server.load("game.credits.ron");
+    let sections = credits
+        .get(&credits_handle)
+        .map_or(Vec::new(), |credits| credits.sections.clone());
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                overflow: Overflow::Hidden,
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(6),
+            ..default()
+        })
+        .insert(CreditsRoot)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::width(Val::Percent(100.0)),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(CreditsRoll)
+                .with_children(|roll| {
+                    for section in sections {
+                        roll.spawn(
+                            TextBundle::from_section(section.title, style(30.0, theme.accent))
+                                .with_style(Style {
+                                    margin: UiRect::top(Val::Px(CREDITS_SECTION_GAP)),
+                                    ..default()
+                                }),
+                        );
+                        for name in section.names {
+                            roll.spawn(TextBundle::from_section(name, style(22.0, theme.text)));
+                        }
+                    }
+                });
+            parent
+                .spawn(
+                    TextBundle::from_section("", style(18.0, theme.muted)).with_style(Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            right: Val::Px(20.0),
+                            bottom: Val::Px(20.0),
+                            ..default()
+                        },
+                        ..default()
+                    }),
+                )
+                .insert(EndingSkipHint);
+        });
+}
+
+/// Stepping into an ending trigger takes control away from the player for the rest of the ending
+fn trigger_ending(
+    mut commands: Commands,
+    mut ending: ResMut<EndingSequence>,
+    mut input_context: ResMut<InputContext>,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    trigger_query: Query<(&GlobalTransform, &EndingTrigger)>,
+) {
+    if ending.stage != EndingStage::NotReached {
+        return;
+    }
+    let Ok((player, player_transform)) = player_query.get_single() else {return;};
+    let position = player_transform.translation();
+    let reached = trigger_query.iter().find(|(transform, trigger)| {
+        let offset = (position - transform.translation()).abs();
+        offset.cmple(trigger.half_extents).all()
+    });
+    let Some((_, trigger)) = reached else {return;};
+
+    ending.start(trigger.cutscene.clone());
+    input_context.open_menu("ending");
+    commands.entity(player).insert(Busy::indefinitely());
+}
+
+/// One press skips one stage, so skipping the cutscene doesn't skip the credits with it
+fn skip_ending(menu_actions: Res<ActionState<MenuAction>>, mut ending: ResMut<EndingSequence>) {
+    let skipped = menu_actions.just_pressed(MenuAction::Confirm)
+        || menu_actions.just_pressed(MenuAction::Back);
+    if skipped {
+        ending.skip();
+    }
+}
+
+/// Cuts the camera between the cutscene's shots, it eases into each the same as into any fixed
+/// camera
+fn play_cutscene(
+    time: Res<Time>,
+    mut ending: ResMut<EndingSequence>,
+    mut camera_query: Query<&mut CameraController, With<MainCamera>>,
+    mut framed_shot: Local<Option<usize>>,
+) {
+    if ending.stage != EndingStage::Cutscene {
+        *framed_shot = None;
+        return;
+    }
+    ending.advance(time.delta());
+    let Some(shot) = ending.current_shot() else {return;};
+    if *framed_shot == Some(ending.shot) {
+        return;
+    }
+    *framed_shot = Some(ending.shot);
+    for mut camera in &mut camera_query {
+        camera.mode = CameraMode::Fixed {
+            position: Vec3::from(shot.camera),
+            look_target: Vec3::from(shot.look_at),
+        };
+    }
+}
+
+fn roll_credits(
+    time: Res<Time>,
+    mut ending: ResMut<EndingSequence>,
+    root_query: Query<&Node, With<CreditsRoot>>,
+    mut roll_query: Query<(&Node, &mut Style), With<CreditsRoll>>,
+) {
+    if ending.stage != EndingStage::Credits {
+        return;
+    }
+    let Ok(root) = root_query.get_single() else {return;};
+    let Ok((roll, mut style)) = roll_query.get_single_mut() else {return;};
+    ending.scrolled += CREDITS_SCROLL_SPEED * time.delta_seconds();
+    // Starts just below the screen and ends once the last name is off the top
+    let screen_height = root.size().y;
+    style.position.top = Val::Px(screen_height - ending.scrolled);
+    if roll.size().y > 0.0 && ending.scrolled > screen_height + roll.size().y {
+        ending.skip();
+    }
+}
+
+fn draw_ending(
+    ending: Res<EndingSequence>,
+    localization: Res<Localization>,
+    device: Res<InputDevice>,
+    menu_bindings: Option<Res<InputMap<MenuAction>>>,
+    mut caption_query: Query<(&mut Visibility, &Children), With<CutsceneCaption>>,
+    mut credits_query: Query<&mut Visibility, (With<CreditsRoot>, Without<CutsceneCaption>)>,
+    mut text_query: Query<&mut Text, Without<EndingSkipHint>>,
+    mut hint_query: Query<&mut Text, With<EndingSkipHint>>,
+) {
+    if !ending.is_changed() && !device.is_changed() {
+        return;
+    }
+    let visible = |shown: bool| {
+        if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    };
+
+    let caption = ending
+        .current_shot()
+        .map_or("", |shot| shot.caption.as_str());
+    for (mut visibility, children) in &mut caption_query {
+        *visibility = visible(!caption.is_empty());
+        for child in children {
+            let Ok(mut text) = text_query.get_mut(*child) else {continue;};
+            if text.sections[0].value != caption {
+                text.sections[0].value = caption.to_string();
+            }
+        }
+    }
+    for mut visibility in &mut credits_query {
+        *visibility = visible(ending.stage == EndingStage::Credits);
+    }
+
+    let Some(menu_bindings) = menu_bindings else {return;};
+    let skip = device.menu_label(&menu_bindings, MenuAction::Confirm);
+    for mut text in &mut hint_query {
+        text.sections[0].value = localization.format("ending.skip", &[&skip]);
+    }
+}
+
+/// Marks the profile as finished and goes back to the level select, where the session is torn
+/// down
+fn finish_ending(
+    mut ending: ResMut<EndingSequence>,
+    mut save_data: ResMut<SaveData>,
+    mut input_context: ResMut<InputContext>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut camera_query: Query<&mut CameraController, With<MainCamera>>,
+) {
+    if ending.stage != EndingStage::Finished {
+        return;
+    }
+    *ending = EndingSequence::default();
+    if !save_data.finished {
+        save_data.finished = true;
+    }
+    input_context.close_menu("ending");
+    for mut camera in &mut camera_query {
+        camera.mode = CameraMode::Normal;
+    }
+    next_state.set(AppState::LevelSelect);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn shot(seconds: f32) -> CutsceneShot {
+        CutsceneShot {
+            camera: [0.0, 5.0, 10.0],
+            look_at: [0.0; 3],
+            seconds,
+            caption: String::new(),
+        }
+    }
+
+    #[test]
+    fn cutscene_cuts_between_shots_then_rolls_credits() {
+        let mut ending = EndingSequence::default();
+        ending.start(vec![shot(1.0), shot(2.0)]);
+        assert_eq!(ending.stage, EndingStage::Cutscene);
+        assert!(!ending.advance(Duration::from_millis(500)));
+        assert!(ending.advance(Duration::from_millis(500)));
+        assert_eq!(ending.current_shot(), Some(&shot(2.0)));
+        assert!(ending.advance(Duration::from_secs(2)));
+        assert_eq!(ending.stage, EndingStage::Credits);
+        assert_eq!(ending.current_shot(), None);
+    }
+
+    #[test]
+    fn skipping_moves_through_one_stage_at_a_time() {
+        let mut ending = EndingSequence::default();
+        ending.start(vec![shot(10.0)]);
+        ending.skip();
+        assert_eq!(ending.stage, EndingStage::Credits);
+        ending.skip();
+        assert_eq!(ending.stage, EndingStage::Finished);
+
+        ending.start(Vec::new());
+        assert_eq!(ending.stage, EndingStage::Credits);
+    }
+}
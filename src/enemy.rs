@@ -0,0 +1,312 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    ActiveRoom, DamagePlayer, HeldItem, HeldStack, Item, ItemId, ItemsInRange, MediumItem, Player,
+    Room, Settling,
+};
+
+const ENEMY_RADIUS: f32 = 0.6;
+const ENEMY_SPEED: f32 = 4.0;
+const ENEMY_SIGHT_RANGE: f32 = 15.0;
+const ENEMY_KNOCKBACK: f32 = 8.0;
+const ENEMY_KNOCKBACK_LIFT: f32 = 4.0;
+const ENEMY_POPULATION_CAP: usize = 12;
+/// Enemies further than this from the active room's center are removed
+const ENEMY_DESPAWN_DISTANCE: f32 = 40.0;
+const ENEMY_STUN_SECONDS: f32 = 5.0;
+/// Landing on an enemy falling at least this fast stuns it instead of hurting the player
+const STOMP_SPEED: f32 = 2.0;
+const STOMP_BOUNCE: f32 = 10.0;
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EnemyPopulation::default()).add_systems(
+            (
+                run_spawners,
+                despawn_distant_enemies,
+                chase_player,
+                handle_enemy_contact,
+                stun_enemies_hit_by_throws,
+                recover_from_stun,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// A simple walker that heads for the player once they're in sight and hurts them on contact
+#[derive(Component)]
+pub struct Enemy {
+    pub damage: u8,
+}
+
+/// Knocked dizzy, it stops chasing and can be carried and thrown like an item until it recovers
+#[derive(Component)]
+pub struct Stunned(pub Timer);
+
+/// Stuns an enemy, giving it the carry profile of an `Item` for as long as it lasts
+pub fn stun_enemy(commands: &mut Commands, entity: Entity) {
+    commands
+        .entity(entity)
+        .insert(Stunned(Timer::from_seconds(
+            ENEMY_STUN_SECONDS,
+            TimerMode::Once,
+        )))
+        .insert(Item {
+            item_id: ItemId::Enemy,
+        })
+        .insert(MediumItem);
+}
+
+/// The most enemies allowed alive at once across the whole level
+#[derive(Resource)]
+pub struct EnemyPopulation {
+    pub cap: usize,
+}
+
+impl Default for EnemyPopulation {
+    fn default() -> Self {
+        EnemyPopulation {
+            cap: ENEMY_POPULATION_CAP,
+        }
+    }
+}
+
+impl EnemyPopulation {
+    pub fn has_room(&self, alive: usize) -> bool {
+        alive < self.cap
+    }
+}
+
+/// Spawns up to `budget` enemies, one per cooldown, while the player is within `range`
+#[derive(Component)]
+pub struct Spawner {
+    pub cooldown: Timer,
+    pub budget: u32,
+    pub max_alive: usize,
+    pub range: f32,
+}
+
+impl Spawner {
+    pub fn new(cooldown: f32, budget: u32, max_alive: usize, range: f32) -> Self {
+        Spawner {
+            cooldown: Timer::from_seconds(cooldown, TimerMode::Repeating),
+            budget,
+            max_alive,
+            range,
+        }
+    }
+}
+
+/// Points back at the spawner an enemy came from so it can be counted and refunded
+#[derive(Component)]
+pub struct SpawnedBy(pub Entity);
+
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::UVSphere {
+                radius: ENEMY_RADIUS,
+                ..default()
+            })),
+            material: materials.add(Color::CRIMSON.into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::ball(ENEMY_RADIUS))
+        .insert(LockedAxes::ROTATION_LOCKED)
+        .insert(Velocity::default())
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(Enemy { damage: 1 })
+        .id()
+}
+
+pub fn run_spawners(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    population: Res<EnemyPopulation>,
+    mut spawner_query: Query<(Entity, &GlobalTransform, &mut Spawner)>,
+    enemy_query: Query<Option<&SpawnedBy>, With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {return;};
+    let mut alive = enemy_query.iter().count();
+    for (spawner_entity, transform, mut spawner) in &mut spawner_query {
+        spawner.cooldown.tick(time.delta());
+        if !spawner.cooldown.just_finished() || spawner.budget == 0 {
+            continue;
+        }
+
+        let position = transform.translation();
+        if position.distance(player_transform.translation) > spawner.range {
+            continue;
+        }
+
+        let own_alive = enemy_query
+            .iter()
+            .filter(|spawned_by| spawned_by.map_or(false, |s| s.0 == spawner_entity))
+            .count();
+        if own_alive >= spawner.max_alive || !population.has_room(alive) {
+            continue;
+        }
+
+        let enemy = spawn_enemy(&mut commands, &mut meshes, &mut materials, position);
+        commands.entity(enemy).insert(SpawnedBy(spawner_entity));
+        spawner.budget -= 1;
+        alive += 1;
+    }
+}
+
+pub fn despawn_distant_enemies(
+    mut commands: Commands,
+    active_room: Res<ActiveRoom>,
+    room_query: Query<&Room>,
+    enemy_query: Query<(Entity, &Transform, Option<&SpawnedBy>), (With<Enemy>, Without<Stunned>)>,
+    mut spawner_query: Query<&mut Spawner>,
+) {
+    let Some(room_entity) = active_room.0 else {return;};
+    let Ok(room) = room_query.get(room_entity) else {return;};
+    for (entity, transform, spawned_by) in &enemy_query {
+        if transform.translation.distance(room.center) <= ENEMY_DESPAWN_DISTANCE {
+            continue;
+        }
+
+        // Give the enemy back to its spawner so the room is stocked again when the player returns
+        if let Some(spawned_by) = spawned_by {
+            if let Ok(mut spawner) = spawner_query.get_mut(spawned_by.0) {
+                spawner.budget += 1;
+            }
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn chase_player(
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<
+        (&Transform, &mut Velocity),
+        (With<Enemy>, Without<Player>, Without<Stunned>),
+    >,
+) {
+    let Ok(player_transform) = player_query.get_single() else {return;};
+    for (transform, mut velocity) in &mut enemy_query {
+        let mut to_player = player_transform.translation - transform.translation;
+        to_player.y = 0.0;
+        let chase = if to_player.length() <= ENEMY_SIGHT_RANGE {
+            to_player.normalize_or_zero() * ENEMY_SPEED
+        } else {
+            Vec3::ZERO
+        };
+        velocity.linvel.x = chase.x;
+        velocity.linvel.z = chase.z;
+    }
+}
+
+pub fn handle_enemy_contact(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut damage_events: EventWriter<DamagePlayer>,
+    enemy_query: Query<(&Enemy, &Transform), Without<Stunned>>,
+    mut player_query: Query<(&Transform, &mut Velocity), With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let (enemy_entity, player_entity) = if enemy_query.contains(*e1) {
+            (*e1, *e2)
+        } else if enemy_query.contains(*e2) {
+            (*e2, *e1)
+        } else {
+            continue;
+        };
+
+        let Ok((player_transform, mut player_velocity)) = player_query.get_mut(player_entity)
+        else {
+            continue;
+        };
+        let Ok((enemy, enemy_transform)) = enemy_query.get(enemy_entity) else {continue;};
+        let stomped = player_transform.translation.y > enemy_transform.translation.y
+            && player_velocity.linvel.y <= -STOMP_SPEED;
+        if stomped {
+            stun_enemy(&mut commands, enemy_entity);
+            player_velocity.linvel.y = STOMP_BOUNCE;
+            continue;
+        }
+
+        let mut away = player_transform.translation - enemy_transform.translation;
+        away.y = 0.0;
+        damage_events.send(DamagePlayer {
+            amount: enemy.damage,
+            knockback: away.normalize_or_zero() * ENEMY_KNOCKBACK + Vec3::Y * ENEMY_KNOCKBACK_LIFT,
+        });
+    }
+}
+
+/// Anything thrown and still in flight stuns the enemy it hits, including other stunned enemies
+pub fn stun_enemies_hit_by_throws(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    enemy_query: Query<(), (With<Enemy>, Without<Stunned>)>,
+    thrown_query: Query<(), With<Settling>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let enemy_entity = if enemy_query.contains(*e1) && thrown_query.contains(*e2) {
+            *e1
+        } else if enemy_query.contains(*e2) && thrown_query.contains(*e1) {
+            *e2
+        } else {
+            continue;
+        };
+        stun_enemy(&mut commands, enemy_entity);
+    }
+}
+
+/// Recovered enemies stop being items, wriggling out of the player's hands if they're held
+pub fn recover_from_stun(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut items_in_range: ResMut<ItemsInRange>,
+    mut enemy_query: Query<(Entity, &mut Stunned, &mut Transform, &GlobalTransform)>,
+    mut player_query: Query<(Entity, &HeldItem, Option<&mut HeldStack>), With<Player>>,
+) {
+    for (entity, mut stunned, mut transform, global_transform) in &mut enemy_query {
+        stunned.0.tick(time.delta());
+        if !stunned.0.finished() {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .remove::<Stunned>()
+            .remove::<Item>()
+            .remove::<MediumItem>();
+        items_in_range.remove(entity);
+        for (player_entity, held_item, stack) in &mut player_query {
+            let stacked = stack.map_or(false, |mut stack| stack.remove(entity));
+            if held_item.entity != entity && !stacked {
+                continue;
+            }
+            // Dropped where it was held rather than snapping back to its offset from the player
+            *transform = global_transform.compute_transform();
+            commands
+                .entity(entity)
+                .remove_parent()
+                .remove::<RigidBody>()
+                .insert(RigidBody::Dynamic);
+            if !stacked {
+                commands.entity(player_entity).remove::<HeldItem>();
+            }
+        }
+    }
+}
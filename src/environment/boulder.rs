@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{DamagePlayer, EntityPools, Item, Player, PoolKind, RespawnPlayer, Weight};
+
+const BOULDER_DENSITY: f32 = 8.0;
+const BOULDER_KNOCKBACK: f32 = 12.0;
+const BOULDER_KNOCKBACK_LIFT: f32 = 6.0;
+const BOULDER_DEFLECT_MIN_SPEED: f32 = 5.0;
+const BOULDER_DEFLECT_STRENGTH: f32 = 0.6;
+
+pub struct BoulderPlugin;
+
+impl Plugin for BoulderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((emit_boulders, handle_boulder_collisions, handle_kill_zones).chain());
+    }
+}
+
+/// A heavy rolling sphere that hurts the player on contact
+#[derive(Component)]
+pub struct Boulder {
+    pub damage: u8,
+    pub radius: f32,
+}
+
+/// Periodically rolls a new boulder out from its position
+#[derive(Component)]
+pub struct BoulderEmitter {
+    pub timer: Timer,
+    pub radius: f32,
+    pub initial_velocity: Vec3,
+}
+
+impl BoulderEmitter {
+    pub fn new(interval: f32, radius: f32, initial_velocity: Vec3) -> Self {
+        BoulderEmitter {
+            timer: Timer::from_seconds(interval, TimerMode::Repeating),
+            radius,
+            initial_velocity,
+        }
+    }
+}
+
+/// Sensor volume that despawns boulders and respawns the player
+#[derive(Component)]
+pub struct KillZone;
+
+pub fn emit_boulders(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pools: ResMut<EntityPools>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut emitter_query: Query<(&GlobalTransform, &mut BoulderEmitter)>,
+    boulder_query: Query<&Boulder>,
+) {
+    for (transform, mut emitter) in &mut emitter_query {
+        emitter.timer.tick(time.delta());
+        if !emitter.timer.just_finished() {
+            continue;
+        }
+
+        let start = Transform::from_translation(transform.translation());
+        // A parked boulder from a different sized emitter has to be reshaped first
+        let parked = pools.take(&mut commands, PoolKind::Boulder);
+        if let Some(boulder) = parked {
+            let same_size = boulder_query
+                .get(boulder)
+                .map_or(false, |parked| parked.radius == emitter.radius);
+            let mut reused = commands.entity(boulder);
+            reused
+                .insert(start)
+                .insert(RigidBody::Dynamic)
+                .insert(Velocity::linear(emitter.initial_velocity));
+            if !same_size {
+                reused
+                    .insert(meshes.add(Mesh::from(shape::UVSphere {
+                        radius: emitter.radius,
+                        ..default()
+                    })))
+                    .insert(Collider::ball(emitter.radius))
+                    .insert(Boulder {
+                        damage: 1,
+                        radius: emitter.radius,
+                    });
+            }
+            continue;
+        }
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::UVSphere {
+                    radius: emitter.radius,
+                    ..default()
+                })),
+                material: materials.add(Color::DARK_GRAY.into()),
+                transform: start,
+                ..default()
+            })
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(emitter.radius))
+            .insert(ColliderMassProperties::Density(BOULDER_DENSITY))
+            .insert(Velocity::linear(emitter.initial_velocity))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(Boulder {
+                damage: 1,
+                radius: emitter.radius,
+            });
+    }
+}
+
+pub fn handle_boulder_collisions(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut damage_events: EventWriter<DamagePlayer>,
+    mut boulder_query: Query<(&Boulder, &Transform, &mut Velocity), Without<Item>>,
+    player_query: Query<&Transform, With<Player>>,
+    item_query: Query<(&Item, &Velocity), Without<Boulder>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let (boulder_entity, other_entity) = if boulder_query.contains(*e1) {
+            (*e1, *e2)
+        } else if boulder_query.contains(*e2) {
+            (*e2, *e1)
+        } else {
+            continue;
+        };
+
+        let boulder_result = boulder_query.get_mut(boulder_entity);
+        let Ok((boulder, boulder_transform, mut boulder_velocity)) = boulder_result else {continue;};
+
+        if let Ok(player_transform) = player_query.get(other_entity) {
+            let mut away = player_transform.translation - boulder_transform.translation;
+            away.y = 0.0;
+            let away = away.try_normalize().unwrap_or(Vec3::Z);
+            damage_events.send(DamagePlayer {
+                amount: boulder.damage,
+                knockback: away * BOULDER_KNOCKBACK + Vec3::Y * BOULDER_KNOCKBACK_LIFT,
+            });
+        } else if let Ok((item, item_velocity)) = item_query.get(other_entity) {
+            if matches!(item.item_id.get_weight(), Weight::Light)
+                || item_velocity.linvel.length() < BOULDER_DEFLECT_MIN_SPEED
+            {
+                continue;
+            }
+            boulder_velocity.linvel += item_velocity.linvel * BOULDER_DEFLECT_STRENGTH;
+        }
+    }
+}
+
+pub fn handle_kill_zones(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut respawn_events: EventWriter<RespawnPlayer>,
+    mut pools: ResMut<EntityPools>,
+    kill_zone_query: Query<(), With<KillZone>>,
+    boulder_query: Query<(), With<Boulder>>,
+    player_query: Query<(), With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let other_entity = if kill_zone_query.contains(*e1) {
+            *e2
+        } else if kill_zone_query.contains(*e2) {
+            *e1
+        } else {
+            continue;
+        };
+
+        if boulder_query.contains(other_entity) {
+            // Held still while parked, `emit_boulders` lets it roll again
+            commands.entity(other_entity).insert(RigidBody::Fixed);
+            pools.release(&mut commands, PoolKind::Boulder, other_entity);
+        } else if player_query.contains(other_entity) {
+            respawn_events.send(RespawnPlayer);
+        }
+    }
+}
@@ -1,63 +1,182 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
-pub fn spawn_chain(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
+const LINK_HALF_HEIGHT: f32 = 0.1;
+const LINK_RADIUS: f32 = 0.1;
+const LINK_SPACING: f32 = (LINK_HALF_HEIGHT + LINK_RADIUS) * 2.0;
+/// Short rope segments are still capsules rather than spheres
+const MIN_LINK_HALF_HEIGHT: f32 = 0.01;
+
+/// Collision group for chain links, hanging ones skip the player's body so a swinging player
+/// doesn't fight the chain
+pub const CHAIN_GROUP: Group = Group::GROUP_2;
+pub const PLAYER_GROUP: Group = Group::GROUP_1;
+
+/// One link in a chain or rope, `index` counts up from the end it was built from
+#[derive(Component)]
+pub struct ChainLink {
+    pub chain: Entity,
+    pub index: usize,
+}
+
+/// Holds every link of a chain or rope in order
+#[derive(Component)]
+pub struct ChainAnchor {
+    pub links: Vec<Entity>,
+}
+
+/// A dynamic body the player can stand on and jump from, even though it moves
+#[derive(Component)]
+pub struct Walkable;
+
+/// One end of a rope at the world space `point`, tied to `body` there unless it hangs loose
+#[derive(Clone, Copy, Debug)]
+pub struct RopeEnd {
+    pub body: Option<Entity>,
+    pub point: Vec3,
+    /// `point` in the body's own space, where its joint is anchored
+    pub local_anchor: Vec3,
+}
+
+impl RopeEnd {
+    pub fn tied(body: Entity, body_transform: &GlobalTransform, point: Vec3) -> Self {
+        RopeEnd {
+            body: Some(body),
+            point,
+            local_anchor: body_transform.affine().inverse().transform_point3(point),
+        }
+    }
+
+    pub fn loose(point: Vec3) -> Self {
+        RopeEnd {
+            body: None,
+            point,
+            local_anchor: Vec3::ZERO,
+        }
+    }
+}
+
+/// Spawns `segments` jointed links running from `from` to `to` as children of a new rope entity,
+/// returns the rope. Tied at both ends a rope is taut enough to walk along, so only a hanging one
+/// lets the player through to grab it
+pub fn build_rope(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    from: RopeEnd,
+    to: RopeEnd,
+    segments: usize,
+) -> Entity {
+    let segments = segments.max(1);
+    let span = to.point - from.point;
+    let half_length = span.length() / segments as f32 / 2.0;
+    let half_height = (half_length - LINK_RADIUS).max(MIN_LINK_HALF_HEIGHT);
+    let rotation =
+        Quat::from_rotation_arc(Vec3::NEG_Y, span.try_normalize().unwrap_or(Vec3::NEG_Y));
+    let walkable = from.body.is_some() && to.body.is_some();
+    let filter = if walkable {
+        Group::ALL
+    } else {
+        Group::ALL ^ PLAYER_GROUP
+    };
+
+    let rope = commands.spawn(SpatialBundle::default()).id();
+    let mesh_shape = shape::Capsule {
+        radius: LINK_RADIUS,
+        rings: 0,
+        depth: half_height * 2.0,
+        latitudes: 6,
+        longitudes: 12,
+        uv_profile: shape::CapsuleUvProfile::Aspect,
+    };
+    let mesh = meshes.add(Mesh::from(mesh_shape));
+    let material = materials.add(Color::WHITE.into());
+
+    let mut links = Vec::with_capacity(segments);
+    let mut previous_entity = None;
+    for index in 0..segments {
+        let center = from.point + span * ((index as f32 + 0.5) / segments as f32);
+        let (parent, anchor1) = match previous_entity {
+            Some(previous_entity) => (Some(previous_entity), Vec3::NEG_Y * half_length),
+            None => (from.body, from.local_anchor),
+        };
+
+        let mut link = commands.spawn(PbrBundle {
+            mesh: mesh.clone(),
+            material: material.clone(),
+            transform: Transform::from_translation(center).with_rotation(rotation),
+            ..default()
+        });
+        link.insert(RigidBody::Dynamic)
+            .insert(Velocity::default())
+            .insert(Collider::capsule_y(half_height, LINK_RADIUS))
+            .insert(CollisionGroups::new(CHAIN_GROUP, filter))
+            .insert(ChainLink { chain: rope, index });
+        if let Some(parent) = parent {
+            let joint = SphericalJointBuilder::new()
+                .local_anchor1(anchor1)
+                .local_anchor2(Vec3::Y * half_length);
+            link.insert(ImpulseJoint::new(parent, joint));
+        }
+        if walkable {
+            link.insert(Walkable);
+        }
+
+        let current_entity = link.id();
+        links.push(current_entity);
+        previous_entity = Some(current_entity);
+    }
+
+    // A body only holds one joint, so the far end is tied from a child of the last link
+    if let (Some(body), Some(last_link)) = (to.body, previous_entity) {
+        let joint = SphericalJointBuilder::new()
+            .local_anchor1(to.local_anchor)
+            .local_anchor2(Vec3::NEG_Y * half_length);
+        let tie = commands
+            .spawn(TransformBundle::default())
+            .insert(ImpulseJoint::new(body, joint))
+            .id();
+        commands.entity(last_link).add_child(tie);
+    }
+
     commands
+        .entity(rope)
+        .push_children(&links)
+        .insert(ChainAnchor { links });
+    rope
+}
+
+/// Spawns a fixed anchor block at `position` with `link_count` links hanging below it, returns the
+/// chain holding both
+pub fn spawn_chain(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    link_count: usize,
+) -> Entity {
+    let anchor = commands
         .spawn(PbrBundle {
             mesh: meshes.add(Mesh::from(shape::Box::new(1.0, 1.0, 1.0))),
             material: materials.add(Color::WHITE.into()),
-            transform: Transform::from_xyz(3.0, 5.0, 3.0),
+            transform: Transform::from_translation(position),
             ..default()
         })
-        .with_children(|parent| {
-            let mesh_shape = shape::Capsule {
-                radius: 0.1,
-                rings: 0,
-                depth: 0.2,
-                latitudes: 6,
-                longitudes: 12,
-                uv_profile: shape::CapsuleUvProfile::Aspect,
-            };
-
-            let mut previous_entity = parent
-                .spawn(PbrBundle {
-                    mesh: meshes.add(Mesh::from(mesh_shape)),
-                    material: materials.add(Color::WHITE.into()),
-                    transform: Transform::from_xyz(0.0, 0.0, 0.0),
-                    ..default()
-                })
-                .insert(RigidBody::Dynamic)
-                .insert(Collider::capsule_y(0.1, 0.1))
-                .id();
-
-            for i in 1..10 {
-                let i_float = i as f32;
-                let current_entity = parent
-                    .spawn(PbrBundle {
-                        mesh: meshes.add(Mesh::from(mesh_shape)),
-                        material: materials.add(Color::WHITE.into()),
-                        transform: Transform::from_xyz(0.0, i_float * -0.2, 0.0),
-                        ..default()
-                    })
-                    .insert(RigidBody::Dynamic)
-                    .insert(Collider::capsule_y(0.1, 0.1))
-                    .id();
-
-                let anchor1 = Vec3::Y * ((i_float - 1.0) * -0.2);
-                let anchor2 = Vec3::Y * (i_float * -0.2);
-                let joint = SphericalJointBuilder::new()
-                    .local_anchor1(anchor1)
-                    .local_anchor2(anchor2);
-
-                parent
-                    .spawn(RigidBody::Dynamic)
-                    .insert(ImpulseJoint::new(previous_entity, joint));
-
-                previous_entity = current_entity;
-            }
-        });
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(0.5, 0.5, 0.5))
+        .id();
+
+    let top = position + Vec3::NEG_Y * 0.5;
+    let bottom = top + Vec3::NEG_Y * LINK_SPACING * link_count as f32;
+    let from = RopeEnd::tied(anchor, &GlobalTransform::from_translation(position), top);
+    let chain = build_rope(
+        commands,
+        meshes,
+        materials,
+        from,
+        RopeEnd::loose(bottom),
+        link_count,
+    );
+    commands.entity(chain).add_child(anchor);
+    chain
 }
@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use std::time::Duration;
+
+use crate::{Grounded, Player, StandingOn};
+
+const CRUMBLE_SHAKE_SECONDS: f32 = 0.5;
+const CRUMBLE_SHAKE_AMOUNT: f32 = 0.06;
+/// How long the platform takes to fall and shrink away once its collider is gone
+const CRUMBLE_FALL_SECONDS: f32 = 0.8;
+const CRUMBLE_FALL_SPEED: f32 = 6.0;
+/// Extra room around the platform the player has to be out of before it comes back
+const CRUMBLE_RESPAWN_CLEARANCE: f32 = 1.0;
+
+pub struct CrumblePlugin;
+
+impl Plugin for CrumblePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((start_crumbling, animate_crumbling).chain());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CrumbleState {
+    Solid,
+    /// Stood on, about to give way
+    Shaking,
+    /// No longer solid, dropping and shrinking out of sight
+    Falling,
+    /// Hidden until its respawn timer runs out
+    Gone,
+}
+
+/// A platform that gives way a moment after the player lands on it and comes back later
+#[derive(Component)]
+pub struct Crumbling {
+    pub home: Vec3,
+    pub half_extents: Vec3,
+    pub respawn_seconds: f32,
+    pub state: CrumbleState,
+    pub timer: Timer,
+}
+
+impl Crumbling {
+    pub fn new(home: Vec3, half_extents: Vec3, respawn_seconds: f32) -> Self {
+        Crumbling {
+            home,
+            half_extents,
+            respawn_seconds,
+            state: CrumbleState::Solid,
+            timer: Timer::from_seconds(0.0, TimerMode::Once),
+        }
+    }
+
+    pub fn start(&mut self) {
+        if self.state == CrumbleState::Solid {
+            self.enter(CrumbleState::Shaking, CRUMBLE_SHAKE_SECONDS);
+        }
+    }
+
+    /// Runs the current stage's timer, returning the stage that was just moved on to if it ran out
+    pub fn advance(&mut self, delta: Duration) -> Option<CrumbleState> {
+        if self.state == CrumbleState::Solid {
+            return None;
+        }
+        self.timer.tick(delta);
+        if !self.timer.finished() {
+            return None;
+        }
+        match self.state {
+            CrumbleState::Shaking => self.enter(CrumbleState::Falling, CRUMBLE_FALL_SECONDS),
+            CrumbleState::Falling => self.enter(CrumbleState::Gone, self.respawn_seconds),
+            CrumbleState::Gone | CrumbleState::Solid => self.state = CrumbleState::Solid,
+        }
+        Some(self.state)
+    }
+
+    /// How far through the current stage the timer is, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        self.timer.percent()
+    }
+
+    /// Whether `position` is close enough that the platform would come back inside it
+    pub fn is_blocked_by(&self, position: Vec3) -> bool {
+        let offset = (position - self.home).abs();
+        offset
+            .cmple(self.half_extents + Vec3::splat(CRUMBLE_RESPAWN_CLEARANCE))
+            .all()
+    }
+
+    fn enter(&mut self, state: CrumbleState, seconds: f32) {
+        self.state = state;
+        self.timer = Timer::from_seconds(seconds, TimerMode::Once);
+    }
+}
+
+pub fn spawn_crumbling_platform(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    size: Vec3,
+    respawn_seconds: f32,
+) -> Entity {
+    let half_extents = size / 2.0;
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(Color::rgb(0.85, 0.72, 0.45).into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(Collider::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+        .insert(RigidBody::KinematicPositionBased)
+        .insert(Crumbling::new(position, half_extents, respawn_seconds))
+        .id()
+}
+
+fn start_crumbling(
+    player_query: Query<&StandingOn, (With<Player>, With<Grounded>)>,
+    mut crumbling_query: Query<&mut Crumbling>,
+) {
+    for standing_on in &player_query {
+        let Ok(mut crumbling) = crumbling_query.get_mut(standing_on.0) else {continue;};
+        crumbling.start();
+    }
+}
+
+/// The collider goes the moment shaking ends, `handle_grounded` skips disabled colliders so
+/// whoever was standing there starts falling that same frame
+fn animate_crumbling(
+    mut commands: Commands,
+    time: Res<Time>,
+    player_query: Query<&Transform, (With<Player>, Without<Crumbling>)>,
+    mut crumbling_query: Query<(Entity, &mut Transform, &mut Visibility, &mut Crumbling)>,
+) {
+    for (entity, mut transform, mut visibility, mut crumbling) in &mut crumbling_query {
+        let blocked = crumbling.state == CrumbleState::Gone
+            && player_query
+                .iter()
+                .any(|player_transform| crumbling.is_blocked_by(player_transform.translation));
+        if blocked {
+            continue;
+        }
+
+        match crumbling.advance(time.delta()) {
+            Some(CrumbleState::Falling) => {
+                commands.entity(entity).insert(ColliderDisabled);
+            }
+            Some(CrumbleState::Gone) => {
+                *visibility = Visibility::Hidden;
+            }
+            Some(CrumbleState::Solid) => {
+                *transform = Transform::from_translation(crumbling.home);
+                *visibility = Visibility::Inherited;
+                commands.entity(entity).remove::<ColliderDisabled>();
+            }
+            _ => {}
+        }
+
+        match crumbling.state {
+            CrumbleState::Shaking => {
+                let elapsed = crumbling.timer.elapsed_secs();
+                let shake = Vec3::new((elapsed * 90.0).sin(), 0.0, (elapsed * 77.0).cos());
+                transform.translation = crumbling.home + shake * CRUMBLE_SHAKE_AMOUNT;
+            }
+            CrumbleState::Falling => {
+                transform.translation.y -= CRUMBLE_FALL_SPEED * time.delta_seconds();
+                transform.scale = Vec3::splat(1.0 - crumbling.progress());
+            }
+            CrumbleState::Solid | CrumbleState::Gone => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn crumbling_runs_through_its_stages_and_comes_back() {
+        let mut crumbling = Crumbling::new(Vec3::ZERO, Vec3::ONE, 3.0);
+        assert_eq!(crumbling.advance(Duration::from_secs(10)), None);
+
+        crumbling.start();
+        assert_eq!(crumbling.state, CrumbleState::Shaking);
+        assert_eq!(crumbling.advance(Duration::from_millis(250)), None);
+        assert_eq!(
+            crumbling.advance(Duration::from_millis(250)),
+            Some(CrumbleState::Falling)
+        );
+        assert_eq!(
+            crumbling.advance(Duration::from_secs_f32(CRUMBLE_FALL_SECONDS)),
+            Some(CrumbleState::Gone)
+        );
+        assert_eq!(crumbling.advance(Duration::from_secs(2)), None);
+        assert_eq!(
+            crumbling.advance(Duration::from_secs(1)),
+            Some(CrumbleState::Solid)
+        );
+    }
+
+    #[test]
+    fn standing_on_a_shaking_platform_does_not_restart_it() {
+        let mut crumbling = Crumbling::new(Vec3::ZERO, Vec3::ONE, 3.0);
+        crumbling.start();
+        crumbling.advance(Duration::from_millis(400));
+        crumbling.start();
+        assert_eq!(
+            crumbling.advance(Duration::from_millis(100)),
+            Some(CrumbleState::Falling)
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{respawn_player, DamagePlayer, Grounded, Player, RespawnPlayer, PLAYER_HALF_HEIGHT};
+
+const CRUSHER_SLAM_SPEED: f32 = 18.0;
+const CRUSHER_RISE_SPEED: f32 = 3.0;
+const CRUSHER_KNOCKBACK: f32 = 10.0;
+const CRUSH_GAP: f32 = 0.4;
+const PENDULUM_KNOCKBACK: f32 = 14.0;
+const HAZARD_KNOCKBACK_LIFT: f32 = 5.0;
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                animate_crushers,
+                animate_pendulums,
+                crush_player,
+                handle_hazard_collisions,
+            )
+                .chain()
+                .before(respawn_player),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CrusherState {
+    Waiting,
+    Slamming,
+    Rising,
+}
+
+/// A block that waits at `top`, slams down to `bottom` and slowly rises again
+#[derive(Component)]
+pub struct Crusher {
+    pub top: Vec3,
+    pub bottom: Vec3,
+    pub half_extents: Vec3,
+    pub state: CrusherState,
+    pub wait: Timer,
+}
+
+impl Crusher {
+    pub fn new(top: Vec3, drop: f32, half_extents: Vec3, wait_seconds: f32) -> Self {
+        Crusher {
+            top,
+            bottom: top - Vec3::Y * drop,
+            half_extents,
+            state: CrusherState::Waiting,
+            wait: Timer::from_seconds(wait_seconds, TimerMode::Once),
+        }
+    }
+
+    pub fn is_slamming(&self) -> bool {
+        self.state == CrusherState::Slamming
+    }
+}
+
+/// Swings a weight hanging `length` below `pivot` back and forth around the horizontal `axis`
+#[derive(Component)]
+pub struct Pendulum {
+    pub pivot: Vec3,
+    pub axis: Vec3,
+    pub length: f32,
+    pub amplitude: f32,
+    pub period: f32,
+    pub elapsed: f32,
+}
+
+impl Pendulum {
+    pub fn angle(&self) -> f32 {
+        self.amplitude * (self.elapsed * std::f32::consts::TAU / self.period).sin()
+    }
+
+    /// The direction the weight is currently moving in, scaled by how fast it's swinging
+    pub fn swing_velocity(&self) -> Vec3 {
+        let frequency = std::f32::consts::TAU / self.period;
+        let angular_speed = self.amplitude * frequency * (self.elapsed * frequency).cos();
+        let arm = Quat::from_axis_angle(self.axis, self.angle()) * Vec3::NEG_Y * self.length;
+        self.axis.cross(arm) * angular_speed
+    }
+
+    pub fn transform(&self) -> Transform {
+        let rotation = Quat::from_axis_angle(self.axis, self.angle());
+        Transform::from_translation(self.pivot + rotation * Vec3::NEG_Y * self.length)
+            .with_rotation(rotation)
+    }
+}
+
+pub fn animate_crushers(time: Res<Time>, mut query: Query<(&mut Transform, &mut Crusher)>) {
+    for (mut transform, mut crusher) in &mut query {
+        match crusher.state {
+            CrusherState::Waiting => {
+                crusher.wait.tick(time.delta());
+                if crusher.wait.finished() {
+                    crusher.wait.reset();
+                    crusher.state = CrusherState::Slamming;
+                }
+            }
+            CrusherState::Slamming => {
+                transform.translation.y -= CRUSHER_SLAM_SPEED * time.delta_seconds();
+                if transform.translation.y <= crusher.bottom.y {
+                    transform.translation.y = crusher.bottom.y;
+                    crusher.state = CrusherState::Rising;
+                }
+            }
+            CrusherState::Rising => {
+                transform.translation.y += CRUSHER_RISE_SPEED * time.delta_seconds();
+                if transform.translation.y >= crusher.top.y {
+                    transform.translation.y = crusher.top.y;
+                    crusher.state = CrusherState::Waiting;
+                }
+            }
+        }
+    }
+}
+
+pub fn animate_pendulums(time: Res<Time>, mut query: Query<(&mut Transform, &mut Pendulum)>) {
+    for (mut transform, mut pendulum) in &mut query {
+        pendulum.elapsed += time.delta_seconds();
+        *transform = pendulum.transform();
+    }
+}
+
+/// A grounded player under a slamming crusher is respawned before the physics step can squeeze them
+/// through the floor
+pub fn crush_player(
+    mut respawn_events: EventWriter<RespawnPlayer>,
+    crusher_query: Query<(&Transform, &Crusher)>,
+    player_query: Query<&Transform, (With<Player>, With<Grounded>)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {return;};
+    let player_position = player_transform.translation;
+
+    for (crusher_transform, crusher) in &crusher_query {
+        if !crusher.is_slamming() {
+            continue;
+        }
+
+        let offset = player_position - crusher_transform.translation;
+        let under_crusher =
+            offset.x.abs() <= crusher.half_extents.x && offset.z.abs() <= crusher.half_extents.z;
+        // Standing on top of a crusher rides it down instead
+        let below_crusher = player_position.y < crusher_transform.translation.y;
+        let gap = (crusher_transform.translation.y - crusher.half_extents.y)
+            - (player_position.y + PLAYER_HALF_HEIGHT);
+
+        if under_crusher && below_crusher && (0.0..CRUSH_GAP).contains(&gap) {
+            respawn_events.send(RespawnPlayer);
+            return;
+        }
+    }
+}
+
+pub fn handle_hazard_collisions(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut damage_events: EventWriter<DamagePlayer>,
+    crusher_query: Query<&Transform, With<Crusher>>,
+    pendulum_query: Query<&Pendulum>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let (hazard_entity, player_transform) = if let Ok(transform) = player_query.get(*e1) {
+            (*e2, transform)
+        } else if let Ok(transform) = player_query.get(*e2) {
+            (*e1, transform)
+        } else {
+            continue;
+        };
+
+        if let Ok(crusher_transform) = crusher_query.get(hazard_entity) {
+            let mut away = player_transform.translation - crusher_transform.translation;
+            away.y = 0.0;
+            let away = away.try_normalize().unwrap_or(Vec3::Z);
+            damage_events.send(DamagePlayer {
+                amount: 1,
+                knockback: away * CRUSHER_KNOCKBACK + Vec3::Y * HAZARD_KNOCKBACK_LIFT,
+            });
+        } else if let Ok(pendulum) = pendulum_query.get(hazard_entity) {
+            let mut swing = pendulum.swing_velocity();
+            swing.y = 0.0;
+            let swing = swing.try_normalize().unwrap_or(Vec3::Z);
+            damage_events.send(DamagePlayer {
+                amount: 1,
+                knockback: swing * PENDULUM_KNOCKBACK + Vec3::Y * HAZARD_KNOCKBACK_LIFT,
+            });
+        }
+    }
+}
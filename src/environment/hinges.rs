@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+const DOOR_DENSITY: f32 = 0.3;
+const DOOR_SPRING_STIFFNESS: f32 = 4.0;
+const DOOR_SPRING_DAMPING: f32 = 1.0;
+const DOOR_MAX_ANGLE: f32 = 1.7;
+const SEESAW_DENSITY: f32 = 0.5;
+const SEESAW_MAX_TILT: f32 = 0.45;
+
+/// A door hung on a vertical hinge along its left edge, it swings open when shoved and springs
+/// closed again
+#[derive(Component)]
+pub struct SwingDoor;
+
+/// A plank balanced on a horizontal pivot at its middle
+#[derive(Component)]
+pub struct Seesaw;
+
+/// `position` is the center of the closed door, returns the hinge with the door as its child
+pub fn spawn_swing_door(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    size: Vec3,
+) -> Entity {
+    let half_extents = size / 2.0;
+    let hinge = commands
+        .spawn(SpatialBundle::from_transform(Transform::from_translation(
+            position - Vec3::X * half_extents.x,
+        )))
+        .insert(RigidBody::Fixed)
+        .id();
+
+    let joint = RevoluteJointBuilder::new(Vec3::Y)
+        .local_anchor1(Vec3::ZERO)
+        .local_anchor2(Vec3::X * -half_extents.x)
+        .limits([-DOOR_MAX_ANGLE, DOOR_MAX_ANGLE])
+        .motor_position(0.0, DOOR_SPRING_STIFFNESS, DOOR_SPRING_DAMPING);
+
+    let door = commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(Color::BISQUE.into()),
+            transform: Transform::from_translation(Vec3::X * half_extents.x),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+        .insert(ColliderMassProperties::Density(DOOR_DENSITY))
+        .insert(Velocity::default())
+        .insert(ImpulseJoint::new(hinge, joint))
+        .insert(SwingDoor)
+        .id();
+
+    commands.entity(hinge).add_child(door);
+    hinge
+}
+
+/// `position` is the top of the pivot post, the plank tilts around the X axis, returns the pivot
+/// with the plank as its child
+pub fn spawn_seesaw(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    size: Vec3,
+) -> Entity {
+    let half_extents = size / 2.0;
+    let pivot = commands
+        .spawn(SpatialBundle::from_transform(Transform::from_translation(
+            position,
+        )))
+        .insert(RigidBody::Fixed)
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(0.6, position.y, 0.6))),
+                material: materials.add(Color::DARK_GRAY.into()),
+                transform: Transform::from_xyz(0.0, -position.y / 2.0, 0.0),
+                ..default()
+            });
+        })
+        .id();
+
+    let joint = RevoluteJointBuilder::new(Vec3::X)
+        .local_anchor1(Vec3::ZERO)
+        .local_anchor2(Vec3::Y * -half_extents.y)
+        .limits([-SEESAW_MAX_TILT, SEESAW_MAX_TILT]);
+
+    let plank = commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(Color::BISQUE.into()),
+            transform: Transform::from_xyz(0.0, half_extents.y, 0.0),
+            ..default()
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+        .insert(ColliderMassProperties::Density(SEESAW_DENSITY))
+        .insert(Velocity::default())
+        .insert(ImpulseJoint::new(pivot, joint))
+        .insert(Seesaw)
+        .id();
+
+    commands.entity(pivot).add_child(plank);
+    pivot
+}
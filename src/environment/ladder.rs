@@ -0,0 +1,405 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{Drift, Momentum, Player, PlayerAction};
+
+const LADDER_WIDTH: f32 = 1.0;
+const LADDER_RUNG_SPACING: f32 = 0.5;
+/// How far out from the ladder the climber's center stays
+const LADDER_CLIMB_DISTANCE: f32 = 0.6;
+const LADDER_CLIMB_SPEED: f32 = 3.0;
+/// The climber's center stops this far under the top, with their hands on the last rung
+const LADDER_TOP_REACH: f32 = 1.0;
+/// How far onto the platform above the player is set down after climbing off the top
+const LADDER_TOP_STEP: f32 = 1.0;
+/// Height of the player's center over whatever they stand on
+const PLAYER_STAND_HEIGHT: f32 = 1.0;
+const LADDER_VOLUME_HALF_EXTENTS: Vec3 = Vec3::new(0.8, 1.0, 0.8);
+/// Walking this fast towards the ladder inside one of its volumes gets on it
+const LADDER_MOUNT_SPEED: f32 = 1.0;
+const LADDER_MOUNT_SECONDS: f32 = 0.3;
+const LADDER_DISMOUNT_SECONDS: f32 = 0.45;
+const LADDER_JUMP_OFF_SPEED: f32 = 5.0;
+
+pub struct LadderPlugin;
+
+impl Plugin for LadderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((mount_ladders, climb_ladders, play_ladder_transitions).chain());
+    }
+}
+
+/// A ladder standing on `bottom`, climbed facing `facing` until the platform `height` above
+#[derive(Component)]
+pub struct Ladder {
+    pub bottom: Vec3,
+    pub height: f32,
+    pub facing: Vec3,
+}
+
+impl Ladder {
+    pub fn new(bottom: Vec3, height: f32, facing: Vec3) -> Self {
+        Ladder {
+            bottom,
+            height,
+            facing: Vec3::new(facing.x, 0.0, facing.z).normalize_or_zero(),
+        }
+    }
+
+    pub fn top(&self) -> Vec3 {
+        self.bottom + Vec3::Y * self.height
+    }
+
+    /// How high the climber's feet can go before they have to climb off the top
+    pub fn max_climb(&self) -> f32 {
+        (self.height - LADDER_TOP_REACH).max(0.0)
+    }
+
+    /// Where the climber's center is once their feet are `climb` up the ladder
+    pub fn climb_position(&self, climb: f32) -> Vec3 {
+        self.bottom - self.facing * LADDER_CLIMB_DISTANCE
+            + Vec3::Y * (PLAYER_STAND_HEIGHT + climb.clamp(0.0, self.max_climb()))
+    }
+
+    /// Where the player stands after climbing off the top, clear of the edge
+    pub fn top_landing(&self) -> Vec3 {
+        self.top() + self.facing * LADDER_TOP_STEP + Vec3::Y * PLAYER_STAND_HEIGHT
+    }
+
+    pub fn rotation(&self) -> Quat {
+        Transform::IDENTITY
+            .looking_at(self.facing, Vec3::Y)
+            .rotation
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LadderEnd {
+    Top,
+    Bottom,
+}
+
+/// Walking into one of these towards the ladder gets the player on it at that end
+#[derive(Component)]
+pub struct LadderVolume {
+    pub ladder: Entity,
+    pub end: LadderEnd,
+}
+
+/// The player is on `ladder` with their feet `climb` above its bottom
+#[derive(Component)]
+pub struct Climbing {
+    pub ladder: Entity,
+    pub climb: f32,
+}
+
+/// Carries the player between the ladder and the ground at one of its ends, they're let go of
+/// the ladder at the end if `dismount`
+#[derive(Component)]
+pub struct LadderTransition {
+    from: Vec3,
+    to: Vec3,
+    timer: Timer,
+    dismount: bool,
+}
+
+impl LadderTransition {
+    fn new(from: Vec3, to: Vec3, seconds: f32, dismount: bool) -> Self {
+        LadderTransition {
+            from,
+            to,
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            dismount,
+        }
+    }
+}
+
+/// A point on the way from `from` to `to`, `t` of the way there. Going up it rises before it
+/// moves across and going down it moves across first, so the path hooks over the ladder's top
+/// instead of catching on the edge
+pub fn ladder_transition_point(from: Vec3, to: Vec3, t: f32) -> Vec3 {
+    let t = t.clamp(0.0, 1.0);
+    let (vertical, horizontal) = if to.y >= from.y {
+        (t * (2.0 - t), t * t)
+    } else {
+        (t * t, t * (2.0 - t))
+    };
+    let across = from.lerp(to, horizontal);
+    Vec3::new(across.x, from.y + (to.y - from.y) * vertical, across.z)
+}
+
+/// Up and down on the stick or keys, as climbing speed from -1 to 1
+fn climb_input(action: &ActionState<PlayerAction>) -> f32 {
+    if action.pressed(PlayerAction::Up) {
+        1.0
+    } else if action.pressed(PlayerAction::Down) {
+        -1.0
+    } else {
+        action
+            .clamped_axis_pair(PlayerAction::Move)
+            .map_or(0.0, |axis_pair| axis_pair.y())
+    }
+}
+
+pub fn spawn_ladder(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    bottom: Vec3,
+    height: f32,
+    facing: Vec3,
+) -> Entity {
+    let ladder = Ladder::new(bottom, height, facing);
+    let material = materials.add(Color::rgb(0.55, 0.35, 0.2).into());
+    let rail = meshes.add(Mesh::from(shape::Box::new(0.1, height, 0.1)));
+    let rung = meshes.add(Mesh::from(shape::Box::new(LADDER_WIDTH, 0.08, 0.08)));
+    // The volumes are children, so placed relative to the ladder's base and turn
+    let to_local = |position: Vec3| ladder.rotation().inverse() * (position - bottom);
+    let volumes = [
+        (LadderEnd::Bottom, to_local(ladder.climb_position(0.0))),
+        (LadderEnd::Top, to_local(ladder.top_landing())),
+    ];
+
+    let entity = commands
+        .spawn(SpatialBundle::from_transform(
+            Transform::from_translation(bottom).with_rotation(ladder.rotation()),
+        ))
+        .with_children(|parent| {
+            for side in [-1.0, 1.0] {
+                parent.spawn(PbrBundle {
+                    mesh: rail.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(side * LADDER_WIDTH / 2.0, height / 2.0, 0.0),
+                    ..default()
+                });
+            }
+            let rungs = (height / LADDER_RUNG_SPACING) as usize;
+            for index in 1..=rungs {
+                parent.spawn(PbrBundle {
+                    mesh: rung.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(0.0, index as f32 * LADDER_RUNG_SPACING, 0.0),
+                    ..default()
+                });
+            }
+        })
+        .insert(ladder)
+        .id();
+
+    commands.entity(entity).with_children(|parent| {
+        for (end, position) in volumes {
+            parent
+                .spawn(TransformBundle::from_transform(
+                    Transform::from_translation(position),
+                ))
+                .insert(Collider::cuboid(
+                    LADDER_VOLUME_HALF_EXTENTS.x,
+                    LADDER_VOLUME_HALF_EXTENTS.y,
+                    LADDER_VOLUME_HALF_EXTENTS.z,
+                ))
+                .insert(Sensor)
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(LadderVolume {
+                    ladder: entity,
+                    end,
+                });
+        }
+    });
+    entity
+}
+
+/// Gets on from the bottom walking into the ladder, or from the top walking back off the edge
+pub fn mount_ladders(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut volumes_touched: Local<Vec<Entity>>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Velocity,
+            &mut GravityScale,
+            &mut Momentum,
+            &mut Drift,
+            Option<&Climbing>,
+        ),
+        With<Player>,
+    >,
+    volume_query: Query<&LadderVolume>,
+    ladder_query: Query<&Ladder>,
+) {
+    let Ok((player_entity, transform, mut velocity, mut gravity_scale, mut momentum, mut drift, climbing)) = player_query.get_single_mut() else {return;};
+    for collision_event in collision_events.iter() {
+        let (CollisionEvent::Started(e1, e2, _) | CollisionEvent::Stopped(e1, e2, _)) =
+            collision_event;
+        let volume = if *e1 == player_entity {
+            *e2
+        } else if *e2 == player_entity {
+            *e1
+        } else {
+            continue;
+        };
+        if !volume_query.contains(volume) {
+            continue;
+        }
+        volumes_touched.retain(|entity| *entity != volume);
+        if matches!(collision_event, CollisionEvent::Started(..)) {
+            volumes_touched.push(volume);
+        }
+    }
+    if climbing.is_some() {
+        return;
+    }
+
+    let flat_velocity = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+    for volume in volumes_touched.iter() {
+        let Ok(volume) = volume_query.get(*volume) else {continue;};
+        let Ok(ladder) = ladder_query.get(volume.ladder) else {continue;};
+        let toward_ladder = flat_velocity.dot(ladder.facing);
+        let climb = match volume.end {
+            LadderEnd::Bottom if toward_ladder > LADDER_MOUNT_SPEED => 0.0,
+            LadderEnd::Top if toward_ladder < -LADDER_MOUNT_SPEED => ladder.max_climb(),
+            _ => continue,
+        };
+
+        velocity.linvel = Vec3::ZERO;
+        gravity_scale.0 = 0.0;
+        momentum.reset();
+        drift.reset();
+        commands
+            .entity(player_entity)
+            .insert(Climbing {
+                ladder: volume.ladder,
+                climb,
+            })
+            .insert(LadderTransition::new(
+                transform.translation,
+                ladder.climb_position(climb),
+                LADDER_MOUNT_SECONDS,
+                false,
+            ));
+        break;
+    }
+}
+
+pub fn climb_ladders(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut GravityScale,
+            &mut Climbing,
+            &ActionState<PlayerAction>,
+        ),
+        (With<Player>, Without<LadderTransition>),
+    >,
+    ladder_query: Query<&Ladder>,
+) {
+    for (player_entity, mut transform, mut velocity, mut gravity_scale, mut climbing, action) in
+        &mut player_query
+    {
+        let Ok(ladder) = ladder_query.get(climbing.ladder) else {
+            gravity_scale.0 = 1.0;
+            commands.entity(player_entity).remove::<Climbing>();
+            continue;
+        };
+
+        if action.just_pressed(PlayerAction::Jump) {
+            velocity.linvel = (Vec3::Y - ladder.facing) * LADDER_JUMP_OFF_SPEED;
+            gravity_scale.0 = 1.0;
+            commands.entity(player_entity).remove::<Climbing>();
+            continue;
+        }
+
+        let input = climb_input(action);
+        climbing.climb = (climbing.climb + input * LADDER_CLIMB_SPEED * time.delta_seconds())
+            .clamp(0.0, ladder.max_climb());
+        velocity.linvel = Vec3::ZERO;
+        transform.rotation = ladder.rotation();
+
+        if climbing.climb >= ladder.max_climb() && input > 0.0 {
+            // Carried up and over the top rather than left to clamber against the edge
+            commands.entity(player_entity).insert(LadderTransition::new(
+                transform.translation,
+                ladder.top_landing(),
+                LADDER_DISMOUNT_SECONDS,
+                true,
+            ));
+        } else if climbing.climb <= 0.0 && input < 0.0 {
+            transform.translation = ladder.climb_position(0.0);
+            gravity_scale.0 = 1.0;
+            commands.entity(player_entity).remove::<Climbing>();
+        } else {
+            transform.translation = ladder.climb_position(climbing.climb);
+        }
+    }
+}
+
+pub fn play_ladder_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut GravityScale,
+            &Climbing,
+            &mut LadderTransition,
+        ),
+        With<Player>,
+    >,
+    ladder_query: Query<&Ladder>,
+) {
+    for (player_entity, mut transform, mut velocity, mut gravity_scale, climbing, mut transition) in
+        &mut player_query
+    {
+        transition.timer.tick(time.delta());
+        transform.translation =
+            ladder_transition_point(transition.from, transition.to, transition.timer.percent());
+        velocity.linvel = Vec3::ZERO;
+        if let Ok(ladder) = ladder_query.get(climbing.ladder) {
+            transform.rotation = ladder.rotation();
+        }
+        if !transition.timer.finished() {
+            continue;
+        }
+
+        commands.entity(player_entity).remove::<LadderTransition>();
+        if transition.dismount {
+            gravity_scale.0 = 1.0;
+            commands.entity(player_entity).remove::<Climbing>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn transitions_rise_before_crossing_the_top() {
+        let from = Vec3::new(0.0, 0.0, 0.0);
+        let to = Vec3::new(2.0, 2.0, 0.0);
+        let halfway = ladder_transition_point(from, to, 0.5);
+        assert!(halfway.y > 1.0);
+        assert!(halfway.x < 1.0);
+        assert_eq!(ladder_transition_point(from, to, 1.0), to);
+
+        let down = ladder_transition_point(to, from, 0.5);
+        assert!(down.x < 1.0);
+        assert!(down.y > 1.0);
+    }
+
+    #[test]
+    fn climbing_stays_on_the_ladder() {
+        let ladder = Ladder::new(Vec3::ZERO, 4.0, Vec3::Z);
+        assert_eq!(ladder.max_climb(), 3.0);
+        assert_eq!(ladder.climb_position(10.0), Vec3::new(0.0, 4.0, -0.6));
+        assert_eq!(ladder.top_landing(), Vec3::new(0.0, 5.0, 1.0));
+    }
+}
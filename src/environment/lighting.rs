@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TimeOfDay::default())
+            .insert_resource(AmbientLight {
+                color: Color::ANTIQUE_WHITE,
+                brightness: 0.45,
+            })
+            .add_startup_system(spawn_sun)
+            .add_systems(
+                (
+                    advance_time_of_day,
+                    update_sun,
+                    update_ambient_light,
+                    handle_night_only_entities,
+                )
+                    .chain(),
+            );
+    }
+}
+
+const NIGHT_AMBIENT_COLOR: Color = Color::rgb(0.25, 0.3, 0.6);
+const DAY_AMBIENT_COLOR: Color = Color::ANTIQUE_WHITE;
+const SUNSET_COLOR: Color = Color::rgb(1.0, 0.55, 0.3);
+const NIGHT_AMBIENT_BRIGHTNESS: f32 = 0.1;
+const DAY_AMBIENT_BRIGHTNESS: f32 = 0.45;
+const SUN_ILLUMINANCE: f32 = 20000.0;
+
+/// The current hour of the in game day, from 0.0 to 24.0
+#[derive(Resource)]
+pub struct TimeOfDay {
+    pub hours: f32,
+    pub day_length_seconds: f32,
+    pub paused: bool,
+}
+
+impl TimeOfDay {
+    pub fn advance(&mut self, seconds: f32) {
+        if !self.paused {
+            self.hours = (self.hours + seconds * 24.0 / self.day_length_seconds).rem_euclid(24.0);
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.hours < 6.0 || self.hours >= 19.0
+    }
+
+    /// Angle of the sun above the horizon in radians, negative while the sun is down
+    pub fn sun_angle(&self) -> f32 {
+        ((self.hours - 6.0) / 24.0) * std::f32::consts::TAU
+    }
+
+    /// How much daylight there is from 0.0 at night to 1.0 at noon
+    pub fn daylight(&self) -> f32 {
+        self.sun_angle().sin().max(0.0)
+    }
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        TimeOfDay {
+            hours: 10.0,
+            day_length_seconds: 600.0,
+            paused: false,
+        }
+    }
+}
+
+/// Entities that only exist in the world while it is night
+#[derive(Component)]
+pub struct RequiresNight;
+
+#[derive(Component)]
+pub struct Sun;
+
+pub fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let [r1, g1, b1, a1] = from.as_rgba_f32();
+    let [r2, g2, b2, a2] = to.as_rgba_f32();
+    let t = t.clamp(0.0, 1.0);
+    Color::rgba(
+        r1 + (r2 - r1) * t,
+        g1 + (g2 - g1) * t,
+        b1 + (b2 - b1) * t,
+        a1 + (a2 - a1) * t,
+    )
+}
+
+fn spawn_sun(mut commands: Commands) {
+    commands
+        .spawn(DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: SUN_ILLUMINANCE,
+                shadows_enabled: true,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Sun);
+}
+
+pub fn advance_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    time_of_day.advance(time.delta_seconds());
+}
+
+pub fn update_sun(
+    time_of_day: Res<TimeOfDay>,
+    mut query: Query<(&mut Transform, &mut DirectionalLight), With<Sun>>,
+) {
+    for (mut transform, mut light) in &mut query {
+        let daylight = time_of_day.daylight();
+        transform.rotation =
+            Quat::from_rotation_y(0.6) * Quat::from_rotation_x(-time_of_day.sun_angle());
+        light.illuminance = SUN_ILLUMINANCE * daylight;
+        light.color = lerp_color(SUNSET_COLOR, Color::WHITE, daylight * 2.0);
+    }
+}
+
+pub fn update_ambient_light(time_of_day: Res<TimeOfDay>, mut ambient_light: ResMut<AmbientLight>) {
+    let daylight = time_of_day.daylight();
+    ambient_light.color = lerp_color(NIGHT_AMBIENT_COLOR, DAY_AMBIENT_COLOR, daylight * 2.0);
+    ambient_light.brightness =
+        NIGHT_AMBIENT_BRIGHTNESS + (DAY_AMBIENT_BRIGHTNESS - NIGHT_AMBIENT_BRIGHTNESS) * daylight;
+}
+
+pub fn handle_night_only_entities(
+    mut commands: Commands,
+    time_of_day: Res<TimeOfDay>,
+    mut query: Query<(Entity, &mut Visibility, Option<&ColliderDisabled>), With<RequiresNight>>,
+) {
+    let is_night = time_of_day.is_night();
+    for (entity, mut visibility, collider_disabled) in &mut query {
+        if is_night {
+            *visibility = Visibility::Inherited;
+            if collider_disabled.is_some() {
+                commands.entity(entity).remove::<ColliderDisabled>();
+            }
+        } else {
+            *visibility = Visibility::Hidden;
+            if collider_disabled.is_none() {
+                commands.entity(entity).insert(ColliderDisabled);
+            }
+        }
+    }
+}
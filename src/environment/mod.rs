@@ -3,3 +3,51 @@ pub use wall::*;
 
 pub mod chain;
 pub use chain::*;
+
+pub mod lighting;
+pub use lighting::*;
+
+pub mod sky;
+pub use sky::*;
+
+pub mod weather;
+pub use weather::*;
+
+pub mod surface;
+pub use surface::*;
+
+pub mod quicksand;
+pub use quicksand::*;
+
+pub mod rails;
+pub use rails::*;
+
+pub mod boulder;
+pub use boulder::*;
+
+pub mod hazards;
+pub use hazards::*;
+
+pub mod prefabs;
+pub use prefabs::*;
+
+pub mod pressure_plate;
+pub use pressure_plate::*;
+
+pub mod platforms;
+pub use platforms::*;
+
+pub mod hinges;
+pub use hinges::*;
+pub mod zipline;
+pub use zipline::*;
+pub mod ladder;
+pub use ladder::*;
+pub mod crumble;
+pub use crumble::*;
+pub mod water;
+pub use water::*;
+pub mod wind_streaks;
+pub use wind_streaks::*;
+pub mod turret;
+pub use turret::*;
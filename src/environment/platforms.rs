@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{LevelSignal, Player, StandingOn, PLAYER_HEAD_HEIGHT};
+
+const ELEVATOR_SAFETY_GAP: f32 = 0.5;
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((move_platforms, call_elevators, move_elevators, carry_riders).chain());
+    }
+}
+
+/// How far a kinematic platform moved this frame, anything standing on it is moved along with it
+#[derive(Component, Default)]
+pub struct PlatformDelta(pub Vec3);
+
+/// A kinematic platform that travels back and forth along `points` while active
+#[derive(Component)]
+pub struct MovingPlatform {
+    pub points: Vec<Vec3>,
+    pub speed: f32,
+    pub active: bool,
+    pub target: usize,
+    pub forward: bool,
+}
+
+impl MovingPlatform {
+    pub fn new(points: Vec<Vec3>, speed: f32, active: bool) -> Self {
+        MovingPlatform {
+            points,
+            speed,
+            active,
+            target: 1,
+            forward: true,
+        }
+    }
+
+    fn advance_target(&mut self) {
+        let last = self.points.len().saturating_sub(1);
+        if self.forward && self.target >= last {
+            self.forward = false;
+        } else if !self.forward && self.target == 0 {
+            self.forward = true;
+        }
+
+        self.target = if self.forward {
+            (self.target + 1).min(last)
+        } else {
+            self.target.saturating_sub(1)
+        };
+    }
+}
+
+/// Sending `on` sends the elevator to the floor at index `stop`
+#[derive(Deserialize, Clone, Debug)]
+pub struct ElevatorCall {
+    pub on: String,
+    pub stop: usize,
+}
+
+/// A platform that moves straight up and down between the heights in `stops` when called
+#[derive(Component)]
+pub struct Elevator {
+    pub stops: Vec<f32>,
+    pub calls: Vec<ElevatorCall>,
+    pub speed: f32,
+    pub half_extents: Vec3,
+    pub current: usize,
+    pub target: usize,
+}
+
+impl Elevator {
+    pub fn new(stops: Vec<f32>, calls: Vec<ElevatorCall>, speed: f32, half_extents: Vec3) -> Self {
+        Elevator {
+            stops,
+            calls,
+            speed,
+            half_extents,
+            current: 0,
+            target: 0,
+        }
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.current != self.target
+    }
+
+    /// Heads back to the stop it last left, `current` becomes the one it was heading for
+    pub fn turn_back(&mut self) {
+        std::mem::swap(&mut self.current, &mut self.target);
+    }
+}
+
+pub fn move_platforms(
+    time: Res<Time>,
+    mut platform_query: Query<(&mut Transform, &mut MovingPlatform, &mut PlatformDelta)>,
+) {
+    for (mut transform, mut platform, mut delta) in &mut platform_query {
+        delta.0 = Vec3::ZERO;
+        if !platform.active || platform.points.len() < 2 {
+            continue;
+        }
+
+        let start = transform.translation;
+        let target = platform.points[platform.target];
+        let remaining = target - transform.translation;
+        let step = platform.speed * time.delta_seconds();
+        if remaining.length() <= step {
+            transform.translation = target;
+            platform.advance_target();
+        } else {
+            transform.translation += remaining.normalize() * step;
+        }
+        delta.0 = transform.translation - start;
+    }
+}
+
+pub fn call_elevators(
+    mut signals: EventReader<LevelSignal>,
+    mut elevator_query: Query<&mut Elevator>,
+) {
+    for signal in signals.iter().filter(|signal| signal.active) {
+        for mut elevator in &mut elevator_query {
+            let call = elevator
+                .calls
+                .iter()
+                .find(|call| call.on == signal.name)
+                .map(|call| call.stop);
+            if let Some(stop) = call {
+                if stop < elevator.stops.len() && !elevator.is_moving() {
+                    elevator.target = stop;
+                }
+            }
+        }
+    }
+}
+
+/// Elevators heading down turn back if the player is underneath rather than pressing them into
+/// the floor
+pub fn move_elevators(
+    time: Res<Time>,
+    mut elevator_query: Query<
+        (Entity, &mut Transform, &mut Elevator, &mut PlatformDelta),
+        Without<Player>,
+    >,
+    player_query: Query<(&Transform, Option<&StandingOn>), With<Player>>,
+) {
+    let player = player_query.get_single().ok();
+
+    for (entity, mut transform, mut elevator, mut delta) in &mut elevator_query {
+        delta.0 = Vec3::ZERO;
+        if !elevator.is_moving() {
+            continue;
+        }
+
+        let target_height = elevator.stops[elevator.target];
+        let direction = (target_height - transform.translation.y).signum();
+
+        if direction < 0.0 {
+            if let Some((player_transform, standing_on)) = player {
+                let riding = standing_on.map_or(false, |standing_on| standing_on.0 == entity);
+                let offset = player_transform.translation - transform.translation;
+                let underneath = offset.x.abs() <= elevator.half_extents.x
+                    && offset.z.abs() <= elevator.half_extents.z
+                    && offset.y < 0.0;
+                let gap = (transform.translation.y - elevator.half_extents.y)
+                    - (player_transform.translation.y + PLAYER_HEAD_HEIGHT);
+                if !riding && underneath && gap < ELEVATOR_SAFETY_GAP {
+                    elevator.turn_back();
+                    continue;
+                }
+            }
+        }
+
+        let start = transform.translation;
+        let step = elevator.speed * time.delta_seconds();
+        if (target_height - transform.translation.y).abs() <= step {
+            transform.translation.y = target_height;
+            elevator.current = elevator.target;
+        } else {
+            transform.translation.y += direction * step;
+        }
+        delta.0 = transform.translation - start;
+    }
+}
+
+pub fn carry_riders(
+    platform_query: Query<&PlatformDelta>,
+    mut rider_query: Query<(&mut Transform, &StandingOn)>,
+) {
+    for (mut transform, standing_on) in &mut rider_query {
+        if let Ok(delta) = platform_query.get(standing_on.0) {
+            transform.translation += delta.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn elevators_turn_back_over_the_player() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_system(move_elevators);
+        let half_extents = Vec3::new(2.0, 0.25, 2.0);
+        let mut elevator = Elevator::new(vec![0.0, 5.0], Vec::new(), 2.0, half_extents);
+        elevator.current = 1;
+        let elevator = app
+            .world
+            .spawn((
+                Transform::from_xyz(0.0, 5.0, 0.0),
+                elevator,
+                PlatformDelta::default(),
+            ))
+            .id();
+        app.world.spawn((Player, Transform::from_xyz(0.0, 3.5, 0.0)));
+
+        app.update();
+        let turned = app.world.get::<Elevator>(elevator).unwrap();
+        assert!(turned.is_moving());
+        assert_eq!(turned.target, 1);
+
+        for _ in 0..5 {
+            app.update();
+        }
+        assert!(app.world.get::<Transform>(elevator).unwrap().translation.y >= 5.0);
+    }
+}
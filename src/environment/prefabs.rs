@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    AmbientSound, Gust, Ledge, MovingPlatform, PlatformDelta, SoundEmitter, SurfaceType, Wall,
+    WindZone,
+};
+
+const LEDGE_THICKNESS: f32 = 0.25;
+const LEDGE_OVERHANG: f32 = 0.1;
+
+/// A reusable bundle of entities that can be placed from code or level data
+pub trait Prefab {
+    fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Entity;
+}
+
+/// A solid box the player can wall jump off
+pub struct WallPrefab {
+    pub position: Vec3,
+    pub size: Vec3,
+    pub color: Color,
+}
+
+impl WallPrefab {
+    pub fn new(position: Vec3, size: Vec3) -> Self {
+        WallPrefab {
+            position,
+            size,
+            color: Color::PURPLE,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl Prefab for WallPrefab {
+    fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Entity {
+        let size = self.size;
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                material: materials.add(self.color.into()),
+                transform: Transform::from_translation(self.position),
+                ..default()
+            })
+            .insert(Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+            .insert(Wall)
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+/// A wall block with a `Ledge` sensor along its top edge for the player to grab
+pub struct LedgeBlockPrefab {
+    pub wall: WallPrefab,
+    pub surface: SurfaceType,
+    pub ledge: bool,
+}
+
+impl LedgeBlockPrefab {
+    pub fn new(position: Vec3, size: Vec3) -> Self {
+        LedgeBlockPrefab {
+            wall: WallPrefab::new(position, size).with_color(Color::BLUE),
+            surface: SurfaceType::default(),
+            ledge: true,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.wall.color = color;
+        self
+    }
+
+    pub fn with_surface(mut self, surface: SurfaceType) -> Self {
+        self.surface = surface;
+        self
+    }
+
+    pub fn with_ledge(mut self, ledge: bool) -> Self {
+        self.ledge = ledge;
+        self
+    }
+}
+
+impl Prefab for LedgeBlockPrefab {
+    fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Entity {
+        let block = self.wall.spawn(commands, meshes, materials);
+        commands.entity(block).insert(self.surface);
+
+        if self.ledge {
+            let size = self.wall.size;
+            commands.entity(block).with_children(|parent| {
+                parent
+                    .spawn(TransformBundle {
+                        local: Transform::from_xyz(0.0, size.y / 2.0 - LEDGE_THICKNESS, 0.0),
+                        ..default()
+                    })
+                    .insert(Ledge)
+                    .insert(Collider::cuboid(
+                        size.x / 2.0 + LEDGE_OVERHANG,
+                        LEDGE_THICKNESS,
+                        size.z / 2.0 + LEDGE_OVERHANG,
+                    ))
+                    .insert(RigidBody::Fixed)
+                    .insert(Sensor);
+            });
+        }
+        block
+    }
+}
+
+/// An invisible volume that pushes anything inside it with `force`
+pub struct WindZonePrefab {
+    pub position: Vec3,
+    pub half_extents: Vec3,
+    pub force: Vec3,
+}
+
+impl WindZonePrefab {
+    pub fn new(position: Vec3, half_extents: Vec3, force: Vec3) -> Self {
+        WindZonePrefab {
+            position,
+            half_extents,
+            force,
+        }
+    }
+}
+
+impl Prefab for WindZonePrefab {
+    fn spawn(
+        &self,
+        commands: &mut Commands,
+        _meshes: &mut Assets<Mesh>,
+        _materials: &mut Assets<StandardMaterial>,
+    ) -> Entity {
+        let half_extents = self.half_extents;
+        commands
+            .spawn(TransformBundle {
+                local: Transform::from_translation(self.position),
+                ..default()
+            })
+            .insert(Collider::cuboid(
+                half_extents.x,
+                half_extents.y,
+                half_extents.z,
+            ))
+            .insert(Sensor)
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(WindZone(self.force))
+            .insert(SoundEmitter::new(AmbientSound::Wind))
+            .insert(RigidBody::Fixed)
+            .id()
+    }
+}
+
+/// A wind zone that travels back and forth along `points`, so the player can ride it
+pub struct GustPrefab {
+    pub points: Vec<Vec3>,
+    pub half_extents: Vec3,
+    pub force: Vec3,
+    pub speed: f32,
+}
+
+impl GustPrefab {
+    pub fn new(points: Vec<Vec3>, half_extents: Vec3, force: Vec3, speed: f32) -> Self {
+        GustPrefab {
+            points,
+            half_extents,
+            force,
+            speed,
+        }
+    }
+}
+
+impl Prefab for GustPrefab {
+    fn spawn(
+        &self,
+        commands: &mut Commands,
+        _meshes: &mut Assets<Mesh>,
+        _materials: &mut Assets<StandardMaterial>,
+    ) -> Entity {
+        let half_extents = self.half_extents;
+        let start = self.points.first().copied().unwrap_or_default();
+        commands
+            .spawn(TransformBundle {
+                local: Transform::from_translation(start),
+                ..default()
+            })
+            .insert(Collider::cuboid(
+                half_extents.x,
+                half_extents.y,
+                half_extents.z,
+            ))
+            .insert(Sensor)
+            .insert(WindZone(self.force))
+            .insert(SoundEmitter::new(AmbientSound::Wind))
+            .insert(Gust)
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(MovingPlatform::new(self.points.clone(), self.speed, true))
+            .insert(PlatformDelta::default())
+            .id()
+    }
+}
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{HeavyItem, Item, LevelSignal, MediumItem, Player, Weight};
+
+const PLATE_PRESS_DEPTH: f32 = 0.1;
+
+pub struct PressurePlatePlugin;
+
+impl Plugin for PressurePlatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(weigh_pressure_plates);
+    }
+}
+
+/// Sends the `LevelSignal` named `name` while something at least `required_weight` is resting on
+/// top
+#[derive(Component)]
+pub struct PressurePlate {
+    pub name: String,
+    pub required_weight: Weight,
+    pub pressed: bool,
+    pub rest_height: f32,
+}
+
+impl PressurePlate {
+    pub fn new(name: String, required_weight: Weight, rest_height: f32) -> Self {
+        PressurePlate {
+            name,
+            required_weight,
+            pressed: false,
+            rest_height,
+        }
+    }
+}
+
+pub fn weigh_pressure_plates(
+    rapier_context: Res<RapierContext>,
+    mut signals: EventWriter<LevelSignal>,
+    mut plate_query: Query<(Entity, &mut Transform, &mut PressurePlate)>,
+    weight_query: Query<
+        (
+            &GlobalTransform,
+            Option<&Player>,
+            Option<&HeavyItem>,
+            Option<&MediumItem>,
+        ),
+        Or<(With<Player>, With<Item>)>,
+    >,
+) {
+    for (plate_entity, mut transform, mut plate) in &mut plate_query {
+        let heaviest = rapier_context
+            .contacts_with(plate_entity)
+            .filter(|contact| contact.has_any_active_contacts())
+            .filter_map(|contact| {
+                let other = if contact.collider1() == plate_entity {
+                    contact.collider2()
+                } else {
+                    contact.collider1()
+                };
+                weight_query.get(other).ok()
+            })
+            .filter(|(other_transform, ..)| other_transform.translation().y > plate.rest_height)
+            .map(|(_, player, heavy, medium)| {
+                if heavy.is_some() {
+                    Weight::Heavy
+                } else if medium.is_some() || player.is_some() {
+                    Weight::Medium
+                } else {
+                    Weight::Light
+                }
+            })
+            .max();
+
+        let pressed = heaviest.map_or(false, |weight| weight >= plate.required_weight);
+        if pressed == plate.pressed {
+            continue;
+        }
+
+        plate.pressed = pressed;
+        transform.translation.y = if pressed {
+            plate.rest_height - PLATE_PRESS_DEPTH
+        } else {
+            plate.rest_height
+        };
+        signals.send(LevelSignal {
+            name: plate.name.clone(),
+            active: pressed,
+        });
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{apply_momentum, Item, Player, PlayerAction, RespawnPlayer, PLAYER_HEAD_HEIGHT};
+
+const QUICKSAND_HORIZONTAL_DAMPING: f32 = 0.3;
+const QUICKSAND_JUMP_FORCE: f32 = 8.0;
+const QUICKSAND_ITEM_DESPAWN_SECONDS: f32 = 3.0;
+
+pub struct QuicksandPlugin;
+
+impl Plugin for QuicksandPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (detect_quicksand, quicksand_jump, sink_in_quicksand)
+                .chain()
+                .after(apply_momentum),
+        );
+    }
+}
+
+/// A sensor volume that slowly swallows anything inside it, `surface_height` is the world height of
+/// its top
+#[derive(Component)]
+pub struct Quicksand {
+    pub sink_speed: f32,
+    pub surface_height: f32,
+}
+
+/// Present on players and items that are currently inside a `Quicksand` volume
+#[derive(Component)]
+pub struct Sinking {
+    pub quicksand: Entity,
+    pub sink_speed: f32,
+    pub surface_height: f32,
+}
+
+/// Items that fell into quicksand are lost for good once this runs out
+#[derive(Component)]
+pub struct SwallowedItem(Timer);
+
+pub fn detect_quicksand(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    quicksand_query: Query<(Entity, &Quicksand)>,
+    sinkable_query: Query<(Option<&RigidBody>, Option<&Item>), Or<(With<Player>, With<Item>)>>,
+    sinking_query: Query<&Sinking>,
+) {
+    for collision_event in collision_events.iter() {
+        let (e1, e2, started) = match collision_event {
+            CollisionEvent::Started(e1, e2, _) => (*e1, *e2, true),
+            CollisionEvent::Stopped(e1, e2, _) => (*e1, *e2, false),
+        };
+
+        let (quicksand_entity, sinking_entity) = if quicksand_query.contains(e1) {
+            (e1, e2)
+        } else if quicksand_query.contains(e2) {
+            (e2, e1)
+        } else {
+            continue;
+        };
+
+        let Ok((rigidbody, item)) = sinkable_query.get(sinking_entity) else {continue;};
+        // Held items are fixed to the player and sink along with them
+        if item.is_some() && rigidbody != Some(&RigidBody::Dynamic) {
+            continue;
+        }
+
+        if started {
+            let (_, quicksand) = quicksand_query.get(quicksand_entity).unwrap();
+            commands.entity(sinking_entity).insert(Sinking {
+                quicksand: quicksand_entity,
+                sink_speed: quicksand.sink_speed,
+                surface_height: quicksand.surface_height,
+            });
+            if item.is_some() {
+                commands
+                    .entity(sinking_entity)
+                    .insert(SwallowedItem(Timer::from_seconds(
+                        QUICKSAND_ITEM_DESPAWN_SECONDS,
+                        TimerMode::Once,
+                    )));
+            }
+        } else if let Ok(sinking) = sinking_query.get(sinking_entity) {
+            if sinking.quicksand == quicksand_entity {
+                commands
+                    .entity(sinking_entity)
+                    .remove::<Sinking>()
+                    .remove::<SwallowedItem>();
+            }
+        }
+    }
+}
+
+pub fn quicksand_jump(
+    mut query: Query<(&mut Velocity, &ActionState<PlayerAction>), (With<Player>, With<Sinking>)>,
+) {
+    for (mut velocity, action) in &mut query {
+        if action.just_pressed(PlayerAction::Jump) {
+            velocity.linvel.y = QUICKSAND_JUMP_FORCE;
+        }
+    }
+}
+
+pub fn sink_in_quicksand(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut respawn_events: EventWriter<RespawnPlayer>,
+    mut query: Query<(
+        Entity,
+        &Transform,
+        &mut Velocity,
+        &Sinking,
+        Option<&mut SwallowedItem>,
+        Option<&Player>,
+    )>,
+) {
+    for (entity, transform, mut velocity, sinking, swallowed, player) in &mut query {
+        velocity.linvel.x *= QUICKSAND_HORIZONTAL_DAMPING;
+        velocity.linvel.z *= QUICKSAND_HORIZONTAL_DAMPING;
+        if velocity.linvel.y < -sinking.sink_speed {
+            velocity.linvel.y = -sinking.sink_speed;
+        }
+
+        if player.is_some() {
+            if transform.translation.y + PLAYER_HEAD_HEIGHT < sinking.surface_height {
+                respawn_events.send(RespawnPlayer);
+            }
+        } else if let Some(mut swallowed) = swallowed {
+            swallowed.0.tick(time.delta());
+            if swallowed.0.finished() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
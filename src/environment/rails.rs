@@ -0,0 +1,376 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{Drift, Grounded, Momentum, Player, PlayerAction};
+
+const RAIL_SAMPLES_PER_SEGMENT: usize = 8;
+const RAIL_CATCH_RADIUS: f32 = 0.75;
+const RAIL_MIN_SPEED: f32 = 6.0;
+const RAIL_SLOPE_ACCELERATION: f32 = 20.0;
+const RAIL_JUMP_FORCE: f32 = 12.0;
+const RAIL_RECATCH_SECONDS: f32 = 0.4;
+const RAIL_BALANCE_WOBBLE: f32 = 0.6;
+const RAIL_BALANCE_CORRECTION: f32 = 2.5;
+const PLAYER_FOOT_OFFSET: f32 = 1.0;
+
+pub struct RailPlugin;
+
+impl Plugin for RailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((catch_rails, grind_rails, jump_off_rails).chain());
+    }
+}
+
+/// A grindable rail following a Catmull-Rom spline through its control points
+#[derive(Component)]
+pub struct Rail {
+    samples: Vec<Vec3>,
+    distances: Vec<f32>,
+}
+
+impl Rail {
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let mut samples = Vec::new();
+        if points.len() < 2 {
+            samples.extend_from_slice(points);
+        } else {
+            for i in 0..points.len() - 1 {
+                let p0 = points[i.saturating_sub(1)];
+                let p1 = points[i];
+                let p2 = points[i + 1];
+                let p3 = points[(i + 2).min(points.len() - 1)];
+                for step in 0..RAIL_SAMPLES_PER_SEGMENT {
+                    let t = step as f32 / RAIL_SAMPLES_PER_SEGMENT as f32;
+                    samples.push(catmull_rom(p0, p1, p2, p3, t));
+                }
+            }
+            samples.push(points[points.len() - 1]);
+        }
+
+        let mut distances = Vec::with_capacity(samples.len());
+        let mut total = 0.0;
+        for (i, sample) in samples.iter().enumerate() {
+            if i > 0 {
+                total += sample.distance(samples[i - 1]);
+            }
+            distances.push(total);
+        }
+
+        Rail { samples, distances }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or_default()
+    }
+
+    pub fn samples(&self) -> &[Vec3] {
+        &self.samples
+    }
+
+    fn segment_at(&self, distance: f32) -> (usize, f32) {
+        let distance = distance.clamp(0.0, self.length());
+        let index = match self
+            .distances
+            .binary_search_by(|d| d.partial_cmp(&distance).unwrap())
+        {
+            Ok(index) | Err(index) => index.clamp(1, self.samples.len() - 1),
+        };
+        let start = self.distances[index - 1];
+        let span = self.distances[index] - start;
+        let t = if span > 0.0 {
+            (distance - start) / span
+        } else {
+            0.0
+        };
+        (index, t)
+    }
+
+    pub fn position_at(&self, distance: f32) -> Vec3 {
+        if self.samples.len() < 2 {
+            return self.samples.first().copied().unwrap_or_default();
+        }
+        let (index, t) = self.segment_at(distance);
+        self.samples[index - 1].lerp(self.samples[index], t)
+    }
+
+    pub fn tangent_at(&self, distance: f32) -> Vec3 {
+        if self.samples.len() < 2 {
+            return Vec3::X;
+        }
+        let (index, _) = self.segment_at(distance);
+        (self.samples[index] - self.samples[index - 1]).normalize_or_zero()
+    }
+
+    /// The distance along the rail of the point closest to `point`, and that closest point
+    pub fn closest(&self, point: Vec3) -> (f32, Vec3) {
+        let mut best = (0.0, self.samples.first().copied().unwrap_or_default());
+        let mut best_distance_squared = f32::MAX;
+        for i in 1..self.samples.len() {
+            let start = self.samples[i - 1];
+            let segment = self.samples[i] - start;
+            let length_squared = segment.length_squared();
+            let t = if length_squared > 0.0 {
+                ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let candidate = start + segment * t;
+            let distance_squared = candidate.distance_squared(point);
+            if distance_squared < best_distance_squared {
+                best_distance_squared = distance_squared;
+                let along = self.distances[i - 1] + (self.distances[i] - self.distances[i - 1]) * t;
+                best = (along, candidate);
+            }
+        }
+        best
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * ((2.0 * p1)
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// The player is locked to `rail`, `speed` is signed along the rail's direction
+#[derive(Component)]
+pub struct Grinding {
+    pub rail: Entity,
+    pub distance: f32,
+    pub speed: f32,
+    pub balance: f32,
+    pub elapsed: f32,
+}
+
+/// Stops the player from catching the rail they just left
+#[derive(Component)]
+pub struct RailCooldown {
+    pub rail: Entity,
+    pub timer: Timer,
+}
+
+pub fn catch_rails(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Velocity,
+            &Momentum,
+            &mut Drift,
+            &mut GravityScale,
+            Option<&mut RailCooldown>,
+        ),
+        (With<Player>, Without<Grinding>, Without<Grounded>),
+    >,
+    rail_query: Query<(Entity, &Rail)>,
+) {
+    for (player_entity, transform, velocity, momentum, mut drift, mut gravity_scale, cooldown) in
+        &mut player_query
+    {
+        let mut ignored_rail = None;
+        if let Some(mut cooldown) = cooldown {
+            cooldown.timer.tick(time.delta());
+            if cooldown.timer.finished() {
+                commands.entity(player_entity).remove::<RailCooldown>();
+            } else {
+                ignored_rail = Some(cooldown.rail);
+            }
+        }
+
+        if velocity.linvel.y > 0.0 {
+            continue;
+        }
+
+        let feet = transform.translation - Vec3::Y * PLAYER_FOOT_OFFSET;
+        for (rail_entity, rail) in &rail_query {
+            if Some(rail_entity) == ignored_rail {
+                continue;
+            }
+
+            let (distance, closest) = rail.closest(feet);
+            if closest.distance(feet) > RAIL_CATCH_RADIUS {
+                continue;
+            }
+
+            let tangent = rail.tangent_at(distance);
+            let mut travel = velocity.linvel + transform.forward() * momentum.get() + drift.0;
+            travel.y = 0.0;
+            let direction = if travel.dot(tangent) >= 0.0 {
+                1.0
+            } else {
+                -1.0
+            };
+            let speed = travel.length().max(momentum.get()).max(RAIL_MIN_SPEED);
+
+            drift.reset();
+            gravity_scale.0 = 0.0;
+            commands
+                .entity(player_entity)
+                .insert(Grinding {
+                    rail: rail_entity,
+                    distance,
+                    speed: speed * direction,
+                    balance: 0.0,
+                    elapsed: 0.0,
+                })
+                .remove::<RailCooldown>();
+            break;
+        }
+    }
+}
+
+pub fn grind_rails(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut Momentum,
+            &mut GravityScale,
+            &mut Grinding,
+            &ActionState<PlayerAction>,
+        ),
+        With<Player>,
+    >,
+    rail_query: Query<&Rail>,
+) {
+    for (
+        player_entity,
+        mut transform,
+        mut velocity,
+        mut momentum,
+        mut gravity_scale,
+        mut grinding,
+        action,
+    ) in &mut player_query
+    {
+        let Ok(rail) = rail_query.get(grinding.rail) else {
+            gravity_scale.0 = 1.0;
+            commands.entity(player_entity).remove::<Grinding>();
+            continue;
+        };
+
+        let delta = time.delta_seconds();
+        let tangent = rail.tangent_at(grinding.distance);
+        let direction = grinding.speed.signum();
+
+        // Going downhill speeds the grind up, uphill slows it down
+        grinding.speed -= tangent.y * RAIL_SLOPE_ACCELERATION * delta;
+        if grinding.speed.abs() < RAIL_MIN_SPEED {
+            grinding.speed = RAIL_MIN_SPEED * direction;
+        }
+        grinding.distance += grinding.speed * delta;
+        grinding.elapsed += delta;
+
+        // Bends and a steady wobble push the player off balance, left and right lean against it
+        let ahead = rail.tangent_at(grinding.distance + grinding.speed * 0.2);
+        let bend = tangent.cross(ahead).y * direction;
+        let mut lean = 0.0;
+        if action.pressed(PlayerAction::Left) {
+            lean -= 1.0;
+        }
+        if action.pressed(PlayerAction::Right) {
+            lean += 1.0;
+        }
+        if let Some(axis_pair) = action.clamped_axis_pair(PlayerAction::Move) {
+            if axis_pair.x() != 0.0 {
+                lean = axis_pair.x();
+            }
+        }
+        let wobble = (grinding.elapsed * 2.3).sin() * RAIL_BALANCE_WOBBLE;
+        grinding.balance +=
+            (wobble + bend * 10.0 + grinding.balance - lean * RAIL_BALANCE_CORRECTION) * delta;
+
+        let travel = tangent * grinding.speed;
+        let off_the_end = grinding.distance <= 0.0 || grinding.distance >= rail.length();
+        let lost_balance = grinding.balance.abs() >= 1.0;
+
+        if off_the_end || lost_balance {
+            let mut exit_velocity = travel;
+            if lost_balance {
+                let side = tangent.cross(Vec3::Y).normalize_or_zero() * direction;
+                exit_velocity += side * grinding.balance.signum() * 3.0;
+            }
+            velocity.linvel = exit_velocity;
+            momentum.set(grinding.speed.abs());
+            gravity_scale.0 = 1.0;
+            commands
+                .entity(player_entity)
+                .remove::<Grinding>()
+                .insert(RailCooldown {
+                    rail: grinding.rail,
+                    timer: Timer::from_seconds(RAIL_RECATCH_SECONDS, TimerMode::Once),
+                });
+            continue;
+        }
+
+        let position = rail.position_at(grinding.distance) + Vec3::Y * PLAYER_FOOT_OFFSET;
+        transform.translation = position;
+        transform.look_at(position + tangent * direction, Vec3::Y);
+        velocity.linvel = travel;
+    }
+}
+
+pub fn jump_off_rails(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Velocity,
+            &mut Momentum,
+            &mut GravityScale,
+            &Grinding,
+            &ActionState<PlayerAction>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (player_entity, mut velocity, mut momentum, mut gravity_scale, grinding, action) in
+        &mut player_query
+    {
+        if action.just_pressed(PlayerAction::Jump) {
+            velocity.linvel.y = RAIL_JUMP_FORCE;
+            momentum.set(grinding.speed.abs());
+            gravity_scale.0 = 1.0;
+            commands
+                .entity(player_entity)
+                .remove::<Grinding>()
+                .insert(RailCooldown {
+                    rail: grinding.rail,
+                    timer: Timer::from_seconds(RAIL_RECATCH_SECONDS, TimerMode::Once),
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rail_passes_through_control_points() {
+        let rail = Rail::from_points(&[Vec3::ZERO, Vec3::X * 4.0, Vec3::new(8.0, 0.0, 4.0)]);
+        assert_eq!(rail.position_at(0.0), Vec3::ZERO);
+        assert!(
+            rail.position_at(rail.length())
+                .distance(Vec3::new(8.0, 0.0, 4.0))
+                < 0.001
+        );
+    }
+
+    #[test]
+    fn closest_point_on_straight_rail() {
+        let rail = Rail::from_points(&[Vec3::ZERO, Vec3::X * 10.0]);
+        let (distance, point) = rail.closest(Vec3::new(3.0, 1.0, 0.0));
+        assert!((distance - 3.0).abs() < 0.001);
+        assert!(point.distance(Vec3::X * 3.0) < 0.001);
+    }
+}
@@ -0,0 +1,528 @@
+use bevy::{
+    pbr::{MaterialPipeline, MaterialPipelineKey, NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        mesh::{Indices, MeshVertexBufferLayout},
+        render_resource::{
+            AsBindGroup, Face, PrimitiveTopology, RenderPipelineDescriptor, ShaderRef,
+            SpecializedMeshPipelineError, TextureViewDescriptor, TextureViewDimension,
+        },
+    },
+    transform::TransformSystem,
+};
+use serde::Deserialize;
+use std::f32::consts::TAU;
+
+use crate::{
+    asset_path, lerp_color, update_sun, CurrentLevel, LevelData, MainCamera, Sun, TimeOfDay,
+    Weather,
+};
+
+/// Inside the camera's far plane, with room for the scenery in front of it
+const SKY_RADIUS: f32 = 900.0;
+const SCENERY_SEGMENTS: usize = 96;
+const SKY_NIGHT_TINT: Color = Color::rgb(0.08, 0.1, 0.22);
+const SKY_SUNSET_TINT: Color = Color::rgb(1.0, 0.6, 0.45);
+const SKY_RAIN_TINT: Color = Color::rgb(0.6, 0.62, 0.68);
+
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(MaterialPlugin::<SkyMaterial>::default())
+            .add_plugin(MaterialPlugin::<SkyCubemapMaterial>::default())
+            .insert_resource(SkyTint(Color::WHITE))
+            .init_resource::<PendingCubemaps>()
+            .configure_set(
+                SkySet::Tint
+                    .after(tint_sky_by_time_of_day)
+                    .before(apply_sky_tint),
+            )
+            .add_systems(
+                (
+                    apply_level_sky,
+                    prepare_cubemaps,
+                    tint_sky_by_time_of_day.after(update_sun),
+                    apply_sky_tint,
+                )
+                    .chain(),
+            )
+            .add_system(darken_sky_in_rain.in_set(SkySet::Tint))
+            .add_system(
+                follow_camera_with_sky
+                    .in_base_set(CoreSet::PostUpdate)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// What's drawn behind a level instead of the empty clear color
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct SkyData {
+    pub backdrop: Backdrop,
+    /// Angular radius of the sun disc in degrees, 0.0 leaves it out
+    pub sun_size: f32,
+    /// Distant silhouettes around the level, nearest first
+    pub scenery: Vec<SceneryLayer>,
+}
+
+impl Default for SkyData {
+    fn default() -> Self {
+        SkyData {
+            backdrop: Backdrop::default(),
+            sun_size: 2.5,
+            scenery: vec![
+                SceneryLayer {
+                    distance: 450.0,
+                    height: 40.0,
+                    base: -20.0,
+                    color: [0.45, 0.6, 0.5],
+                    follow: 0.6,
+                    seed: 1,
+                },
+                SceneryLayer {
+                    distance: 700.0,
+                    height: 110.0,
+                    base: -30.0,
+                    color: [0.6, 0.7, 0.8],
+                    follow: 0.9,
+                    seed: 2,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub enum Backdrop {
+    /// Blends from the horizon up to the zenith above and down to the ground below
+    Gradient {
+        zenith: [f32; 3],
+        horizon: [f32; 3],
+        ground: [f32; 3],
+    },
+    /// An image of six square faces stacked top to bottom
+    Cubemap(String),
+}
+
+impl Default for Backdrop {
+    fn default() -> Self {
+        Backdrop::Gradient {
+            zenith: [0.25, 0.45, 0.85],
+            horizon: [0.75, 0.85, 0.95],
+            ground: [0.35, 0.4, 0.45],
+        }
+    }
+}
+
+/// A ring of hills around the level that drifts with the camera, so it reads as far away
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct SceneryLayer {
+    pub distance: f32,
+    /// Height of the tallest peak above the layer's base
+    pub height: f32,
+    pub base: f32,
+    pub color: [f32; 3],
+    /// How much of the camera's movement the layer keeps up with, 1.0 looks endlessly far
+    pub follow: f32,
+    /// Picks the shape of the ridge
+    pub seed: u32,
+}
+
+impl Default for SceneryLayer {
+    fn default() -> Self {
+        SceneryLayer {
+            distance: 500.0,
+            height: 60.0,
+            base: -20.0,
+            color: [0.5, 0.6, 0.7],
+            follow: 0.8,
+            seed: 0,
+        }
+    }
+}
+
+/// Multiplies the whole backdrop. Worked out from the time of day every frame, then anything in
+/// `SkySet::Tint` can adjust it further before it's applied
+#[derive(Resource)]
+pub struct SkyTint(pub Color);
+
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SkySet {
+    Tint,
+}
+
+/// The dome the backdrop is drawn on, centered on the camera
+#[derive(Component)]
+pub struct SkyDome;
+
+#[derive(Component)]
+pub struct Scenery {
+    pub follow: f32,
+    pub base: f32,
+    pub color: Color,
+}
+
+/// Cubemap images still waiting to load before they can be viewed as cubes
+#[derive(Resource, Default)]
+pub struct PendingCubemaps(Vec<Handle<Image>>);
+
+/// A gradient sky with a sun disc, lit by the `Sun`
+#[derive(AsBindGroup, TypeUuid, Clone)]
+#[uuid = "5e2b9c41-7d08-4f3a-b6e1-93c4a8d2f017"]
+pub struct SkyMaterial {
+    #[uniform(0)]
+    pub zenith: Color,
+    #[uniform(0)]
+    pub horizon: Color,
+    #[uniform(0)]
+    pub ground: Color,
+    #[uniform(0)]
+    pub sun_color: Color,
+    #[uniform(0)]
+    pub tint: Color,
+    #[uniform(0)]
+    pub sun_direction: Vec3,
+    /// In radians
+    #[uniform(0)]
+    pub sun_size: f32,
+}
+
+impl Material for SkyMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/sky.wgsl".into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // Seen from inside
+        descriptor.primitive.cull_mode = Some(Face::Front);
+        Ok(())
+    }
+}
+
+#[derive(AsBindGroup, TypeUuid, Clone)]
+#[uuid = "a4d7e3f2-1b6c-4e8d-9f05-62c1b7a9d3e8"]
+pub struct SkyCubemapMaterial {
+    #[uniform(0)]
+    pub tint: Color,
+    #[texture(1, dimension = "cube")]
+    #[sampler(2)]
+    pub image: Handle<Image>,
+}
+
+impl Material for SkyCubemapMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/sky_cubemap.wgsl".into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = Some(Face::Front);
+        Ok(())
+    }
+}
+
+/// Height of a scenery ridge from 0.0 to 1.0 at `angle` around the ring. Only whole numbers of
+/// waves fit around it, so the ridge meets itself without a seam
+pub fn ridge_height(angle: f32, seed: u32) -> f32 {
+    let phase = seed as f32 * 1.618;
+    let wave = (angle * 3.0 + phase).sin() * 0.5
+        + (angle * 7.0 + phase * 2.0).sin() * 0.3
+        + (angle * 13.0 + phase * 3.0).sin() * 0.2;
+    0.5 + 0.5 * wave
+}
+
+fn rgb([r, g, b]: [f32; 3]) -> Color {
+    Color::rgb(r, g, b)
+}
+
+fn multiply_colors(a: Color, b: Color) -> Color {
+    let [r1, g1, b1, a1] = a.as_rgba_f32();
+    let [r2, g2, b2, a2] = b.as_rgba_f32();
+    Color::rgba(r1 * r2, g1 * g2, b1 * b2, a1 * a2)
+}
+
+/// Where a scenery layer sits for a camera at `camera`, trailing it by less the further it is
+pub fn scenery_position(camera: Vec3, follow: f32, base: f32) -> Vec3 {
+    Vec3::new(camera.x * follow, base, camera.z * follow)
+}
+
+/// A band of upright quads around the origin with a ridged top edge, facing inwards
+fn scenery_mesh(layer: &SceneryLayer) -> Mesh {
+    let mut positions = Vec::with_capacity((SCENERY_SEGMENTS + 1) * 2);
+    let mut normals = Vec::with_capacity((SCENERY_SEGMENTS + 1) * 2);
+    let mut uvs = Vec::with_capacity((SCENERY_SEGMENTS + 1) * 2);
+    let mut indices = Vec::with_capacity(SCENERY_SEGMENTS * 6);
+    for segment in 0..=SCENERY_SEGMENTS {
+        let along = segment as f32 / SCENERY_SEGMENTS as f32;
+        let angle = along * TAU;
+        let (sin, cos) = angle.sin_cos();
+        let top = layer.height * ridge_height(angle, layer.seed);
+        positions.push([cos * layer.distance, 0.0, sin * layer.distance]);
+        positions.push([cos * layer.distance, top, sin * layer.distance]);
+        normals.extend([[-cos, 0.0, -sin]; 2]);
+        uvs.extend([[along, 1.0], [along, 0.0]]);
+        if segment < SCENERY_SEGMENTS {
+            let bottom = segment as u32 * 2;
+            indices.extend([
+                bottom,
+                bottom + 1,
+                bottom + 2,
+                bottom + 2,
+                bottom + 1,
+                bottom + 3,
+            ]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// Swaps the backdrop and scenery over whenever the level does, and puts them back if leaving
+/// play cleared them away
+fn apply_level_sky(
+    mut commands: Commands,
+    mut level_events: EventReader<AssetEvent<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    mut sky_materials: ResMut<Assets<SkyMaterial>>,
+    mut cubemap_materials: ResMut<Assets<SkyCubemapMaterial>>,
+    mut pending_cubemaps: ResMut<PendingCubemaps>,
+    mut clear_color: ResMut<ClearColor>,
+    sky_query: Query<Entity, Or<(With<SkyDome>, With<Scenery>)>>,
+    dome_query: Query<(), With<SkyDome>>,
+) {
+    let Some(current_level) = current_level else {return;};
+    let mut reload = current_level.is_changed() || dome_query.is_empty();
+    for event in level_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                reload |= *handle == current_level.0;
+            }
+            AssetEvent::Removed { handle: _ } => (),
+        }
+    }
+    if !reload {
+        return;
+    }
+    let Some(level) = levels.get(&current_level.0) else {return;};
+
+    for entity in &sky_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let sky = &level.sky;
+    let dome_mesh = meshes.add(Mesh::from(shape::UVSphere {
+        radius: SKY_RADIUS,
+        sectors: 48,
+        stacks: 24,
+    }));
+    let mut dome = commands.spawn_empty();
+    match &sky.backdrop {
+        Backdrop::Gradient {
+            zenith,
+            horizon,
+            ground,
+        } => {
+            clear_color.0 = rgb(*horizon);
+            dome.insert(MaterialMeshBundle {
+                mesh: dome_mesh,
+                material: sky_materials.add(SkyMaterial {
+                    zenith: rgb(*zenith),
+                    horizon: rgb(*horizon),
+                    ground: rgb(*ground),
+                    sun_color: Color::WHITE,
+                    tint: Color::WHITE,
+                    sun_direction: Vec3::Y,
+                    sun_size: sky.sun_size.to_radians(),
+                }),
+                ..default()
+            });
+        }
+        Backdrop::Cubemap(path) => {
+            let image = asset_server.load(asset_path(path));
+            pending_cubemaps.0.push(image.clone());
+            dome.insert(MaterialMeshBundle {
+                mesh: dome_mesh,
+                material: cubemap_materials.add(SkyCubemapMaterial {
+                    tint: Color::WHITE,
+                    image,
+                }),
+                ..default()
+            });
+        }
+    }
+    dome.insert(NotShadowCaster)
+        .insert(NotShadowReceiver)
+        .insert(Name::new("Sky"))
+        .insert(SkyDome);
+
+    for layer in &sky.scenery {
+        let color = rgb(layer.color);
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(scenery_mesh(layer)),
+                material: standard_materials.add(StandardMaterial {
+                    base_color: color,
+                    unlit: true,
+                    double_sided: true,
+                    cull_mode: None,
+                    ..default()
+                }),
+                transform: Transform::from_xyz(0.0, layer.base, 0.0),
+                ..default()
+            })
+            .insert(NotShadowCaster)
+            .insert(NotShadowReceiver)
+            .insert(Name::new("Scenery"))
+            .insert(Scenery {
+                follow: layer.follow,
+                base: layer.base,
+                color,
+            });
+    }
+}
+
+/// Loaded images are six faces stacked in one, so they're reinterpreted as a cube once they
+/// arrive, and every cubemap material is touched so it's prepared again with the cube view
+fn prepare_cubemaps(
+    asset_server: Res<AssetServer>,
+    mut pending_cubemaps: ResMut<PendingCubemaps>,
+    mut images: ResMut<Assets<Image>>,
+    mut cubemap_materials: ResMut<Assets<SkyCubemapMaterial>>,
+) {
+    let mut prepared = false;
+    pending_cubemaps.0.retain(|handle| {
+        if asset_server.get_load_state(handle) == bevy::asset::LoadState::Failed {
+            return false;
+        }
+        let Some(image) = images.get_mut(handle) else {return true;};
+        if image.texture_descriptor.array_layer_count() == 1 {
+            let size = image.texture_descriptor.size;
+            image.reinterpret_stacked_2d_as_array(size.height / size.width.max(1));
+            image.texture_view_descriptor = Some(TextureViewDescriptor {
+                dimension: Some(TextureViewDimension::Cube),
+                ..default()
+            });
+        }
+        prepared = true;
+        false
+    });
+    if prepared {
+        for _ in cubemap_materials.iter_mut() {}
+    }
+}
+
+/// Darkens the sky towards night and warms it around sunrise and sunset
+pub fn tint_sky_by_time_of_day(time_of_day: Res<TimeOfDay>, mut tint: ResMut<SkyTint>) {
+    let daylight = time_of_day.daylight();
+    let dusk = lerp_color(SKY_NIGHT_TINT, SKY_SUNSET_TINT, daylight * 4.0);
+    tint.0 = lerp_color(dusk, Color::WHITE, daylight * 2.0 - 0.5);
+}
+
+fn darken_sky_in_rain(weather: Res<Weather>, mut tint: ResMut<SkyTint>) {
+    if weather.kind.is_raining() {
+        tint.0 = multiply_colors(tint.0, SKY_RAIN_TINT);
+    }
+}
+
+pub fn apply_sky_tint(
+    tint: Res<SkyTint>,
+    time_of_day: Res<TimeOfDay>,
+    mut sky_materials: ResMut<Assets<SkyMaterial>>,
+    mut cubemap_materials: ResMut<Assets<SkyCubemapMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    sun_query: Query<(&Transform, &DirectionalLight), With<Sun>>,
+    dome_query: Query<
+        (
+            Option<&Handle<SkyMaterial>>,
+            Option<&Handle<SkyCubemapMaterial>>,
+        ),
+        With<SkyDome>,
+    >,
+    scenery_query: Query<(&Scenery, &Handle<StandardMaterial>)>,
+) {
+    let tint = tint.0;
+    let sun = sun_query.get_single().ok();
+    for (sky_material, cubemap_material) in &dome_query {
+        if let Some(material) = sky_material.and_then(|handle| sky_materials.get_mut(handle)) {
+            material.tint = tint;
+            if let Some((transform, light)) = sun {
+                material.sun_direction = transform.back();
+                material.sun_color = light.color * time_of_day.daylight();
+            }
+        }
+        if let Some(material) =
+            cubemap_material.and_then(|handle| cubemap_materials.get_mut(handle))
+        {
+            material.tint = tint;
+        }
+    }
+    for (scenery, handle) in &scenery_query {
+        let Some(material) = standard_materials.get_mut(handle) else {continue;};
+        material.base_color = multiply_colors(scenery.color, tint);
+    }
+}
+
+/// The dome stays centered on the camera, the scenery only partly keeps up
+fn follow_camera_with_sky(
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut dome_query: Query<&mut Transform, (With<SkyDome>, Without<MainCamera>)>,
+    mut scenery_query: Query<(&mut Transform, &Scenery), (Without<SkyDome>, Without<MainCamera>)>,
+) {
+    let Ok(camera) = camera_query.get_single() else {return;};
+    for mut transform in &mut dome_query {
+        transform.translation = camera.translation;
+    }
+    for (mut transform, scenery) in &mut scenery_query {
+        transform.translation = scenery_position(camera.translation, scenery.follow, scenery.base);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ridges_wrap_around_without_a_seam() {
+        for seed in 0..10 {
+            assert!((ridge_height(0.0, seed) - ridge_height(TAU, seed)).abs() < 1e-4);
+            for step in 0..100 {
+                let height = ridge_height(step as f32 / 100.0 * TAU, seed);
+                assert!((0.0..=1.0).contains(&height));
+            }
+        }
+    }
+
+    #[test]
+    fn nearer_scenery_trails_the_camera_further() {
+        let camera = Vec3::new(100.0, 20.0, -50.0);
+        assert_eq!(
+            scenery_position(camera, 1.0, -10.0),
+            Vec3::new(100.0, -10.0, -50.0)
+        );
+        let near = scenery_position(camera, 0.5, -10.0);
+        let far = scenery_position(camera, 0.9, -10.0);
+        assert!(camera.distance(near) > camera.distance(far));
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// What a piece of ground is made of, read by the grounded raycast to change how the player moves
+#[derive(Component, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SurfaceType {
+    #[default]
+    Grass,
+    Ice,
+    Sand,
+    Metal,
+}
+
+impl SurfaceType {
+    /// Scales how quickly the player reaches top speed
+    pub fn acceleration_multiplier(&self) -> f32 {
+        use SurfaceType::*;
+        match self {
+            Grass => 1.0,
+            Ice => 0.4,
+            Sand => 0.6,
+            Metal => 1.2,
+        }
+    }
+
+    /// Scales the speed the player moves at once accelerated
+    pub fn speed_multiplier(&self) -> f32 {
+        use SurfaceType::*;
+        match self {
+            Grass => 1.0,
+            Ice => 1.1,
+            Sand => 0.6,
+            Metal => 1.0,
+        }
+    }
+
+    pub fn friction(&self) -> f32 {
+        use SurfaceType::*;
+        match self {
+            Grass => 1.0,
+            Ice => 0.05,
+            Sand => 1.5,
+            Metal => 0.8,
+        }
+    }
+
+    /// Seconds between footsteps while running on this surface
+    pub fn footstep_interval(&self) -> f32 {
+        match self {
+            SurfaceType::Sand => 0.4,
+            _ => 0.3,
+        }
+    }
+}
+
+/// The surface the player is currently standing on, kept up to date by `handle_grounded`
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GroundSurface(pub SurfaceType);
@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{DamagePlayer, Item, Player, Weight};
+
+const TURRET_MUZZLE_HEIGHT: f32 = 1.2;
+const PROJECTILE_RADIUS: f32 = 0.25;
+const PROJECTILE_SPEED: f32 = 7.0;
+const PROJECTILE_LIFETIME: f32 = 6.0;
+const PROJECTILE_KNOCKBACK: f32 = 6.0;
+const PROJECTILE_KNOCKBACK_LIFT: f32 = 4.0;
+const TURRET_BREAK_SPEED: f32 = 6.0;
+
+pub struct TurretPlugin;
+
+impl Plugin for TurretPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                fire_turrets,
+                expire_projectiles,
+                handle_projectile_collisions,
+                break_turrets,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Fires a slow projectile at the player every `timer` while it can see them
+#[derive(Component)]
+pub struct Turret {
+    pub timer: Timer,
+    pub range: f32,
+}
+
+impl Turret {
+    pub fn new(interval: f32, range: f32) -> Self {
+        Turret {
+            timer: Timer::from_seconds(interval, TimerMode::Repeating),
+            range,
+        }
+    }
+}
+
+/// A turret shot, it hurts the player and breaks on anything else it touches
+#[derive(Component)]
+pub struct Projectile {
+    pub damage: u8,
+    pub lifetime: Timer,
+}
+
+impl Projectile {
+    pub fn new(damage: u8) -> Self {
+        Projectile {
+            damage,
+            lifetime: Timer::from_seconds(PROJECTILE_LIFETIME, TimerMode::Once),
+        }
+    }
+}
+
+pub fn spawn_turret(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    interval: f32,
+    range: f32,
+) -> Entity {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(1.2, 1.6, 1.2))),
+            material: materials.add(Color::MAROON.into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(0.6, 0.8, 0.6))
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(Turret::new(interval, range))
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(0.3, 0.3, 1.0))),
+                material: materials.add(Color::DARK_GRAY.into()),
+                transform: Transform::from_xyz(0.0, TURRET_MUZZLE_HEIGHT - 0.8, -0.8),
+                ..default()
+            });
+        })
+        .id()
+}
+
+pub fn fire_turrets(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut turret_query: Query<(Entity, &mut Transform, &mut Turret), Without<Player>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+) {
+    let Ok((player_entity, player_transform)) = player_query.get_single() else {return;};
+    for (turret_entity, mut transform, mut turret) in &mut turret_query {
+        let muzzle = transform.translation + Vec3::Y * (TURRET_MUZZLE_HEIGHT - 0.8);
+        let to_player = player_transform.translation - muzzle;
+        if to_player.length() > turret.range {
+            continue;
+        }
+
+        // Turn to track the player while they are in range, whether or not there is a clear shot
+        let target = Vec3::new(
+            player_transform.translation.x,
+            transform.translation.y,
+            player_transform.translation.z,
+        );
+        if target.distance_squared(transform.translation) > 0.0 {
+            transform.look_at(target, Vec3::Y);
+        }
+
+        turret.timer.tick(time.delta());
+        if !turret.timer.just_finished() {
+            continue;
+        }
+
+        let direction = to_player.normalize_or_zero();
+        let filter = QueryFilter::new()
+            .exclude_sensors()
+            .exclude_collider(turret_entity);
+        let hit = rapier_context.cast_ray(muzzle, direction, turret.range, true, filter);
+        if !matches!(hit, Some((entity, _)) if entity == player_entity) {
+            continue;
+        }
+
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::UVSphere {
+                    radius: PROJECTILE_RADIUS,
+                    ..default()
+                })),
+                material: materials.add(Color::ORANGE_RED.into()),
+                transform: Transform::from_translation(muzzle + direction * 1.0),
+                ..default()
+            })
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(PROJECTILE_RADIUS))
+            .insert(GravityScale(0.0))
+            .insert(Velocity::linear(direction * PROJECTILE_SPEED))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(Projectile::new(1));
+    }
+}
+
+pub fn expire_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectile_query: Query<(Entity, &mut Projectile)>,
+) {
+    for (entity, mut projectile) in &mut projectile_query {
+        projectile.lifetime.tick(time.delta());
+        if projectile.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn handle_projectile_collisions(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut damage_events: EventWriter<DamagePlayer>,
+    projectile_query: Query<(&Projectile, &Velocity)>,
+    player_query: Query<(), With<Player>>,
+    sensor_query: Query<(), With<Sensor>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let (projectile_entity, other_entity) = if projectile_query.contains(*e1) {
+            (*e1, *e2)
+        } else if projectile_query.contains(*e2) {
+            (*e2, *e1)
+        } else {
+            continue;
+        };
+        // Shots fly through sensors, like the player's wall and ledge sensors that reach out past
+        // its body
+        if sensor_query.contains(other_entity) {
+            continue;
+        }
+
+        // Held and thrown items soak up shots like any other obstacle, only the player takes damage
+        let Ok((projectile, velocity)) = projectile_query.get(projectile_entity) else {continue;};
+        if player_query.contains(other_entity) {
+            let away = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z).normalize_or_zero();
+            damage_events.send(DamagePlayer {
+                amount: projectile.damage,
+                knockback: away * PROJECTILE_KNOCKBACK + Vec3::Y * PROJECTILE_KNOCKBACK_LIFT,
+            });
+        }
+        commands.entity(projectile_entity).despawn_recursive();
+    }
+}
+
+pub fn break_turrets(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    turret_query: Query<(), With<Turret>>,
+    item_query: Query<(&Item, &Velocity)>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let (turret_entity, other_entity) = if turret_query.contains(*e1) {
+            (*e1, *e2)
+        } else if turret_query.contains(*e2) {
+            (*e2, *e1)
+        } else {
+            continue;
+        };
+
+        let Ok((item, velocity)) = item_query.get(other_entity) else {continue;};
+        if item.item_id.get_weight() >= Weight::Medium
+            && velocity.linvel.length() >= TURRET_BREAK_SPEED
+        {
+            commands.entity(turret_entity).despawn_recursive();
+        }
+    }
+}
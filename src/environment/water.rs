@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    carry_riders, AmbientSound, HeavyItem, Item, MediumItem, PlatformDelta, SoundEmitter, Walkable,
+    Weight, GRAVITY,
+};
+
+/// How quickly water slows whatever is in it, per second when fully submerged
+const WATER_DRAG: f32 = 1.5;
+/// Extra drag on bobbing up and down, so floating bodies settle instead of bouncing forever
+const WATER_VERTICAL_DRAG: f32 = 3.0;
+const WATER_ANGULAR_DRAG: f32 = 2.0;
+
+pub struct WaterPlugin;
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(float_items.before(carry_riders));
+    }
+}
+
+/// A body of water filling a box around its transform, loose items float or sink in it by weight
+#[derive(Component)]
+pub struct Water {
+    pub half_extents: Vec3,
+}
+
+/// An item in the water that can be stood on and carries whoever rides it, like a raft
+#[derive(Component)]
+pub struct Afloat;
+
+/// How much of a body `half_height` either side of `center_y` is under `surface_height`, from 0
+/// to 1
+pub fn submerged_fraction(center_y: f32, half_height: f32, surface_height: f32) -> f32 {
+    if half_height <= 0.0 {
+        return if center_y <= surface_height { 1.0 } else { 0.0 };
+    }
+    ((surface_height - (center_y - half_height)) / (half_height * 2.0)).clamp(0.0, 1.0)
+}
+
+pub fn spawn_water(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    half_extents: Vec3,
+) -> Entity {
+    let size = half_extents * 2.0;
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(0.2, 0.45, 0.8, 0.6),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(Collider::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+        .insert(Sensor)
+        .insert(Water { half_extents })
+        .insert(SoundEmitter::new(AmbientSound::Water))
+        .id()
+}
+
+/// Pushes loose items up by how much of them is under the surface and slows them down, light and
+/// medium items end up floating and heavy ones sink to the bottom
+pub fn float_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    water_query: Query<(&GlobalTransform, &Water)>,
+    mut item_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &RigidBody,
+            &Item,
+            &mut Velocity,
+            Option<&mut PlatformDelta>,
+            Option<&Afloat>,
+            Option<&HeavyItem>,
+            Option<&MediumItem>,
+        ),
+        Without<Parent>,
+    >,
+) {
+    let delta_seconds = time.delta_seconds();
+    for (entity, transform, body, item, mut velocity, platform_delta, afloat, heavy, medium) in
+        &mut item_query
+    {
+        let position = transform.translation();
+        let half_height = item.item_id.height() / 2.0;
+        let fraction = if *body == RigidBody::Dynamic {
+            water_query
+                .iter()
+                .filter_map(|(water_transform, water)| {
+                    let offset = (position - water_transform.translation()).abs();
+                    let inside = offset.x <= water.half_extents.x
+                        && offset.z <= water.half_extents.z
+                        && offset.y <= water.half_extents.y + half_height;
+                    let surface = water_transform.translation().y + water.half_extents.y;
+                    inside.then(|| submerged_fraction(position.y, half_height, surface))
+                })
+                .fold(0.0, f32::max)
+        } else {
+            0.0
+        };
+
+        if fraction <= 0.0 {
+            if afloat.is_some() {
+                commands
+                    .entity(entity)
+                    .remove::<Afloat>()
+                    .remove::<Walkable>()
+                    .remove::<PlatformDelta>();
+            }
+            continue;
+        }
+
+        let weight = if heavy.is_some() {
+            Weight::Heavy
+        } else if medium.is_some() {
+            Weight::Medium
+        } else {
+            Weight::Light
+        };
+        velocity.linvel.y += -GRAVITY.y * weight.buoyancy() * fraction * delta_seconds;
+        let drag = (WATER_DRAG * fraction * delta_seconds).min(1.0);
+        let vertical_drag = (WATER_VERTICAL_DRAG * fraction * delta_seconds).min(1.0);
+        let angular_drag = (WATER_ANGULAR_DRAG * fraction * delta_seconds).min(1.0);
+        velocity.linvel.x *= 1.0 - drag;
+        velocity.linvel.z *= 1.0 - drag;
+        velocity.linvel.y *= 1.0 - vertical_drag;
+        velocity.angvel *= 1.0 - angular_drag;
+
+        // Riders are carried along the water, the physics keeps them on top as it bobs
+        let drift = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z) * delta_seconds;
+        match (afloat, platform_delta) {
+            (Some(_), Some(mut platform_delta)) => platform_delta.0 = drift,
+            _ => {
+                commands
+                    .entity(entity)
+                    .insert(Afloat)
+                    .insert(Walkable)
+                    .insert(PlatformDelta(drift));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn submerged_fraction_follows_the_surface() {
+        assert_eq!(submerged_fraction(5.0, 1.0, 0.0), 0.0);
+        assert_eq!(submerged_fraction(0.0, 1.0, 0.0), 0.5);
+        assert_eq!(submerged_fraction(-0.5, 1.0, 0.0), 0.75);
+        assert_eq!(submerged_fraction(-5.0, 1.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn only_heavy_items_sink() {
+        assert!(Weight::Light.buoyancy() > 1.0);
+        assert!(Weight::Medium.buoyancy() > 1.0);
+        assert!(Weight::Heavy.buoyancy() < 1.0);
+    }
+}
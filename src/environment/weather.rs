@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    spawn_raindrop, CurrentLevel, EntityPools, LevelData, MainCamera, ParticleAssets, Player,
+    WindZone,
+};
+
+const WET_FRICTION: f32 = 0.2;
+const RAIN_AREA: f32 = 20.0;
+const RAIN_HEIGHT: f32 = 15.0;
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Weather::default()).add_systems(
+            (
+                apply_level_weather,
+                advance_weather,
+                handle_storm_wind,
+                handle_wet_ground,
+                spawn_rain,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Rain,
+    Storm,
+}
+
+impl WeatherKind {
+    pub fn is_raining(&self) -> bool {
+        matches!(self, WeatherKind::Rain | WeatherKind::Storm)
+    }
+}
+
+/// One step of a level's weather script, the kind of weather and how long it lasts
+#[derive(Deserialize, Clone)]
+pub struct WeatherStep {
+    pub kind: WeatherKind,
+    pub seconds: f32,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WeatherScript {
+    pub steps: Vec<WeatherStep>,
+    pub storm_wind: [f32; 3],
+}
+
+impl Default for WeatherScript {
+    fn default() -> Self {
+        WeatherScript {
+            steps: Vec::new(),
+            storm_wind: [6.0, 0.0, 0.0],
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    pub script: WeatherScript,
+    step_index: usize,
+    step_timer: Timer,
+}
+
+impl Weather {
+    pub fn set_script(&mut self, script: WeatherScript) {
+        self.script = script;
+        self.step_index = 0;
+        self.start_step();
+    }
+
+    /// Forces the weather until the next scripted step begins
+    pub fn set(&mut self, kind: WeatherKind) {
+        self.kind = kind;
+    }
+
+    pub fn storm_wind(&self) -> Vec3 {
+        Vec3::from(self.script.storm_wind)
+    }
+
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        if self.script.steps.is_empty() {
+            return;
+        }
+        self.step_timer.tick(delta);
+        if self.step_timer.finished() {
+            self.step_index = (self.step_index + 1) % self.script.steps.len();
+            self.start_step();
+        }
+    }
+
+    fn start_step(&mut self) {
+        if let Some(step) = self.script.steps.get(self.step_index) {
+            self.kind = step.kind;
+            self.step_timer = Timer::from_seconds(step.seconds, TimerMode::Once);
+        } else {
+            self.kind = WeatherKind::Clear;
+        }
+    }
+}
+
+/// The level wide wind zone spawned while a storm is active
+#[derive(Component)]
+pub struct StormWind;
+
+/// Stores the friction a ground collider had before it got wet
+#[derive(Component)]
+pub struct Wet(Option<Friction>);
+
+pub fn apply_level_weather(
+    mut weather: ResMut<Weather>,
+    mut level_events: EventReader<AssetEvent<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+) {
+    let Some(current_level) = current_level else {return;};
+    let mut reload = current_level.is_changed();
+    for event in level_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                reload |= *handle == current_level.0;
+            }
+            AssetEvent::Removed { handle: _ } => (),
+        }
+    }
+    if !reload {
+        return;
+    }
+    if let Some(level) = levels.get(&current_level.0) {
+        weather.set_script(level.weather.clone());
+    }
+}
+
+/// Only marks the weather changed when it turns, the storm wind and wet ground react to that
+pub fn advance_weather(time: Res<Time>, mut weather: ResMut<Weather>) {
+    let kind = weather.kind;
+    weather.bypass_change_detection().tick(time.delta());
+    if weather.kind != kind {
+        weather.set_changed();
+    }
+}
+
+pub fn handle_storm_wind(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    storm_query: Query<Entity, With<StormWind>>,
+) {
+    if !weather.is_changed() {
+        return;
+    }
+
+    let storming = weather.kind == WeatherKind::Storm;
+    if storming && storm_query.is_empty() {
+        commands
+            .spawn(TransformBundle::default())
+            .insert(Collider::cuboid(500.0, 500.0, 500.0))
+            .insert(Sensor)
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(WindZone(weather.storm_wind()))
+            .insert(RigidBody::Fixed)
+            .insert(StormWind);
+    } else if !storming {
+        for entity in &storm_query {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn handle_wet_ground(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    dry_query: Query<
+        (Entity, Option<&RigidBody>, Option<&Friction>),
+        (With<Collider>, Without<Sensor>, Without<Wet>),
+    >,
+    wet_query: Query<(Entity, &Wet)>,
+) {
+    if weather.kind.is_raining() {
+        for (entity, rigidbody, friction) in &dry_query {
+            let is_ground = rigidbody.map_or(true, |body| *body == RigidBody::Fixed);
+            if is_ground {
+                commands
+                    .entity(entity)
+                    .insert(Wet(friction.copied()))
+                    .insert(Friction {
+                        coefficient: WET_FRICTION,
+                        combine_rule: CoefficientCombineRule::Min,
+                    });
+            }
+        }
+    } else if weather.is_changed() {
+        for (entity, wet) in &wet_query {
+            if let Some(friction) = wet.0 {
+                commands.entity(entity).insert(friction);
+            } else {
+                commands.entity(entity).remove::<Friction>();
+            }
+            commands.entity(entity).remove::<Wet>();
+        }
+    }
+}
+
+pub fn spawn_rain(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    mut pools: ResMut<EntityPools>,
+    particle_assets: Res<ParticleAssets>,
+    player_query: Query<&Transform, With<Player>>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut drop_index: Local<u32>,
+) {
+    if !weather.kind.is_raining() {
+        return;
+    }
+    let center = player_query
+        .get_single()
+        .or_else(|_| camera_query.get_single())
+        .map(|transform| transform.translation);
+    let Ok(center) = center else {return;};
+
+    let drops_per_frame = if weather.kind == WeatherKind::Storm {
+        6
+    } else {
+        3
+    };
+    let wind = if weather.kind == WeatherKind::Storm {
+        weather.storm_wind()
+    } else {
+        Vec3::ZERO
+    };
+
+    for _ in 0..drops_per_frame {
+        // Scatter drops over the rain area with a cheap golden angle spiral rather than pulling in
+        // an rng
+        *drop_index = drop_index.wrapping_add(1);
+        let i = *drop_index as f32;
+        let angle = i * 2.399_963;
+        let radius = RAIN_AREA * ((i * 0.618_034).fract()).sqrt();
+        let offset = Vec3::new(angle.cos() * radius, RAIN_HEIGHT, angle.sin() * radius);
+        spawn_raindrop(
+            &mut commands,
+            &mut pools,
+            &particle_assets,
+            center + offset,
+            wind,
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use bevy::{
+    prelude::*,
+    render::primitives::{Aabb, Frustum},
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::{MainCamera, StormWind, WindZone};
+
+/// Streaks per cubic unit of wind zone
+const STREAK_DENSITY: f32 = 0.15;
+const MIN_STREAKS: usize = 4;
+const MAX_STREAKS: usize = 48;
+/// How fast a streak travels per unit of wind force
+const STREAK_SPEED_PER_FORCE: f32 = 0.6;
+const STREAK_LENGTH_PER_FORCE: f32 = 0.08;
+const STREAK_MIN_LENGTH: f32 = 0.3;
+const STREAK_MAX_LENGTH: f32 = 1.5;
+/// Share of the zone near its sides over which streaks thin out instead of popping in and out
+const STREAK_EDGE_FADE: f32 = 0.2;
+
+pub struct WindStreakPlugin;
+
+impl Plugin for WindStreakPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_wind_streak_assets)
+            .add_systems((spawn_wind_streaks, animate_wind_streaks).chain());
+    }
+}
+
+#[derive(Resource)]
+pub struct WindStreakAssets {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+}
+
+/// A wind zone's streaks, `half_extents` is the box they stream through
+#[derive(Component)]
+pub struct WindStreaks {
+    pub half_extents: Vec3,
+    /// Whether the zone was in view last frame, streaks are hidden and left alone while it isn't
+    pub on_camera: bool,
+}
+
+/// One streak, `lane` is where it starts in the zone from -1 to 1 on each axis
+#[derive(Component)]
+pub struct WindStreak {
+    pub lane: Vec3,
+}
+
+/// Enough streaks to fill the zone without a huge one spawning hundreds
+pub fn streak_count(half_extents: Vec3) -> usize {
+    let volume = half_extents.x * half_extents.y * half_extents.z * 8.0;
+    ((volume * STREAK_DENSITY) as usize).clamp(MIN_STREAKS, MAX_STREAKS)
+}
+
+/// Where a streak starting at `lane` is after travelling `travelled` in zone space, wrapping
+/// around to the far side of the zone as it leaves
+pub fn streak_position(lane: Vec3, travelled: Vec3) -> Vec3 {
+    let wrap = |value: f32| (value + 1.0).rem_euclid(2.0) - 1.0;
+    let moved = lane + travelled;
+    Vec3::new(wrap(moved.x), wrap(moved.y), wrap(moved.z))
+}
+
+fn load_wind_streak_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(WindStreakAssets {
+        mesh: meshes.add(Mesh::from(shape::Box::new(0.03, 0.03, 1.0))),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(1.0, 1.0, 1.0, 0.35),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Fills each new wind zone with streaks, except the storm's, which covers the whole level and
+/// already has rain blowing through it
+fn spawn_wind_streaks(
+    mut commands: Commands,
+    streak_assets: Res<WindStreakAssets>,
+    zone_query: Query<(Entity, &Collider), (Added<WindZone>, Without<StormWind>)>,
+) {
+    for (entity, collider) in &zone_query {
+        let Some(cuboid) = collider.as_cuboid() else {continue;};
+        let half_extents = cuboid.half_extents();
+        // Spread lanes evenly through the box with a low discrepancy sequence
+        let lanes = (0..streak_count(half_extents)).map(|i| {
+            let i = i as f32 + 1.0;
+            let spread = Vec3::new(i * 0.819_172, i * 0.671_043, i * 0.549_700);
+            Vec3::new(spread.x.fract(), spread.y.fract(), spread.z.fract()) * 2.0 - Vec3::ONE
+        });
+        commands
+            .entity(entity)
+            .insert(WindStreaks {
+                half_extents,
+                on_camera: true,
+            })
+            .insert(VisibilityBundle::default())
+            .with_children(|parent| {
+                for lane in lanes {
+                    parent
+                        .spawn(PbrBundle {
+                            mesh: streak_assets.mesh.clone(),
+                            material: streak_assets.material.clone(),
+                            transform: Transform::from_translation(lane * half_extents),
+                            ..default()
+                        })
+                        .insert(WindStreak { lane });
+                }
+            });
+    }
+}
+
+/// Streams each zone's streaks along its force, faster and longer the stronger it blows
+fn animate_wind_streaks(
+    time: Res<Time>,
+    camera_query: Query<&Frustum, With<MainCamera>>,
+    mut zone_query: Query<(
+        &GlobalTransform,
+        &WindZone,
+        &mut WindStreaks,
+        &mut Visibility,
+        &Children,
+    )>,
+    mut streak_query: Query<(&WindStreak, &mut Transform)>,
+) {
+    let Ok(frustum) = camera_query.get_single() else {return;};
+    for (transform, wind, mut streaks, mut visibility, children) in &mut zone_query {
+        let bounds = Aabb::from_min_max(-streaks.half_extents, streaks.half_extents);
+        let on_camera = frustum.intersects_obb(&bounds, &transform.compute_matrix(), true, true);
+        if on_camera != streaks.on_camera {
+            streaks.on_camera = on_camera;
+            *visibility = if on_camera {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+        let strength = wind.0.length();
+        if !on_camera || strength <= 0.0 {
+            continue;
+        }
+
+        // Zones don't turn, so the force is the same in zone space as in the world
+        let direction = wind.0 / strength;
+        let up = if direction.y.abs() > 0.99 {
+            Vec3::Z
+        } else {
+            Vec3::Y
+        };
+        let length =
+            (strength * STREAK_LENGTH_PER_FORCE).clamp(STREAK_MIN_LENGTH, STREAK_MAX_LENGTH);
+        let travelled = direction * strength * STREAK_SPEED_PER_FORCE * time.elapsed_seconds()
+            / streaks.half_extents;
+
+        for child in children {
+            let Ok((streak, mut streak_transform)) = streak_query.get_mut(*child) else {continue;};
+            let position = streak_position(streak.lane, travelled);
+            let edge = 1.0 - position.abs().max_element();
+            let fade = (edge / STREAK_EDGE_FADE).clamp(0.0, 1.0);
+            *streak_transform = Transform::from_translation(position * streaks.half_extents)
+                .looking_to(direction, up)
+                .with_scale(Vec3::new(fade, fade, length * fade));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn streak_count_follows_zone_size() {
+        assert_eq!(streak_count(Vec3::splat(0.5)), MIN_STREAKS);
+        assert_eq!(streak_count(Vec3::new(3.0, 2.0, 3.0)), 21);
+        assert_eq!(streak_count(Vec3::splat(50.0)), MAX_STREAKS);
+    }
+
+    #[test]
+    fn streaks_wrap_to_the_far_side() {
+        let position = streak_position(Vec3::new(0.5, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!((position - Vec3::new(-0.5, 0.0, 0.0)).length() < 1e-5);
+        let position = streak_position(Vec3::ZERO, Vec3::new(0.0, -0.25, 0.0));
+        assert!((position - Vec3::new(0.0, -0.25, 0.0)).length() < 1e-5);
+    }
+}
@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{Drift, Grounded, Momentum, Player, PlayerAction};
+
+const ZIPLINE_CATCH_RADIUS: f32 = 0.9;
+const ZIPLINE_ANCHOR_REACH: f32 = 3.0;
+const ZIPLINE_HAND_OFFSET: f32 = 1.6;
+const ZIPLINE_DRAG: f32 = 0.15;
+const ZIPLINE_MAX_SPEED: f32 = 24.0;
+const ZIPLINE_JUMP_FORCE: f32 = 8.0;
+const ZIPLINE_RECATCH_SECONDS: f32 = 0.5;
+
+pub struct ZipLinePlugin;
+
+impl Plugin for ZipLinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((catch_ziplines, slide_ziplines, jump_off_ziplines).chain());
+    }
+}
+
+/// A straight cable strung between two anchors, riders slide towards whichever end is lower
+#[derive(Component)]
+pub struct ZipLine {
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl ZipLine {
+    pub fn new(start: Vec3, end: Vec3) -> Self {
+        ZipLine { start, end }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    pub fn direction(&self) -> Vec3 {
+        (self.end - self.start).normalize_or_zero()
+    }
+
+    pub fn position_at(&self, distance: f32) -> Vec3 {
+        self.start + self.direction() * distance.clamp(0.0, self.length())
+    }
+
+    /// The distance along the line of the point closest to `point`, and that closest point
+    pub fn closest(&self, point: Vec3) -> (f32, Vec3) {
+        let distance = (point - self.start)
+            .dot(self.direction())
+            .clamp(0.0, self.length());
+        (distance, self.position_at(distance))
+    }
+
+    /// The distance along the line of the lower anchor, where riders get off
+    pub fn low_end(&self) -> f32 {
+        if self.end.y <= self.start.y {
+            self.length()
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The player is hanging from `line` by their hands, `speed` is signed along the line's direction
+#[derive(Component)]
+pub struct Ziplining {
+    pub line: Entity,
+    pub distance: f32,
+    pub speed: f32,
+}
+
+/// Stops the player from catching the line they just let go of
+#[derive(Component)]
+pub struct ZipLineCooldown {
+    pub line: Entity,
+    pub timer: Timer,
+}
+
+fn release(line: Entity) -> ZipLineCooldown {
+    ZipLineCooldown {
+        line,
+        timer: Timer::from_seconds(ZIPLINE_RECATCH_SECONDS, TimerMode::Once),
+    }
+}
+
+pub fn catch_ziplines(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Velocity,
+            &mut Drift,
+            &mut GravityScale,
+            Option<&mut ZipLineCooldown>,
+        ),
+        (With<Player>, Without<Ziplining>, Without<Grounded>),
+    >,
+    line_query: Query<(Entity, &ZipLine)>,
+) {
+    for (player_entity, transform, velocity, mut drift, mut gravity_scale, cooldown) in
+        &mut player_query
+    {
+        let mut ignored_line = None;
+        if let Some(mut cooldown) = cooldown {
+            cooldown.timer.tick(time.delta());
+            if cooldown.timer.finished() {
+                commands.entity(player_entity).remove::<ZipLineCooldown>();
+            } else {
+                ignored_line = Some(cooldown.line);
+            }
+        }
+
+        let hands = transform.translation + Vec3::Y * ZIPLINE_HAND_OFFSET;
+        for (line_entity, line) in &line_query {
+            if Some(line_entity) == ignored_line {
+                continue;
+            }
+
+            // The line can only be caught by jumping onto it close to one of its anchors
+            let (distance, closest) = line.closest(hands);
+            let from_anchor = distance.min(line.length() - distance);
+            if from_anchor > ZIPLINE_ANCHOR_REACH || closest.distance(hands) > ZIPLINE_CATCH_RADIUS
+            {
+                continue;
+            }
+
+            drift.reset();
+            gravity_scale.0 = 0.0;
+            commands
+                .entity(player_entity)
+                .insert(Ziplining {
+                    line: line_entity,
+                    distance,
+                    speed: velocity.linvel.dot(line.direction()),
+                })
+                .remove::<ZipLineCooldown>();
+            break;
+        }
+    }
+}
+
+pub fn slide_ziplines(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut Momentum,
+            &mut GravityScale,
+            &mut Ziplining,
+        ),
+        With<Player>,
+    >,
+    line_query: Query<&ZipLine>,
+) {
+    for (
+        player_entity,
+        mut transform,
+        mut velocity,
+        mut momentum,
+        mut gravity_scale,
+        mut zipline,
+    ) in &mut player_query
+    {
+        let Ok(line) = line_query.get(zipline.line) else {
+            gravity_scale.0 = 1.0;
+            commands.entity(player_entity).remove::<Ziplining>();
+            continue;
+        };
+
+        let delta = time.delta_seconds();
+        let direction = line.direction();
+
+        // Only the part of gravity running along the cable moves the rider
+        zipline.speed += rapier_config.gravity.dot(direction) * delta;
+        zipline.speed -= zipline.speed * ZIPLINE_DRAG * delta;
+        zipline.speed = zipline.speed.clamp(-ZIPLINE_MAX_SPEED, ZIPLINE_MAX_SPEED);
+        zipline.distance += zipline.speed * delta;
+
+        let travel = direction * zipline.speed;
+        let low_end = line.low_end();
+        let reached_low_end = if low_end > 0.0 {
+            zipline.distance >= low_end
+        } else {
+            zipline.distance <= low_end
+        };
+
+        if reached_low_end {
+            velocity.linvel = travel;
+            momentum.set(Vec3::new(travel.x, 0.0, travel.z).length());
+            gravity_scale.0 = 1.0;
+            commands
+                .entity(player_entity)
+                .remove::<Ziplining>()
+                .insert(release(zipline.line));
+            continue;
+        }
+
+        // Riding up past the high anchor just stops the rider there
+        if zipline.distance <= 0.0 || zipline.distance >= line.length() {
+            zipline.distance = zipline.distance.clamp(0.0, line.length());
+            zipline.speed = 0.0;
+        }
+
+        let position = line.position_at(zipline.distance) - Vec3::Y * ZIPLINE_HAND_OFFSET;
+        transform.translation = position;
+        let facing = Vec3::new(direction.x, 0.0, direction.z) * zipline.speed.signum();
+        if facing.length_squared() > 0.0 {
+            transform.look_at(position + facing, Vec3::Y);
+        }
+        velocity.linvel = travel;
+    }
+}
+
+pub fn jump_off_ziplines(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Velocity,
+            &mut Momentum,
+            &mut GravityScale,
+            &Ziplining,
+            &ActionState<PlayerAction>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (player_entity, mut velocity, mut momentum, mut gravity_scale, zipline, action) in
+        &mut player_query
+    {
+        if action.just_pressed(PlayerAction::Jump) {
+            // Keep the speed built up on the line and add a hop on top of it
+            velocity.linvel.y = velocity.linvel.y.max(0.0) + ZIPLINE_JUMP_FORCE;
+            momentum.set(Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z).length());
+            gravity_scale.0 = 1.0;
+            commands
+                .entity(player_entity)
+                .remove::<Ziplining>()
+                .insert(release(zipline.line));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn closest_point_is_clamped_to_the_anchors() {
+        let line = ZipLine::new(Vec3::ZERO, Vec3::new(10.0, -5.0, 0.0));
+        let (distance, point) = line.closest(Vec3::new(-4.0, 2.0, 0.0));
+        assert_eq!(distance, 0.0);
+        assert_eq!(point, Vec3::ZERO);
+
+        let (distance, point) = line.closest(Vec3::new(20.0, -10.0, 0.0));
+        assert!((distance - line.length()).abs() < 0.001);
+        assert!(point.distance(line.end) < 0.001);
+    }
+
+    #[test]
+    fn low_end_follows_the_lower_anchor() {
+        let downhill = ZipLine::new(Vec3::new(0.0, 8.0, 0.0), Vec3::new(12.0, 2.0, 0.0));
+        assert_eq!(downhill.low_end(), downhill.length());
+
+        let uphill = ZipLine::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(12.0, 8.0, 0.0));
+        assert_eq!(uphill.low_end(), 0.0);
+    }
+}
@@ -0,0 +1,254 @@
+use bevy::{
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{render_resource::PrimitiveTopology, view::NoFrustumCulling},
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    AddConsoleCommand, HeldItem, LaunchOptions, Player, PlayerGrabSensor, ThrownItem,
+    GRAB_SENSOR_VERTICES,
+};
+
+const THROW_ARC_SECONDS: f32 = 1.5;
+const THROW_ARC_STEP: f32 = 0.05;
+const PROBE_MISS_COLOR: Color = Color::LIME_GREEN;
+const PROBE_HIT_COLOR: Color = Color::RED;
+const SENSOR_COLOR: Color = Color::CYAN;
+const THROW_ARC_COLOR: Color = Color::YELLOW;
+
+pub struct DebugGizmosPlugin;
+
+impl Plugin for DebugGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DebugGizmos::default())
+            .insert_resource(DebugProbes::default())
+            .add_startup_system(spawn_gizmo_lines)
+            .add_systems(
+                (toggle_debug_gizmos, draw_player_gizmos, build_gizmo_lines)
+                    .chain()
+                    .in_base_set(CoreSet::PostUpdate),
+            )
+            .add_console_command("gizmos", "gizmos", toggle_gizmos_command);
+    }
+}
+
+/// A ray some system cast, `hit` is how far along it something was found
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Probe {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub length: f32,
+    pub hit: Option<f32>,
+}
+
+impl Probe {
+    pub fn new(origin: Vec3, direction: Vec3, length: f32, hit: Option<f32>) -> Self {
+        Probe {
+            origin,
+            direction,
+            length,
+            hit,
+        }
+    }
+
+    /// Where the ray stopped, at whatever it hit or its full length
+    pub fn end(&self) -> Vec3 {
+        self.origin + self.direction * self.hit.unwrap_or(self.length)
+    }
+}
+
+/// The last ray each player sensor cast, kept so they can be drawn between casts
+#[derive(Resource, Default)]
+pub struct DebugProbes {
+    pub grounded: Option<Probe>,
+    pub wall: Option<Probe>,
+    pub ledge: Option<Probe>,
+    pub camera: Option<Probe>,
+}
+
+/// Lines to draw this frame, F3 with `--debug` or the `gizmos` console command shows them
+#[derive(Resource, Default)]
+pub struct DebugGizmos {
+    pub enabled: bool,
+    lines: Vec<(Vec3, Vec3, Color)>,
+}
+
+impl DebugGizmos {
+    pub fn line(&mut self, start: Vec3, end: Vec3, color: Color) {
+        self.lines.push((start, end, color));
+    }
+
+    pub fn path(&mut self, points: &[Vec3], color: Color) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color);
+        }
+    }
+
+    pub fn probe(&mut self, probe: &Probe) {
+        let color = if probe.hit.is_some() {
+            PROBE_HIT_COLOR
+        } else {
+            PROBE_MISS_COLOR
+        };
+        self.line(probe.origin, probe.end(), color);
+    }
+}
+
+/// The mesh every gizmo line is packed into
+#[derive(Component)]
+pub struct GizmoLines;
+
+/// Where a thrown item will be at each step, ignoring whatever it hits on the way
+pub fn throw_arc(position: Vec3, velocity: Vec3, gravity: Vec3) -> Vec<Vec3> {
+    let steps = (THROW_ARC_SECONDS / THROW_ARC_STEP) as usize;
+    (0..=steps)
+        .map(|step| {
+            let seconds = step as f32 * THROW_ARC_STEP;
+            position + velocity * seconds + gravity * seconds * seconds * 0.5
+        })
+        .collect()
+}
+
+fn spawn_gizmo_lines(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Starts as a single point, filled in with real lines once gizmos are turned on
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0]; 2]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[0.0, 0.0, 0.0, 0.0]; 2]);
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(mesh),
+            material: materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                unlit: true,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        // Its bounds are only worked out once, from that first point
+        .insert(NoFrustumCulling)
+        .insert(NotShadowCaster)
+        .insert(GizmoLines);
+}
+
+fn toggle_debug_gizmos(
+    keyboard: Res<Input<KeyCode>>,
+    options: Option<Res<LaunchOptions>>,
+    mut gizmos: ResMut<DebugGizmos>,
+) {
+    if !options.map_or(false, |options| options.debug_overlay) {
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::F3) {
+        gizmos.enabled = !gizmos.enabled;
+    }
+}
+
+fn toggle_gizmos_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let mut gizmos = world.resource_mut::<DebugGizmos>();
+    gizmos.enabled = !gizmos.enabled;
+    Ok(format!(
+        "gizmos {}",
+        if gizmos.enabled { "on" } else { "off" }
+    ))
+}
+
+fn draw_player_gizmos(
+    mut gizmos: ResMut<DebugGizmos>,
+    probes: Res<DebugProbes>,
+    rapier_config: Res<RapierConfiguration>,
+    player_query: Query<(&Transform, &Velocity, Option<&HeldItem>), With<Player>>,
+    sensor_query: Query<&GlobalTransform, With<PlayerGrabSensor>>,
+) {
+    if !gizmos.enabled {
+        return;
+    }
+    for probe in [probes.grounded, probes.wall, probes.ledge, probes.camera]
+        .iter()
+        .flatten()
+    {
+        gizmos.probe(probe);
+    }
+
+    for sensor_transform in &sensor_query {
+        let corners = GRAB_SENSOR_VERTICES.map(|vertex| sensor_transform.transform_point(vertex));
+        for index in 0..4 {
+            let next = (index + 1) % 4;
+            gizmos.line(corners[index], corners[next], SENSOR_COLOR);
+            gizmos.line(corners[index + 4], corners[next + 4], SENSOR_COLOR);
+            gizmos.line(corners[index], corners[index + 4], SENSOR_COLOR);
+        }
+    }
+
+    for (transform, velocity, held_item) in &player_query {
+        if held_item.is_none() {
+            continue;
+        }
+        let throw = ThrownItem::from_player(transform, velocity.linvel);
+        let arc = throw_arc(
+            throw.throw_position,
+            throw.throw_velocity,
+            rapier_config.gravity,
+        );
+        gizmos.path(&arc, THROW_ARC_COLOR);
+    }
+}
+
+fn build_gizmo_lines(
+    mut gizmos: ResMut<DebugGizmos>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut line_query: Query<(&Handle<Mesh>, &mut Visibility), With<GizmoLines>>,
+) {
+    let lines = std::mem::take(&mut gizmos.lines);
+    for (handle, mut visibility) in &mut line_query {
+        let shown = gizmos.enabled && !lines.is_empty();
+        let wanted = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+        if !shown {
+            continue;
+        }
+        let Some(mesh) = meshes.get_mut(handle) else {continue;};
+        let positions: Vec<[f32; 3]> = lines
+            .iter()
+            .flat_map(|(start, end, _)| [start.to_array(), end.to_array()])
+            .collect();
+        let colors: Vec<[f32; 4]> = lines
+            .iter()
+            .flat_map(|(_, _, color)| [color.as_rgba_f32(), color.as_rgba_f32()])
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn probes_stop_at_their_hit() {
+        let probe = Probe::new(Vec3::ZERO, Vec3::NEG_Y, 1.1, Some(0.5));
+        assert_eq!(probe.end(), Vec3::new(0.0, -0.5, 0.0));
+        let miss = Probe::new(Vec3::ZERO, Vec3::NEG_Y, 1.1, None);
+        assert_eq!(miss.end(), Vec3::new(0.0, -1.1, 0.0));
+    }
+
+    #[test]
+    fn throw_arc_falls_under_gravity() {
+        let arc = throw_arc(Vec3::ZERO, Vec3::new(0.0, 10.0, 15.0), Vec3::Y * -30.0);
+        assert_eq!(arc[0], Vec3::ZERO);
+        let peak = arc.iter().map(|point| point.y).fold(f32::MIN, f32::max);
+        assert!(peak > 0.0 && arc.last().unwrap().y < 0.0);
+    }
+}
@@ -0,0 +1,254 @@
+use bevy::{
+    ecs::system::CommandQueue, prelude::*, scene::ScenePlugin, time::TimeUpdateStrategy,
+    utils::Instant,
+};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use std::time::Duration;
+
+use crate::{
+    spawn_player, ControlPreset, EntityPools, GameConfig, InputListenerBundle, ParticleAssets,
+    PlayerAction, PlayerGrabbingPlugin, PlayerMovementPlugin, PlayerSpeed, Wall, GRAVITY,
+};
+
+/// Every `update` of a headless app advances time and physics by exactly this much
+pub const HEADLESS_TICK: f32 = 1.0 / 60.0;
+
+/// The movement and grabbing systems on real Rapier physics, without a window, renderer or
+/// input devices, so scripted runs play out the same every time
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(AssetPlugin::default())
+        .add_asset::<Mesh>()
+        .add_plugin(ScenePlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            HEADLESS_TICK,
+        )))
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+        .insert_resource(RapierConfiguration {
+            gravity: GRAVITY,
+            timestep_mode: TimestepMode::Fixed {
+                dt: HEADLESS_TICK,
+                substeps: 1,
+            },
+            ..default()
+        })
+        .insert_resource(PlayerSpeed::default())
+        .insert_resource(GameConfig::default())
+        .insert_resource(ParticleAssets::default())
+        .insert_resource(EntityPools::default())
+        .add_plugin(PlayerMovementPlugin)
+        .add_plugin(PlayerGrabbingPlugin);
+    app
+}
+
+pub fn spawn_headless_player(app: &mut App, position: Vec3) -> Entity {
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    let player = spawn_player(
+        &mut commands,
+        position,
+        InputListenerBundle::input_map(0, ControlPreset::Standard),
+    );
+    queue.apply(&mut app.world);
+    player
+}
+
+/// A fixed box centered on `position`, marked as a `Wall` when the player should be able to
+/// wall jump off it
+pub fn spawn_headless_block(
+    app: &mut App,
+    position: Vec3,
+    half_extents: Vec3,
+    wall: bool,
+) -> Entity {
+    let mut block = app.world.spawn((
+        TransformBundle::from_transform(Transform::from_translation(position)),
+        RigidBody::Fixed,
+        Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+    ));
+    if wall {
+        block.insert(Wall);
+    }
+    block.id()
+}
+
+/// Runs one tick with exactly the actions in `held` pressed, standing in for the input manager
+pub fn step(app: &mut App, player: Entity, held: &[PlayerAction]) {
+    if let Some(mut action_state) = app.world.get_mut::<ActionState<PlayerAction>>(player) {
+        let now = Instant::now();
+        action_state.tick(now, now - Duration::from_secs_f32(HEADLESS_TICK));
+        for action in PlayerAction::variants() {
+            if held.contains(&action) {
+                action_state.press(action);
+            } else {
+                action_state.release(action);
+            }
+        }
+    }
+    app.update();
+}
+
+pub fn idle(app: &mut App, player: Entity, ticks: usize) {
+    for _ in 0..ticks {
+        step(app, player, &[]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Grounded, KnockedDown, Momentum, PlayerKnockdownPlugin, Walljump};
+
+    const MAX_AIR_TICKS: usize = 300;
+
+    fn player_position(app: &App, player: Entity) -> Vec3 {
+        app.world.get::<Transform>(player).unwrap().translation
+    }
+
+    fn is_grounded(app: &App, player: Entity) -> bool {
+        app.world.get::<Grounded>(player).is_some()
+    }
+
+    /// A player settled on a wide floor whose top is at y = 0
+    fn app_with_floor() -> (App, Entity) {
+        let mut app = headless_app();
+        spawn_headless_block(
+            &mut app,
+            Vec3::new(0.0, -0.5, 0.0),
+            Vec3::new(50.0, 0.5, 50.0),
+            false,
+        );
+        let player = spawn_headless_player(&mut app, Vec3::new(0.0, 1.5, 0.0));
+        idle(&mut app, player, 30);
+        assert!(
+            is_grounded(&app, player),
+            "player never landed on the floor"
+        );
+        (app, player)
+    }
+
+    #[test]
+    fn chained_jumps_climb_higher_each_time() {
+        let (mut app, player) = app_with_floor();
+        let start = player_position(&app, player).y;
+
+        let mut peaks = Vec::new();
+        for _ in 0..3 {
+            step(&mut app, player, &[PlayerAction::Jump]);
+            let mut peak = player_position(&app, player).y - start;
+            let mut ticks = 0;
+            // Jump again the moment they land, inside the landing window that chains jumps
+            while ticks < 10 || !is_grounded(&app, player) {
+                assert!(ticks < MAX_AIR_TICKS, "player never came back down");
+                step(&mut app, player, &[]);
+                peak = peak.max(player_position(&app, player).y - start);
+                ticks += 1;
+            }
+            peaks.push(peak);
+        }
+
+        assert!(
+            peaks[0] > 0.5,
+            "single jump barely left the ground: {peaks:?}"
+        );
+        assert!(peaks[0] < peaks[1], "double jump wasn't higher: {peaks:?}");
+        assert!(peaks[1] < peaks[2], "triple jump wasn't higher: {peaks:?}");
+    }
+
+    fn jumps_after_walking_off(ticks_after_ledge: usize) -> bool {
+        let mut app = headless_app();
+        let platform = spawn_headless_block(
+            &mut app,
+            Vec3::new(0.0, -0.5, 0.0),
+            Vec3::new(2.0, 0.5, 2.0),
+            false,
+        );
+        let player = spawn_headless_player(&mut app, Vec3::new(0.0, 1.5, 0.0));
+        idle(&mut app, player, 30);
+        assert!(
+            is_grounded(&app, player),
+            "player never landed on the platform"
+        );
+
+        app.world.despawn(platform);
+        idle(&mut app, player, ticks_after_ledge);
+        step(&mut app, player, &[PlayerAction::Jump]);
+        step(&mut app, player, &[]);
+        app.world.get::<Velocity>(player).unwrap().linvel.y > 0.0
+    }
+
+    #[test]
+    fn jumping_just_after_losing_the_ground_still_jumps() {
+        assert!(jumps_after_walking_off(6));
+    }
+
+    #[test]
+    fn jumping_long_after_losing_the_ground_does_not() {
+        assert!(!jumps_after_walking_off(30));
+    }
+
+    #[test]
+    fn wall_jumps_push_up_and_away_from_the_wall() {
+        let mut app = headless_app();
+        let player = spawn_headless_player(&mut app, Vec3::new(0.9, 10.0, 0.0));
+        idle(&mut app, player, 5);
+
+        let height = player_position(&app, player).y;
+        spawn_headless_block(
+            &mut app,
+            Vec3::new(2.0, height, 0.0),
+            Vec3::new(0.5, 20.0, 20.0),
+            true,
+        );
+        idle(&mut app, player, 3);
+        assert!(
+            app.world.get::<Walljump>(player).is_some(),
+            "player never clung to the wall"
+        );
+
+        let before = player_position(&app, player);
+        step(&mut app, player, &[PlayerAction::Jump]);
+        idle(&mut app, player, 10);
+        let after = player_position(&app, player);
+        assert!(
+            after.y > before.y,
+            "wall jump didn't go up: {before} -> {after}"
+        );
+        assert!(
+            after.x < before.x - 1.0,
+            "wall jump didn't push away: {before} -> {after}"
+        );
+    }
+
+    fn knocked_down_by_wall_at(momentum: f32) -> bool {
+        let mut app = headless_app();
+        app.add_plugin(PlayerKnockdownPlugin);
+        let player = spawn_headless_player(&mut app, Vec3::new(0.9, 10.0, 0.0));
+        idle(&mut app, player, 5);
+
+        app.world.get_mut::<Momentum>(player).unwrap().set(momentum);
+        let height = player_position(&app, player).y;
+        spawn_headless_block(
+            &mut app,
+            Vec3::new(2.0, height, 0.0),
+            Vec3::new(0.5, 20.0, 20.0),
+            true,
+        );
+        idle(&mut app, player, 3);
+        app.world.get::<KnockedDown>(player).is_some()
+    }
+
+    #[test]
+    fn hitting_a_wall_at_speed_knocks_the_player_down() {
+        assert!(knocked_down_by_wall_at(20.0));
+    }
+
+    #[test]
+    fn touching_a_wall_slowly_does_not() {
+        assert!(!knocked_down_by_wall_at(5.0));
+    }
+}
@@ -1,16 +1,20 @@
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
-use crate::PlayerAction;
+use crate::{AddConsoleCommand, PlayerAction};
 
 pub struct IdeaPlugin;
 
 impl Plugin for IdeaPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(PlayerIdeas::with_ideas(vec![Idea::Cube, Idea::Spring]))
+            .add_event::<CreationBuilt>()
+            .add_event::<IdeaChanged>()
             .add_system(cycle_ideas)
             .add_system(load_current_idea)
-            .add_system(unload_ideas);
+            .add_system(unload_ideas)
+            .add_console_command("give", "give idea <name>", give_idea);
     }
 }
 
@@ -96,6 +100,14 @@ impl PlayerIdeas {
         }
     }
 
+    /// Takes the loaded ideas if they make `creation`, keeping them out of the pool until recalled
+    pub fn spend_loaded_for(&mut self, creation: CreationType) -> Vec<Idea> {
+        if CreationType::from_ideas(self.loaded_ideas.iter().collect()) != Some(creation) {
+            return Vec::new();
+        }
+        std::mem::take(&mut self.loaded_ideas)
+    }
+
     pub fn get_idea(&mut self, idea: Idea) {
         self.ideas.push(idea);
         self.available_ideas.push(idea);
@@ -118,12 +130,13 @@ impl PlayerIdeas {
     }
 }
 
-#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Deserialize)]
 pub enum Idea {
     Cube,
     Spring,
     Wheel,
     Rope,
+    Balloon,
 }
 
 impl std::fmt::Display for Idea {
@@ -134,20 +147,75 @@ impl std::fmt::Display for Idea {
             Spring => write!(f, "Spring"),
             Wheel => write!(f, "Wheel"),
             Rope => write!(f, "Rope"),
+            Balloon => write!(f, "Balloon"),
+        }
+    }
+}
+
+impl std::str::FromStr for Idea {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        use Idea::*;
+        match name.to_lowercase().as_str() {
+            "cube" => Ok(Cube),
+            "spring" => Ok(Spring),
+            "wheel" => Ok(Wheel),
+            "rope" => Ok(Rope),
+            "balloon" => Ok(Balloon),
+            _ => Err(format!("no idea called '{name}'")),
         }
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
 pub enum CreationType {
     Crate,
     Launcher,
     PogoStick,
+    FloatingPlatform,
+    Cart,
+    Rope,
+}
+
+impl std::str::FromStr for CreationType {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        use CreationType::*;
+        match name.to_lowercase().as_str() {
+            "crate" => Ok(Crate),
+            "launcher" => Ok(Launcher),
+            "pogostick" => Ok(PogoStick),
+            "floatingplatform" => Ok(FloatingPlatform),
+            "cart" => Ok(Cart),
+            "rope" => Ok(Rope),
+            _ => Err(format!("no creation called {name}")),
+        }
+    }
 }
 
 #[derive(Component)]
 pub struct Creation;
 
+/// Sent whenever `PlayerIdeas` changes, so UI can redraw without polling it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IdeaChanged {
+    /// The idea ready to load moved
+    Selected,
+    Loaded(Idea),
+    Unloaded,
+    Gained(Idea),
+    /// Loaded ideas went into a creation
+    Spent,
+    /// Ideas came back from a creation that broke
+    Recalled,
+}
+
+/// Sent when the player makes a creation, by `build_creations` or once a rope's anchors are
+/// picked
+pub struct CreationBuilt(pub CreationType);
+
 impl CreationType {
     pub fn from_ideas(mut ideas: Vec<&Idea>) -> Option<Self> {
         ideas.sort_by(|a, b| a.partial_cmp(b).unwrap());
@@ -162,6 +230,8 @@ impl CreationType {
             2 => match sorted_iter.next().unwrap() {
                 Idea::Cube => match sorted_iter.next().unwrap() {
                     Idea::Spring => Some(CreationType::Launcher),
+                    Idea::Wheel => Some(CreationType::Cart),
+                    Idea::Balloon => Some(CreationType::FloatingPlatform),
                     _ => None,
                 },
                 _ => None,
@@ -170,6 +240,7 @@ impl CreationType {
             1 => match sorted_iter.next().unwrap() {
                 Idea::Cube => Some(CreationType::Crate),
                 Idea::Spring => Some(CreationType::PogoStick),
+                Idea::Rope => Some(CreationType::Rope),
                 _ => None,
             },
             _ => None,
@@ -179,37 +250,59 @@ impl CreationType {
 
 pub fn cycle_ideas(
     mut player_ideas: ResMut<PlayerIdeas>,
+    mut idea_events: EventWriter<IdeaChanged>,
     query: Query<&ActionState<PlayerAction>>,
 ) {
     for action in &query {
+        if !player_ideas.ideas_available() {
+            continue;
+        }
         if action.just_pressed(PlayerAction::CycleIdeasForward) {
             player_ideas.scroll_forward();
+            idea_events.send(IdeaChanged::Selected);
         }
         if action.just_pressed(PlayerAction::CycleIdeasBackward) {
             player_ideas.scroll_backward();
+            idea_events.send(IdeaChanged::Selected);
         }
     }
 }
 
 pub fn unload_ideas(
     mut player_ideas: ResMut<PlayerIdeas>,
+    mut idea_events: EventWriter<IdeaChanged>,
     query: Query<&ActionState<PlayerAction>>,
 ) {
     for action in &query {
-        if action.just_pressed(PlayerAction::UnloadIdeas) {
+        if action.just_pressed(PlayerAction::UnloadIdeas) && player_ideas.ideas_loaded() {
             player_ideas.unload_ideas();
+            idea_events.send(IdeaChanged::Unloaded);
         }
     }
 }
 
+fn give_idea(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let ["idea", name] = args else {return Err("expected an idea".to_string());};
+    let idea: Idea = name.parse()?;
+    world.resource_mut::<PlayerIdeas>().get_idea(idea);
+    world
+        .resource_mut::<Events<IdeaChanged>>()
+        .send(IdeaChanged::Gained(idea));
+    Ok(format!("got the {idea} idea"))
+}
+
 pub fn load_current_idea(
     mut player_ideas: ResMut<PlayerIdeas>,
+    mut idea_events: EventWriter<IdeaChanged>,
     query: Query<&ActionState<PlayerAction>>,
 ) {
     for action in &query {
-        if action.just_pressed(PlayerAction::LoadIdea) {
-            player_ideas.load_idea();
+        if !action.just_pressed(PlayerAction::LoadIdea) {
+            continue;
         }
+        let Some(idea) = player_ideas.available_ideas.get(player_ideas.current_index).copied() else {continue;};
+        player_ideas.load_idea();
+        idea_events.send(IdeaChanged::Loaded(idea));
     }
 }
 
@@ -238,6 +331,20 @@ mod test {
         assert_eq!(trampoline_box, CreationType::Launcher);
     }
 
+    #[test]
+    fn creation_cube_and_balloon_floats() {
+        use Idea::*;
+        let raft = CreationType::from_ideas(vec![&Balloon, &Cube]).unwrap();
+        assert_eq!(raft, CreationType::FloatingPlatform);
+    }
+
+    #[test]
+    fn creation_cube_and_wheel_rolls() {
+        use Idea::*;
+        let cart = CreationType::from_ideas(vec![&Wheel, &Cube]).unwrap();
+        assert_eq!(cart, CreationType::Cart);
+    }
+
     #[test]
     fn player_ideas_recall_all_ideas() {
         use Idea::*;
@@ -257,4 +364,29 @@ mod test {
         player_ideas.recall_ideas(vec![Rope, Spring]);
         assert_eq!(player_ideas.available_ideas, vec![Rope, Spring]);
     }
+
+    #[test]
+    fn player_ideas_only_spend_loaded_ideas_that_make_the_creation() {
+        use Idea::*;
+        let mut player_ideas = PlayerIdeas::with_ideas(vec![Cube, Wheel]);
+        player_ideas.load_idea();
+        player_ideas.load_idea();
+        assert!(player_ideas
+            .spend_loaded_for(CreationType::Crate)
+            .is_empty());
+        assert_eq!(player_ideas.loaded_ideas.len(), 2);
+
+        let mut spent = player_ideas.spend_loaded_for(CreationType::Cart);
+        spent.sort();
+        assert_eq!(spent, vec![Cube, Wheel]);
+        assert!(!player_ideas.ideas_loaded());
+        assert!(!player_ideas.ideas_available());
+    }
+
+    #[test]
+    fn ideas_parse_by_name() {
+        assert_eq!("Spring".parse::<Idea>(), Ok(Idea::Spring));
+        assert_eq!("balloon".parse::<Idea>(), Ok(Idea::Balloon));
+        assert!("hat".parse::<Idea>().is_err());
+    }
 }
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::{
+    despawn_creation, Cart, GameConfig, Grounded, IdeaChanged, Movement, Player, PlayerIdeas,
+    SpentIdeas,
+};
+
+pub struct ImaginationPlugin;
+
+impl Plugin for ImaginationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Imagination::default())
+            .add_systems((track_materialized_creations, upkeep_imagination).chain());
+    }
+}
+
+/// Energy that keeps creations materialized, running dry recalls the oldest one
+#[derive(Resource)]
+pub struct Imagination {
+    current: f32,
+    max: f32,
+    /// Creations made from spent ideas, oldest first
+    creations: VecDeque<Entity>,
+    /// Keeps one empty meter from recalling every creation in a row
+    grace: Timer,
+}
+
+impl Imagination {
+    pub fn percent(&self) -> f32 {
+        if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current >= self.max
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn set_max(&mut self, max: f32) {
+        self.max = max;
+        self.current = self.current.min(max);
+    }
+
+    pub fn drain(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.0);
+    }
+
+    pub fn regen(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
+    pub fn materialized(&self) -> usize {
+        self.creations.len()
+    }
+}
+
+impl Default for Imagination {
+    fn default() -> Self {
+        Imagination {
+            current: 100.0,
+            max: 100.0,
+            creations: VecDeque::new(),
+            grace: Timer::from_seconds(0.0, TimerMode::Once),
+        }
+    }
+}
+
+fn track_materialized_creations(
+    mut imagination: ResMut<Imagination>,
+    added_query: Query<(Entity, &SpentIdeas), Added<SpentIdeas>>,
+    creation_query: Query<(), With<SpentIdeas>>,
+) {
+    imagination
+        .creations
+        .retain(|entity| creation_query.contains(*entity));
+    for (entity, spent) in &added_query {
+        // Free creations don't hold anything up
+        if !spent.0.is_empty() {
+            imagination.creations.push_back(entity);
+        }
+    }
+}
+
+fn upkeep_imagination(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_config: Res<GameConfig>,
+    mut imagination: ResMut<Imagination>,
+    mut player_ideas: ResMut<PlayerIdeas>,
+    mut idea_events: EventWriter<IdeaChanged>,
+    player_query: Query<&Movement, (With<Player>, With<Grounded>)>,
+    creation_query: Query<(&SpentIdeas, Option<&Cart>)>,
+) {
+    let config = &game_config.imagination;
+    let delta = time.delta_seconds();
+    imagination.set_max(config.max);
+    imagination.grace.tick(time.delta());
+
+    let spent_ideas: usize = imagination
+        .creations
+        .iter()
+        .filter_map(|entity| creation_query.get(*entity).ok())
+        .map(|(spent, _)| spent.0.len())
+        .sum();
+    imagination.drain(spent_ideas as f32 * config.upkeep_per_idea * delta);
+    let idle = player_query
+        .get_single()
+        .map_or(false, |movement| !movement.is_moving());
+    if idle {
+        imagination.regen(config.regen_per_second * delta);
+    }
+
+    if !imagination.is_empty() || !imagination.grace.finished() {
+        return;
+    }
+    let Some(oldest) = imagination.creations.pop_front() else {return;};
+    let Ok((spent, cart)) = creation_query.get(oldest) else {return;};
+    despawn_creation(&mut commands, oldest, cart);
+    player_ideas.recall_ideas(spent.0.clone());
+    idea_events.send(IdeaChanged::Recalled);
+    imagination.grace = Timer::from_seconds(config.recall_grace_seconds, TimerMode::Once);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn imagination_stays_within_its_limits() {
+        let mut imagination = Imagination::default();
+        imagination.drain(250.0);
+        assert!(imagination.is_empty());
+        imagination.regen(25.0);
+        assert_eq!(imagination.percent(), 0.25);
+
+        imagination.set_max(20.0);
+        assert!(imagination.is_full());
+        imagination.regen(5.0);
+        assert_eq!(imagination.percent(), 1.0);
+    }
+}
@@ -0,0 +1,252 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    axislike::AxisType,
+    prelude::*,
+    user_input::{InputKind, UserInput},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{AddConsoleCommand, AppState, GameSettings, LaunchOptions, Player, PlayerAction};
+
+pub struct InputContextPlugin;
+
+impl Plugin for InputContextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(InputManagerPlugin::<MenuAction>::default())
+            .insert_resource(InputContext::default())
+            .init_resource::<ActionState<MenuAction>>()
+            .add_system(apply_menu_bindings)
+            // After every menu has had its say this frame, so the switch lands before the next
+            // input update
+            .add_system(switch_input_context.in_base_set(CoreSet::PostUpdate))
+            .add_console_command("bind", "bind Player(Jump, Keyboard(J))", bind_command);
+    }
+}
+
+/// What menus listen to, kept apart from `PlayerAction` so confirming a menu never jumps
+#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Serialize, Deserialize)]
+pub enum MenuAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    NextPage,
+    Pause,
+    Delete,
+}
+
+impl MenuAction {
+    pub fn build_input_map(gamepad: usize) -> InputMap<MenuAction> {
+        use MenuAction::*;
+
+        let mut input_map = InputMap::new([
+            (KeyCode::Up, Up),
+            (KeyCode::Down, Down),
+            (KeyCode::Left, Left),
+            (KeyCode::Right, Right),
+            (KeyCode::Return, Confirm),
+            (KeyCode::C, Back),
+            (KeyCode::Tab, NextPage),
+            (KeyCode::Escape, Pause),
+            (KeyCode::Delete, Delete),
+        ])
+        .set_gamepad(Gamepad { id: gamepad })
+        .build();
+
+        input_map.insert_multiple([
+            (GamepadButtonType::DPadUp, Up),
+            (GamepadButtonType::DPadDown, Down),
+            (GamepadButtonType::DPadLeft, Left),
+            (GamepadButtonType::DPadRight, Right),
+            (GamepadButtonType::South, Confirm),
+            (GamepadButtonType::East, Back),
+            (GamepadButtonType::RightTrigger, NextPage),
+            (GamepadButtonType::Start, Pause),
+            (GamepadButtonType::West, Delete),
+        ]);
+
+        input_map
+    }
+}
+
+/// Whether input drives the player or the menus. Menus ask for it by name while they're open,
+/// and outside of `AppState::Playing` it's always the menus
+#[derive(Resource, Default, Debug)]
+pub struct InputContext {
+    menus: Vec<&'static str>,
+}
+
+impl InputContext {
+    pub fn open_menu(&mut self, source: &'static str) {
+        self.close_menu(source);
+        self.menus.push(source);
+    }
+
+    pub fn close_menu(&mut self, source: &'static str) {
+        self.menus.retain(|open| *open != source);
+    }
+
+    pub fn is_menu_open(&self) -> bool {
+        !self.menus.is_empty()
+    }
+}
+
+/// A binding the player picked in place of a default one, for either set of actions
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Rebind {
+    Player(PlayerAction, InputKind),
+    Menu(MenuAction, InputKind),
+}
+
+impl Rebind {
+    /// Whether this and `other` rebind the same action on the same kind of device
+    pub fn replaces(&self, other: &Rebind) -> bool {
+        match (self, other) {
+            (Rebind::Player(a, input_a), Rebind::Player(b, input_b)) => {
+                a == b && is_gamepad_input(input_a) == is_gamepad_input(input_b)
+            }
+            (Rebind::Menu(a, input_a), Rebind::Menu(b, input_b)) => {
+                a == b && is_gamepad_input(input_a) == is_gamepad_input(input_b)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn apply_to_player(rebinds: &[Rebind], input_map: &mut InputMap<PlayerAction>) {
+        for rebind in rebinds {
+            if let Rebind::Player(action, input) = rebind {
+                rebind_action(input_map, *action, *input);
+            }
+        }
+    }
+
+    pub fn apply_to_menus(rebinds: &[Rebind], input_map: &mut InputMap<MenuAction>) {
+        for rebind in rebinds {
+            if let Rebind::Menu(action, input) = rebind {
+                rebind_action(input_map, *action, *input);
+            }
+        }
+    }
+}
+
+/// Gamepad buttons and sticks, as opposed to the keyboard and mouse
+pub fn is_gamepad_input(input: &InputKind) -> bool {
+    match input {
+        InputKind::GamepadButton(_) => true,
+        InputKind::SingleAxis(axis) => matches!(axis.axis_type, AxisType::Gamepad(_)),
+        InputKind::DualAxis(axis) => matches!(axis.x.axis_type, AxisType::Gamepad(_)),
+        _ => false,
+    }
+}
+
+/// Binds `action` to `input` in place of whatever it had on the same kind of device
+pub fn rebind_action<A: Actionlike>(input_map: &mut InputMap<A>, action: A, input: InputKind) {
+    let replaced: Vec<UserInput> = input_map
+        .get(action)
+        .iter()
+        .filter(|bound| match bound {
+            UserInput::Single(kind) => is_gamepad_input(kind) == is_gamepad_input(&input),
+            _ => false,
+        })
+        .cloned()
+        .collect();
+    for bound in replaced {
+        input_map.remove(action, bound);
+    }
+    input_map.insert(input, action);
+}
+
+fn apply_menu_bindings(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    options: Option<Res<LaunchOptions>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let gamepad = options.map_or(0, |options| options.gamepad);
+    let mut input_map = MenuAction::build_input_map(gamepad);
+    Rebind::apply_to_menus(&settings.rebinds, &mut input_map);
+    commands.insert_resource(input_map);
+}
+
+/// Hands input to the menus or back to the player. Whatever is held through the switch is
+/// consumed, so the press that closed a menu can't also jump
+fn switch_input_context(
+    state: Res<State<AppState>>,
+    context: Res<InputContext>,
+    mut player_actions: ResMut<ToggleActions<PlayerAction>>,
+    mut menu_actions: ResMut<ToggleActions<MenuAction>>,
+    mut menu_state: ResMut<ActionState<MenuAction>>,
+    mut player_query: Query<&mut ActionState<PlayerAction>, With<Player>>,
+) {
+    let in_menu = context.is_menu_open() || state.0 != AppState::Playing;
+    if player_actions.enabled != in_menu && menu_actions.enabled == in_menu {
+        return;
+    }
+    player_actions.enabled = !in_menu;
+    menu_actions.enabled = in_menu;
+    if in_menu {
+        menu_state.consume_all();
+    } else {
+        for mut action_state in &mut player_query {
+            action_state.consume_all();
+        }
+    }
+}
+
+fn bind_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let rebind = ron::from_str::<Rebind>(&args.join(" ")).map_err(|error| error.to_string())?;
+    let mut settings = world.resource_mut::<GameSettings>();
+    settings.rebinds.retain(|bound| !bound.replaces(&rebind));
+    settings.rebinds.push(rebind);
+    Ok(format!("bound {rebind:?}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rebinding_only_replaces_the_same_device() {
+        let mut input_map = MenuAction::build_input_map(0);
+        rebind_action(
+            &mut input_map,
+            MenuAction::Confirm,
+            InputKind::Keyboard(KeyCode::Space),
+        );
+        let bound = input_map.get(MenuAction::Confirm);
+        assert!(bound.contains(&UserInput::Single(InputKind::Keyboard(KeyCode::Space))));
+        assert!(!bound.contains(&UserInput::Single(InputKind::Keyboard(KeyCode::Return))));
+        assert!(bound.contains(&UserInput::Single(InputKind::GamepadButton(
+            GamepadButtonType::South
+        ))));
+    }
+
+    #[test]
+    fn rebinds_replace_their_own_action_and_device() {
+        let jump = Rebind::Player(PlayerAction::Jump, InputKind::Keyboard(KeyCode::J));
+        let jump_again = Rebind::Player(PlayerAction::Jump, InputKind::Keyboard(KeyCode::K));
+        let jump_pad = Rebind::Player(
+            PlayerAction::Jump,
+            InputKind::GamepadButton(GamepadButtonType::North),
+        );
+        let confirm = Rebind::Menu(MenuAction::Confirm, InputKind::Keyboard(KeyCode::J));
+        assert!(jump_again.replaces(&jump));
+        assert!(!jump_pad.replaces(&jump));
+        assert!(!confirm.replaces(&jump));
+    }
+
+    #[test]
+    fn menus_hold_input_until_the_last_one_closes() {
+        let mut context = InputContext::default();
+        context.open_menu("pause");
+        context.open_menu("shop");
+        context.close_menu("pause");
+        assert!(context.is_menu_open());
+        context.close_menu("shop");
+        assert!(!context.is_menu_open());
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(feature = "inspector")]
+use bevy::input::common_conditions::input_toggle_active;
+use bevy::prelude::*;
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::quick::{ResourceInspectorPlugin, WorldInspectorPlugin};
+
+#[cfg(feature = "inspector")]
+use crate::PlayerSpeed;
+
+/// Does nothing unless built with the `inspector` feature, then F4 opens and closes the windows
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    #[cfg(feature = "inspector")]
+    fn build(&self, app: &mut App) {
+        app.add_plugin(WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::F4)))
+            .add_plugin(
+                ResourceInspectorPlugin::<PlayerSpeed>::new()
+                    .run_if(input_toggle_active(false, KeyCode::F4)),
+            );
+    }
+
+    #[cfg(not(feature = "inspector"))]
+    fn build(&self, _app: &mut App) {}
+}
@@ -1,10 +1,29 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
 
-#[derive(PartialEq, Clone, Copy, Default)]
+use crate::{Creation, CurrentLevel, KillZone, LevelData};
+
+/// Anything that falls this far is gone, even where no kill zone was placed
+const KILL_PLANE_HEIGHT: f32 = -100.0;
+const LEVEL_BOUNDS_MARGIN: f32 = 10.0;
+const ITEM_RESPAWN_DELAY: f32 = 2.0;
+
+pub struct ItemRespawnPlugin;
+
+impl Plugin for ItemRespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((record_spawn_origins, detect_lost_items, respawn_lost_items).chain());
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Default, Debug, Reflect, FromReflect)]
 pub enum ItemId {
     #[default]
     WoodenCrate,
+    Critter,
+    /// An enemy carried while it's stunned
+    Enemy,
 }
 
 impl ItemId {
@@ -12,6 +31,8 @@ impl ItemId {
         use ItemId::*;
         match *self {
             WoodenCrate => Vec3::new(0.0, 1.5, -1.65),
+            Critter => Vec3::new(0.0, 1.2, -1.0),
+            Enemy => Vec3::new(0.0, 1.7, -1.3),
         }
     }
 
@@ -24,27 +45,103 @@ impl ItemId {
         use Weight::*;
         match self {
             WoodenCrate => Medium,
+            Critter => Light,
+            Enemy => Medium,
+        }
+    }
+
+    /// Mesh and color of the small stand-in shown on the HUD while the item is held
+    pub fn icon(&self) -> (Mesh, Color) {
+        match self {
+            ItemId::WoodenCrate => (Mesh::from(shape::Cube { size: 0.25 }), Color::BEIGE),
+            ItemId::Critter => (
+                Mesh::from(shape::UVSphere {
+                    radius: 0.1,
+                    ..default()
+                }),
+                Color::GOLD,
+            ),
+            ItemId::Enemy => (
+                Mesh::from(shape::UVSphere {
+                    radius: 0.15,
+                    ..default()
+                }),
+                Color::CRIMSON,
+            ),
+        }
+    }
+
+    /// How tall the item stands, items stacked on it sit this much higher
+    pub fn height(&self) -> f32 {
+        match self {
+            ItemId::WoodenCrate => 2.0,
+            ItemId::Critter => 0.6,
+            ItemId::Enemy => 1.2,
         }
     }
 
     pub fn into_collider(&self) -> Collider {
         match self {
             ItemId::WoodenCrate => Collider::cuboid(1.0, 1.0, 1.0),
+            ItemId::Critter => Collider::ball(0.3),
+            ItemId::Enemy => Collider::ball(0.6),
         }
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize)]
 pub enum Weight {
     Light,
     Medium,
     Heavy,
 }
 
+impl Weight {
+    /// Share of the wind's push that reaches a body this heavy
+    pub fn wind_catch(&self) -> f32 {
+        match self {
+            Weight::Light => 1.0,
+            Weight::Medium => 0.6,
+            Weight::Heavy => 0.25,
+        }
+    }
+
+    /// How much of a held stack's capacity an item this heavy takes up
+    pub fn stack_load(&self) -> u32 {
+        match self {
+            Weight::Light => 1,
+            Weight::Medium => 2,
+            Weight::Heavy => 4,
+        }
+    }
+
+    /// How hard water pushes up on a fully submerged body this heavy, as a share of gravity,
+    /// anything over 1 floats
+    pub fn buoyancy(&self) -> f32 {
+        match self {
+            Weight::Light => 1.8,
+            Weight::Medium => 1.4,
+            Weight::Heavy => 0.5,
+        }
+    }
+}
+
 #[derive(Component, Clone, Copy, Default)]
 pub struct Item {
     pub item_id: ItemId,
 }
 
+/// Where an item or creation first appeared, it is put back here if lost out of bounds
+#[derive(Component, Clone, Copy)]
+pub struct SpawnOrigin(pub Transform);
+
+/// Out of bounds and waiting to respawn, held still and hidden until then
+#[derive(Component)]
+pub struct Lost {
+    timer: Timer,
+    body: RigidBody,
+}
+
 #[derive(Component)]
 pub struct HeavyItem;
 #[derive(Component)]
@@ -52,6 +149,112 @@ pub struct MediumItem;
 #[derive(Component)]
 pub struct LightItem;
 
+pub fn record_spawn_origins(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &Transform),
+        (
+            Or<(With<Item>, With<Creation>)>,
+            Without<SpawnOrigin>,
+            Without<Parent>,
+        ),
+    >,
+) {
+    for (entity, transform) in &query {
+        commands.entity(entity).insert(SpawnOrigin(*transform));
+    }
+}
+
+pub fn detect_lost_items(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+    kill_zone_query: Query<(), With<KillZone>>,
+    mut item_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &RigidBody,
+            Option<&mut Velocity>,
+            Option<&mut Visibility>,
+        ),
+        (
+            Or<(With<Item>, With<Creation>)>,
+            With<SpawnOrigin>,
+            Without<Lost>,
+            Without<Parent>,
+        ),
+    >,
+) {
+    let level = current_level.and_then(|current_level| levels.get(&current_level.0));
+    let mut lost = Vec::new();
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        if kill_zone_query.contains(*e1) {
+            lost.push(*e2);
+        } else if kill_zone_query.contains(*e2) {
+            lost.push(*e1);
+        }
+    }
+    for (entity, transform, ..) in &item_query {
+        let position = transform.translation();
+        let outside_level = level.map_or(false, |level| {
+            !level.contains(position, LEVEL_BOUNDS_MARGIN)
+        });
+        if position.y < KILL_PLANE_HEIGHT || outside_level {
+            lost.push(entity);
+        }
+    }
+
+    lost.sort();
+    lost.dedup();
+    for entity in lost {
+        let Ok((_, _, body, velocity, visibility)) = item_query.get_mut(entity) else {continue;};
+        if let Some(mut velocity) = velocity {
+            *velocity = Velocity::zero();
+        }
+        if let Some(mut visibility) = visibility {
+            *visibility = Visibility::Hidden;
+        }
+        commands
+            .entity(entity)
+            .insert(Lost {
+                timer: Timer::from_seconds(ITEM_RESPAWN_DELAY, TimerMode::Once),
+                body: *body,
+            })
+            .insert(RigidBody::Fixed);
+    }
+}
+
+pub fn respawn_lost_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &mut Lost,
+        &SpawnOrigin,
+        &mut Transform,
+        Option<&mut Velocity>,
+        Option<&mut Visibility>,
+    )>,
+) {
+    for (entity, mut lost, origin, mut transform, velocity, visibility) in &mut query {
+        lost.timer.tick(time.delta());
+        if !lost.timer.finished() {
+            continue;
+        }
+        *transform = origin.0;
+        if let Some(mut velocity) = velocity {
+            *velocity = Velocity::zero();
+        }
+        if let Some(mut visibility) = visibility {
+            *visibility = Visibility::Inherited;
+        }
+        commands.entity(entity).remove::<Lost>().insert(lost.body);
+    }
+}
+
 // pub struct ItemInfo {
 //     pub mesh: Handle<Mesh>,
 //     pub color: Color,
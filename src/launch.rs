@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use std::net::{IpAddr, SocketAddr};
+
+const DEFAULT_LEVEL: &str = "levels/playground.level.ron";
+const DEFAULT_METRICS_PATH: &str = "session_metrics.json";
+const DEFAULT_ONLINE_PORT: u16 = 5737;
+
+/// Startup settings read from the command line, e.g.
+/// `paintbrush --level=levels/other.level.ron --debug --window=1280x720 --gamepad=1 --god`
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct LaunchOptions {
+    pub level: String,
+    /// Plays a generated level from this seed instead of loading `level`
+    pub seed: Option<u64>,
+    pub debug_overlay: bool,
+    pub window_size: Option<(f32, f32)>,
+    pub gamepad: usize,
+    pub god_mode: bool,
+    pub fixed_camera: bool,
+    /// Where to write the session's gameplay metrics on exit, set with `--metrics[=path]`
+    pub metrics_path: Option<String>,
+    /// Playing with others, set with `--host[=[ip:]port]` or `--join=address[:port]`
+    pub online: Option<OnlineRole>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OnlineRole {
+    /// `ip` is the address others join on, worked out from the network when it isn't given
+    Host { ip: Option<IpAddr>, port: u16 },
+    Join { address: String },
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        LaunchOptions {
+            level: DEFAULT_LEVEL.to_string(),
+            seed: None,
+            debug_overlay: false,
+            window_size: None,
+            gamepad: 0,
+            god_mode: false,
+            fixed_camera: false,
+            metrics_path: None,
+            online: None,
+        }
+    }
+}
+
+impl LaunchOptions {
+    /// Unknown flags are skipped so other plugins can read their own
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut options = LaunchOptions::default();
+        for arg in args {
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            match (flag.as_str(), value) {
+                ("--level", Some(level)) => options.level = level,
+                ("--seed", Some(seed)) => options.seed = seed.parse().ok(),
+                ("--debug", _) => options.debug_overlay = true,
+                ("--window", Some(size)) => options.window_size = parse_size(&size),
+                ("--gamepad", Some(index)) => {
+                    options.gamepad = index.parse().unwrap_or(options.gamepad)
+                }
+                ("--god", _) => options.god_mode = true,
+                ("--fixed-camera", _) => options.fixed_camera = true,
+                ("--metrics", path) => {
+                    options.metrics_path =
+                        Some(path.unwrap_or_else(|| DEFAULT_METRICS_PATH.to_string()))
+                }
+                ("--host", value) => {
+                    let address = value
+                        .as_deref()
+                        .and_then(|value| value.parse::<SocketAddr>().ok());
+                    let port = value.and_then(|port| port.parse().ok());
+                    options.online = Some(OnlineRole::Host {
+                        ip: address.map(|address| address.ip()),
+                        port: address
+                            .map(|address| address.port())
+                            .or(port)
+                            .unwrap_or(DEFAULT_ONLINE_PORT),
+                    });
+                }
+                ("--join", Some(address)) => {
+                    let address = if address.contains(':') {
+                        address
+                    } else {
+                        format!("{address}:{DEFAULT_ONLINE_PORT}")
+                    };
+                    options.online = Some(OnlineRole::Join { address });
+                }
+                _ => (),
+            }
+        }
+        options
+    }
+
+    pub fn window(&self) -> Window {
+        let mut window = Window::default();
+        if let Some((width, height)) = self.window_size {
+            window.resolution = (width, height).into();
+        }
+        // There are no flags in a browser, the canvas grows to fill the page instead
+        #[cfg(target_arch = "wasm32")]
+        {
+            window.fit_canvas_to_parent = true;
+        }
+        window
+    }
+}
+
+fn parse_size(size: &str) -> Option<(f32, f32)> {
+    let (width, height) = size.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn no_flags_gives_defaults() {
+        let options = LaunchOptions::from_args(args(&["paintbrush"]));
+        assert_eq!(options, LaunchOptions::default());
+    }
+
+    #[test]
+    fn flags_are_parsed() {
+        let options = LaunchOptions::from_args(args(&[
+            "paintbrush",
+            "--level=levels/test.level.ron",
+            "--seed=7",
+            "--debug",
+            "--window=1280x720",
+            "--gamepad=2",
+            "--god",
+            "--stress",
+            "--metrics",
+        ]));
+        assert_eq!(options.level, "levels/test.level.ron");
+        assert_eq!(options.seed, Some(7));
+        assert!(options.debug_overlay);
+        assert_eq!(options.window_size, Some((1280.0, 720.0)));
+        assert_eq!(options.gamepad, 2);
+        assert!(options.god_mode);
+        assert!(!options.fixed_camera);
+        assert_eq!(options.metrics_path.as_deref(), Some(DEFAULT_METRICS_PATH));
+    }
+
+    #[test]
+    fn bad_values_are_ignored() {
+        let options = LaunchOptions::from_args(args(&["--window=wide", "--gamepad=first"]));
+        assert_eq!(options.window_size, None);
+        assert_eq!(options.gamepad, 0);
+    }
+
+    #[test]
+    fn online_flags_fill_in_the_default_port() {
+        let options = LaunchOptions::from_args(args(&["--host"]));
+        let port = DEFAULT_ONLINE_PORT;
+        assert_eq!(options.online, Some(OnlineRole::Host { ip: None, port }));
+
+        let options = LaunchOptions::from_args(args(&["--host=6000"]));
+        let port = 6000;
+        assert_eq!(options.online, Some(OnlineRole::Host { ip: None, port }));
+
+        let options = LaunchOptions::from_args(args(&["--host=192.168.1.20:6000"]));
+        let ip = Some(IpAddr::from([192, 168, 1, 20]));
+        assert_eq!(options.online, Some(OnlineRole::Host { ip, port }));
+
+        let options = LaunchOptions::from_args(args(&["--join=192.168.1.20"]));
+        let address = format!("192.168.1.20:{DEFAULT_ONLINE_PORT}");
+        assert_eq!(options.online, Some(OnlineRole::Join { address }));
+
+        let options = LaunchOptions::from_args(args(&["--join=example.org:6000"]));
+        let address = "example.org:6000".to_string();
+        assert_eq!(options.online, Some(OnlineRole::Join { address }));
+    }
+}
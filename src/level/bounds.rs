@@ -0,0 +1,280 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{vignette_image, CurrentLevel, LevelData, MainCamera, Player, RoomData};
+
+const BOUNDS_THICKNESS: f32 = 1.0;
+/// Generated bounds reach this far over the highest room, so launchers and balloons stay inside
+const BOUNDS_HEADROOM: f32 = 26.0;
+/// And this far under the lowest, past where the kill zones catch a fall
+const BOUNDS_FOOTING: f32 = 30.0;
+/// The warning starts fading in this close to the edge
+const BOUNDS_WARNING_DISTANCE: f32 = 6.0;
+const BOUNDS_WARNING_ALPHA: f32 = 0.45;
+const BOUNDS_WARNING_COLOR: Color = Color::rgb(0.6, 0.3, 1.0);
+const BOUNDS_WARNING_SIZE: f32 = 4.0;
+
+pub struct LevelBoundsPlugin;
+
+impl Plugin for LevelBoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_bounds_warnings)
+            .add_systems((spawn_level_bounds, warn_near_bounds).chain());
+    }
+}
+
+/// The box a level is played in. Levels can give their own, otherwise it's generated around
+/// their rooms
+#[derive(Resource, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct LevelBounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl LevelBounds {
+    /// Just fits every room, with headroom over the top and footing under the bottom
+    pub fn around_rooms(rooms: &[RoomData]) -> Option<LevelBounds> {
+        let mut corners = rooms.iter().map(|room| {
+            let center = Vec3::from(room.center);
+            let half_extents = Vec3::from(room.half_extents);
+            (center - half_extents, center + half_extents)
+        });
+        let first = corners.next()?;
+        let (min, max) = corners.fold(first, |(min, max), (room_min, room_max)| {
+            (min.min(room_min), max.max(room_max))
+        });
+        Some(LevelBounds {
+            min: (min - Vec3::Y * BOUNDS_FOOTING).into(),
+            max: (max + Vec3::Y * BOUNDS_HEADROOM).into(),
+        })
+    }
+
+    /// How far `point` is from the closest side or the ceiling, negative once it's outside. The
+    /// floor is left to the kill zones
+    pub fn distance_inside(&self, point: Vec3) -> f32 {
+        self.nearest_edge(point).2
+    }
+
+    /// The closest point on a side or the ceiling to `point`, the inward normal there and how far
+    /// away it is
+    pub fn nearest_edge(&self, point: Vec3) -> (Vec3, Vec3, f32) {
+        let min = Vec3::from(self.min);
+        let max = Vec3::from(self.max);
+        [
+            (Vec3::X, point.x - min.x),
+            (Vec3::NEG_X, max.x - point.x),
+            (Vec3::Z, point.z - min.z),
+            (Vec3::NEG_Z, max.z - point.z),
+            (Vec3::NEG_Y, max.y - point.y),
+        ]
+        .into_iter()
+        .map(|(normal, distance)| (point - normal * distance, normal, distance))
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .unwrap_or((point, Vec3::Y, f32::INFINITY))
+    }
+
+    /// Centers and half extents of the four sides and the ceiling, just outside the bounds
+    pub fn walls(&self) -> [(Vec3, Vec3); 5] {
+        let min = Vec3::from(self.min);
+        let max = Vec3::from(self.max);
+        let center = (min + max) / 2.0;
+        let half = (max - min) / 2.0 + Vec3::splat(BOUNDS_THICKNESS);
+        let offset = half - Vec3::splat(BOUNDS_THICKNESS / 2.0);
+        let thin = BOUNDS_THICKNESS / 2.0;
+        [
+            (center - Vec3::X * offset.x, Vec3::new(thin, half.y, half.z)),
+            (center + Vec3::X * offset.x, Vec3::new(thin, half.y, half.z)),
+            (center - Vec3::Z * offset.z, Vec3::new(half.x, half.y, thin)),
+            (center + Vec3::Z * offset.z, Vec3::new(half.x, half.y, thin)),
+            (center + Vec3::Y * offset.y, Vec3::new(half.x, thin, half.z)),
+        ]
+    }
+}
+
+/// How strongly to warn from `distance` inside the bounds, 0.0 while well clear of the edge
+pub fn bounds_warning(distance: f32) -> f32 {
+    (1.0 - distance / BOUNDS_WARNING_DISTANCE).clamp(0.0, 1.0)
+}
+
+/// One of the invisible colliders keeping the player inside the level
+#[derive(Component)]
+pub struct Boundary;
+
+/// Glows on the edge where the player is about to run into it, turned to face the camera
+#[derive(Component)]
+pub struct BoundsWarning;
+
+/// Tints the edges of the screen while the player is close to the bounds
+#[derive(Component)]
+pub struct BoundsVignette;
+
+/// Rebuilt whenever the level changes, the same as its rooms
+pub fn spawn_level_bounds(
+    mut commands: Commands,
+    mut level_events: EventReader<AssetEvent<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+    boundary_query: Query<Entity, With<Boundary>>,
+) {
+    let Some(current_level) = current_level else {return;};
+    let mut reload = current_level.is_changed();
+    for event in level_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                reload |= *handle == current_level.0;
+            }
+            AssetEvent::Removed { handle: _ } => (),
+        }
+    }
+    if !reload {
+        return;
+    }
+
+    for entity in &boundary_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<LevelBounds>();
+    let Some(bounds) = levels.get(&current_level.0).and_then(LevelData::bounds) else {return;};
+
+    for (center, half_extents) in bounds.walls() {
+        commands
+            .spawn(TransformBundle {
+                local: Transform::from_translation(center),
+                ..default()
+            })
+            .insert(Collider::cuboid(
+                half_extents.x,
+                half_extents.y,
+                half_extents.z,
+            ))
+            .insert(RigidBody::Fixed)
+            .insert(Name::new("Boundary"))
+            .insert(Boundary);
+    }
+    commands.insert_resource(bounds);
+}
+
+fn spawn_bounds_warnings(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Circle::new(BOUNDS_WARNING_SIZE))),
+            material: materials.add(StandardMaterial {
+                base_color: Color::NONE,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(NotShadowCaster)
+        .insert(BoundsWarning);
+
+    commands
+        .spawn(ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..default()
+            },
+            image: images.add(vignette_image()).into(),
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(-1),
+            ..default()
+        })
+        .insert(BoundsVignette);
+}
+
+fn warn_near_bounds(
+    bounds: Option<Res<LevelBounds>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_query: Query<&Transform, With<Player>>,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<Player>)>,
+    mut warning_query: Query<
+        (&mut Transform, &mut Visibility, &Handle<StandardMaterial>),
+        (With<BoundsWarning>, Without<Player>, Without<MainCamera>),
+    >,
+    mut vignette_query: Query<&mut BackgroundColor, With<BoundsVignette>>,
+) {
+    let edge = bounds
+        .as_ref()
+        .zip(player_query.get_single().ok())
+        .map(|(bounds, player)| bounds.nearest_edge(player.translation));
+    let strength = edge.map_or(0.0, |(_, _, distance)| bounds_warning(distance));
+    let color = BOUNDS_WARNING_COLOR.with_a(BOUNDS_WARNING_ALPHA * strength);
+
+    for mut background in &mut vignette_query {
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+
+    for (mut transform, mut visibility, material) in &mut warning_query {
+        let Some((point, normal, _)) = edge.filter(|_| strength > 0.0) else {*visibility = Visibility::Hidden; continue;};
+        // Nudged in off the boundary so it isn't hidden by anything flush against it
+        transform.translation = point + normal * 0.05;
+        if let Ok(camera) = camera_query.get_single() {
+            transform.look_at(camera.translation, Vec3::Y);
+        }
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = color;
+        }
+        *visibility = Visibility::Inherited;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn room(center: [f32; 3], half_extents: [f32; 3]) -> RoomData {
+        RoomData {
+            name: String::new(),
+            center,
+            half_extents,
+            props: Vec::new(),
+            mood: None,
+            diorama: None,
+        }
+    }
+
+    #[test]
+    fn generated_bounds_fit_every_room() {
+        let rooms = [
+            room([-12.5, 12.0, 0.0], [12.5, 12.0, 25.0]),
+            room([12.5, 12.0, 0.0], [12.5, 12.0, 25.0]),
+        ];
+        let bounds = LevelBounds::around_rooms(&rooms).unwrap();
+        assert_eq!(bounds.min, [-25.0, -BOUNDS_FOOTING, -25.0]);
+        assert_eq!(bounds.max, [25.0, 24.0 + BOUNDS_HEADROOM, 25.0]);
+        assert!(LevelBounds::around_rooms(&[]).is_none());
+    }
+
+    #[test]
+    fn the_nearest_edge_ignores_the_floor() {
+        let bounds = LevelBounds {
+            min: [-10.0, -10.0, -10.0],
+            max: [10.0, 10.0, 10.0],
+        };
+        let (point, normal, distance) = bounds.nearest_edge(Vec3::new(8.0, -9.0, 0.0));
+        assert_eq!(point, Vec3::new(10.0, -9.0, 0.0));
+        assert_eq!(normal, Vec3::NEG_X);
+        assert_eq!(distance, 2.0);
+        assert!(bounds.distance_inside(Vec3::new(0.0, 0.0, 12.0)) < 0.0);
+    }
+
+    #[test]
+    fn the_warning_fades_in_towards_the_edge() {
+        assert_eq!(bounds_warning(BOUNDS_WARNING_DISTANCE * 2.0), 0.0);
+        assert!(bounds_warning(BOUNDS_WARNING_DISTANCE / 2.0) > 0.0);
+        assert_eq!(bounds_warning(0.0), 1.0);
+    }
+}
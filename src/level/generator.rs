@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+
+use crate::{
+    ChallengeReward, Idea, LevelData, Mood, PropData, RoomData, SkyData, SurfaceType, WeatherScript,
+    GRAVITY,
+};
+
+/// Mirrors the single jump force in `Jump`
+const SINGLE_JUMP_FORCE: f32 = 10.0;
+/// The player's base run speed, generated gaps never assume they are sprinting
+const RUN_SPEED: f32 = 7.5;
+/// Only this much of a jump's full height and reach is ever asked of the player
+const REACH_MARGIN: f32 = 0.8;
+const MIN_GAP: f32 = 1.0;
+const PLATFORM_ATTEMPTS: usize = 16;
+const WALL_SHAFT_EVERY: usize = 5;
+const WALL_SHAFT_WIDTH: f32 = 4.0;
+const WALL_SHAFT_HEIGHT: f32 = 10.0;
+
+/// How far the player can get with a single jump from a standing run
+#[derive(Clone, Copy)]
+pub struct JumpLimits {
+    pub jump_force: f32,
+    pub gravity: f32,
+    pub run_speed: f32,
+}
+
+impl Default for JumpLimits {
+    fn default() -> Self {
+        JumpLimits {
+            jump_force: SINGLE_JUMP_FORCE,
+            gravity: GRAVITY.y.abs(),
+            run_speed: RUN_SPEED,
+        }
+    }
+}
+
+impl JumpLimits {
+    pub fn max_height(&self) -> f32 {
+        self.jump_force * self.jump_force / (2.0 * self.gravity)
+    }
+
+    /// Horizontal distance covered before landing `rise` above the take off height
+    pub fn reach(&self, rise: f32) -> Option<f32> {
+        let discriminant = self.jump_force * self.jump_force - 2.0 * self.gravity * rise;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let air_time = (self.jump_force + discriminant.sqrt()) / self.gravity;
+        Some(air_time * self.run_speed)
+    }
+
+    /// Whether a gap of `gap` edge to edge that climbs `rise` can be cleared with room to spare
+    pub fn can_clear(&self, gap: f32, rise: f32) -> bool {
+        if rise > self.max_height() * REACH_MARGIN {
+            return false;
+        }
+        self.reach(rise)
+            .map_or(false, |reach| gap <= reach * REACH_MARGIN)
+    }
+}
+
+/// Small splitmix64 generator, plenty for laying out levels and the same on every platform
+pub struct SeededRng(u64);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A float in `0.0..1.0`
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+/// A generated platform, `center` is the middle of its box and `size` its full extents
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneratedPlatform {
+    pub center: Vec3,
+    pub size: Vec3,
+}
+
+impl GeneratedPlatform {
+    pub fn top(&self) -> f32 {
+        self.center.y + self.size.y / 2.0
+    }
+
+    /// Edge to edge distance to `other` across the floor, along the line between their centers
+    pub fn gap_to(&self, other: &GeneratedPlatform) -> f32 {
+        let offset = Vec2::new(
+            other.center.x - self.center.x,
+            other.center.z - self.center.z,
+        );
+        let direction = offset.normalize_or_zero();
+        let own_half = half_width_along(self.size, direction);
+        let other_half = half_width_along(other.size, direction);
+        offset.length() - own_half - other_half
+    }
+}
+
+/// How far a ray from a box's center in `direction` travels before leaving its footprint
+fn half_width_along(size: Vec3, direction: Vec2) -> f32 {
+    let across_x = if direction.x.abs() > f32::EPSILON {
+        size.x / 2.0 / direction.x.abs()
+    } else {
+        f32::MAX
+    };
+    let across_z = if direction.y.abs() > f32::EPSILON {
+        size.z / 2.0 / direction.y.abs()
+    } else {
+        f32::MAX
+    };
+    across_x.min(across_z)
+}
+
+/// Lays out a chain of `count` platforms where each one can be jumped to from the last
+pub fn generate_platforms(seed: u64, count: usize, limits: JumpLimits) -> Vec<GeneratedPlatform> {
+    let mut rng = SeededRng::new(seed);
+    let mut platforms = vec![GeneratedPlatform {
+        center: Vec3::new(0.0, -0.5, 0.0),
+        size: Vec3::new(6.0, 1.0, 6.0),
+    }];
+    let mut heading = rng.range(0.0, std::f32::consts::TAU);
+
+    while platforms.len() < count {
+        let previous = *platforms.last().unwrap();
+        let mut next = None;
+        for _ in 0..PLATFORM_ATTEMPTS {
+            heading += rng.range(-0.8, 0.8);
+            let size = Vec3::new(rng.range(2.5, 6.0), 1.0, rng.range(2.5, 6.0));
+            let rise = rng.range(-2.0, limits.max_height() * REACH_MARGIN);
+            let max_gap = limits.reach(rise).unwrap_or(0.0) * REACH_MARGIN;
+            let gap = rng.range(MIN_GAP, max_gap.max(MIN_GAP));
+
+            let direction = Vec2::new(heading.cos(), heading.sin());
+            let along = half_width_along(previous.size, direction)
+                + gap
+                + half_width_along(size, direction);
+            let candidate = GeneratedPlatform {
+                center: Vec3::new(
+                    previous.center.x + direction.x * along,
+                    previous.top() + rise - size.y / 2.0,
+                    previous.center.z + direction.y * along,
+                ),
+                size,
+            };
+            if limits.can_clear(
+                previous.gap_to(&candidate),
+                candidate.top() - previous.top(),
+            ) {
+                next = Some(candidate);
+                break;
+            }
+        }
+
+        // Fall back to a short flat hop if nothing random fits
+        let next = next.unwrap_or_else(|| {
+            let direction = Vec2::new(heading.cos(), heading.sin());
+            let along = half_width_along(previous.size, direction) * 2.0 + MIN_GAP;
+            GeneratedPlatform {
+                center: previous.center + Vec3::new(direction.x * along, 0.0, direction.y * along),
+                size: previous.size,
+            }
+        });
+        platforms.push(next);
+    }
+    platforms
+}
+
+/// Builds a single room level around a generated platform chain, with wall jump shafts,
+/// crates to carry and a coin run along the route that rewards an idea
+pub fn generate_level(seed: u64, count: usize) -> LevelData {
+    let platforms = generate_platforms(seed, count, JumpLimits::default());
+    let mut rng = SeededRng::new(seed ^ 0x5eed);
+    let mut props = Vec::new();
+
+    for (index, platform) in platforms.iter().enumerate() {
+        let surface = if rng.chance(0.15) {
+            SurfaceType::Ice
+        } else {
+            SurfaceType::default()
+        };
+        props.push(PropData::Block {
+            position: platform.center.into(),
+            size: platform.size.into(),
+            ledge: true,
+            surface,
+            stylized: false,
+        });
+
+        let top = platform.top();
+        if index > 0 && index % WALL_SHAFT_EVERY == 0 {
+            // Two facing walls beside the platform to climb with a wall jump chain
+            let side = Vec3::new(platform.size.x / 2.0 + 1.0, 0.0, 0.0);
+            for offset in [side, side + Vec3::X * WALL_SHAFT_WIDTH] {
+                props.push(PropData::Wall {
+                    position: (platform.center + offset + Vec3::Y * WALL_SHAFT_HEIGHT / 2.0).into(),
+                    size: [0.5, WALL_SHAFT_HEIGHT, platform.size.z],
+                });
+            }
+        } else if index > 0 && rng.chance(0.2) {
+            props.push(PropData::Crate {
+                position: [platform.center.x, top + 1.5, platform.center.z],
+            });
+        }
+    }
+
+    let ideas = [
+        Idea::Cube,
+        Idea::Spring,
+        Idea::Wheel,
+        Idea::Rope,
+        Idea::Balloon,
+    ];
+    let reward = ideas[(rng.next_u64() % ideas.len() as u64) as usize];
+    let start = platforms[0];
+    props.push(PropData::Challenge {
+        position: [
+            start.center.x + 2.0,
+            start.top() + 0.1,
+            start.center.z + 2.0,
+        ],
+        seconds: platforms.len() as f32 * 2.5,
+        coins: platforms
+            .iter()
+            .skip(1)
+            .map(|platform| [platform.center.x, platform.top() + 1.0, platform.center.z])
+            .collect(),
+        reward: ChallengeReward::Idea(reward),
+    });
+
+    let (min, max) = platforms.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), platform| {
+            (
+                min.min(platform.center - platform.size / 2.0),
+                max.max(platform.center + platform.size / 2.0),
+            )
+        },
+    );
+    let min = min - Vec3::new(10.0, 25.0, 10.0);
+    let max = max + Vec3::new(10.0, 35.0, 10.0);
+    let center = (min + max) / 2.0;
+    let half_extents = (max - min) / 2.0;
+
+    props.push(PropData::KillZone {
+        position: [center.x, min.y + 2.0, center.z],
+        half_extents: [half_extents.x, 2.0, half_extents.z],
+    });
+
+    LevelData {
+        rooms: vec![RoomData {
+            name: format!("Generated {seed}"),
+            center: center.into(),
+            half_extents: half_extents.into(),
+            props,
+            mood: None,
+            diorama: None,
+        }],
+        weather: WeatherScript::default(),
+        mood: Mood::default(),
+        quests: Vec::new(),
+        sky: SkyData::default(),
+        bounds: None,
+        tutorial: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_platforms() {
+        let limits = JumpLimits::default();
+        assert_eq!(
+            generate_platforms(42, 20, limits),
+            generate_platforms(42, 20, limits)
+        );
+        assert_ne!(
+            generate_platforms(42, 20, limits),
+            generate_platforms(43, 20, limits)
+        );
+    }
+
+    #[test]
+    fn every_platform_is_reachable_from_the_last() {
+        let limits = JumpLimits::default();
+        for seed in 0..50 {
+            let platforms = generate_platforms(seed, 30, limits);
+            assert_eq!(platforms.len(), 30);
+            for pair in platforms.windows(2) {
+                let gap = pair[0].gap_to(&pair[1]);
+                let rise = pair[1].top() - pair[0].top();
+                assert!(
+                    limits.can_clear(gap, rise),
+                    "seed {seed}: {gap} gap, {rise} rise"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reach_shrinks_as_the_landing_rises() {
+        let limits = JumpLimits::default();
+        assert!(limits.reach(0.0).unwrap() > limits.reach(1.0).unwrap());
+        assert!(limits.reach(limits.max_height() + 0.1).is_none());
+    }
+}
@@ -0,0 +1,267 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_path, spawn_enemy, AudioUnlocked, Enemy, EnemyPopulation, MovingPlatform, Player,
+    PlayerAction, Settling,
+};
+
+const DOOR_SPEED: f32 = 3.0;
+
+pub struct LevelLogicPlugin;
+
+impl Plugin for LevelLogicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LevelSignal>().add_systems(
+            (
+                fire_triggers,
+                press_switches,
+                hit_switches_with_throws,
+                run_reactions,
+                animate_doors,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// A named on/off signal, sent by triggers and switches and listened to by `Reaction`s
+#[derive(Clone, Debug)]
+pub struct LevelSignal {
+    pub name: String,
+    pub active: bool,
+}
+
+/// A sensor that sends `name` when the player enters and, unless `once` is set, turns it off again
+/// when they leave
+#[derive(Component)]
+pub struct Trigger {
+    pub name: String,
+    pub once: bool,
+    pub fired: bool,
+}
+
+impl Trigger {
+    pub fn new(name: String, once: bool) -> Self {
+        Trigger {
+            name,
+            once,
+            fired: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum ReactionAction {
+    OpenDoor,
+    StartPlatform,
+    SpawnEnemies(Vec<[f32; 3]>),
+    PlaySound(String),
+}
+
+/// Sends `name` each time the player presses grab while standing inside it
+#[derive(Component)]
+pub struct Switch {
+    pub name: String,
+    pub player_inside: bool,
+}
+
+impl Switch {
+    pub fn new(name: String) -> Self {
+        Switch {
+            name,
+            player_inside: false,
+        }
+    }
+}
+
+/// Runs `action` whenever the `LevelSignal` named `on` is sent
+#[derive(Component)]
+pub struct Reaction {
+    pub on: String,
+    pub action: ReactionAction,
+}
+
+/// Slides by `open_offset` from where it was placed while open
+#[derive(Component)]
+pub struct Door {
+    pub closed_position: Vec3,
+    pub open_offset: Vec3,
+    pub open: bool,
+}
+
+pub fn fire_triggers(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut signals: EventWriter<LevelSignal>,
+    mut trigger_query: Query<&mut Trigger>,
+    player_query: Query<(), With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let (e1, e2, started) = match collision_event {
+            CollisionEvent::Started(e1, e2, _) => (*e1, *e2, true),
+            CollisionEvent::Stopped(e1, e2, _) => (*e1, *e2, false),
+        };
+
+        let trigger_entity = if player_query.contains(e1) {
+            e2
+        } else if player_query.contains(e2) {
+            e1
+        } else {
+            continue;
+        };
+
+        let Ok(mut trigger) = trigger_query.get_mut(trigger_entity) else {continue;};
+        if trigger.once && (trigger.fired || !started) {
+            continue;
+        }
+
+        trigger.fired = true;
+        signals.send(LevelSignal {
+            name: trigger.name.clone(),
+            active: started,
+        });
+    }
+}
+
+pub fn press_switches(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut signals: EventWriter<LevelSignal>,
+    mut switch_query: Query<&mut Switch>,
+    player_query: Query<&ActionState<PlayerAction>, With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let (e1, e2, started) = match collision_event {
+            CollisionEvent::Started(e1, e2, _) => (*e1, *e2, true),
+            CollisionEvent::Stopped(e1, e2, _) => (*e1, *e2, false),
+        };
+
+        let switch_entity = if player_query.contains(e1) {
+            e2
+        } else if player_query.contains(e2) {
+            e1
+        } else {
+            continue;
+        };
+
+        if let Ok(mut switch) = switch_query.get_mut(switch_entity) {
+            switch.player_inside = started;
+        }
+    }
+
+    let Ok(action) = player_query.get_single() else {return;};
+    if !action.just_pressed(PlayerAction::Grab) {
+        return;
+    }
+
+    for switch in &switch_query {
+        if switch.player_inside {
+            signals.send(LevelSignal {
+                name: switch.name.clone(),
+                active: true,
+            });
+        }
+    }
+}
+
+/// Anything thrown and still in flight flips the switch it hits, the same as pressing Grab in it
+pub fn hit_switches_with_throws(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut signals: EventWriter<LevelSignal>,
+    switch_query: Query<&Switch>,
+    thrown_query: Query<(), With<Settling>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let switch_entity = if thrown_query.contains(*e1) {
+            *e2
+        } else if thrown_query.contains(*e2) {
+            *e1
+        } else {
+            continue;
+        };
+
+        if let Ok(switch) = switch_query.get(switch_entity) {
+            signals.send(LevelSignal {
+                name: switch.name.clone(),
+                active: true,
+            });
+        }
+    }
+}
+
+pub fn run_reactions(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    audio_unlocked: Res<AudioUnlocked>,
+    population: Res<EnemyPopulation>,
+    mut signals: EventReader<LevelSignal>,
+    mut reaction_query: Query<(&Reaction, Option<&mut Door>, Option<&mut MovingPlatform>)>,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    let mut alive = enemy_query.iter().count();
+    for signal in signals.iter() {
+        for (reaction, door, platform) in &mut reaction_query {
+            if reaction.on != signal.name {
+                continue;
+            }
+
+            match &reaction.action {
+                ReactionAction::OpenDoor => {
+                    if let Some(mut door) = door {
+                        door.open = signal.active;
+                    }
+                }
+                ReactionAction::StartPlatform => {
+                    if let Some(mut platform) = platform {
+                        platform.active = signal.active;
+                    }
+                }
+                ReactionAction::SpawnEnemies(positions) => {
+                    if !signal.active {
+                        continue;
+                    }
+                    for position in positions {
+                        if !population.has_room(alive) {
+                            break;
+                        }
+                        alive += 1;
+                        spawn_enemy(
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                            Vec3::from(*position),
+                        );
+                    }
+                }
+                ReactionAction::PlaySound(path) => {
+                    if signal.active && audio_unlocked.0 {
+                        audio.play(asset_server.load(asset_path(path)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn animate_doors(time: Res<Time>, mut door_query: Query<(&mut Transform, &Door)>) {
+    for (mut transform, door) in &mut door_query {
+        let target = if door.open {
+            door.closed_position + door.open_offset
+        } else {
+            door.closed_position
+        };
+        let remaining = target - transform.translation;
+        let step = DOOR_SPEED * time.delta_seconds();
+        if remaining.length() <= step {
+            transform.translation = target;
+        } else {
+            transform.translation += remaining.normalize() * step;
+        }
+    }
+}
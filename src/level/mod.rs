@@ -0,0 +1,167 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    asset_path, AddConsoleCommand, DioramaView, LaunchOptions, Mood, QuestData, SkyData,
+    TutorialStep, WeatherScript,
+};
+
+pub mod props;
+pub use props::*;
+
+pub mod rooms;
+pub use rooms::*;
+
+pub mod logic;
+pub use logic::*;
+
+pub mod generator;
+pub use generator::*;
+
+pub mod bounds;
+pub use bounds::*;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<LevelData>()
+            .init_asset_loader::<LevelDataLoader>()
+            .add_startup_system(load_starting_level)
+            .add_plugin(RoomStreamingPlugin)
+            .add_plugin(LevelLogicPlugin)
+            .add_plugin(LevelBoundsPlugin)
+            .add_console_command(
+                "spawn",
+                "spawn item crate | spawn balloon",
+                spawn_prop_command,
+            );
+    }
+}
+
+/// A level file, the world is split into rooms that are streamed in and out around the player
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "8f0d2b7e-6a3c-4e51-b9d4-1c7a5e2f9b36"]
+pub struct LevelData {
+    pub rooms: Vec<RoomData>,
+    #[serde(default)]
+    pub weather: WeatherScript,
+    #[serde(default)]
+    pub mood: Mood,
+    #[serde(default)]
+    pub quests: Vec<QuestData>,
+    #[serde(default)]
+    pub sky: SkyData,
+    /// Generated around the rooms when it's left out
+    #[serde(default)]
+    pub bounds: Option<LevelBounds>,
+    #[serde(default)]
+    pub tutorial: Vec<TutorialStep>,
+}
+
+impl LevelData {
+    /// Whether `point` is inside any room, grown by `margin` on every side
+    pub fn contains(&self, point: Vec3, margin: f32) -> bool {
+        self.rooms.iter().any(|room| {
+            let offset = (point - Vec3::from(room.center)).abs();
+            offset
+                .cmple(Vec3::from(room.half_extents) + Vec3::splat(margin))
+                .all()
+        })
+    }
+
+    pub fn bounds(&self) -> Option<LevelBounds> {
+        self.bounds
+            .or_else(|| LevelBounds::around_rooms(&self.rooms))
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct RoomData {
+    pub name: String,
+    pub center: [f32; 3],
+    pub half_extents: [f32; 3],
+    #[serde(default)]
+    pub props: Vec<PropData>,
+    /// Replaces the level's mood while the player is in this room
+    #[serde(default)]
+    pub mood: Option<Mood>,
+    /// Shows the room from a fixed isometric angle while the player is in it, for puzzle rooms
+    #[serde(default)]
+    pub diorama: Option<DioramaView>,
+}
+
+#[derive(Resource)]
+pub struct CurrentLevel(pub Handle<LevelData>);
+
+#[derive(Default)]
+pub struct LevelDataLoader;
+
+impl AssetLoader for LevelDataLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level = ron::de::from_bytes::<LevelData>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+const GENERATED_PLATFORMS: usize = 40;
+
+fn load_starting_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    options: Res<LaunchOptions>,
+    mut levels: ResMut<Assets<LevelData>>,
+) {
+    let handle = if let Some(seed) = options.seed {
+        levels.add(generate_level(seed, GENERATED_PLATFORMS))
+    } else {
+        asset_server.load(asset_path(&options.level))
+    };
+    commands.insert_resource(CurrentLevel(handle));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn points_outside_every_room_are_out_of_the_level() {
+        let room = |center: [f32; 3]| RoomData {
+            name: String::new(),
+            center,
+            half_extents: [10.0, 5.0, 10.0],
+            props: Vec::new(),
+            mood: None,
+            diorama: None,
+        };
+        let level = LevelData {
+            rooms: vec![room([0.0, 0.0, 0.0]), room([30.0, 0.0, 0.0])],
+            weather: WeatherScript::default(),
+            mood: Mood::default(),
+            quests: Vec::new(),
+            sky: SkyData::default(),
+            bounds: None,
+            tutorial: Vec::new(),
+        };
+        assert!(level.contains(Vec3::new(25.0, 4.0, -9.0), 0.0));
+        assert!(!level.contains(Vec3::new(15.0, 0.0, 0.0), 0.0));
+        assert!(level.contains(Vec3::new(0.0, -7.0, 0.0), 3.0));
+        assert!(!level.contains(Vec3::new(0.0, -9.0, 0.0), 3.0));
+    }
+}
@@ -0,0 +1,849 @@
+use bevy::{ecs::system::SystemState, prelude::*};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    spawn_balloon, spawn_chain, spawn_coin, spawn_costume, spawn_critter, spawn_crumbling_platform,
+    spawn_ending_trigger, spawn_ladder, spawn_memory, spawn_seesaw, spawn_shopkeeper,
+    spawn_signpost, spawn_swing_door, spawn_turret, spawn_water, AmbientSound, BoulderEmitter,
+    ChallengeReward, ChallengeTrigger, Costume, Crusher, CutsceneShot, Door, Elevator,
+    ElevatorCall, EntityPools, GustPrefab, HintTrigger, Item, KillZone, LedgeBlockPrefab,
+    MediumItem, MemoryRecord, MovingPlatform, Pendulum, PlatformDelta, Player, PlayerAction,
+    Prefab, PressurePlate, Quicksand, Rail, Reaction, ReactionAction, ShopStock, ShowHint,
+    SoundEmitter, Spawner, Stylized, SurfaceType, Switch, Trigger, WallPrefab, Weight,
+    WindZonePrefab, ZipLine,
+};
+
+const CONSOLE_SPAWN_DISTANCE: f32 = 3.0;
+
+/// A single piece of level geometry or interactable as described in a level file
+#[derive(Deserialize, Clone)]
+pub enum PropData {
+    Block {
+        position: [f32; 3],
+        size: [f32; 3],
+        #[serde(default)]
+        ledge: bool,
+        #[serde(default)]
+        surface: SurfaceType,
+        #[serde(default)]
+        stylized: bool,
+    },
+    Wall {
+        position: [f32; 3],
+        size: [f32; 3],
+    },
+    Crate {
+        position: [f32; 3],
+    },
+    WindZone {
+        position: [f32; 3],
+        half_extents: [f32; 3],
+        force: [f32; 3],
+    },
+    Gust {
+        points: Vec<[f32; 3]>,
+        half_extents: [f32; 3],
+        force: [f32; 3],
+        speed: f32,
+    },
+    Quicksand {
+        position: [f32; 3],
+        half_extents: [f32; 3],
+        sink_speed: f32,
+    },
+    Rail {
+        points: Vec<[f32; 3]>,
+    },
+    Chain {
+        anchor: [f32; 3],
+        links: usize,
+    },
+    BoulderEmitter {
+        position: [f32; 3],
+        interval: f32,
+        radius: f32,
+        #[serde(default)]
+        velocity: [f32; 3],
+    },
+    KillZone {
+        position: [f32; 3],
+        half_extents: [f32; 3],
+    },
+    Challenge {
+        position: [f32; 3],
+        seconds: f32,
+        coins: Vec<[f32; 3]>,
+        reward: ChallengeReward,
+    },
+    Crusher {
+        position: [f32; 3],
+        size: [f32; 3],
+        drop: f32,
+        wait_seconds: f32,
+    },
+    Pendulum {
+        pivot: [f32; 3],
+        axis: [f32; 3],
+        length: f32,
+        amplitude: f32,
+        period: f32,
+    },
+    Trigger {
+        position: [f32; 3],
+        half_extents: [f32; 3],
+        name: String,
+        #[serde(default)]
+        once: bool,
+    },
+    Hint {
+        position: [f32; 3],
+        half_extents: [f32; 3],
+        id: String,
+        action: PlayerAction,
+    },
+    Reaction {
+        on: String,
+        action: ReactionAction,
+    },
+    Door {
+        position: [f32; 3],
+        size: [f32; 3],
+        open_offset: [f32; 3],
+        on: String,
+    },
+    MovingPlatform {
+        points: Vec<[f32; 3]>,
+        size: [f32; 3],
+        speed: f32,
+        #[serde(default)]
+        on: Option<String>,
+    },
+    PressurePlate {
+        position: [f32; 3],
+        name: String,
+        required_weight: Weight,
+    },
+    Switch {
+        position: [f32; 3],
+        name: String,
+    },
+    Elevator {
+        position: [f32; 3],
+        size: [f32; 3],
+        stops: Vec<f32>,
+        calls: Vec<ElevatorCall>,
+        speed: f32,
+    },
+    SwingDoor {
+        position: [f32; 3],
+        size: [f32; 3],
+    },
+    Seesaw {
+        position: [f32; 3],
+        size: [f32; 3],
+    },
+    ZipLine {
+        start: [f32; 3],
+        end: [f32; 3],
+    },
+    /// Climbed facing `facing` from `bottom` up onto the platform `height` above
+    Ladder {
+        bottom: [f32; 3],
+        height: f32,
+        facing: [f32; 3],
+    },
+    Balloon {
+        position: [f32; 3],
+    },
+    Turret {
+        position: [f32; 3],
+        interval: f32,
+        range: f32,
+    },
+    Spawner {
+        position: [f32; 3],
+        cooldown: f32,
+        budget: u32,
+        max_alive: usize,
+        range: f32,
+    },
+    Memory {
+        position: [f32; 3],
+        id: String,
+        title: String,
+        text: String,
+        #[serde(default)]
+        image: Option<String>,
+    },
+    Costume {
+        position: [f32; 3],
+        costume: Costume,
+    },
+    Critter {
+        position: [f32; 3],
+    },
+    Coin {
+        position: [f32; 3],
+        #[serde(default = "default_coin_amount")]
+        amount: u8,
+    },
+    Shop {
+        position: [f32; 3],
+        stock: Vec<ShopStock>,
+    },
+    Signpost {
+        position: [f32; 3],
+        text: String,
+    },
+    Crumbling {
+        position: [f32; 3],
+        size: [f32; 3],
+        #[serde(default = "default_crumble_respawn_seconds")]
+        respawn_seconds: f32,
+    },
+    Water {
+        position: [f32; 3],
+        half_extents: [f32; 3],
+    },
+    /// Walking in here plays `cutscene` and rolls the credits
+    Ending {
+        position: [f32; 3],
+        half_extents: [f32; 3],
+        #[serde(default)]
+        cutscene: Vec<CutsceneShot>,
+    },
+}
+
+fn default_coin_amount() -> u8 {
+    1
+}
+
+fn default_crumble_respawn_seconds() -> f32 {
+    3.0
+}
+
+/// Spawns a prop just in front of the player from the console, e.g. `spawn item crate`
+pub fn spawn_prop_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let mut state: SystemState<(
+        Commands,
+        ResMut<EntityPools>,
+        ResMut<Assets<Mesh>>,
+        ResMut<Assets<StandardMaterial>>,
+        Query<&Transform, With<Player>>,
+    )> = SystemState::new(world);
+    let (mut commands, mut pools, mut meshes, mut materials, player_query) = state.get_mut(world);
+    let Ok(player) = player_query.get_single() else {return Err("no player to spawn beside".to_string());};
+    let position =
+        (player.translation + player.forward() * CONSOLE_SPAWN_DISTANCE + Vec3::Y).into();
+    let prop = match args {
+        ["item", "crate"] => PropData::Crate { position },
+        ["balloon"] => PropData::Balloon { position },
+        _ => return Err("unknown prop".to_string()),
+    };
+    spawn_prop(
+        &mut commands,
+        &mut pools,
+        &mut meshes,
+        &mut materials,
+        &prop,
+    );
+    state.apply(world);
+    Ok(format!("spawned {}", args.join(" ")))
+}
+
+pub fn spawn_prop(
+    commands: &mut Commands,
+    pools: &mut EntityPools,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    prop: &PropData,
+) -> Entity {
+    match prop {
+        PropData::Block {
+            position,
+            size,
+            ledge,
+            surface,
+            stylized,
+        } => {
+            let block = LedgeBlockPrefab::new(Vec3::from(*position), Vec3::from(*size))
+                .with_surface(*surface)
+                .with_ledge(*ledge)
+                .spawn(commands, meshes, materials);
+            if *stylized {
+                commands.entity(block).insert(Stylized::default());
+            }
+            block
+        }
+        PropData::Wall { position, size } => {
+            WallPrefab::new(Vec3::from(*position), Vec3::from(*size))
+                .spawn(commands, meshes, materials)
+        }
+        PropData::Crate { position } => commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(2.0, 2.0, 2.0))),
+                material: materials.add(Color::BEIGE.into()),
+                transform: Transform::from_translation(Vec3::from(*position)),
+                ..default()
+            })
+            .insert(Collider::cuboid(1.0, 1.0, 1.0))
+            .insert(Item::default())
+            .insert(MediumItem)
+            .insert(RigidBody::Dynamic)
+            .insert(LockedAxes::ROTATION_LOCKED_X | LockedAxes::ROTATION_LOCKED_Z)
+            .insert(Velocity::default())
+            .id(),
+        PropData::WindZone {
+            position,
+            half_extents,
+            force,
+        } => WindZonePrefab::new(
+            Vec3::from(*position),
+            Vec3::from(*half_extents),
+            Vec3::from(*force),
+        )
+        .spawn(commands, meshes, materials),
+        PropData::Gust {
+            points,
+            half_extents,
+            force,
+            speed,
+        } => GustPrefab::new(
+            points.iter().map(|point| Vec3::from(*point)).collect(),
+            Vec3::from(*half_extents),
+            Vec3::from(*force),
+            *speed,
+        )
+        .spawn(commands, meshes, materials),
+        PropData::Quicksand {
+            position,
+            half_extents,
+            sink_speed,
+        } => {
+            let position = Vec3::from(*position);
+            let half_extents = Vec3::from(*half_extents);
+            commands
+                .spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(
+                        half_extents.x * 2.0,
+                        half_extents.y * 2.0,
+                        half_extents.z * 2.0,
+                    ))),
+                    material: materials.add(StandardMaterial {
+                        base_color: Color::rgba(0.76, 0.6, 0.4, 0.85),
+                        alpha_mode: AlphaMode::Blend,
+                        ..default()
+                    }),
+                    transform: Transform::from_translation(position),
+                    ..default()
+                })
+                .insert(Collider::cuboid(
+                    half_extents.x,
+                    half_extents.y,
+                    half_extents.z,
+                ))
+                .insert(Sensor)
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(Quicksand {
+                    sink_speed: *sink_speed,
+                    surface_height: position.y + half_extents.y,
+                })
+                .insert(RigidBody::Fixed)
+                .id()
+        }
+        PropData::Rail { points } => {
+            let points: Vec<Vec3> = points.iter().map(|point| Vec3::from(*point)).collect();
+            let rail = Rail::from_points(&points);
+            let mesh = meshes.add(Mesh::from(shape::Box::new(0.15, 0.15, 1.0)));
+            let material = materials.add(Color::SILVER.into());
+            let samples = rail.samples().to_vec();
+
+            commands
+                .spawn(SpatialBundle::default())
+                .insert(rail)
+                .with_children(|parent| {
+                    for pair in samples.windows(2) {
+                        let length = pair[0].distance(pair[1]);
+                        if length <= 0.0 {
+                            continue;
+                        }
+                        let midpoint = (pair[0] + pair[1]) / 2.0;
+                        parent.spawn(PbrBundle {
+                            mesh: mesh.clone(),
+                            material: material.clone(),
+                            transform: Transform::from_translation(midpoint)
+                                .looking_at(pair[1], Vec3::Y)
+                                .with_scale(Vec3::new(1.0, 1.0, length)),
+                            ..default()
+                        });
+                    }
+                })
+                .id()
+        }
+        PropData::Chain { anchor, links } => {
+            spawn_chain(commands, meshes, materials, Vec3::from(*anchor), *links)
+        }
+        PropData::BoulderEmitter {
+            position,
+            interval,
+            radius,
+            velocity,
+        } => commands
+            .spawn(TransformBundle {
+                local: Transform::from_translation(Vec3::from(*position)),
+                ..default()
+            })
+            .insert(BoulderEmitter::new(
+                *interval,
+                *radius,
+                Vec3::from(*velocity),
+            ))
+            .id(),
+        PropData::KillZone {
+            position,
+            half_extents,
+        } => {
+            let half_extents = Vec3::from(*half_extents);
+            commands
+                .spawn(TransformBundle {
+                    local: Transform::from_translation(Vec3::from(*position)),
+                    ..default()
+                })
+                .insert(Collider::cuboid(
+                    half_extents.x,
+                    half_extents.y,
+                    half_extents.z,
+                ))
+                .insert(Sensor)
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(KillZone)
+                .insert(RigidBody::Fixed)
+                .id()
+        }
+        PropData::Challenge {
+            position,
+            seconds,
+            coins,
+            reward,
+        } => commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Cylinder {
+                    radius: 0.8,
+                    height: 0.2,
+                    ..default()
+                })),
+                material: materials.add(Color::RED.into()),
+                transform: Transform::from_translation(Vec3::from(*position)),
+                ..default()
+            })
+            .insert(Collider::cylinder(0.5, 0.8))
+            .insert(Sensor)
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(RigidBody::Fixed)
+            .insert(ChallengeTrigger {
+                coins: coins.iter().map(|coin| Vec3::from(*coin)).collect(),
+                seconds: *seconds,
+                reward: *reward,
+            })
+            .id(),
+        PropData::Crusher {
+            position,
+            size,
+            drop,
+            wait_seconds,
+        } => {
+            let position = Vec3::from(*position);
+            let half_extents = Vec3::from(*size) / 2.0;
+            commands
+                .spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(size[0], size[1], size[2]))),
+                    material: materials.add(Color::DARK_GRAY.into()),
+                    transform: Transform::from_translation(position),
+                    ..default()
+                })
+                .insert(Collider::cuboid(
+                    half_extents.x,
+                    half_extents.y,
+                    half_extents.z,
+                ))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(Crusher::new(position, *drop, half_extents, *wait_seconds))
+                .insert(SoundEmitter::new(AmbientSound::Machinery))
+                .id()
+        }
+        PropData::Pendulum {
+            pivot,
+            axis,
+            length,
+            amplitude,
+            period,
+        } => {
+            let pendulum = Pendulum {
+                pivot: Vec3::from(*pivot),
+                axis: Vec3::from(*axis).normalize(),
+                length: *length,
+                amplitude: *amplitude,
+                period: *period,
+                elapsed: 0.0,
+            };
+            commands
+                .spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::UVSphere {
+                        radius: 1.0,
+                        ..default()
+                    })),
+                    material: materials.add(Color::MAROON.into()),
+                    transform: pendulum.transform(),
+                    ..default()
+                })
+                .insert(Collider::ball(1.0))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(pendulum)
+                .id()
+        }
+        PropData::Trigger {
+            position,
+            half_extents,
+            name,
+            once,
+        } => {
+            let half_extents = Vec3::from(*half_extents);
+            commands
+                .spawn(TransformBundle {
+                    local: Transform::from_translation(Vec3::from(*position)),
+                    ..default()
+                })
+                .insert(Collider::cuboid(
+                    half_extents.x,
+                    half_extents.y,
+                    half_extents.z,
+                ))
+                .insert(Sensor)
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(RigidBody::Fixed)
+                .insert(Trigger::new(name.clone(), *once))
+                .id()
+        }
+        PropData::Hint {
+            position,
+            half_extents,
+            id,
+            action,
+        } => {
+            let half_extents = Vec3::from(*half_extents);
+            commands
+                .spawn(TransformBundle {
+                    local: Transform::from_translation(Vec3::from(*position)),
+                    ..default()
+                })
+                .insert(Collider::cuboid(
+                    half_extents.x,
+                    half_extents.y,
+                    half_extents.z,
+                ))
+                .insert(Sensor)
+                .insert(ActiveEvents::COLLISION_EVENTS)
+                .insert(RigidBody::Fixed)
+                .insert(HintTrigger(ShowHint::new(id, *action)))
+                .id()
+        }
+        PropData::Reaction { on, action } => commands
+            .spawn(TransformBundle::default())
+            .insert(Reaction {
+                on: on.clone(),
+                action: action.clone(),
+            })
+            .id(),
+        PropData::Door {
+            position,
+            size,
+            open_offset,
+            on,
+        } => {
+            let position = Vec3::from(*position);
+            let door = WallPrefab::new(position, Vec3::from(*size))
+                .with_color(Color::OLIVE)
+                .spawn(commands, meshes, materials);
+            commands
+                .entity(door)
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(Door {
+                    closed_position: position,
+                    open_offset: Vec3::from(*open_offset),
+                    open: false,
+                })
+                .insert(Reaction {
+                    on: on.clone(),
+                    action: ReactionAction::OpenDoor,
+                });
+            door
+        }
+        PropData::MovingPlatform {
+            points,
+            size,
+            speed,
+            on,
+        } => {
+            let points: Vec<Vec3> = points.iter().map(|point| Vec3::from(*point)).collect();
+            // A level without any points gets a platform that stays put rather than a panic
+            let start = points.first().copied().unwrap_or_default();
+            let size = Vec3::from(*size);
+            let mut platform = commands.spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                material: materials.add(Color::ORANGE.into()),
+                transform: Transform::from_translation(start),
+                ..default()
+            });
+            platform
+                .insert(Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(MovingPlatform::new(points, *speed, on.is_none()))
+                .insert(PlatformDelta::default())
+                .insert(SoundEmitter::new(AmbientSound::Machinery));
+            if let Some(on) = on {
+                platform.insert(Reaction {
+                    on: on.clone(),
+                    action: ReactionAction::StartPlatform,
+                });
+            }
+            platform.id()
+        }
+        PropData::PressurePlate {
+            position,
+            name,
+            required_weight,
+        } => {
+            let position = Vec3::from(*position);
+            commands
+                .spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(2.0, 0.2, 2.0))),
+                    material: materials.add(Color::GOLD.into()),
+                    transform: Transform::from_translation(position),
+                    ..default()
+                })
+                .insert(Collider::cuboid(1.0, 0.1, 1.0))
+                .insert(RigidBody::Fixed)
+                .insert(PressurePlate::new(
+                    name.clone(),
+                    *required_weight,
+                    position.y,
+                ))
+                .id()
+        }
+        PropData::Switch { position, name } => commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(0.6, 1.2, 0.6))),
+                material: materials.add(Color::LIME_GREEN.into()),
+                transform: Transform::from_translation(Vec3::from(*position)),
+                ..default()
+            })
+            .insert(Collider::cuboid(1.0, 1.0, 1.0))
+            .insert(Sensor)
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(RigidBody::Fixed)
+            .insert(Switch::new(name.clone()))
+            .id(),
+        PropData::Elevator {
+            position,
+            size,
+            stops,
+            calls,
+            speed,
+        } => {
+            let size = Vec3::from(*size);
+            let half_extents = size / 2.0;
+            let mut position = Vec3::from(*position);
+            position.y = stops.first().copied().unwrap_or(position.y);
+            commands
+                .spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+                    material: materials.add(Color::SILVER.into()),
+                    transform: Transform::from_translation(position),
+                    ..default()
+                })
+                .insert(Collider::cuboid(
+                    half_extents.x,
+                    half_extents.y,
+                    half_extents.z,
+                ))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(SurfaceType::Metal)
+                .insert(PlatformDelta::default())
+                .insert(Elevator::new(
+                    stops.clone(),
+                    calls.clone(),
+                    *speed,
+                    half_extents,
+                ))
+                .insert(SoundEmitter::new(AmbientSound::Machinery))
+                .id()
+        }
+        PropData::SwingDoor { position, size } => spawn_swing_door(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            Vec3::from(*size),
+        ),
+        PropData::Seesaw { position, size } => spawn_seesaw(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            Vec3::from(*size),
+        ),
+        PropData::Ladder {
+            bottom,
+            height,
+            facing,
+        } => spawn_ladder(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*bottom),
+            *height,
+            Vec3::from(*facing),
+        ),
+        PropData::ZipLine { start, end } => {
+            let start = Vec3::from(*start);
+            let end = Vec3::from(*end);
+            let length = start.distance(end);
+            let cable_material = materials.add(Color::DARK_GRAY.into());
+            let post_material = materials.add(Color::BEIGE.into());
+
+            commands
+                .spawn(SpatialBundle::default())
+                .insert(ZipLine::new(start, end))
+                .with_children(|parent| {
+                    parent.spawn(PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Box::new(0.08, 0.08, length))),
+                        material: cable_material,
+                        transform: Transform::from_translation((start + end) / 2.0)
+                            .looking_at(end, Vec3::Y),
+                        ..default()
+                    });
+                    // Posts run from the ground up to each anchor
+                    for anchor in [start, end] {
+                        parent
+                            .spawn(PbrBundle {
+                                mesh: meshes.add(Mesh::from(shape::Box::new(0.4, anchor.y, 0.4))),
+                                material: post_material.clone(),
+                                transform: Transform::from_xyz(anchor.x, anchor.y / 2.0, anchor.z),
+                                ..default()
+                            })
+                            .insert(Collider::cuboid(0.2, anchor.y / 2.0, 0.2));
+                    }
+                })
+                .id()
+        }
+        PropData::Balloon { position } => {
+            spawn_balloon(commands, meshes, materials, Vec3::from(*position))
+        }
+        PropData::Turret {
+            position,
+            interval,
+            range,
+        } => spawn_turret(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            *interval,
+            *range,
+        ),
+        PropData::Spawner {
+            position,
+            cooldown,
+            budget,
+            max_alive,
+            range,
+        } => commands
+            .spawn(TransformBundle {
+                local: Transform::from_translation(Vec3::from(*position)),
+                ..default()
+            })
+            .insert(Spawner::new(*cooldown, *budget, *max_alive, *range))
+            .id(),
+        PropData::Memory {
+            position,
+            id,
+            title,
+            text,
+            image,
+        } => spawn_memory(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            MemoryRecord {
+                id: id.clone(),
+                title: title.clone(),
+                text: text.clone(),
+                image: image.clone(),
+            },
+        ),
+        PropData::Costume { position, costume } => {
+            spawn_costume(commands, meshes, materials, Vec3::from(*position), *costume)
+        }
+        PropData::Critter { position } => {
+            spawn_critter(commands, meshes, materials, Vec3::from(*position))
+        }
+        PropData::Coin { position, amount } => spawn_coin(
+            commands,
+            pools,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            *amount,
+        ),
+        PropData::Shop { position, stock } => spawn_shopkeeper(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            stock.clone(),
+        ),
+        PropData::Signpost { position, text } => spawn_signpost(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            text.clone(),
+        ),
+        PropData::Crumbling {
+            position,
+            size,
+            respawn_seconds,
+        } => spawn_crumbling_platform(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            Vec3::from(*size),
+            *respawn_seconds,
+        ),
+        PropData::Water {
+            position,
+            half_extents,
+        } => spawn_water(
+            commands,
+            meshes,
+            materials,
+            Vec3::from(*position),
+            Vec3::from(*half_extents),
+        ),
+        PropData::Ending {
+            position,
+            half_extents,
+            cutscene,
+        } => spawn_ending_trigger(
+            commands,
+            Vec3::from(*position),
+            Vec3::from(*half_extents),
+            cutscene.clone(),
+        ),
+    }
+}
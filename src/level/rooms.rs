@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{spawn_prop, AppState, CurrentLevel, EntityPools, LevelData, Pickup, Player, PoolKind};
+
+/// Rooms whose centers are further than this from the active room have their contents despawned
+const ROOM_STREAM_DISTANCE: f32 = 40.0;
+
+pub struct RoomStreamingPlugin;
+
+impl Plugin for RoomStreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ActiveRoom::default())
+            .add_systems((spawn_rooms, detect_room_entry, stream_rooms).chain())
+            .add_system(unload_rooms.in_schedule(OnExit(AppState::Playing)));
+    }
+}
+
+#[derive(Component)]
+pub struct Room {
+    pub index: usize,
+    pub center: Vec3,
+}
+
+/// Marks a room whose contents are currently spawned
+#[derive(Component)]
+pub struct RoomLoaded;
+
+/// Points back at the room entity that spawned this entity
+#[derive(Component)]
+pub struct RoomContent(pub Entity);
+
+#[derive(Resource, Default)]
+pub struct ActiveRoom(pub Option<Entity>);
+
+pub fn spawn_rooms(
+    mut commands: Commands,
+    mut level_events: EventReader<AssetEvent<LevelData>>,
+    mut active_room: ResMut<ActiveRoom>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+    room_query: Query<Entity, With<Room>>,
+    content_query: Query<Entity, With<RoomContent>>,
+) {
+    let Some(current_level) = current_level else {return;};
+    // Picking another level swaps the rooms over even if it was loaded before
+    let mut reload = current_level.is_changed();
+    for event in level_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                reload |= *handle == current_level.0;
+            }
+            AssetEvent::Removed { handle: _ } => (),
+        }
+    }
+    if !reload {
+        return;
+    }
+
+    for entity in content_query.iter().chain(room_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    active_room.0 = None;
+    // One that's still loading gets its rooms once it's created
+    let Some(level) = levels.get(&current_level.0) else {return;};
+
+    for (index, room) in level.rooms.iter().enumerate() {
+        let center = Vec3::from(room.center);
+        let half_extents = Vec3::from(room.half_extents);
+        commands
+            .spawn(TransformBundle {
+                local: Transform::from_translation(center),
+                ..default()
+            })
+            .insert(Name::new(room.name.clone()))
+            .insert(Room { index, center })
+            .insert(Collider::cuboid(
+                half_extents.x,
+                half_extents.y,
+                half_extents.z,
+            ))
+            .insert(Sensor)
+            .insert(ActiveEvents::COLLISION_EVENTS);
+    }
+}
+
+pub fn detect_room_entry(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut active_room: ResMut<ActiveRoom>,
+    player_query: Query<Entity, With<Player>>,
+    room_query: Query<Entity, With<Room>>,
+) {
+    let Ok(player_entity) = player_query.get_single() else {return;};
+    for collision_event in collision_events.iter() {
+        if let CollisionEvent::Started(e1, e2, _) = collision_event {
+            let room_entity = if *e1 == player_entity && room_query.contains(*e2) {
+                *e2
+            } else if *e2 == player_entity && room_query.contains(*e1) {
+                *e1
+            } else {
+                continue;
+            };
+
+            if active_room.0 != Some(room_entity) {
+                active_room.0 = Some(room_entity);
+            }
+        }
+    }
+}
+
+pub fn stream_rooms(
+    mut commands: Commands,
+    mut pools: ResMut<EntityPools>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    active_room: Res<ActiveRoom>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+    room_query: Query<(Entity, &Room, Option<&RoomLoaded>)>,
+    content_query: Query<(Entity, &RoomContent, Option<&Pickup>)>,
+) {
+    if !active_room.is_changed() {
+        return;
+    }
+    let Some(current_level) = current_level else {return;};
+    let Some(level) = levels.get(&current_level.0) else {return;};
+    let active_center = active_room
+        .0
+        .and_then(|entity| room_query.get(entity).ok())
+        .map(|(_, room, _)| room.center);
+    let Some(active_center) = active_center else {return;};
+
+    for (room_entity, room, loaded) in &room_query {
+        let should_load = room.center.distance(active_center) <= ROOM_STREAM_DISTANCE;
+
+        if should_load && loaded.is_none() {
+            let Some(room_data) = level.rooms.get(room.index) else {continue;};
+            for prop in &room_data.props {
+                let prop_entity =
+                    spawn_prop(&mut commands, &mut pools, &mut meshes, &mut materials, prop);
+                commands
+                    .entity(prop_entity)
+                    .insert(RoomContent(room_entity));
+            }
+            commands.entity(room_entity).insert(RoomLoaded);
+        } else if !should_load && loaded.is_some() {
+            // Coins are parked for the next room to load rather than thrown away
+            for (content_entity, content, pickup) in &content_query {
+                if content.0 != room_entity {
+                    continue;
+                }
+                if let Some(Pickup::Coin(_)) = pickup {
+                    commands.entity(content_entity).remove::<RoomContent>();
+                    pools.release(&mut commands, PoolKind::Coin, content_entity);
+                } else {
+                    commands.entity(content_entity).despawn_recursive();
+                }
+            }
+            commands.entity(room_entity).remove::<RoomLoaded>();
+        }
+    }
+}
+
+/// The rooms outlive a play session but their contents don't, so they're all marked unloaded to
+/// stream back in next time
+fn unload_rooms(
+    mut commands: Commands,
+    mut active_room: ResMut<ActiveRoom>,
+    room_query: Query<Entity, With<RoomLoaded>>,
+) {
+    active_room.0 = None;
+    for entity in &room_query {
+        commands.entity(entity).remove::<RoomLoaded>();
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::{
+    asset::{Asset, LoadState},
+    prelude::*,
+    utils::HashSet,
+};
+
+use crate::{
+    language_path, CampaignData, CreditsData, GameConfig, LanguageFile, LaunchOptions, LevelData,
+    LANGUAGES,
+};
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<AppState>()
+            .insert_resource(AssetCollection::default())
+            .add_systems(
+                (collect_assets, spawn_loading_screen).in_schedule(OnEnter(AppState::Loading)),
+            )
+            .add_system(track_loading.in_set(OnUpdate(AppState::Loading)))
+            .add_system(despawn_loading_screen.in_schedule(OnExit(AppState::Loading)))
+            .add_system(remember_session_start.in_schedule(OnEnter(AppState::Playing)))
+            .add_system(despawn_session.in_schedule(OnExit(AppState::Playing)));
+    }
+}
+
+/// Turns a path written by hand, in a level or launch flag, into one the asset server can load on
+/// every platform. Browsers fetch assets over HTTP, where backslashes, `./` and a leading
+/// `assets/` all point somewhere else than on disk
+pub fn asset_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = path.trim_start_matches("./").trim_start_matches('/');
+    path.strip_prefix("assets/").unwrap_or(path).to_string()
+}
+
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppState {
+    #[default]
+    Loading,
+    /// Picking which save slot to play, right after loading
+    ProfileSelect,
+    /// Picking which unlocked campaign level to play
+    LevelSelect,
+    Playing,
+}
+
+/// Every entity that existed before play started, so leaving play can clear out what it spawned
+#[derive(Resource, Default)]
+pub struct SessionStart(pub HashSet<Entity>);
+
+/// Everything the game needs on screen from the first frame, held so nothing streams in late
+#[derive(Resource, Default)]
+pub struct AssetCollection {
+    pub font: Handle<Font>,
+    pub grass: Handle<Image>,
+    handles: Vec<HandleUntyped>,
+}
+
+impl AssetCollection {
+    pub fn add<T: Asset>(&mut self, handle: Handle<T>) -> Handle<T> {
+        self.handles.push(handle.clone_untyped());
+        handle
+    }
+
+    /// How many of the collected assets have finished, failed loads count as finished
+    pub fn finished(&self, asset_server: &AssetServer) -> usize {
+        self.handles
+            .iter()
+            .filter(|handle| {
+                matches!(
+                    asset_server.get_load_state(handle.id()),
+                    LoadState::Loaded | LoadState::Failed
+                )
+            })
+            .count()
+    }
+
+    pub fn progress(&self, asset_server: &AssetServer) -> f32 {
+        if self.handles.is_empty() {
+            return 1.0;
+        }
+        self.finished(asset_server) as f32 / self.handles.len() as f32
+    }
+}
+
+#[derive(Component)]
+pub struct LoadingScreen;
+
+#[derive(Component)]
+pub struct LoadingBarFill;
+
+pub fn collect_assets(
+    mut collection: ResMut<AssetCollection>,
+    asset_server: Res<AssetServer>,
+    options: Res<LaunchOptions>,
+) {
+    let font = collection.add(asset_server.load("FiraSans-Bold.ttf"));
+    let grass = collection.add(asset_server.load("grass.png"));
+    collection.font = font;
+    collection.grass = grass;
+    for path in [
+        "audio/footstep_grass.wav",
+        "audio/footstep_ice.wav",
+        "audio/footstep_sand.wav",
+        "audio/footstep_metal.wav",
+        "audio/chime.wav",
+        "audio/bonk.wav",
+        "audio/water_loop.wav",
+        "audio/wind_loop.wav",
+        "audio/machinery_loop.wav",
+    ] {
+        collection.add::<AudioSource>(asset_server.load(path));
+    }
+    if options.seed.is_none() {
+        collection.add::<LevelData>(asset_server.load(asset_path(&options.level)));
+    }
+    collection.add::<GameConfig>(asset_server.load("game.config.ron"));
+    collection.add::<CreditsData>(asset_server.load("game.credits.ron"));
+    collection.add::<CampaignData>(asset_server.load("game.campaign.ron"));
+    for code in LANGUAGES {
+        collection.add::<LanguageFile>(asset_server.load(language_path(code).as_str()));
+    }
+}
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::rgb(0.1, 0.08, 0.15).into(),
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .insert(LoadingScreen)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(400.0), Val::Px(24.0)),
+                        padding: UiRect::all(Val::Px(3.0)),
+                        ..default()
+                    },
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                                ..default()
+                            },
+                            background_color: Color::TURQUOISE.into(),
+                            ..default()
+                        })
+                        .insert(LoadingBarFill);
+                });
+        });
+}
+
+pub fn track_loading(
+    asset_server: Res<AssetServer>,
+    collection: Res<AssetCollection>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut fill_query: Query<&mut Style, With<LoadingBarFill>>,
+) {
+    let progress = collection.progress(&asset_server);
+    for mut style in &mut fill_query {
+        style.size.width = Val::Percent(progress * 100.0);
+    }
+    if progress >= 1.0 {
+        next_state.set(AppState::ProfileSelect);
+    }
+}
+
+fn despawn_loading_screen(mut commands: Commands, query: Query<Entity, With<LoadingScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Nothing the other `OnEnter(AppState::Playing)` systems spawn exists until the schedule ends,
+/// so this only sees what was there before play started
+fn remember_session_start(mut commands: Commands, query: Query<Entity>) {
+    commands.insert_resource(SessionStart(query.iter().collect()));
+}
+
+/// Going back to the menus takes the player, level and HUD away so playing again starts fresh
+fn despawn_session(
+    mut commands: Commands,
+    session_start: Res<SessionStart>,
+    query: Query<Entity, Without<Parent>>,
+) {
+    for entity in &query {
+        if !session_start.0.contains(&entity) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn asset_paths_load_the_same_over_http() {
+        assert_eq!(
+            asset_path("levels/playground.level.ron"),
+            "levels/playground.level.ron"
+        );
+        assert_eq!(
+            asset_path("assets\\levels\\other.level.ron"),
+            "levels/other.level.ron"
+        );
+        assert_eq!(asset_path("./audio/chime.wav"), "audio/chime.wav");
+        assert_eq!(
+            asset_path("/assets/memories/shell.png"),
+            "memories/shell.png"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+use crate::{asset_path, AssetCollection, GameSettings};
+
+/// Every language with a file under `assets/lang/`, in the order the settings page cycles through
+/// them
+pub const LANGUAGES: [&str; 2] = ["en", "es"];
+/// Keys missing from the current language are looked up here before showing the key itself
+const FALLBACK_LANGUAGE: &str = "en";
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<LanguageFile>()
+            .init_asset_loader::<LanguageFileLoader>()
+            .insert_resource(Locale(FALLBACK_LANGUAGE.to_string()))
+            .insert_resource(Localization::default())
+            .add_startup_system(load_languages)
+            .add_systems((sync_locale, refresh_localization, update_localized_text).chain());
+    }
+}
+
+pub fn language_path(code: &str) -> String {
+    format!("lang/{code}.lang.ron")
+}
+
+/// The language UI text is shown in, as the code of its file in `assets/lang/`
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Locale(pub String);
+
+/// A `.lang.ron` file, languages written in a script the default font lacks name a `font` to use
+/// instead
+#[derive(Deserialize, TypeUuid, Default)]
+#[uuid = "b41d6c2e-7f3a-4c85-9e10-5d2a8f6b3c71"]
+pub struct LanguageFile {
+    #[serde(default)]
+    pub font: Option<String>,
+    pub strings: HashMap<String, String>,
+}
+
+#[derive(Default)]
+pub struct LanguageFileLoader;
+
+impl AssetLoader for LanguageFileLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let language = ron::de::from_bytes::<LanguageFile>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(language));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang.ron"]
+    }
+}
+
+/// The strings of the current `Locale`, rebuilt whenever it or its file changes
+#[derive(Resource, Default)]
+pub struct Localization {
+    pub languages: HashMap<String, Handle<LanguageFile>>,
+    pub strings: HashMap<String, String>,
+    pub fallback: HashMap<String, String>,
+    pub font: Option<Handle<Font>>,
+    /// Every loaded language by code, each named in its own language
+    pub names: HashMap<String, String>,
+}
+
+impl Localization {
+    pub fn text(&self, key: &str) -> String {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Looks up `key` and fills each `{}` in it with the next of `args`
+    pub fn format(&self, key: &str, args: &[&str]) -> String {
+        fill(&self.text(key), args)
+    }
+
+    pub fn language_name(&self, code: &str) -> String {
+        self.names
+            .get(code)
+            .cloned()
+            .unwrap_or_else(|| code.to_string())
+    }
+
+    /// The current language's own font, or `default` if it reads fine in the usual one
+    pub fn font(&self, default: &Handle<Font>) -> Handle<Font> {
+        self.font.clone().unwrap_or_else(|| default.clone())
+    }
+}
+
+pub fn fill(template: &str, args: &[&str]) -> String {
+    let mut args = args.iter();
+    let mut pieces = template.split("{}");
+    let mut filled = pieces.next().unwrap_or_default().to_string();
+    for piece in pieces {
+        filled.push_str(args.next().copied().unwrap_or_default());
+        filled.push_str(piece);
+    }
+    filled
+}
+
+/// Keeps a section of this entity's `Text` showing the translation of `key`
+#[derive(Component)]
+pub struct Localized {
+    pub key: String,
+    pub section: usize,
+}
+
+impl Localized {
+    pub fn new(key: &str, section: usize) -> Self {
+        Localized {
+            key: key.to_string(),
+            section,
+        }
+    }
+}
+
+fn load_languages(asset_server: Res<AssetServer>, mut localization: ResMut<Localization>) {
+    for code in LANGUAGES {
+        let handle = asset_server.load(language_path(code).as_str());
+        localization.languages.insert(code.to_string(), handle);
+    }
+}
+
+fn sync_locale(settings: Option<Res<GameSettings>>, mut locale: ResMut<Locale>) {
+    let Some(settings) = settings else {return;};
+    if settings.is_changed() && locale.0 != settings.language {
+        locale.0 = settings.language.clone();
+    }
+}
+
+pub fn refresh_localization(
+    locale: Res<Locale>,
+    asset_server: Res<AssetServer>,
+    languages: Res<Assets<LanguageFile>>,
+    mut language_events: EventReader<AssetEvent<LanguageFile>>,
+    mut localization: ResMut<Localization>,
+) {
+    let files_changed = language_events.iter().count() > 0;
+    if !(locale.is_changed() || files_changed) {
+        return;
+    }
+    let language = |code: &str| {
+        localization
+            .languages
+            .get(code)
+            .and_then(|handle| languages.get(handle))
+    };
+    let strings = language(&locale.0).map(|file| file.strings.clone());
+    let fallback = language(FALLBACK_LANGUAGE).map(|file| file.strings.clone());
+    let font = language(&locale.0)
+        .and_then(|file| file.font.as_ref())
+        .map(|path| asset_server.load(asset_path(path)));
+    let names = LANGUAGES
+        .iter()
+        .filter_map(|code| {
+            let name = language(code)?.strings.get("language.name")?;
+            Some((code.to_string(), name.clone()))
+        })
+        .collect();
+
+    localization.strings = strings.unwrap_or_default();
+    localization.fallback = fallback.unwrap_or_default();
+    localization.font = font;
+    localization.names = names;
+}
+
+pub fn update_localized_text(
+    localization: Res<Localization>,
+    assets: Option<Res<AssetCollection>>,
+    added_query: Query<(), Added<Localized>>,
+    mut query: Query<(&Localized, &mut Text)>,
+) {
+    if !(localization.is_changed() || !added_query.is_empty()) {
+        return;
+    }
+    for (localized, mut text) in &mut query {
+        if let Some(section) = text.sections.get_mut(localized.section) {
+            section.value = localization.text(&localized.key);
+        }
+        if let Some(assets) = &assets {
+            let font = localization.font(&assets.font);
+            for section in &mut text.sections {
+                section.style.font = font.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_filled_in_order() {
+        assert_eq!(fill("{} of {} coins", &["3", "5"]), "3 of 5 coins");
+        assert_eq!(fill("Coins: {}", &[]), "Coins: ");
+        assert_eq!(fill("No placeholders", &["3"]), "No placeholders");
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_english_then_the_key() {
+        let mut localization = Localization::default();
+        localization
+            .strings
+            .insert("hud.ideas".to_string(), "Ideas: ".to_string());
+        localization
+            .fallback
+            .insert("hud.coins".to_string(), "Coins: ".to_string());
+        assert_eq!(localization.text("hud.ideas"), "Ideas: ");
+        assert_eq!(localization.text("hud.coins"), "Coins: ");
+        assert_eq!(localization.text("hud.missing"), "hud.missing");
+    }
+}
@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::InputManagerPlugin;
 
@@ -23,9 +23,155 @@ pub use ideas::*;
 pub mod ui;
 pub use ui::*;
 
+pub mod config;
+pub use config::*;
+
+pub mod vfx;
+pub use vfx::*;
+
+pub mod level;
+pub use level::*;
+
+pub mod audio;
+pub use audio::*;
+
+pub mod challenge;
+pub use challenge::*;
+
+pub mod enemy;
+pub use enemy::*;
+
+pub mod balloon;
+pub use balloon::*;
+
+pub mod post_processing;
+pub use post_processing::*;
+
+pub mod stylized;
+pub use stylized::*;
+
+pub mod loading;
+pub use loading::*;
+
+pub mod stress;
+pub use stress::*;
+
+pub mod launch;
+pub use launch::*;
+
+pub mod console;
+pub use console::*;
+
+pub mod time_scale;
+pub use time_scale::*;
+
+pub mod save;
+pub use save::*;
+
+pub mod memories;
+pub use memories::*;
+
+pub mod shop;
+pub use shop::*;
+
+pub mod quests;
+pub use quests::*;
+
+pub mod settings;
+pub use settings::*;
+
+pub mod localization;
+pub use localization::*;
+
+pub mod gizmos;
+pub use gizmos::*;
+
+pub mod inspector;
+pub use inspector::*;
+
+pub mod metrics;
+pub use metrics::*;
+
+pub mod snapshot;
+pub use snapshot::*;
+
+pub mod creations;
+pub use creations::*;
+
+pub mod building;
+pub use building::*;
+
+pub mod rope;
+pub use rope::*;
+
+pub mod cart;
+pub use cart::*;
+
+pub mod durability;
+pub use durability::*;
+
+pub mod imagination;
+pub use imagination::*;
+
+pub mod costumes;
+pub use costumes::*;
+
+pub mod critters;
+pub use critters::*;
+
+pub mod input;
+pub use input::*;
+
+pub mod web;
+pub use web::*;
+
+pub mod signposts;
+pub use signposts::*;
+
+pub mod decals;
+pub use decals::*;
+
+pub mod pool;
+pub use pool::*;
+
+pub mod ending;
+pub use ending::*;
+
+pub mod campaign;
+pub use campaign::*;
+
+pub mod tutorial;
+pub use tutorial::*;
+
+pub mod online;
+pub use online::*;
+
+pub mod spectator;
+pub use spectator::*;
+
+#[cfg(test)]
+pub mod headless;
+
+pub const GRAVITY: Vec3 = Vec3::new(0.0, -30.0, 0.0);
+/// How fast wind can speed up a loose body, more than gravity so an updraft lifts what catches it
+/// fully
+const WIND_BODY_ACCELERATION: f32 = 40.0;
+
 #[derive(Component)]
 pub struct PlayerGrabSensor;
 
+/// Corners of the wedge in front of the player that items can be grabbed from, bottom then top
+pub const GRAB_SENSOR_VERTICES: [Vec3; 8] = [
+    Vec3::new(0.0, -0.5, 0.0),
+    Vec3::new(1.00, -0.5, -1.00),
+    Vec3::new(0.0, -0.5, -1.25),
+    Vec3::new(-1.00, -0.5, -1.00),
+    Vec3::new(0.0, 0.5, 0.0),
+    Vec3::new(1.00, 0.5, -1.00),
+    Vec3::new(0.0, 0.5, -1.25),
+    Vec3::new(-1.00, 0.5, -1.00),
+];
+
 #[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
 pub enum SysLabel {
     SetForces,
@@ -33,23 +179,98 @@ pub enum SysLabel {
 }
 
 fn main() {
+    let options = LaunchOptions::from_args(std::env::args());
     App::new()
-        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                .set(AssetPlugin {
+                    // Assets come over HTTP in a browser, there's no folder to watch
+                    watch_for_changes: cfg!(not(target_arch = "wasm32")),
+                    ..default()
+                })
+                .set(WindowPlugin {
+                    primary_window: Some(options.window()),
+                    ..default()
+                }),
+        )
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
-        .add_plugin(RapierDebugRenderPlugin::default())
+        .add_plugin(RapierDebugRenderPlugin {
+            enabled: options.debug_overlay,
+            ..default()
+        })
+        .insert_resource(options)
         .add_plugin(InputManagerPlugin::<PlayerAction>::default())
+        .add_plugin(InputContextPlugin)
+        .add_plugin(LoadingPlugin)
         .add_plugin(PlayerPlugin)
         .add_plugin(CameraControlPlugin)
         .add_plugin(PhysiscsInteractablesPlugin)
         .add_plugin(UiPlugin)
         .add_plugin(IdeaPlugin)
+        .add_plugin(ConfigPlugin)
+        .add_plugin(VfxPlugin)
+        .add_plugin(DecalPlugin)
+        .add_plugin(EntityPoolPlugin)
+        .add_plugin(LevelPlugin)
+        .add_plugin(LightingPlugin)
+        .add_plugin(SkyPlugin)
+        .add_plugin(WeatherPlugin)
+        .add_plugin(GameAudioPlugin)
+        .add_plugin(QuicksandPlugin)
+        .add_plugin(RailPlugin)
+        .add_plugin(BoulderPlugin)
+        .add_plugin(HazardPlugin)
+        .add_plugin(ChallengePlugin)
+        .add_plugin(EnemyPlugin)
+        .add_plugin(PressurePlatePlugin)
+        .add_plugin(PlatformPlugin)
+        .add_plugin(ZipLinePlugin)
+        .add_plugin(LadderPlugin)
+        .add_plugin(CrumblePlugin)
+        .add_plugin(WaterPlugin)
+        .add_plugin(WindStreakPlugin)
+        .add_plugin(BalloonPlugin)
+        .add_plugin(TurretPlugin)
+        .add_plugin(PostProcessingPlugin)
+        .add_plugin(StylizedPlugin)
+        .add_plugin(StressPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(TimeScalePlugin)
+        .add_plugin(ItemRespawnPlugin)
+        .add_plugin(SavePlugin)
+        .add_plugin(MemoryPlugin)
+        .add_plugin(PickupPlugin)
+        .add_plugin(ShopPlugin)
+        .add_plugin(QuestPlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(LocalizationPlugin)
+        .add_plugin(DebugGizmosPlugin)
+        .add_plugin(InspectorPlugin)
+        .add_plugin(MetricsPlugin)
+        .add_plugin(SnapshotPlugin)
+        .add_plugin(CreationPlugin)
+        .add_plugin(BuildingPlugin)
+        .add_plugin(RopePlugin)
+        .add_plugin(CartPlugin)
+        .add_plugin(DurabilityPlugin)
+        .add_plugin(ImaginationPlugin)
+        .add_plugin(CostumePlugin)
+        .add_plugin(CritterPlugin)
+        .add_plugin(SignpostPlugin)
+        .add_plugin(EndingPlugin)
+        .add_plugin(CampaignPlugin)
+        .add_plugin(TutorialPlugin)
+        .add_plugin(OnlinePlugin)
+        .add_plugin(SpectatorPlugin)
         .insert_resource(RapierConfiguration {
-            gravity: Vec3::Y * -30.0,
+            gravity: GRAVITY,
             ..default()
         })
         .insert_resource(PlayerSpeed::default())
-        .add_startup_system(spawn_world)
+        .add_system(spawn_world.in_schedule(OnEnter(AppState::Playing)))
         .add_system(rotate_block)
+        .add_plugins(WebPlugins)
         .run();
 }
 
@@ -62,7 +283,8 @@ impl Movement {
     }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
 pub struct Momentum(f32);
 
 impl Momentum {
@@ -99,18 +321,54 @@ pub struct Ledge;
 #[derive(Component)]
 pub struct WindZone(pub Vec3);
 
-impl WindZone {
-    pub fn get_force(&self) -> OutsideForce {
-        OutsideForce(self.0)
-    }
-}
+/// A wind zone that travels, its `MovingPlatform` path carries the sensor along with it
+#[derive(Component)]
+pub struct Gust;
 
 #[derive(Component)]
 pub struct DebugBall;
 
-#[derive(Default, Component, Reflect)]
-#[reflect(Component)]
-pub struct OutsideForce(pub Vec3);
+/// Something pushing on a body, each source adds and takes back only its own force
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ForceSource {
+    Wind(Entity),
+}
+
+/// Every push acting on a body at once, summed by whatever moves the body
+#[derive(Component, Default, Debug)]
+pub struct ExternalForces(HashMap<ForceSource, Vec3>);
+
+impl ExternalForces {
+    pub fn get(&self, source: ForceSource) -> Option<Vec3> {
+        self.0.get(&source).copied()
+    }
+
+    pub fn register(&mut self, source: ForceSource, force: Vec3) {
+        self.0.insert(source, force);
+    }
+
+    pub fn remove(&mut self, source: ForceSource) {
+        self.0.remove(&source);
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = ForceSource> + '_ {
+        self.0.keys().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn total(&self) -> Vec3 {
+        self.0.values().copied().sum()
+    }
+}
+
+impl FromIterator<(ForceSource, Vec3)> for ExternalForces {
+    fn from_iter<I: IntoIterator<Item = (ForceSource, Vec3)>>(forces: I) -> Self {
+        ExternalForces(forces.into_iter().collect())
+    }
+}
 
 pub fn rotate_block(time: Res<Time>, mut query: Query<&mut Transform, With<Rot>>) {
     for mut transform in &mut query {
@@ -118,24 +376,17 @@ pub fn rotate_block(time: Res<Time>, mut query: Query<&mut Transform, With<Rot>>
     }
 }
 
-pub fn spawn_world(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    asset_server: Res<AssetServer>,
-) {
-    // Player
+/// Everything the player needs to move and collide, without a mesh so headless tests can spawn it
+pub fn spawn_player(commands: &mut Commands, position: Vec3, input: InputListenerBundle) -> Entity {
     commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Capsule::default())),
-            material: materials.add(Color::TURQUOISE.into()),
-            transform: Transform::from_xyz(-1.0, 30.0, 0.0),
-            ..default()
-        })
+        .spawn(SpatialBundle::from_transform(Transform::from_translation(
+            position,
+        )))
         .insert(RigidBody::Dynamic)
         .insert(Velocity::default())
         .insert(LockedAxes::ROTATION_LOCKED)
         .insert(Collider::capsule_y(0.5, 0.5))
+        .insert(CollisionGroups::new(PLAYER_GROUP, Group::ALL))
         .insert(Movement::default())
         .insert(Damping {
             linear_damping: 0.2,
@@ -145,7 +396,16 @@ pub fn spawn_world(
         .insert(Jump::default())
         .insert(Drift::default())
         .insert(Momentum::default())
-        .insert(InputListenerBundle::input_map())
+        .insert(Stamina::default())
+        .insert(RewindMeter::default())
+        .insert(CostumeModifiers::default())
+        .insert(ExternalForces::default())
+        .insert(Health::default())
+        .insert(FallTracker::default())
+        .insert(IdleTimer::default())
+        .insert(GroundSurface::default())
+        .insert(MinimapIcon::Player)
+        .insert(input)
         .insert(Friction {
             coefficient: 1.0,
             combine_rule: CoefficientCombineRule::Min,
@@ -161,16 +421,7 @@ pub fn spawn_world(
                 .insert(ActiveEvents::COLLISION_EVENTS);
 
             // Hand Sensor Verts
-            let vertices = vec![
-                Vec3::new(0.0, -0.5, 0.0),
-                Vec3::new(1.00, -0.5, -1.00),
-                Vec3::new(0.0, -0.5, -1.25),
-                Vec3::new(-1.00, -0.5, -1.00),
-                Vec3::new(0.0, 0.5, 0.0),
-                Vec3::new(1.00, 0.5, -1.00),
-                Vec3::new(0.0, 0.5, -1.25),
-                Vec3::new(-1.00, 0.5, -1.00),
-            ];
+            let vertices = GRAB_SENSOR_VERTICES.to_vec();
 
             let indices = vec![
                 [0, 1, 4],
@@ -198,20 +449,35 @@ pub fn spawn_world(
                 .insert(PlayerLedgeSensor)
                 .insert(Sensor)
                 .insert(ActiveEvents::COLLISION_EVENTS);
-        });
+        })
+        .id()
+}
 
-    // Light
-    commands.insert_resource(AmbientLight {
-        color: Color::ANTIQUE_WHITE,
-        brightness: 0.45,
-    });
+pub fn spawn_world(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetCollection>,
+    options: Res<LaunchOptions>,
+    settings: Res<GameSettings>,
+) {
+    // Player
+    let player = spawn_player(
+        &mut commands,
+        Vec3::new(-1.0, 30.0, 0.0),
+        InputListenerBundle::input_map(options.gamepad, settings.preset),
+    );
+    commands
+        .entity(player)
+        .insert(meshes.add(Mesh::from(shape::Capsule::default())))
+        .insert(materials.add(Color::TURQUOISE.into()));
 
     // Ground
     commands
         .spawn(PbrBundle {
             mesh: meshes.add(Mesh::from(shape::Box::new(50.0, 1.0, 50.0))),
             material: materials.add(StandardMaterial {
-                base_color_texture: Some(asset_server.load("grass.png")),
+                base_color_texture: Some(assets.grass.clone()),
                 alpha_mode: AlphaMode::Blend,
                 unlit: true,
                 ..default()
@@ -220,111 +486,7 @@ pub fn spawn_world(
             ..default()
         })
         .insert(Collider::cuboid(25.0, 0.5, 25.0))
-        .insert(RigidBody::Fixed);
-
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(50.0, 50.0, 1.0))),
-            material: materials.add(Color::PURPLE.into()),
-            transform: Transform::from_xyz(0.0, 24.5, 25.0),
-            ..default()
-        })
-        .insert(Collider::cuboid(25.0, 25.0, 0.5))
-        .insert(Wall)
-        .insert(RigidBody::Fixed);
-
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(50.0, 50.0, 1.0))),
-            material: materials.add(Color::PURPLE.into()),
-            transform: Transform::from_xyz(0.0, 24.5, -25.0),
-            ..default()
-        })
-        .insert(Collider::cuboid(25.0, 25.0, 0.5))
-        .insert(Wall)
-        .insert(RigidBody::Fixed);
-
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(1.0, 50.0, 50.0))),
-            material: materials.add(Color::PURPLE.into()),
-            transform: Transform::from_xyz(25.0, 24.5, 0.0),
-            ..default()
-        })
-        .insert(Collider::cuboid(0.5, 25.0, 25.0))
-        .insert(Wall)
-        .insert(RigidBody::Fixed);
-
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(1.0, 50.0, 50.0))),
-            material: materials.add(Color::PURPLE.into()),
-            transform: Transform::from_xyz(-25.0, 24.5, 0.0),
-            ..default()
-        })
-        .insert(Collider::cuboid(0.5, 25.0, 25.0))
-        .insert(Wall)
-        .insert(RigidBody::Fixed);
-    //
-    // Block
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(5.0, 5.0, 5.0))),
-            material: materials.add(Color::BLUE.into()),
-            transform: Transform::from_xyz(0.0, 2.5, 0.0),
-            ..default()
-        })
-        .insert(Collider::cuboid(2.5, 2.5, 2.5))
-        .insert(Wall)
-        .insert(RigidBody::Fixed)
-        .with_children(|parent| {
-            parent
-                .spawn(TransformBundle {
-                    local: Transform::from_xyz(0.0, 2.25, 0.0),
-                    ..default()
-                })
-                .insert(Ledge)
-                .insert(Collider::cuboid(2.6, 0.25, 2.6))
-                .insert(RigidBody::Fixed)
-                .insert(Sensor);
-        });
-
-    // Crate
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(2.0, 2.0, 2.0))),
-            material: materials.add(Color::BEIGE.into()),
-            transform: Transform::from_xyz(0.0, 10.0, 0.0),
-            ..default()
-        })
-        .insert(Collider::cuboid(1.0, 1.0, 1.0))
-        .insert(Item::default())
-        .insert(MediumItem)
-        .insert(RigidBody::Dynamic)
-        .insert(LockedAxes::ROTATION_LOCKED_X | LockedAxes::ROTATION_LOCKED_Z)
-        .insert(Velocity::default());
-
-    // Wall jump blocks
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(1.0, 40.0, 5.0))),
-            material: materials.add(Color::BLUE.into()),
-            transform: Transform::from_xyz(10.0, 20.0, 10.0),
-            ..default()
-        })
-        .insert(Collider::cuboid(0.5, 20.0, 2.5))
-        .insert(Wall)
-        .insert(RigidBody::Fixed);
-
-    commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(1.0, 40.0, 5.0))),
-            material: materials.add(Color::BLUE.into()),
-            transform: Transform::from_xyz(15.0, 20.0, 10.0),
-            ..default()
-        })
-        .insert(Collider::cuboid(0.5, 20.0, 2.5))
-        .insert(Wall)
+        .insert(SurfaceType::Grass)
         .insert(RigidBody::Fixed);
 
     // // Wind Zone
@@ -340,44 +502,143 @@ pub fn spawn_world(
     //     .insert(RigidBody::Fixed);
 }
 
+/// Overlaps are checked every frame rather than on collision events, so a `Gust` sweeping over
+/// something pushes it even though neither of them started or stopped touching on their own
 pub fn handle_entering_wind_zones(
     mut commands: Commands,
-    mut collision_events: EventReader<CollisionEvent>,
+    rapier_context: Res<RapierContext>,
     zone_query: Query<(Entity, &WindZone), With<Sensor>>,
-    movable_query: Query<(Entity, Option<&OutsideForce>), (With<Movement>, With<Collider>)>,
+    mut movable_query: Query<
+        (
+            Entity,
+            &RigidBody,
+            Option<&Movement>,
+            Option<&mut ExternalForces>,
+        ),
+        (
+            Or<(With<Movement>, With<Item>, With<Creation>)>,
+            With<Collider>,
+        ),
+    >,
 ) {
-    for (zone_entity, windzone) in &zone_query {
-        for (movable_entity, has_force) in &movable_query {
-            for collision_event in collision_events.iter() {
-                match collision_event {
-                    CollisionEvent::Started(e1, e2, _flags) => {
-                        if (*e1 == zone_entity && *e2 == movable_entity)
-                            || (*e2 == zone_entity && *e1 == movable_entity)
-                        {
-                            if has_force.is_none() {
-                                commands.entity(movable_entity).insert(windzone.get_force());
-                            }
-                        }
-                    }
-                    CollisionEvent::Stopped(e1, e2, _flags) => {
-                        if (*e1 == zone_entity && *e2 == movable_entity)
-                            || (*e2 == zone_entity && *e1 == movable_entity)
-                        {
-                            if has_force.is_some() {
-                                commands.entity(movable_entity).remove::<OutsideForce>();
-                            }
-                        }
-                    }
-                }
+    for (movable_entity, body, movement, forces) in &mut movable_query {
+        // Items and creations only drift while loose, not while held or built into something
+        if movement.is_none() && *body != RigidBody::Dynamic {
+            continue;
+        }
+        let winds: Vec<(ForceSource, Vec3)> = zone_query
+            .iter()
+            .filter(|(zone_entity, _)| {
+                rapier_context.intersection_pair(*zone_entity, movable_entity) == Some(true)
+            })
+            .map(|(zone_entity, windzone)| (ForceSource::Wind(zone_entity), windzone.0))
+            .collect();
+        let Some(mut forces) = forces else {
+            if !winds.is_empty() {
+                commands
+                    .entity(movable_entity)
+                    .insert(ExternalForces::from_iter(winds));
+            }
+            continue;
+        };
+
+        for (source, force) in &winds {
+            if forces.get(*source) != Some(*force) {
+                forces.register(*source, *force);
             }
         }
+        // Includes zones despawned while pushing, like a storm ending
+        let gone: Vec<ForceSource> = forces
+            .sources()
+            .filter(|source| match source {
+                ForceSource::Wind(_) => !winds.iter().any(|(wind, _)| wind == source),
+            })
+            .collect();
+        for source in gone {
+            forces.remove(source);
+        }
+    }
+}
+
+/// Speeds loose items and creations up along the wind until they move as fast as it, light ones
+/// catch enough of an updraft to rise against gravity
+pub fn push_bodies_with_wind(
+    time: Res<Time>,
+    mut body_query: Query<
+        (
+            &mut Velocity,
+            &ExternalForces,
+            &RigidBody,
+            Option<&HeavyItem>,
+            Option<&MediumItem>,
+        ),
+        Without<Movement>,
+    >,
+) {
+    for (mut velocity, forces, body, heavy, medium) in &mut body_query {
+        if *body != RigidBody::Dynamic || forces.is_empty() {
+            continue;
+        }
+        let weight = if heavy.is_some() {
+            Weight::Heavy
+        } else if medium.is_some() {
+            Weight::Medium
+        } else {
+            Weight::Light
+        };
+        let max_change = weight.wind_catch() * WIND_BODY_ACCELERATION * time.delta_seconds();
+        velocity.linvel = catch_wind(velocity.linvel, forces.total(), max_change);
+    }
+}
+
+/// Speeds `velocity` up along `wind` by at most `max_change`, never past the wind's own speed
+pub fn catch_wind(velocity: Vec3, wind: Vec3, max_change: f32) -> Vec3 {
+    let speed = wind.length();
+    if speed == 0.0 {
+        return velocity;
+    }
+    let direction = wind / speed;
+    let along = velocity.dot(direction);
+    if along >= speed {
+        return velocity;
     }
+    velocity + direction * (speed - along).min(max_change)
 }
 
 pub struct PhysiscsInteractablesPlugin;
 
 impl Plugin for PhysiscsInteractablesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(handle_entering_wind_zones);
+        app.add_systems((handle_entering_wind_zones, push_bodies_with_wind).chain());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn external_forces_stack_by_source() {
+        let first = ForceSource::Wind(Entity::from_raw(1));
+        let second = ForceSource::Wind(Entity::from_raw(2));
+        let mut forces = ExternalForces::default();
+        forces.register(first, Vec3::X * 4.0);
+        forces.register(second, Vec3::Z * 2.0);
+        assert_eq!(forces.total(), Vec3::new(4.0, 0.0, 2.0));
+
+        forces.register(first, Vec3::X);
+        forces.remove(second);
+        assert_eq!(forces.total(), Vec3::X);
+        forces.remove(first);
+        assert!(forces.is_empty());
+    }
+
+    #[test]
+    fn bodies_catch_wind_up_to_its_speed() {
+        let wind = Vec3::Y * 5.0;
+        assert_eq!(catch_wind(Vec3::X, wind, 2.0), Vec3::new(1.0, 2.0, 0.0));
+        assert_eq!(catch_wind(Vec3::Y * 4.0, wind, 2.0), wind);
+        assert_eq!(catch_wind(Vec3::Y * 8.0, wind, 2.0), Vec3::Y * 8.0);
+        assert_eq!(catch_wind(Vec3::X, Vec3::ZERO, 2.0), Vec3::X);
     }
 }
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{MemoryRecord, Player, SaveData};
+
+const MEMORY_RADIUS: f32 = 0.35;
+const MEMORY_SPIN_SPEED: f32 = 1.5;
+
+pub struct MemoryPlugin;
+
+impl Plugin for MemoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((hide_found_memories, spin_memories, collect_memories));
+    }
+}
+
+/// A hidden lore collectible, touching it adds it to the gallery for good
+#[derive(Component)]
+pub struct Memory(pub MemoryRecord);
+
+pub fn spawn_memory(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    record: MemoryRecord,
+) -> Entity {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(
+                Mesh::try_from(shape::Icosphere {
+                    radius: MEMORY_RADIUS,
+                    subdivisions: 1,
+                })
+                .unwrap(),
+            ),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgb(0.7, 0.6, 1.0),
+                emissive: Color::rgb(0.5, 0.4, 1.0),
+                ..default()
+            }),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(Collider::ball(MEMORY_RADIUS))
+        .insert(Sensor)
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(RigidBody::Fixed)
+        .insert(Memory(record))
+        .id()
+}
+
+/// Memories already in the save don't come back when their room streams in again
+fn hide_found_memories(
+    mut commands: Commands,
+    save_data: Res<SaveData>,
+    query: Query<(Entity, &Memory), Added<Memory>>,
+) {
+    for (entity, memory) in &query {
+        if save_data.has_memory(&memory.0.id) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn spin_memories(time: Res<Time>, mut query: Query<&mut Transform, With<Memory>>) {
+    for mut transform in &mut query {
+        transform.rotate_y(MEMORY_SPIN_SPEED * time.delta_seconds());
+    }
+}
+
+pub fn collect_memories(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut save_data: ResMut<SaveData>,
+    memory_query: Query<&Memory>,
+    player_query: Query<(), With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let memory_entity = if player_query.contains(*e1) {
+            *e2
+        } else if player_query.contains(*e2) {
+            *e1
+        } else {
+            continue;
+        };
+
+        let Ok(memory) = memory_query.get(memory_entity) else {continue;};
+        save_data.add_memory(memory.0.clone());
+        commands.entity(memory_entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,380 @@
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ActiveProfile, AppState, AssetCollection, CreationBuilt, Grounded, JumpStage, Jumped,
+    LaunchOptions, LedgeGrab, Player, RespawnPlayer, SaveData, ThrownItem,
+};
+
+/// Real seconds between adding the session's metrics to the profile's stats
+const STATS_SAVE_SECONDS: f32 = 30.0;
+
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SessionMetrics::default())
+            .insert_resource(SavedSessionMetrics::default())
+            .add_system(spawn_metrics_overlay.in_schedule(OnEnter(AppState::Playing)))
+            .add_systems((
+                count_jumps,
+                count_ledge_grabs,
+                count_deaths,
+                count_thrown_items,
+                count_creations,
+                track_air_time,
+                track_falls,
+                update_metrics_overlay,
+            ))
+            .add_systems((write_session_metrics, save_profile_stats).in_base_set(CoreSet::Last));
+    }
+}
+
+/// Tallies of how the player has moved this session, for tuning how movement feels
+#[derive(Resource, Serialize, Default, Clone, Debug, PartialEq)]
+pub struct SessionMetrics {
+    pub single_jumps: u32,
+    pub double_jumps: u32,
+    pub triple_jumps: u32,
+    pub wall_jumps: u32,
+    pub side_somersaults: u32,
+    pub backflips: u32,
+    pub long_jumps: u32,
+    pub ledge_grabs: u32,
+    pub deaths: u32,
+    pub items_thrown: u32,
+    pub seconds_grounded: f32,
+    pub seconds_airborne: f32,
+    pub creations_built: u32,
+    /// Furthest the player has dropped before landing, in units
+    pub furthest_fall: f32,
+}
+
+impl SessionMetrics {
+    pub fn record_jump(&mut self, jumped: Jumped) {
+        let count = match jumped {
+            Jumped::Stage(JumpStage::Single) => &mut self.single_jumps,
+            Jumped::Stage(JumpStage::Double) => &mut self.double_jumps,
+            Jumped::Stage(JumpStage::Triple) => &mut self.triple_jumps,
+            Jumped::Wall => &mut self.wall_jumps,
+            Jumped::SideSomersault => &mut self.side_somersaults,
+            Jumped::Backflip => &mut self.backflips,
+            Jumped::Long => &mut self.long_jumps,
+        };
+        *count += 1;
+    }
+
+    pub fn jumps(&self) -> u32 {
+        self.single_jumps
+            + self.double_jumps
+            + self.triple_jumps
+            + self.wall_jumps
+            + self.side_somersaults
+            + self.backflips
+            + self.long_jumps
+    }
+
+    pub fn seconds_played(&self) -> f32 {
+        self.seconds_grounded + self.seconds_airborne
+    }
+
+    /// Share of play time spent in the air, from 0 to 1
+    pub fn airborne_share(&self) -> f32 {
+        let total = self.seconds_played();
+        if total > 0.0 {
+            self.seconds_airborne / total
+        } else {
+            0.0
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Jumps: {}/{}/{}  Wall: {}  Side: {}  Back: {}  Long: {}\n\
+             Ledge grabs: {}  Deaths: {}  Thrown: {}\n\
+             Airborne: {:.0}%",
+            self.single_jumps,
+            self.double_jumps,
+            self.triple_jumps,
+            self.wall_jumps,
+            self.side_somersaults,
+            self.backflips,
+            self.long_jumps,
+            self.ledge_grabs,
+            self.deaths,
+            self.items_thrown,
+            self.airborne_share() * 100.0,
+        )
+    }
+}
+
+/// Totals across every session played on a profile, kept in its save
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ProfileStats {
+    pub seconds_played: f32,
+    pub deaths: u32,
+    pub jumps: u32,
+    pub creations_built: u32,
+    pub items_thrown: u32,
+    /// Furthest drop the player has landed from and lived, in units
+    pub furthest_fall: f32,
+}
+
+impl ProfileStats {
+    /// These totals with whatever the session counted between `before` and `now` added on
+    pub fn with_session(&self, before: &SessionMetrics, now: &SessionMetrics) -> ProfileStats {
+        ProfileStats {
+            seconds_played: self.seconds_played + now.seconds_played() - before.seconds_played(),
+            deaths: self.deaths + now.deaths - before.deaths,
+            jumps: self.jumps + now.jumps() - before.jumps(),
+            creations_built: self.creations_built + now.creations_built - before.creations_built,
+            items_thrown: self.items_thrown + now.items_thrown - before.items_thrown,
+            furthest_fall: self.furthest_fall.max(now.furthest_fall),
+        }
+    }
+}
+
+/// The session's metrics as they were when last added to the profile's stats
+#[derive(Resource, Default)]
+pub struct SavedSessionMetrics(pub SessionMetrics);
+
+/// Reads as minutes and seconds, with hours in front once there are any
+pub fn format_playtime(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// Shown alongside the performance overlay with the `--debug` flag
+#[derive(Component)]
+pub struct MetricsOverlay;
+
+fn count_jumps(mut metrics: ResMut<SessionMetrics>, mut jump_events: EventReader<Jumped>) {
+    for jumped in jump_events.iter() {
+        metrics.record_jump(*jumped);
+    }
+}
+
+fn count_ledge_grabs(
+    mut metrics: ResMut<SessionMetrics>,
+    grab_query: Query<(), (With<Player>, Added<LedgeGrab>)>,
+) {
+    metrics.ledge_grabs += grab_query.iter().count() as u32;
+}
+
+fn count_deaths(
+    mut metrics: ResMut<SessionMetrics>,
+    mut respawn_events: EventReader<RespawnPlayer>,
+) {
+    metrics.deaths += respawn_events.iter().count() as u32;
+}
+
+fn count_thrown_items(
+    mut metrics: ResMut<SessionMetrics>,
+    thrown_query: Query<(), Added<ThrownItem>>,
+) {
+    metrics.items_thrown += thrown_query.iter().count() as u32;
+}
+
+fn count_creations(
+    mut metrics: ResMut<SessionMetrics>,
+    mut built_events: EventReader<CreationBuilt>,
+) {
+    metrics.creations_built += built_events.iter().count() as u32;
+}
+
+/// Measures each drop from its highest point to where the player lands, a respawn on the way
+/// down means the fall wasn't survived
+fn track_falls(
+    mut metrics: ResMut<SessionMetrics>,
+    mut respawn_events: EventReader<RespawnPlayer>,
+    mut highest: Local<Option<f32>>,
+    player_query: Query<(&Transform, Option<&Grounded>), With<Player>>,
+) {
+    if respawn_events.iter().count() > 0 {
+        *highest = None;
+        return;
+    }
+    let Ok((transform, grounded)) = player_query.get_single() else {return;};
+    let height = transform.translation.y;
+    if grounded.is_none() {
+        *highest = Some(highest.map_or(height, |highest| highest.max(height)));
+    } else if let Some(highest) = highest.take() {
+        let fall = highest - height;
+        if fall > metrics.furthest_fall {
+            metrics.furthest_fall = fall;
+        }
+    }
+}
+
+fn track_air_time(
+    time: Res<Time>,
+    mut metrics: ResMut<SessionMetrics>,
+    player_query: Query<Option<&Grounded>, With<Player>>,
+) {
+    let Ok(grounded) = player_query.get_single() else {return;};
+    if grounded.is_some() {
+        metrics.seconds_grounded += time.delta_seconds();
+    } else {
+        metrics.seconds_airborne += time.delta_seconds();
+    }
+}
+
+fn spawn_metrics_overlay(
+    mut commands: Commands,
+    assets: Res<AssetCollection>,
+    options: Res<LaunchOptions>,
+) {
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 18.0,
+                    color: Color::YELLOW,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    top: Val::Percent(40.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(if options.debug_overlay {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        })
+        .insert(MetricsOverlay);
+}
+
+fn update_metrics_overlay(
+    metrics: Res<SessionMetrics>,
+    mut overlay_query: Query<(&mut Text, &Visibility), With<MetricsOverlay>>,
+) {
+    let Ok((mut text, visibility)) = overlay_query.get_single_mut() else {return;};
+    if *visibility == Visibility::Hidden || !metrics.is_changed() {
+        return;
+    }
+    text.sections[0].value = metrics.summary();
+}
+
+fn write_session_metrics(
+    metrics: Res<SessionMetrics>,
+    options: Option<Res<LaunchOptions>>,
+    mut exit_events: EventReader<AppExit>,
+) {
+    if exit_events.iter().count() == 0 {
+        return;
+    }
+    let Some(path) = options.and_then(|options| options.metrics_path.clone()) else {return;};
+    let written = serde_json::to_string_pretty(&*metrics)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| std::fs::write(&path, contents).map_err(|error| error.to_string()));
+    if let Err(error) = written {
+        println!("Couldn't write metrics to {path}: {error}");
+    }
+}
+
+/// Adds the session's metrics to the profile's stats every so often, and once more on the way out
+fn save_profile_stats(
+    time: Res<Time>,
+    metrics: Res<SessionMetrics>,
+    profile: Option<Res<ActiveProfile>>,
+    mut saved: ResMut<SavedSessionMetrics>,
+    mut save_data: ResMut<SaveData>,
+    mut since_saved: Local<f32>,
+    mut exit_events: EventReader<AppExit>,
+) {
+    let Some(profile) = profile else {return;};
+    let exiting = exit_events.iter().count() > 0;
+    *since_saved += time.raw_delta_seconds();
+    if *since_saved < STATS_SAVE_SECONDS && !exiting {
+        return;
+    }
+    *since_saved = 0.0;
+    if saved.0 == *metrics {
+        return;
+    }
+    save_data.stats = save_data.stats.with_session(&saved.0, &metrics);
+    saved.0 = metrics.clone();
+    // The app is gone before the usual save writer runs again
+    if exiting {
+        if let Err(error) = save_data.write(&profile) {
+            println!("Couldn't write save data: {error}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn jumps_are_counted_by_kind() {
+        let mut metrics = SessionMetrics::default();
+        metrics.record_jump(Jumped::Stage(JumpStage::Single));
+        metrics.record_jump(Jumped::Stage(JumpStage::Triple));
+        metrics.record_jump(Jumped::Wall);
+        metrics.record_jump(Jumped::Wall);
+        assert_eq!(metrics.single_jumps, 1);
+        assert_eq!(metrics.double_jumps, 0);
+        assert_eq!(metrics.triple_jumps, 1);
+        assert_eq!(metrics.wall_jumps, 2);
+    }
+
+    #[test]
+    fn airborne_share_without_play_time_is_zero() {
+        let mut metrics = SessionMetrics::default();
+        assert_eq!(metrics.airborne_share(), 0.0);
+        metrics.seconds_grounded = 3.0;
+        metrics.seconds_airborne = 1.0;
+        assert_eq!(metrics.airborne_share(), 0.25);
+    }
+
+    #[test]
+    fn profile_stats_only_add_what_happened_since_last_time() {
+        let before = SessionMetrics {
+            deaths: 2,
+            single_jumps: 5,
+            seconds_grounded: 10.0,
+            ..default()
+        };
+        let now = SessionMetrics {
+            deaths: 3,
+            wall_jumps: 1,
+            seconds_airborne: 4.0,
+            furthest_fall: 12.0,
+            ..before.clone()
+        };
+
+        let stats = ProfileStats {
+            deaths: 7,
+            jumps: 20,
+            seconds_played: 100.0,
+            furthest_fall: 30.0,
+            ..default()
+        }
+        .with_session(&before, &now);
+        assert_eq!(stats.deaths, 8);
+        assert_eq!(stats.jumps, 21);
+        assert_eq!(stats.seconds_played, 104.0);
+        assert_eq!(stats.furthest_fall, 30.0);
+    }
+
+    #[test]
+    fn playtime_shows_hours_once_there_are_any() {
+        assert_eq!(format_playtime(75.0), "1:15");
+        assert_eq!(format_playtime(3723.9), "1:02:03");
+    }
+}
@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+#[cfg(not(feature = "online"))]
+use crate::{LaunchOptions, OnlineRole};
+
+#[cfg(feature = "online")]
+pub mod protocol;
+#[cfg(feature = "online")]
+pub use protocol::*;
+
+#[cfg(feature = "online")]
+pub mod sync;
+#[cfg(feature = "online")]
+pub use sync::*;
+
+/// Does nothing unless built with the `online` feature, then `--host` or `--join` plays with
+/// others, seeing their players, what they carry and what they build
+pub struct OnlinePlugin;
+
+impl Plugin for OnlinePlugin {
+    #[cfg(feature = "online")]
+    fn build(&self, app: &mut App) {
+        app.add_plugin(OnlineSyncPlugin);
+    }
+
+    #[cfg(not(feature = "online"))]
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(warn_offline);
+    }
+}
+
+#[cfg(not(feature = "online"))]
+fn warn_offline(options: Res<LaunchOptions>) {
+    match &options.online {
+        Some(OnlineRole::Host { ip: _, port }) => {
+            warn!("Built without the `online` feature, not hosting on port {port}");
+        }
+        Some(OnlineRole::Join { address }) => {
+            warn!("Built without the `online` feature, not joining {address}");
+        }
+        None => (),
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::{
+    prelude::*,
+    reflect::{
+        serde::{ReflectSerializer, UntypedReflectDeserializer},
+        TypeRegistryInternal,
+    },
+};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::CreationType;
+
+/// Changes whenever the messages below do, so mismatched builds refuse to connect
+pub const PROTOCOL_ID: u64 = 1;
+/// Remote players are drawn this far in the past, so there's nearly always a newer state to
+/// move towards
+pub const INTERPOLATION_DELAY: f32 = 0.1;
+/// States older than this behind the newest are dropped
+const SNAPSHOT_HISTORY_SECONDS: f32 = 1.0;
+
+/// Everything sent between players, as JSON
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum NetMessage {
+    /// Sent unreliably every frame
+    PlayerState(PlayerState),
+    /// Sent reliably, everyone else builds the same creation in the same place
+    CreationSpawned {
+        creation: CreationType,
+        position: [f32; 3],
+        rotation: [f32; 4],
+    },
+    /// Sent reliably by the host when someone disconnects
+    PlayerLeft { id: u64 },
+}
+
+impl NetMessage {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// One player's place and state, the components are serialized through reflection so new ones
+/// only have to be added to the synced list
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlayerState {
+    pub id: u64,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    /// Each synced component the player has, one they don't is left out
+    pub components: Vec<String>,
+    pub held_item: Option<String>,
+}
+
+/// Serializes any registered reflected value as RON, tagged with its type name
+pub fn serialize_reflected(value: &dyn Reflect, registry: &TypeRegistryInternal) -> Option<String> {
+    ron::to_string(&ReflectSerializer::new(value, registry)).ok()
+}
+
+pub fn deserialize_reflected(
+    text: &str,
+    registry: &TypeRegistryInternal,
+) -> Option<Box<dyn Reflect>> {
+    let mut deserializer = ron::Deserializer::from_str(text).ok()?;
+    UntypedReflectDeserializer::new(registry)
+        .deserialize(&mut deserializer)
+        .ok()
+}
+
+/// A remote player's recent states by when they arrived, sampled a little in the past
+#[derive(Default)]
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<(f32, Vec3, Quat)>,
+}
+
+impl SnapshotBuffer {
+    pub fn push(&mut self, time: f32, translation: Vec3, rotation: Quat) {
+        self.snapshots.push_back((time, translation, rotation));
+        while self
+            .snapshots
+            .front()
+            .map_or(false, |(oldest, ..)| time - oldest > SNAPSHOT_HISTORY_SECONDS)
+        {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Where the player was at `time`, between the states either side of it, or the closest one
+    /// when it's outside them all
+    pub fn sample(&self, time: f32) -> Option<(Vec3, Quat)> {
+        let (first_time, first_translation, first_rotation) = *self.snapshots.front()?;
+        if time <= first_time {
+            return Some((first_translation, first_rotation));
+        }
+        for (from, to) in self.snapshots.iter().zip(self.snapshots.iter().skip(1)) {
+            if time > to.0 {
+                continue;
+            }
+            let t = ((time - from.0) / (to.0 - from.0).max(f32::EPSILON)).clamp(0.0, 1.0);
+            return Some((from.1.lerp(to.1, t), from.2.slerp(to.2, t)));
+        }
+        let (_, translation, rotation) = *self.snapshots.back()?;
+        Some((translation, rotation))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn snapshots_are_interpolated_between_arrivals() {
+        let mut buffer = SnapshotBuffer::default();
+        assert!(buffer.sample(0.0).is_none());
+        buffer.push(1.0, Vec3::ZERO, Quat::IDENTITY);
+        buffer.push(1.2, Vec3::new(2.0, 0.0, 0.0), Quat::IDENTITY);
+
+        let (before, _) = buffer.sample(0.5).unwrap();
+        assert_eq!(before, Vec3::ZERO);
+        let (between, _) = buffer.sample(1.1).unwrap();
+        assert!((between - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-4);
+        let (after, _) = buffer.sample(2.0).unwrap();
+        assert_eq!(after, Vec3::new(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn old_snapshots_are_dropped() {
+        let mut buffer = SnapshotBuffer::default();
+        buffer.push(0.0, Vec3::ZERO, Quat::IDENTITY);
+        buffer.push(2.0, Vec3::ONE, Quat::IDENTITY);
+        let (oldest, _) = buffer.sample(0.0).unwrap();
+        assert_eq!(oldest, Vec3::ONE);
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let message = NetMessage::PlayerLeft { id: 7 };
+        let Some(NetMessage::PlayerLeft { id }) = NetMessage::from_bytes(&message.to_bytes()) else {panic!("message didn't round trip");};
+        assert_eq!(id, 7);
+    }
+}
@@ -0,0 +1,471 @@
+use bevy::{prelude::*, reflect::TypeRegistryInternal, utils::HashMap};
+use bevy_renet::{
+    renet::{
+        ClientAuthentication, DefaultChannel, RenetClient, RenetConnectionConfig, RenetServer,
+        ServerAuthentication, ServerConfig, ServerEvent,
+    },
+    RenetClientPlugin, RenetServerPlugin,
+};
+use std::{
+    any::type_name,
+    io::ErrorKind,
+    net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    time::SystemTime,
+};
+
+use crate::{
+    deserialize_reflected, serialize_reflected, spawn_creation, AppState, Creation, CreationType,
+    Grounded, HeldItem, ItemId, LaunchOptions, Momentum, NetMessage, OnlineRole, Player,
+    PlayerState, SnapshotBuffer, INTERPOLATION_DELAY, PROTOCOL_ID,
+};
+
+const MAX_CLIENTS: usize = 8;
+/// The host always plays as this id, clients pick their own from the clock
+const HOST_ID: u64 = 0;
+/// Held item icons are made for the HUD, scaled up to about the size of the item itself
+const HELD_ITEM_SCALE: f32 = 4.0;
+
+pub struct OnlineSyncPlugin;
+
+impl Plugin for OnlineSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(RenetServerPlugin::default())
+            .add_plugin(RenetClientPlugin::default())
+            .add_event::<NetMessage>()
+            .insert_resource(RemotePlayers::default())
+            .add_startup_system(start_online)
+            .add_systems(
+                (
+                    receive_messages,
+                    send_local_state,
+                    send_local_creations,
+                    track_remote_players,
+                    spawn_remote_creations,
+                    interpolate_remote_players,
+                    show_remote_held_items,
+                )
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(
+                apply_remote_components
+                    .after(track_remote_players)
+                    .in_set(OnUpdate(AppState::Playing)),
+            );
+    }
+}
+
+/// Which of this game's players is the local one
+#[derive(Resource)]
+pub struct LocalPlayerId(pub u64);
+
+/// Someone else's player, moved to where they are a moment ago
+#[derive(Component)]
+pub struct Puppet {
+    pub id: u64,
+    pub held_item: Option<ItemId>,
+}
+
+/// The mesh of what a puppet is carrying
+#[derive(Component)]
+pub struct PuppetHeldItem;
+
+/// Built because someone else built it, so it isn't sent back out
+#[derive(Component)]
+pub struct RemoteCreation;
+
+#[derive(Default)]
+pub struct RemotePlayer {
+    pub entity: Option<Entity>,
+    pub snapshots: SnapshotBuffer,
+    /// The latest synced components, waiting to be applied to the puppet
+    pub components: Option<Vec<String>>,
+    pub held_item: Option<ItemId>,
+}
+
+#[derive(Resource, Default)]
+pub struct RemotePlayers(pub HashMap<u64, RemotePlayer>);
+
+/// The components kept in step on every player's puppet, by their reflected type name
+pub fn synced_components() -> [&'static str; 2] {
+    [type_name::<Momentum>(), type_name::<Grounded>()]
+}
+
+fn start_online(mut commands: Commands, options: Res<LaunchOptions>) {
+    let Some(role) = &options.online else {return;};
+    let Ok(current_time) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) else {return;};
+    match role {
+        OnlineRole::Host { ip, port } => {
+            // Clients are turned away unless they asked for the exact address the server gives
+            // here, so it has to be one they can reach rather than 0.0.0.0
+            let public_address = SocketAddr::new(ip.unwrap_or_else(network_ip), *port);
+            let server = UdpSocket::bind(public_address).and_then(|socket| {
+                let config = ServerConfig::new(
+                    MAX_CLIENTS,
+                    PROTOCOL_ID,
+                    public_address,
+                    ServerAuthentication::Unsecure,
+                );
+                RenetServer::new(
+                    current_time,
+                    config,
+                    RenetConnectionConfig::default(),
+                    socket,
+                )
+            });
+            match server {
+                Ok(server) => {
+                    info!("Hosting on {public_address}");
+                    commands.insert_resource(server);
+                    commands.insert_resource(LocalPlayerId(HOST_ID));
+                }
+                Err(error) => error!("Couldn't host on {public_address}: {error}"),
+            }
+        }
+        OnlineRole::Join { address } => {
+            let id = current_time.as_millis() as u64;
+            let server_address = address
+                .to_socket_addrs()
+                .ok()
+                .and_then(|mut found| found.next());
+            let connected = server_address
+                .ok_or_else(|| std::io::Error::new(ErrorKind::NotFound, "no such address"))
+                .and_then(|server_address| connect(current_time, id, server_address));
+            match connected {
+                Ok(client) => {
+                    info!("Joining {address}");
+                    commands.insert_resource(client);
+                    commands.insert_resource(LocalPlayerId(id));
+                }
+                Err(error) => error!("Couldn't join {address}: {error}"),
+            }
+        }
+    }
+}
+
+/// The address this machine is reached on over its network, found by asking the OS which one it
+/// would send from. Nothing is actually sent
+fn network_ip() -> IpAddr {
+    UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("8.8.8.8:80")?;
+            socket.local_addr()
+        })
+        .map(|address| address.ip())
+        .unwrap_or_else(|error| {
+            warn!(
+                "Couldn't find this machine's network address, only local games will join: {error}"
+            );
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        })
+}
+
+fn connect(
+    current_time: std::time::Duration,
+    id: u64,
+    server_address: SocketAddr,
+) -> std::io::Result<RenetClient> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    let authentication = ClientAuthentication::Unsecure {
+        client_id: id,
+        protocol_id: PROTOCOL_ID,
+        server_addr: server_address,
+        user_data: None,
+    };
+    RenetClient::new(
+        current_time,
+        socket,
+        RenetConnectionConfig::default(),
+        authentication,
+    )
+}
+
+/// The host passes everything a client sends on to the other clients as well as reading it
+fn receive_messages(
+    server: Option<ResMut<RenetServer>>,
+    client: Option<ResMut<RenetClient>>,
+    mut server_events: EventReader<ServerEvent>,
+    mut messages: EventWriter<NetMessage>,
+) {
+    let channels = [DefaultChannel::Reliable, DefaultChannel::Unreliable];
+    if let Some(mut server) = server {
+        for event in server_events.iter() {
+            let ServerEvent::ClientDisconnected(id) = event else {continue;};
+            let left = NetMessage::PlayerLeft { id: *id };
+            server.broadcast_message(DefaultChannel::Reliable, left.to_bytes());
+            messages.send(left);
+        }
+        for id in server.clients_id() {
+            for channel in channels {
+                while let Some(bytes) = server.receive_message(id, channel) {
+                    server.broadcast_message_except(id, channel, bytes.clone());
+                    messages.send_batch(NetMessage::from_bytes(&bytes));
+                }
+            }
+        }
+    }
+    if let Some(mut client) = client {
+        for channel in channels {
+            while let Some(bytes) = client.receive_message(channel) {
+                messages.send_batch(NetMessage::from_bytes(&bytes));
+            }
+        }
+    }
+}
+
+fn send(
+    server: &mut Option<ResMut<RenetServer>>,
+    client: &mut Option<ResMut<RenetClient>>,
+    channel: DefaultChannel,
+    message: &NetMessage,
+) {
+    if let Some(server) = server {
+        server.broadcast_message(channel, message.to_bytes());
+    }
+    if let Some(client) = client {
+        if client.is_connected() {
+            client.send_message(channel, message.to_bytes());
+        }
+    }
+}
+
+fn send_local_state(
+    mut server: Option<ResMut<RenetServer>>,
+    mut client: Option<ResMut<RenetClient>>,
+    local_id: Option<Res<LocalPlayerId>>,
+    type_registry: Res<AppTypeRegistry>,
+    player_query: Query<
+        (&Transform, &Momentum, Option<&Grounded>, Option<&HeldItem>),
+        With<Player>,
+    >,
+) {
+    let Some(local_id) = local_id else {return;};
+    let Ok((transform, momentum, grounded, held_item)) = player_query.get_single() else {return;};
+    let registry = type_registry.read();
+    let mut synced: Vec<&dyn Reflect> = vec![momentum];
+    if let Some(grounded) = grounded {
+        synced.push(grounded);
+    }
+    let state = PlayerState {
+        id: local_id.0,
+        translation: transform.translation.to_array(),
+        rotation: transform.rotation.to_array(),
+        components: synced
+            .into_iter()
+            .filter_map(|component| serialize_reflected(component, &registry))
+            .collect(),
+        held_item: held_item.and_then(|held_item| serialize_reflected(held_item, &registry)),
+    };
+    send(
+        &mut server,
+        &mut client,
+        DefaultChannel::Unreliable,
+        &NetMessage::PlayerState(state),
+    );
+}
+
+fn send_local_creations(
+    mut server: Option<ResMut<RenetServer>>,
+    mut client: Option<ResMut<RenetClient>>,
+    creation_query: Query<
+        (&CreationType, &Transform),
+        (Added<CreationType>, With<Creation>, Without<RemoteCreation>),
+    >,
+) {
+    for (creation, transform) in &creation_query {
+        let message = NetMessage::CreationSpawned {
+            creation: *creation,
+            position: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+        };
+        send(&mut server, &mut client, DefaultChannel::Reliable, &message);
+    }
+}
+
+/// Buffers each remote player's states and keeps a puppet around for everyone still connected
+fn track_remote_players(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut messages: EventReader<NetMessage>,
+    mut remote_players: ResMut<RemotePlayers>,
+    type_registry: Res<AppTypeRegistry>,
+    local_id: Option<Res<LocalPlayerId>>,
+) {
+    let registry = type_registry.read();
+    for message in messages.iter() {
+        match message {
+            NetMessage::PlayerState(state) => {
+                if local_id
+                    .as_ref()
+                    .map_or(false, |local_id| local_id.0 == state.id)
+                {
+                    continue;
+                }
+                let remote = remote_players.0.entry(state.id).or_default();
+                let translation = Vec3::from_array(state.translation);
+                let rotation = Quat::from_array(state.rotation);
+                remote
+                    .snapshots
+                    .push(time.elapsed_seconds(), translation, rotation);
+                remote.components = Some(state.components.clone());
+                remote.held_item = state
+                    .held_item
+                    .as_deref()
+                    .and_then(|text| held_item_id(text, &registry));
+
+                // Puppets go with the rest of the level when it's left
+                let alive = remote
+                    .entity
+                    .map_or(false, |entity| commands.get_entity(entity).is_some());
+                if !alive {
+                    let entity = commands
+                        .spawn(PbrBundle {
+                            mesh: meshes.add(Mesh::from(shape::Capsule {
+                                radius: 0.5,
+                                depth: 1.0,
+                                ..default()
+                            })),
+                            material: materials.add(Color::rgb(0.4, 0.7, 0.95).into()),
+                            transform: Transform::from_translation(translation)
+                                .with_rotation(rotation),
+                            ..default()
+                        })
+                        .insert(Name::new(format!("Remote Player {}", state.id)))
+                        .insert(Puppet {
+                            id: state.id,
+                            held_item: None,
+                        })
+                        .id();
+                    remote.entity = Some(entity);
+                }
+            }
+            NetMessage::PlayerLeft { id } => {
+                let Some(remote) = remote_players.0.remove(id) else {continue;};
+                let Some(entity) = remote.entity else {continue;};
+                if let Some(puppet) = commands.get_entity(entity) {
+                    puppet.despawn_recursive();
+                }
+            }
+            NetMessage::CreationSpawned { .. } => (),
+        }
+    }
+}
+
+/// The `ItemId` out of a reflected `HeldItem`, its entity only means something to the sender
+fn held_item_id(text: &str, registry: &TypeRegistryInternal) -> Option<ItemId> {
+    let held_item = deserialize_reflected(text, registry)?;
+    let ReflectRef::Struct(held_item) = held_item.reflect_ref() else {return None;};
+    ItemId::from_reflect(held_item.field("item")?)
+}
+
+fn spawn_remote_creations(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut messages: EventReader<NetMessage>,
+) {
+    for message in messages.iter() {
+        let NetMessage::CreationSpawned { creation, position, rotation } = message else {continue;};
+        let position = Vec3::from_array(*position);
+        let entity = spawn_creation(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            *creation,
+            position,
+        );
+        let transform =
+            Transform::from_translation(position).with_rotation(Quat::from_array(*rotation));
+        commands
+            .entity(entity)
+            .insert(RemoteCreation)
+            .insert(transform);
+    }
+}
+
+fn interpolate_remote_players(
+    time: Res<Time>,
+    remote_players: Res<RemotePlayers>,
+    mut puppet_query: Query<&mut Transform, With<Puppet>>,
+) {
+    let render_time = time.elapsed_seconds() - INTERPOLATION_DELAY;
+    for remote in remote_players.0.values() {
+        let Some(entity) = remote.entity else {continue;};
+        let Ok(mut transform) = puppet_query.get_mut(entity) else {continue;};
+        let Some((translation, rotation)) = remote.snapshots.sample(render_time) else {continue;};
+        transform.translation = translation;
+        transform.rotation = rotation;
+    }
+}
+
+/// Sets each puppet's synced components to the ones last received, through their registered
+/// `ReflectComponent`, taking off any the remote player no longer has
+fn apply_remote_components(world: &mut World) {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = type_registry.read();
+    let pending: Vec<(Entity, Vec<String>)> = world
+        .resource_mut::<RemotePlayers>()
+        .0
+        .values_mut()
+        .filter_map(|remote| Some((remote.entity?, remote.components.take()?)))
+        .collect();
+
+    for (entity, components) in pending {
+        let received: Vec<Box<dyn Reflect>> = components
+            .iter()
+            .filter_map(|text| deserialize_reflected(text, &registry))
+            .collect();
+        let Some(mut puppet) = world.get_entity_mut(entity) else {continue;};
+        for name in synced_components() {
+            let Some(registration) = registry.get_with_name(name) else {continue;};
+            let Some(reflect_component) = registration.data::<ReflectComponent>() else {continue;};
+            match received
+                .iter()
+                .find(|component| component.type_name() == name)
+            {
+                Some(component) => reflect_component.apply_or_insert(&mut puppet, &**component),
+                None => reflect_component.remove(&mut puppet),
+            }
+        }
+    }
+}
+
+fn show_remote_held_items(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    remote_players: Res<RemotePlayers>,
+    mut puppet_query: Query<(&mut Puppet, Option<&Children>)>,
+    held_item_query: Query<(), With<PuppetHeldItem>>,
+) {
+    for remote in remote_players.0.values() {
+        let Some(entity) = remote.entity else {continue;};
+        let Ok((mut puppet, children)) = puppet_query.get_mut(entity) else {continue;};
+        if puppet.held_item == remote.held_item {
+            continue;
+        }
+        puppet.held_item = remote.held_item;
+
+        for child in children.into_iter().flatten() {
+            if held_item_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        let Some(item) = remote.held_item else {continue;};
+        let (mesh, color) = item.icon();
+        commands.entity(entity).with_children(|parent| {
+            parent
+                .spawn(PbrBundle {
+                    mesh: meshes.add(mesh),
+                    material: materials.add(color.into()),
+                    transform: Transform::from_translation(item.held_position())
+                        .with_rotation(item.held_rotation())
+                        .with_scale(Vec3::splat(HELD_ITEM_SCALE)),
+                    ..default()
+                })
+                .insert(PuppetHeldItem);
+        });
+    }
+}
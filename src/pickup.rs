@@ -1,6 +1,21 @@
 use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
 
-#[derive(Component, Eq, PartialEq)]
+use crate::{EntityPools, Player, PoolKind, Pooled};
+
+const COIN_RADIUS: f32 = 0.4;
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PickupsInventory::default())
+            .add_event::<PickupCollected>()
+            .add_system(collect_pickups);
+    }
+}
+
+#[derive(Component, Clone, Eq, PartialEq)]
 pub enum Pickup {
     Coin(u8),
     Health(u8),
@@ -35,5 +50,114 @@ impl Pickup {
 pub struct PickupsInventory(HashMap<String, u8>);
 
 impl PickupsInventory {
-    pub fn add(&mut self, pickup: Pickup) {}
+    pub fn add(&mut self, pickup: Pickup) {
+        let count = self.0.entry(pickup.get_resource_name()).or_insert(0);
+        *count = count.saturating_add(pickup.get_amount());
+    }
+
+    pub fn get(&self, resource_name: &str) -> u8 {
+        self.0.get(resource_name).copied().unwrap_or(0)
+    }
+
+    /// Takes `amount` away only if there is enough of it
+    pub fn spend(&mut self, resource_name: &str, amount: u8) -> bool {
+        match self.0.get_mut(resource_name) {
+            Some(count) if *count >= amount => {
+                *count -= amount;
+                true
+            }
+            _ => amount == 0,
+        }
+    }
+}
+
+pub struct PickupCollected(pub Pickup);
+
+pub fn spawn_coin(
+    commands: &mut Commands,
+    pools: &mut EntityPools,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    amount: u8,
+) -> Entity {
+    let transform = Transform::from_translation(position)
+        .with_rotation(Quat::from_rotation_x(std::f32::consts::FRAC_PI_2));
+    if let Some(coin) = pools.take(commands, PoolKind::Coin) {
+        commands
+            .entity(coin)
+            .insert(transform)
+            .insert(Pickup::Coin(amount));
+        return coin;
+    }
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cylinder {
+                radius: COIN_RADIUS,
+                height: 0.1,
+                ..default()
+            })),
+            material: materials.add(Color::GOLD.into()),
+            transform,
+            ..default()
+        })
+        .insert(Collider::ball(COIN_RADIUS))
+        .insert(Sensor)
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(RigidBody::Fixed)
+        .insert(Pickup::Coin(amount))
+        .id()
+}
+
+pub fn collect_pickups(
+    mut commands: Commands,
+    mut pools: ResMut<EntityPools>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut inventory: ResMut<PickupsInventory>,
+    mut collected: EventWriter<PickupCollected>,
+    pickup_query: Query<&Pickup, Without<Pooled>>,
+    player_query: Query<(), With<Player>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let pickup_entity = if player_query.contains(*e1) {
+            *e2
+        } else if player_query.contains(*e2) {
+            *e1
+        } else {
+            continue;
+        };
+
+        let Ok(pickup) = pickup_query.get(pickup_entity) else {continue;};
+        inventory.add(pickup.clone());
+        collected.send(PickupCollected(pickup.clone()));
+        if let Pickup::Coin(_) = pickup {
+            pools.release(&mut commands, PoolKind::Coin, pickup_entity);
+        } else {
+            commands.entity(pickup_entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn coins_add_up_and_are_spent() {
+        let mut inventory = PickupsInventory::default();
+        inventory.add(Pickup::Coin(3));
+        inventory.add(Pickup::Coin(4));
+        assert_eq!(inventory.get("Coins"), 7);
+        assert!(!inventory.spend("Coins", 8));
+        assert!(inventory.spend("Coins", 5));
+        assert_eq!(inventory.get("Coins"), 2);
+    }
+
+    #[test]
+    fn nothing_is_spent_from_an_empty_inventory() {
+        let mut inventory = PickupsInventory::default();
+        assert!(!inventory.spend("Coins", 1));
+        assert!(inventory.spend("Coins", 0));
+    }
 }
@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    respawn_player, spawn_dust, Climbing, DamagePlayer, EntityPools, FallDamageConfig, GameConfig,
+    Grinding, Grounded, Landing, LedgeGrab, Momentum, ParticleAssets, Player, PlayerAction, Riding,
+    StandingOn, Swinging, Water, Ziplining,
+};
+
+/// Ground at least this bouncy throws the player back up instead of taking the landing
+const BOUNCE_RESTITUTION: f32 = 0.5;
+/// A roll carries the player on the way they were heading instead of stopping them dead
+const ROLL_MOMENTUM: f32 = 8.0;
+
+pub struct PlayerFallDamagePlugin;
+
+impl Plugin for PlayerFallDamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                track_falls.after(respawn_player),
+                land_from_falls,
+                roll_hard_landings,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// How fast the player was last falling and when Crouch was last pressed, read when they land
+#[derive(Component, Default)]
+pub struct FallTracker {
+    pub speed: f32,
+    pub since_crouch: f32,
+    /// Off until the player first lands, so the drop into a level at the start doesn't count
+    pub armed: bool,
+}
+
+impl FallTracker {
+    pub fn crouched_within(&self, seconds: f32) -> bool {
+        self.since_crouch <= seconds
+    }
+}
+
+/// A landing that hurts once `timer` runs out, unless Crouch is pressed first to roll out of it
+#[derive(Component)]
+pub struct HardLanding {
+    pub amount: u8,
+    pub timer: Timer,
+}
+
+/// Health lost landing at `speed`, nothing below the config's minimum speed
+pub fn fall_damage(speed: f32, config: &FallDamageConfig) -> u8 {
+    if !config.enabled || speed < config.min_speed {
+        return 0;
+    }
+    let extra = (speed - config.min_speed) / config.speed_per_damage.max(f32::EPSILON);
+    (1.0 + extra).min(u8::MAX as f32) as u8
+}
+
+/// Water breaks any fall and holding on to something stops one, so the speed only counts while
+/// dropping freely
+fn track_falls(
+    time: Res<Time>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &Velocity,
+            &ActionState<PlayerAction>,
+            &mut FallTracker,
+            Option<&Grounded>,
+        ),
+        With<Player>,
+    >,
+    held_query: Query<
+        (),
+        Or<(
+            With<LedgeGrab>,
+            With<Climbing>,
+            With<Swinging>,
+            With<Ziplining>,
+            With<Grinding>,
+            With<Riding>,
+        )>,
+    >,
+    water_query: Query<(&GlobalTransform, &Water)>,
+) {
+    for (entity, transform, velocity, action, mut tracker, grounded) in &mut query {
+        if action.just_pressed(PlayerAction::Crouch) {
+            tracker.since_crouch = 0.0;
+        } else {
+            tracker.since_crouch += time.delta_seconds();
+        }
+        if grounded.is_some() {
+            continue;
+        }
+
+        let position = transform.translation;
+        let in_water = water_query.iter().any(|(water_transform, water)| {
+            let offset = (position - water_transform.translation()).abs();
+            offset.cmple(water.half_extents).all()
+        });
+        tracker.speed = if in_water || held_query.contains(entity) {
+            0.0
+        } else {
+            (-velocity.linvel.y).max(0.0)
+        };
+    }
+}
+
+/// Lands the player, bouncing off springy ground or rolling if Crouch was pressed just before
+fn land_from_falls(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut pools: ResMut<EntityPools>,
+    particle_assets: Res<ParticleAssets>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut FallTracker,
+            &mut Momentum,
+            Option<&StandingOn>,
+        ),
+        (With<Player>, With<Grounded>, Added<Landing>),
+    >,
+    restitution_query: Query<&Restitution>,
+) {
+    let config = &config.fall_damage;
+    for (entity, transform, mut tracker, mut momentum, standing_on) in &mut query {
+        let speed = tracker.speed;
+        tracker.speed = 0.0;
+        if !tracker.armed {
+            tracker.armed = true;
+            continue;
+        }
+
+        let bounced = standing_on
+            .and_then(|ground| restitution_query.get(ground.0).ok())
+            .map_or(false, |restitution| {
+                restitution.coefficient >= BOUNCE_RESTITUTION
+            });
+        let amount = fall_damage(speed, config);
+        if bounced || amount == 0 {
+            continue;
+        }
+
+        if tracker.crouched_within(config.roll_window_seconds) {
+            roll(
+                &mut commands,
+                &mut pools,
+                &particle_assets,
+                transform,
+                &mut momentum,
+            );
+        } else {
+            commands.entity(entity).insert(HardLanding {
+                amount,
+                timer: Timer::from_seconds(config.roll_window_seconds, TimerMode::Once),
+            });
+        }
+    }
+}
+
+/// Gives the player the rest of the window to roll before the landing hurts
+fn roll_hard_landings(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pools: ResMut<EntityPools>,
+    particle_assets: Res<ParticleAssets>,
+    mut damage_events: EventWriter<DamagePlayer>,
+    mut query: Query<(
+        Entity,
+        &Transform,
+        &ActionState<PlayerAction>,
+        &mut HardLanding,
+        &mut Momentum,
+    )>,
+) {
+    for (entity, transform, action, mut landing, mut momentum) in &mut query {
+        if action.just_pressed(PlayerAction::Crouch) {
+            roll(
+                &mut commands,
+                &mut pools,
+                &particle_assets,
+                transform,
+                &mut momentum,
+            );
+            commands.entity(entity).remove::<HardLanding>();
+            continue;
+        }
+
+        landing.timer.tick(time.delta());
+        if landing.timer.finished() {
+            damage_events.send(DamagePlayer {
+                amount: landing.amount,
+                knockback: Vec3::ZERO,
+            });
+            commands.entity(entity).remove::<HardLanding>();
+        }
+    }
+}
+
+fn roll(
+    commands: &mut Commands,
+    pools: &mut EntityPools,
+    particle_assets: &ParticleAssets,
+    transform: &Transform,
+    momentum: &mut Momentum,
+) {
+    momentum.set(ROLL_MOMENTUM);
+    let feet = transform.translation - Vec3::Y;
+    spawn_dust(
+        commands,
+        pools,
+        particle_assets,
+        feet,
+        transform.back() + Vec3::Y,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fall_damage_grows_with_landing_speed() {
+        let config = FallDamageConfig::default();
+        assert_eq!(fall_damage(20.0, &config), 0);
+        assert_eq!(fall_damage(24.0, &config), 1);
+        assert_eq!(fall_damage(31.0, &config), 1);
+        assert_eq!(fall_damage(32.0, &config), 2);
+        assert_eq!(fall_damage(41.0, &config), 3);
+    }
+
+    #[test]
+    fn fall_damage_can_be_turned_off() {
+        let config = FallDamageConfig {
+            enabled: false,
+            ..default()
+        };
+        assert_eq!(fall_damage(100.0, &config), 0);
+    }
+}
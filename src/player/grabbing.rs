@@ -5,15 +5,41 @@ use bevy::{prelude::*, utils::HashMap};
 use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
 
+const SETTLE_SPEED: f32 = 0.2;
+const SETTLE_SPIN: f32 = 0.3;
+const SETTLE_SECONDS: f32 = 0.5;
+/// How long after pressing Grab with empty hands an incoming item is still caught
+const CATCH_WINDOW_SECONDS: f32 = 0.3;
+/// Items that weren't thrown count as airborne once they fall faster than this
+const CATCH_FALL_SPEED: f32 = 2.0;
+/// Total `Weight::stack_load` a stack can reach, the held item included
+const MAX_STACK_LOAD: u32 = 4;
+/// Running faster than this sets a stack swaying
+const WOBBLE_SPEED: f32 = 8.0;
+/// Sway gained per second for each unit of speed over `WOBBLE_SPEED` and each stacked item
+const WOBBLE_BUILDUP: f32 = 0.01;
+/// Sway lost per second while the player is slower than `WOBBLE_SPEED`
+const WOBBLE_SETTLE: f32 = 0.3;
+const WOBBLE_FREQUENCY: f32 = 6.0;
+/// Swaying this far, in radians, topples the top item
+const TOPPLE_SWAY: f32 = 0.35;
+
 pub struct PlayerGrabbingPlugin;
 
 impl Plugin for PlayerGrabbingPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ItemsInRange::default())
+        app.register_type::<HeldItem>()
+            .register_type::<ItemId>()
+            .insert_resource(ItemsInRange::default())
             .add_system(detect_items)
             .add_system(grab_item.after(detect_items))
-            .add_system(throw_item)
-            .add_system(handle_thrown_momentum.after(throw_item));
+            .add_system(open_catch_window)
+            .add_system(catch_items.after(grab_item).after(open_catch_window))
+            .add_system(stack_item.after(detect_items))
+            .add_system(throw_item.after(stack_item))
+            .add_system(wobble_stacks.after(throw_item))
+            .add_system(handle_thrown_momentum.after(throw_item))
+            .add_system(settle_thrown_items.after(handle_thrown_momentum));
     }
 }
 
@@ -51,6 +77,12 @@ impl ItemsInRange {
         self.items.is_empty()
     }
 
+    /// The closest item without taking it out of range
+    pub fn closest(&self) -> Option<(Entity, Weight)> {
+        let (entity, _) = self.closest_item?;
+        self.items.get(&entity).map(|weight| (entity, *weight))
+    }
+
     pub fn get_closest(&mut self) -> Option<(Entity, Weight)> {
         if let Some((entity, _)) = self.closest_item {
             self.items.remove_entry(&entity)
@@ -60,7 +92,7 @@ impl ItemsInRange {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
 pub struct HeldItem {
     pub item: ItemId,
     pub entity: Entity,
@@ -72,6 +104,109 @@ impl HeldItem {
     }
 }
 
+/// Grab was pressed with nothing in reach, an airborne item reaching the grab sensor before
+/// the timer runs out is caught
+#[derive(Component)]
+pub struct CatchWindow(Timer);
+
+pub struct StackedItem {
+    pub entity: Entity,
+    pub weight: Weight,
+    pub height: f32,
+}
+
+/// Items piled on top of the `HeldItem`, bottom first, thrown along with it
+#[derive(Component)]
+pub struct HeldStack {
+    base_load: u32,
+    base_height: f32,
+    items: Vec<StackedItem>,
+    sway: f32,
+    phase: f32,
+}
+
+impl HeldStack {
+    pub fn new(base_weight: Weight, base_height: f32) -> Self {
+        HeldStack {
+            base_load: base_weight.stack_load(),
+            base_height,
+            items: Vec::new(),
+            sway: 0.0,
+            phase: 0.0,
+        }
+    }
+
+    pub fn load(&self) -> u32 {
+        self.items
+            .iter()
+            .fold(self.base_load, |load, item| load + item.weight.stack_load())
+    }
+
+    pub fn can_take(&self, weight: Weight) -> bool {
+        self.load() + weight.stack_load() <= MAX_STACK_LOAD
+    }
+
+    pub fn push(&mut self, entity: Entity, weight: Weight, height: f32) {
+        self.items.push(StackedItem {
+            entity,
+            weight,
+            height,
+        });
+    }
+
+    pub fn pop(&mut self) -> Option<StackedItem> {
+        self.items.pop()
+    }
+
+    /// Takes `entity` out wherever it is in the stack, the items above drop down to fill its place
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        let count = self.items.len();
+        self.items.retain(|item| item.entity != entity);
+        self.items.len() != count
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Each stacked item with how far above the held item's center it sits
+    pub fn offsets(&self) -> impl Iterator<Item = (Entity, f32)> + '_ {
+        let mut bottom = self.base_height / 2.0;
+        self.items.iter().map(move |item| {
+            let offset = bottom + item.height / 2.0;
+            bottom += item.height;
+            (item.entity, offset)
+        })
+    }
+
+    /// Builds sway while the player runs at `speed`, true once it's enough to topple the top item
+    pub fn wobble(&mut self, speed: f32, delta: f32) -> bool {
+        let excess = speed - WOBBLE_SPEED;
+        if excess > 0.0 {
+            self.sway += excess * self.items.len() as f32 * WOBBLE_BUILDUP * delta;
+        } else {
+            self.sway = (self.sway - WOBBLE_SETTLE * delta).max(0.0);
+        }
+        self.phase += WOBBLE_FREQUENCY * delta;
+
+        if self.sway >= TOPPLE_SWAY && !self.items.is_empty() {
+            self.sway = 0.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// How far the stack leans forward right now, negative leans back
+    pub fn tilt(&self) -> f32 {
+        self.sway * self.phase.sin()
+    }
+}
+
 enum ItemDetectionStatus {
     Hit(Entity),
     NoHit,
@@ -90,16 +225,44 @@ impl ThrownItem {
             throw_position,
         }
     }
+
+    /// Thrown forward and up from in front of the player, carrying their own velocity
+    pub fn from_player(player_transform: &Transform, player_velocity: Vec3) -> Self {
+        let player_forward = player_transform.forward().normalize_or_zero();
+        let throw_velocity = (player_forward * 15.0) + (Vec3::Y * 10.0) + player_velocity;
+        let throw_position = player_transform.translation + (player_forward * 1.2);
+        ThrownItem::new(throw_velocity, throw_position)
+    }
+}
+
+/// On a thrown item until it comes to rest, it tumbles freely with CCD on so a fast throw
+/// can't pass through thin walls. `locked_axes` is what it is locked to again once settled
+#[derive(Component)]
+pub struct Settling {
+    pub locked_axes: LockedAxes,
+    rest_timer: Timer,
+}
+
+impl Settling {
+    pub fn new(locked_axes: LockedAxes) -> Self {
+        Settling {
+            locked_axes,
+            rest_timer: Timer::from_seconds(SETTLE_SECONDS, TimerMode::Once),
+        }
+    }
 }
 
 pub fn detect_items(
     mut items_in_range: ResMut<ItemsInRange>,
     mut collision_events: EventReader<CollisionEvent>,
-    player_query: Query<&Transform, (With<Player>, Without<HeldItem>)>,
+    player_query: Query<&Transform, With<Player>>,
     grab_sensor_query: Query<Entity, (With<PlayerGrabSensor>, Without<Player>, Without<Item>)>,
-    item_query: Query<(Entity, &Transform, Option<&HeavyItem>, Option<&MediumItem>), With<Item>>,
+    item_query: Query<
+        (Entity, &Transform, Option<&HeavyItem>, Option<&MediumItem>),
+        (With<Item>, Without<Parent>),
+    >,
 ) {
-    let sensor_entity = grab_sensor_query.single();
+    let Ok(sensor_entity) = grab_sensor_query.get_single() else {return;};
     for collision_event in collision_events.iter() {
         for player_transform in &player_query {
             match collision_event {
@@ -118,13 +281,7 @@ pub fn detect_items(
                         let (_, item_transform, heavy, medium) =
                             item_query.get(item_entity).unwrap();
 
-                        let item_weight = if heavy.is_some() {
-                            Weight::Heavy
-                        } else if medium.is_some() {
-                            Weight::Medium
-                        } else {
-                            Weight::Light
-                        };
+                        let item_weight = marked_weight(heavy, medium);
 
                         let distance = player_transform
                             .translation
@@ -155,42 +312,39 @@ pub fn detect_items(
 pub fn grab_item(
     mut commands: Commands,
     mut items_in_range: ResMut<ItemsInRange>,
-    player_query: Query<(Entity, &ActionState<PlayerAction>), (With<Player>,)>,
-    mut item_query: Query<(Entity, &mut Transform, &Item, Option<&RigidBody>), With<Item>>,
+    player_query: Query<(Entity, &ActionState<PlayerAction>, Option<&HeldItem>), With<Player>>,
+    mut item_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &Item,
+            Option<&RigidBody>,
+            Option<&Settling>,
+        ),
+        With<Item>,
+    >,
 ) {
     if !items_in_range.is_empty() {
-        let Ok((player_entity, player_action)) = player_query.get_single() else {println!("No Player with an action state found in grab item, skipping"); return;};
+        let Ok((player_entity, player_action, held_item)) = player_query.get_single() else {println!("No Player with an action state found in grab item, skipping"); return;};
+        // Already holding something, stack_item decides what the press does
+        if held_item.is_some() {
+            return;
+        }
 
         if player_action.just_pressed(PlayerAction::Grab) {
             if let Some((item_entity, item_weight)) = items_in_range.get_closest() {
-                use Weight::*;
-                match item_weight {
-                    Heavy => {
-                        commands.entity(player_entity).insert(HeavyItem);
-                    }
-                    Medium => {
-                        commands.entity(player_entity).insert(MediumItem);
-                    }
-                    Light => {
-                        commands.entity(player_entity).insert(LightItem);
-                    }
-                }
-
-                if let Ok((_, mut item_transform, item, item_rigidbody)) =
+                if let Ok((_, mut item_transform, item, item_rigidbody, settling)) =
                     item_query.get_mut(item_entity)
                 {
-                    commands
-                        .entity(player_entity)
-                        .add_child(item_entity)
-                        .insert(HeldItem::new(item.item_id, item_entity));
-                    item_transform.rotation = item.item_id.held_rotation();
-                    item_transform.translation = item.item_id.held_position();
-                    if item_rigidbody.is_some() {
-                        commands
-                            .entity(item_entity)
-                            .remove::<RigidBody>()
-                            .insert(RigidBody::Fixed);
-                    }
+                    hold_item(
+                        &mut commands,
+                        player_entity,
+                        (item_entity, item_weight),
+                        &mut item_transform,
+                        item,
+                        item_rigidbody,
+                        settling,
+                    );
                 } else {
                     println!("Something went wrong while holding an item");
                 };
@@ -199,12 +353,222 @@ pub fn grab_item(
     }
 }
 
+fn marked_weight(heavy: Option<&HeavyItem>, medium: Option<&MediumItem>) -> Weight {
+    if heavy.is_some() {
+        Weight::Heavy
+    } else if medium.is_some() {
+        Weight::Medium
+    } else {
+        Weight::Light
+    }
+}
+
+/// Parents the item to the player in its carry pose, ending any throw it was still in
+fn hold_item(
+    commands: &mut Commands,
+    player_entity: Entity,
+    (item_entity, item_weight): (Entity, Weight),
+    item_transform: &mut Transform,
+    item: &Item,
+    item_rigidbody: Option<&RigidBody>,
+    settling: Option<&Settling>,
+) {
+    insert_weight_marker(commands, player_entity, item_weight);
+    end_throw(commands, item_entity, settling);
+    commands
+        .entity(player_entity)
+        .add_child(item_entity)
+        .insert(HeldItem::new(item.item_id, item_entity));
+    item_transform.rotation = item.item_id.held_rotation();
+    item_transform.translation = item.item_id.held_position();
+    if item_rigidbody.is_some() {
+        commands
+            .entity(item_entity)
+            .remove::<RigidBody>()
+            .insert(RigidBody::Fixed);
+    }
+}
+
+fn insert_weight_marker(commands: &mut Commands, player_entity: Entity, weight: Weight) {
+    use Weight::*;
+    match weight {
+        Heavy => {
+            commands.entity(player_entity).insert(HeavyItem);
+        }
+        Medium => {
+            commands.entity(player_entity).insert(MediumItem);
+        }
+        Light => {
+            commands.entity(player_entity).insert(LightItem);
+        }
+    }
+}
+
+fn end_throw(commands: &mut Commands, item_entity: Entity, settling: Option<&Settling>) {
+    if let Some(settling) = settling {
+        commands
+            .entity(item_entity)
+            .remove::<Settling>()
+            .remove::<Ccd>()
+            .insert(settling.locked_axes);
+    }
+}
+
+/// Grabbing while holding a light item piles the closest item on top of it, as long as the
+/// stack can bear its weight, otherwise the press is left for throw_item
+pub fn stack_item(
+    mut commands: Commands,
+    mut items_in_range: ResMut<ItemsInRange>,
+    mut player_query: Query<
+        (
+            Entity,
+            &HeldItem,
+            Option<&mut HeldStack>,
+            &mut ActionState<PlayerAction>,
+        ),
+        With<Player>,
+    >,
+    weight_query: Query<(Option<&HeavyItem>, Option<&MediumItem>)>,
+    mut item_query: Query<(&mut Transform, &Item, Option<&RigidBody>, Option<&Settling>)>,
+) {
+    let Ok((player_entity, held_item, stack, mut action)) = player_query.get_single_mut() else {return;};
+    if !action.just_pressed(PlayerAction::Grab) {
+        return;
+    }
+    let Some((item_entity, item_weight)) = items_in_range.closest() else {return;};
+    let Ok((base_heavy, base_medium)) = weight_query.get(held_item.entity) else {return;};
+    if marked_weight(base_heavy, base_medium) != Weight::Light {
+        return;
+    }
+    let mut new_stack = None;
+    let stack = match stack {
+        Some(stack) => stack.into_inner(),
+        None => new_stack.insert(HeldStack::new(Weight::Light, held_item.item.height())),
+    };
+    if !stack.can_take(item_weight) {
+        return;
+    }
+    let Ok((mut item_transform, item, item_rigidbody, settling)) = item_query.get_mut(item_entity) else {return;};
+
+    items_in_range.remove(item_entity);
+    stack.push(item_entity, item_weight, item.item_id.height());
+    insert_weight_marker(&mut commands, player_entity, item_weight);
+    end_throw(&mut commands, item_entity, settling);
+    commands.entity(player_entity).add_child(item_entity);
+    // Put in place by wobble_stacks, this only keeps it from flashing at its old spot
+    item_transform.rotation = item.item_id.held_rotation();
+    item_transform.translation = held_item.item.held_position();
+    if item_rigidbody.is_some() {
+        commands
+            .entity(item_entity)
+            .remove::<RigidBody>()
+            .insert(RigidBody::Fixed);
+    }
+    if let Some(stack) = new_stack {
+        commands.entity(player_entity).insert(stack);
+    }
+    action.consume(PlayerAction::Grab);
+}
+
+/// Only opens with empty hands and nothing to grab, otherwise the press already grabbed or threw
+pub fn open_catch_window(
+    mut commands: Commands,
+    time: Res<Time>,
+    items_in_range: Res<ItemsInRange>,
+    mut player_query: Query<
+        (Entity, &ActionState<PlayerAction>, Option<&mut CatchWindow>),
+        (With<Player>, Without<HeldItem>),
+    >,
+) {
+    for (player_entity, action, window) in &mut player_query {
+        if action.just_pressed(PlayerAction::Grab) && items_in_range.is_empty() {
+            commands
+                .entity(player_entity)
+                .insert(CatchWindow(Timer::from_seconds(
+                    CATCH_WINDOW_SECONDS,
+                    TimerMode::Once,
+                )));
+            continue;
+        }
+
+        let Some(mut window) = window else {continue;};
+        window.0.tick(time.delta());
+        if window.0.finished() {
+            commands.entity(player_entity).remove::<CatchWindow>();
+        }
+    }
+}
+
+/// An item still in flight that reaches the grab sensor during the window lands in the
+/// player's hands instead of bouncing off them
+pub fn catch_items(
+    mut commands: Commands,
+    mut items_in_range: ResMut<ItemsInRange>,
+    mut collision_events: EventReader<CollisionEvent>,
+    player_query: Query<
+        (Entity, &ActionState<PlayerAction>),
+        (With<Player>, With<CatchWindow>, Without<HeldItem>),
+    >,
+    grab_sensor_query: Query<Entity, (With<PlayerGrabSensor>, Without<Item>)>,
+    mut item_query: Query<
+        (
+            &mut Transform,
+            &Item,
+            Option<&Velocity>,
+            Option<&RigidBody>,
+            Option<&Settling>,
+            Option<&HeavyItem>,
+            Option<&MediumItem>,
+        ),
+        Without<Parent>,
+    >,
+) {
+    let Ok((player_entity, action)) = player_query.get_single() else {return;};
+    let Ok(sensor_entity) = grab_sensor_query.get_single() else {return;};
+    // A fresh press is handled by grab_item, catching as well would hold two things
+    if action.just_pressed(PlayerAction::Grab) {
+        return;
+    }
+
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let item_entity = if *e1 == sensor_entity {
+            *e2
+        } else if *e2 == sensor_entity {
+            *e1
+        } else {
+            continue;
+        };
+
+        let Ok((mut item_transform, item, velocity, rigidbody, settling, heavy, medium)) = item_query.get_mut(item_entity) else {continue;};
+        let falling = velocity.map_or(false, |velocity| velocity.linvel.y < -CATCH_FALL_SPEED);
+        if settling.is_none() && !falling {
+            continue;
+        }
+
+        items_in_range.remove(item_entity);
+        hold_item(
+            &mut commands,
+            player_entity,
+            (item_entity, marked_weight(heavy, medium)),
+            &mut item_transform,
+            item,
+            rigidbody,
+            settling,
+        );
+        commands.entity(player_entity).remove::<CatchWindow>();
+        return;
+    }
+}
+
+/// Throws the held item, and the whole stack on top of it with it
 pub fn throw_item(
     mut commands: Commands,
     player_query: Query<
         (
             Entity,
             &HeldItem,
+            Option<&HeldStack>,
             &Transform,
             &Velocity,
             &ActionState<PlayerAction>,
@@ -212,7 +576,7 @@ pub fn throw_item(
         With<Player>,
     >,
 ) {
-    for (player_entity, held_item, player_transform, player_velocity, player_action) in
+    for (player_entity, held_item, stack, player_transform, player_velocity, player_action) in
         &player_query
     {
         if player_action.just_pressed(PlayerAction::Grab) {
@@ -220,29 +584,112 @@ pub fn throw_item(
                 entity: item_entity,
                 item: item_id,
             } = held_item;
-            let player_forward = player_transform.forward().normalize_or_zero();
-            let throw_velocity =
-                (player_forward * 15.0) + (Vec3::Y * 10.0) + player_velocity.linvel;
-            let throw_position = player_transform.translation + (player_forward * 1.2);
-
+            let thrown = ThrownItem::from_player(player_transform, player_velocity.linvel);
+            for (stacked_entity, offset) in stack.into_iter().flat_map(HeldStack::offsets) {
+                let position = thrown.throw_position + Vec3::Y * offset;
+                commands
+                    .entity(stacked_entity)
+                    .remove_parent()
+                    .insert(ThrownItem::new(thrown.throw_velocity, position))
+                    .remove::<RigidBody>()
+                    .insert(RigidBody::Dynamic);
+            }
             commands
                 .entity(*item_entity)
                 .remove_parent()
-                .insert(ThrownItem::new(throw_velocity, throw_position))
+                .insert(thrown)
                 .remove::<RigidBody>()
                 .insert(RigidBody::Dynamic);
 
-            commands.entity(player_entity).remove::<HeldItem>();
+            commands
+                .entity(player_entity)
+                .remove::<HeldItem>()
+                .remove::<HeldStack>();
+        }
+    }
+}
+
+/// Leans stacks as the player runs, dropping the top item whenever one sways too far
+pub fn wobble_stacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            Entity,
+            &HeldItem,
+            &mut HeldStack,
+            &GlobalTransform,
+            &Velocity,
+        ),
+        With<Player>,
+    >,
+    mut item_query: Query<(&mut Transform, &Item)>,
+) {
+    for (player_entity, held_item, mut stack, player_transform, player_velocity) in
+        &mut player_query
+    {
+        let speed = Vec3::new(player_velocity.linvel.x, 0.0, player_velocity.linvel.z).length();
+        if stack.wobble(speed, time.delta_seconds()) {
+            if let Some(toppled) = stack.pop() {
+                if let Ok((mut transform, _)) = item_query.get_mut(toppled.entity) {
+                    // Falls off from where it was rather than from its offset in the player
+                    let world = player_transform
+                        .mul_transform(*transform)
+                        .compute_transform();
+                    let velocity = player_velocity.linvel + player_transform.back() * 2.0;
+                    *transform = world;
+                    commands
+                        .entity(toppled.entity)
+                        .remove_parent()
+                        .insert(ThrownItem::new(velocity, world.translation))
+                        .remove::<RigidBody>()
+                        .insert(RigidBody::Dynamic);
+                }
+            }
+        }
+        if stack.is_empty() {
+            commands.entity(player_entity).remove::<HeldStack>();
+            continue;
+        }
+
+        let lean = Quat::from_rotation_x(-stack.tilt());
+        let base = held_item.item.held_position();
+        for (entity, offset) in stack.offsets() {
+            let Ok((mut transform, item)) = item_query.get_mut(entity) else {continue;};
+            transform.translation = base + lean * (Vec3::Y * offset);
+            transform.rotation = lean * item.item_id.held_rotation();
         }
     }
 }
 
 pub fn handle_thrown_momentum(
     mut commands: Commands,
-    mut item_query: Query<(Entity, &ThrownItem, &mut Velocity, &mut Transform), With<RigidBody>>,
+    mut item_query: Query<
+        (
+            Entity,
+            &ThrownItem,
+            &mut Velocity,
+            &mut Transform,
+            Option<&LockedAxes>,
+            Option<&Settling>,
+        ),
+        With<RigidBody>,
+    >,
 ) {
-    for (item_entity, thrown_item, mut item_velocity, mut item_transform) in &mut item_query {
-        commands.entity(item_entity).remove::<ThrownItem>();
+    for (item_entity, thrown_item, mut item_velocity, mut item_transform, locked_axes, settling) in
+        &mut item_query
+    {
+        // Caught and thrown again before landing, the axes it started with still apply
+        let locked_axes = settling
+            .map(|settling| settling.locked_axes)
+            .or(locked_axes.copied())
+            .unwrap_or(LockedAxes::empty());
+        commands
+            .entity(item_entity)
+            .remove::<ThrownItem>()
+            .insert(Ccd::enabled())
+            .insert(LockedAxes::empty())
+            .insert(Settling::new(locked_axes));
 
         println!("Item Velo: {:?}", thrown_item.throw_velocity);
         item_velocity.linvel = thrown_item.throw_velocity;
@@ -250,3 +697,64 @@ pub fn handle_thrown_momentum(
         item_transform.translation = thrown_item.throw_position;
     }
 }
+
+pub fn settle_thrown_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut item_query: Query<(Entity, &Velocity, &mut Transform, &mut Settling)>,
+) {
+    for (item_entity, velocity, mut transform, mut settling) in &mut item_query {
+        if velocity.linvel.length() < SETTLE_SPEED && velocity.angvel.length() < SETTLE_SPIN {
+            settling.rest_timer.tick(time.delta());
+        } else {
+            settling.rest_timer.reset();
+        }
+
+        if settling.rest_timer.finished() {
+            let upright = LockedAxes::ROTATION_LOCKED_X | LockedAxes::ROTATION_LOCKED_Z;
+            if settling.locked_axes.contains(upright) {
+                let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+                transform.rotation = Quat::from_rotation_y(yaw);
+            }
+            commands
+                .entity(item_entity)
+                .remove::<Settling>()
+                .remove::<Ccd>()
+                .insert(settling.locked_axes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stacks_hold_only_what_their_load_allows() {
+        let mut stack = HeldStack::new(Weight::Light, 0.5);
+        assert!(!stack.can_take(Weight::Heavy));
+        assert!(stack.can_take(Weight::Medium));
+        stack.push(Entity::from_raw(1), Weight::Medium, 2.0);
+        stack.push(Entity::from_raw(2), Weight::Light, 0.5);
+        assert_eq!(stack.load(), MAX_STACK_LOAD);
+        assert!(!stack.can_take(Weight::Light));
+
+        let offsets: Vec<f32> = stack.offsets().map(|(_, offset)| offset).collect();
+        assert_eq!(offsets, vec![1.25, 2.5]);
+
+        assert!(stack.remove(Entity::from_raw(1)));
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.offsets().next().unwrap().1, 0.5);
+    }
+
+    #[test]
+    fn running_too_fast_topples_the_stack() {
+        let mut stack = HeldStack::new(Weight::Light, 0.6);
+        stack.push(Entity::from_raw(1), Weight::Light, 0.6);
+        for _ in 0..60 {
+            assert!(!stack.wobble(WOBBLE_SPEED, 1.0 / 60.0));
+        }
+        let toppled = (0..600).any(|_| stack.wobble(WOBBLE_SPEED * 2.0, 1.0 / 60.0));
+        assert!(toppled);
+    }
+}
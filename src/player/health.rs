@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    Drift, KnockedDown, LaunchOptions, Momentum, Player, RespawnPlayer, KNOCKDOWN_KNOCKBACK,
+};
+
+const PLAYER_MAX_HEALTH: u8 = 3;
+const INVULNERABILITY_SECONDS: f32 = 1.5;
+
+pub struct PlayerHealthPlugin;
+
+impl Plugin for PlayerHealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamagePlayer>()
+            .add_systems((tick_invulnerability, handle_player_damage).chain());
+    }
+}
+
+#[derive(Component)]
+pub struct Health {
+    pub current: u8,
+    pub max: u8,
+}
+
+impl Health {
+    pub fn is_depleted(&self) -> bool {
+        self.current == 0
+    }
+
+    pub fn damage(&mut self, amount: u8) {
+        self.current = self.current.saturating_sub(amount);
+    }
+
+    pub fn heal(&mut self, amount: u8) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
+    pub fn refill(&mut self) {
+        self.current = self.max;
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Health {
+            current: PLAYER_MAX_HEALTH,
+            max: PLAYER_MAX_HEALTH,
+        }
+    }
+}
+
+/// Present for a short while after the player is hurt, further damage is ignored until it runs out
+#[derive(Component)]
+pub struct Invulnerable(Timer);
+
+impl Invulnerable {
+    pub fn new() -> Self {
+        Invulnerable(Timer::from_seconds(
+            INVULNERABILITY_SECONDS,
+            TimerMode::Once,
+        ))
+    }
+
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        self.0.tick(delta);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.0.finished()
+    }
+}
+
+/// Send to hurt the player, `knockback` replaces their velocity when the hit lands
+pub struct DamagePlayer {
+    pub amount: u8,
+    pub knockback: Vec3,
+}
+
+pub fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in &mut query {
+        invulnerable.tick(time.delta());
+        if invulnerable.finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+pub fn handle_player_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamagePlayer>,
+    mut respawn_events: EventWriter<RespawnPlayer>,
+    options: Option<Res<LaunchOptions>>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Health,
+            &mut Velocity,
+            &mut Momentum,
+            &mut Drift,
+        ),
+        (With<Player>, Without<Invulnerable>),
+    >,
+) {
+    let Some(damage) = damage_events.iter().max_by_key(|damage| damage.amount) else {return;};
+
+    let god_mode = options.map_or(false, |options| options.god_mode);
+    for (entity, mut health, mut velocity, mut momentum, mut drift) in &mut query {
+        if !god_mode {
+            health.damage(damage.amount);
+        }
+        if health.is_depleted() {
+            health.refill();
+            respawn_events.send(RespawnPlayer);
+            continue;
+        }
+
+        velocity.linvel = damage.knockback;
+        momentum.reset();
+        drift.reset();
+        commands.entity(entity).insert(Invulnerable::new());
+        if damage.knockback.length() >= KNOCKDOWN_KNOCKBACK {
+            let mut impact = damage.knockback;
+            impact.y = 0.0;
+            commands
+                .entity(entity)
+                .insert(KnockedDown::new(impact.normalize_or_zero()));
+        }
+    }
+}
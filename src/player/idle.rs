@@ -0,0 +1,129 @@
+use crate::{CameraController, Grounded, HeldItem, Item, Momentum, Player, PlayerAction};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+const IDLE_TIMEOUT_SECONDS: f32 = 8.0;
+const IDLE_CAMERA_ORBIT_SPEED: f32 = 6.0;
+const IDLE_FIDGET_SPEED: f32 = 1.5;
+const IDLE_FIDGET_SWAY: f32 = 0.2;
+const IDLE_HELD_ITEM_BOB: f32 = 0.1;
+
+pub struct PlayerIdlePlugin;
+
+impl Plugin for PlayerIdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((handle_idle_timer, idle_behaviors).chain());
+    }
+}
+
+#[derive(Component)]
+pub struct IdleTimer(Timer);
+
+impl IdleTimer {
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        self.0.tick(delta);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.0.finished()
+    }
+
+    pub fn reset(&mut self) {
+        self.0.reset();
+    }
+}
+
+impl Default for IdleTimer {
+    fn default() -> Self {
+        IdleTimer(Timer::from_seconds(IDLE_TIMEOUT_SECONDS, TimerMode::Once))
+    }
+}
+
+/// Present while the player is idling, stores what needs to be restored once input resumes
+#[derive(Component)]
+pub struct Idle {
+    pub camera_angle: f32,
+    pub facing: Quat,
+    pub elapsed: f32,
+}
+
+pub fn handle_idle_timer(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut IdleTimer,
+            &Momentum,
+            &ActionState<PlayerAction>,
+            Option<&Idle>,
+            Option<&HeldItem>,
+            Option<&Grounded>,
+        ),
+        With<Player>,
+    >,
+    mut camera_query: Query<&mut CameraController>,
+    mut item_query: Query<(&mut Transform, &Item), Without<Player>>,
+) {
+    for (entity, mut transform, mut idle_timer, momentum, action, idle, held_item, grounded) in
+        &mut player_query
+    {
+        if !action.get_pressed().is_empty() {
+            idle_timer.reset();
+            if let Some(idle) = idle {
+                transform.rotation = idle.facing;
+                if let Ok(mut camera) = camera_query.get_single_mut() {
+                    camera.angle = idle.camera_angle;
+                }
+                if let Some(held_item) = held_item {
+                    if let Ok((mut item_transform, item)) = item_query.get_mut(held_item.entity) {
+                        item_transform.translation = item.item_id.held_position();
+                    }
+                }
+                commands.entity(entity).remove::<Idle>();
+            }
+            continue;
+        }
+
+        if idle.is_none() && grounded.is_some() && !momentum.has_momentum() {
+            idle_timer.tick(time.delta());
+            if idle_timer.finished() {
+                let camera_angle = camera_query
+                    .get_single()
+                    .map(|camera| camera.angle)
+                    .unwrap_or_default();
+                commands.entity(entity).insert(Idle {
+                    camera_angle,
+                    facing: transform.rotation,
+                    elapsed: 0.0,
+                });
+            }
+        }
+    }
+}
+
+pub fn idle_behaviors(
+    time: Res<Time>,
+    mut player_query: Query<(&mut Transform, &mut Idle, Option<&HeldItem>), With<Player>>,
+    mut camera_query: Query<&mut CameraController>,
+    mut item_query: Query<(&mut Transform, &Item), Without<Player>>,
+) {
+    for (mut transform, mut idle, held_item) in &mut player_query {
+        idle.elapsed += time.delta_seconds();
+        let fidget = (idle.elapsed * IDLE_FIDGET_SPEED).sin();
+
+        transform.rotation = idle.facing * Quat::from_rotation_y(fidget * IDLE_FIDGET_SWAY);
+
+        if let Some(held_item) = held_item {
+            if let Ok((mut item_transform, item)) = item_query.get_mut(held_item.entity) {
+                item_transform.translation =
+                    item.item_id.held_position() + Vec3::Y * fidget.abs() * IDLE_HELD_ITEM_BOB;
+            }
+        }
+
+        if let Ok(mut camera) = camera_query.get_single_mut() {
+            camera.angle += IDLE_CAMERA_ORBIT_SPEED * time.delta_seconds();
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use bevy::prelude::*;
 use leafwing_input_manager::{prelude::*, *};
+use serde::{Deserialize, Serialize};
 
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+use crate::ControlPreset;
+
+#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Default, Serialize, Deserialize)]
 pub enum PlayerAction {
     #[default]
     Up,
@@ -19,6 +22,13 @@ pub enum PlayerAction {
     LoadIdea,
     Move,
     Crouch,
+    Sprint,
+    FirstPerson,
+    Look,
+    Interact,
+    Rewind,
+    /// Opens the pause menu, which closes again with `MenuAction::Pause`
+    Pause,
 }
 
 #[derive(Bundle)]
@@ -28,45 +38,65 @@ pub struct InputListenerBundle {
 }
 
 impl InputListenerBundle {
-    pub fn input_map() -> InputListenerBundle {
+    pub fn input_map(gamepad: usize, preset: ControlPreset) -> InputListenerBundle {
+        InputListenerBundle {
+            input_manager: InputManagerBundle {
+                input_map: Self::build_input_map(gamepad, preset),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn build_input_map(gamepad: usize, preset: ControlPreset) -> InputMap<PlayerAction> {
         use PlayerAction::*;
 
+        let (south, west) = match preset {
+            ControlPreset::Standard => (Jump, Grab),
+            ControlPreset::Swapped => (Grab, Jump),
+        };
+
         let mut input_map = input_map::InputMap::new([
             (KeyCode::W, Up),
             (KeyCode::S, Down),
             (KeyCode::A, Left),
             (KeyCode::D, Right),
-            (KeyCode::Space, Jump),
+            (KeyCode::Space, south),
             (KeyCode::Q, CameraLeft),
             (KeyCode::E, CameraRight),
             (KeyCode::Z, CameraMode),
-            (KeyCode::X, Grab),
+            (KeyCode::X, west),
             (KeyCode::R, Crouch),
+            (KeyCode::LShift, Sprint),
+            (KeyCode::F, FirstPerson),
+            (KeyCode::C, Interact),
+            (KeyCode::T, Rewind),
+            (KeyCode::Escape, Pause),
         ])
-        //DEBUG THIS IS ALL DEBUG, DONT HARDCODE A GAMEPAD ID
-        .set_gamepad(Gamepad { id: 0 })
+        .set_gamepad(Gamepad { id: gamepad })
         .build();
 
         input_map
             .insert_multiple([
-                (GamepadButtonType::South, Jump),
-                (GamepadButtonType::West, Grab),
-                (GamepadButtonType::RightTrigger, Crouch),
-                (GamepadButtonType::RightTrigger2, CameraRight),
-                (GamepadButtonType::LeftTrigger2, CameraLeft),
+                (GamepadButtonType::South, south),
+                (GamepadButtonType::West, west),
+                (GamepadButtonType::RightTrigger2, Crouch),
+                (GamepadButtonType::LeftTrigger2, Sprint),
+                (GamepadButtonType::RightTrigger, CameraRight),
+                (GamepadButtonType::LeftTrigger, CameraLeft),
                 (GamepadButtonType::DPadRight, CycleIdeasForward),
                 (GamepadButtonType::DPadLeft, CycleIdeasBackward),
                 (GamepadButtonType::DPadUp, LoadIdea),
                 (GamepadButtonType::DPadDown, UnloadIdeas),
                 (GamepadButtonType::Select, CameraMode),
+                (GamepadButtonType::RightThumb, FirstPerson),
+                (GamepadButtonType::North, Interact),
+                (GamepadButtonType::East, Rewind),
+                (GamepadButtonType::Start, Pause),
             ])
-            .insert(DualAxis::left_stick(), Move);
+            .insert(DualAxis::left_stick(), Move)
+            .insert(DualAxis::right_stick(), Look)
+            .insert(DualAxis::mouse_motion(), Look);
 
-        InputListenerBundle {
-            input_manager: InputManagerBundle {
-                input_map,
-                ..Default::default()
-            },
-        }
+        input_map
     }
 }
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    Drift, LedgeGrab, Momentum, Movement, Player, PlayerSpeed, PlayerWallSensor, Skidding, Wall,
+    Walljump,
+};
+
+const WALL_IMPACT_SPEED: f32 = 18.0;
+pub const KNOCKDOWN_KNOCKBACK: f32 = 12.0;
+const KNOCKDOWN_SECONDS: f32 = 1.2;
+const KNOCKDOWN_SPIN: f32 = 8.0;
+const GET_UP_RATE: f32 = 6.0;
+const UPRIGHT_TOLERANCE: f32 = 0.05;
+
+pub struct PlayerKnockdownPlugin;
+
+impl Plugin for PlayerKnockdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                detect_wall_impacts,
+                start_knockdowns,
+                recover_from_knockdowns,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The player is tumbling with rotation unlocked, `impact` is the direction they were hit towards
+#[derive(Component)]
+pub struct KnockedDown {
+    pub impact: Vec3,
+    pub timer: Timer,
+}
+
+impl KnockedDown {
+    pub fn new(impact: Vec3) -> Self {
+        KnockedDown {
+            impact,
+            timer: Timer::from_seconds(KNOCKDOWN_SECONDS, TimerMode::Once),
+        }
+    }
+
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        self.timer.tick(delta);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.timer.finished()
+    }
+}
+
+/// The player's own collider reports no collisions, so hits are picked up by their wall sensor
+pub fn detect_wall_impacts(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    player_query: Query<(&Transform, &Momentum), (With<Player>, Without<KnockedDown>)>,
+    sensor_query: Query<&Parent, With<PlayerWallSensor>>,
+    wall_query: Query<(), With<Wall>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let (sensor, wall_entity) = if sensor_query.contains(*e1) {
+            (*e1, *e2)
+        } else if sensor_query.contains(*e2) {
+            (*e2, *e1)
+        } else {
+            continue;
+        };
+
+        if !wall_query.contains(wall_entity) {
+            continue;
+        }
+
+        let Ok(parent) = sensor_query.get(sensor) else {continue;};
+        let player_entity = parent.get();
+        let Ok((transform, momentum)) = player_query.get(player_entity) else {continue;};
+        if momentum.get() >= WALL_IMPACT_SPEED {
+            commands
+                .entity(player_entity)
+                .insert(KnockedDown::new(transform.back()));
+        }
+    }
+}
+
+pub fn start_knockdowns(
+    mut commands: Commands,
+    mut player_speed: ResMut<PlayerSpeed>,
+    mut query: Query<
+        (
+            Entity,
+            &KnockedDown,
+            &mut Velocity,
+            &mut Momentum,
+            &mut Drift,
+            &mut Movement,
+        ),
+        Added<KnockedDown>,
+    >,
+) {
+    for (entity, knocked_down, mut velocity, mut momentum, mut drift, mut movement) in &mut query {
+        let spin_axis = Vec3::Y.cross(knocked_down.impact).normalize_or_zero();
+        velocity.angvel = spin_axis * KNOCKDOWN_SPIN;
+        momentum.reset();
+        drift.reset();
+        movement.0 = Vec3::ZERO;
+        player_speed.reset();
+        commands
+            .entity(entity)
+            .insert(LockedAxes::empty())
+            .remove::<Walljump>()
+            .remove::<LedgeGrab>()
+            .remove::<Skidding>();
+    }
+}
+
+/// Once the tumble is over the player springs back upright, facing the way they were heading
+pub fn recover_from_knockdowns(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut KnockedDown, &mut Transform, &mut Velocity)>,
+) {
+    for (entity, mut knocked_down, mut transform, mut velocity) in &mut query {
+        knocked_down.tick(time.delta());
+        if !knocked_down.finished() {
+            continue;
+        }
+
+        let mut heading = transform.forward();
+        heading.y = 0.0;
+        let heading = heading.try_normalize().unwrap_or(Vec3::NEG_Z);
+        let upright = Transform::default().looking_to(heading, Vec3::Y).rotation;
+
+        velocity.angvel = Vec3::ZERO;
+        transform.rotation = transform
+            .rotation
+            .slerp(upright, (GET_UP_RATE * time.delta_seconds()).min(1.0));
+
+        if transform.rotation.angle_between(upright) <= UPRIGHT_TOLERANCE {
+            transform.rotation = upright;
+            commands
+                .entity(entity)
+                .insert(LockedAxes::ROTATION_LOCKED)
+                .remove::<KnockedDown>();
+        }
+    }
+}
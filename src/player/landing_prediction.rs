@@ -0,0 +1,157 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    handle_grounded, Climbing, Grinding, Grounded, LedgeGrab, Player, Riding, Swinging, Ziplining,
+    GRAVITY,
+};
+
+/// The trajectory is followed this far ahead, anything longer is a fall out of the level
+const PREDICTION_MAX_SECONDS: f32 = 3.0;
+/// Length of each straight piece of the arc that's raycast along
+const PREDICTION_STEP_SECONDS: f32 = 0.05;
+/// From the player's origin down to the bottom of their capsule
+const PLAYER_FEET_OFFSET: f32 = 1.0;
+/// Lifts the shadow just off the ground so it doesn't flicker into it
+const SHADOW_LIFT: f32 = 0.03;
+const SHADOW_MIN_SCALE: f32 = 0.4;
+const SHADOW_MAX_SCALE: f32 = 1.0;
+/// The shadow is at its smallest this long or more before landing
+const SHADOW_SHRINK_SECONDS: f32 = 1.0;
+
+pub struct PlayerLandingPredictionPlugin;
+
+impl Plugin for PlayerLandingPredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PredictedLanding>()
+            .add_startup_system(spawn_landing_shadow)
+            .add_systems(
+                (predict_landings, place_landing_shadow)
+                    .chain()
+                    .after(handle_grounded),
+            );
+    }
+}
+
+/// Sent every frame the player is in the air with ground somewhere along their arc, where their
+/// feet will touch down and how many seconds from now
+#[derive(Clone, Copy, Debug)]
+pub struct PredictedLanding {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub seconds: f32,
+}
+
+/// A soft dark blob under the player's predicted landing, so jumps can be judged in depth
+#[derive(Component)]
+pub struct LandingShadow;
+
+/// Where something launched from `start` at `velocity` is after `seconds` under `gravity`
+pub fn trajectory_point(start: Vec3, velocity: Vec3, gravity: Vec3, seconds: f32) -> Vec3 {
+    start + velocity * seconds + gravity * (0.5 * seconds * seconds)
+}
+
+/// Casts along the arc one step at a time, from the player's feet, until it meets solid ground
+fn predict_landings(
+    rapier_context: Res<RapierContext>,
+    mut landing_events: EventWriter<PredictedLanding>,
+    query: Query<
+        (Entity, &Transform, &Velocity, &GravityScale),
+        (
+            With<Player>,
+            Without<Grounded>,
+            Without<LedgeGrab>,
+            Without<Grinding>,
+            Without<Swinging>,
+            Without<Ziplining>,
+            Without<Climbing>,
+            Without<Riding>,
+        ),
+    >,
+) {
+    for (entity, transform, velocity, gravity_scale) in &query {
+        let feet = transform.translation - Vec3::Y * PLAYER_FEET_OFFSET;
+        let gravity = GRAVITY * gravity_scale.0;
+        let filter = QueryFilter::exclude_dynamic()
+            .exclude_sensors()
+            .exclude_collider(entity);
+
+        let steps = (PREDICTION_MAX_SECONDS / PREDICTION_STEP_SECONDS) as usize;
+        for step in 0..steps {
+            let start_seconds = step as f32 * PREDICTION_STEP_SECONDS;
+            let from = trajectory_point(feet, velocity.linvel, gravity, start_seconds);
+            let to = trajectory_point(
+                feet,
+                velocity.linvel,
+                gravity,
+                start_seconds + PREDICTION_STEP_SECONDS,
+            );
+            let length = from.distance(to);
+            if length <= f32::EPSILON {
+                continue;
+            }
+            let direction = (to - from) / length;
+            let hit = rapier_context.cast_ray_and_get_normal(from, direction, length, true, filter);
+            let Some((_, intersection)) = hit else {continue;};
+            landing_events.send(PredictedLanding {
+                position: intersection.point,
+                normal: intersection.normal,
+                seconds: start_seconds + PREDICTION_STEP_SECONDS * intersection.toi / length,
+            });
+            break;
+        }
+    }
+}
+
+fn spawn_landing_shadow(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Circle::new(0.5))),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(0.0, 0.0, 0.0, 0.35),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(NotShadowCaster)
+        .insert(LandingShadow);
+}
+
+/// Lays the shadow flat on the predicted landing spot, growing as the ground comes up
+fn place_landing_shadow(
+    mut landing_events: EventReader<PredictedLanding>,
+    mut shadow_query: Query<(&mut Transform, &mut Visibility), With<LandingShadow>>,
+) {
+    let landing = landing_events.iter().last().copied();
+    for (mut transform, mut visibility) in &mut shadow_query {
+        let Some(landing) = landing else {*visibility = Visibility::Hidden; continue;};
+        let closeness = 1.0 - (landing.seconds / SHADOW_SHRINK_SECONDS).min(1.0);
+        let scale = SHADOW_MIN_SCALE + (SHADOW_MAX_SCALE - SHADOW_MIN_SCALE) * closeness;
+        *transform = Transform::from_translation(landing.position + landing.normal * SHADOW_LIFT)
+            .with_rotation(Quat::from_rotation_arc(Vec3::Z, landing.normal))
+            .with_scale(Vec3::splat(scale));
+        *visibility = Visibility::Inherited;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn trajectory_follows_a_ballistic_arc() {
+        let start = Vec3::new(0.0, 1.0, 0.0);
+        let velocity = Vec3::new(2.0, 15.0, 0.0);
+        let apex = trajectory_point(start, velocity, GRAVITY, 0.5);
+        assert!((apex - Vec3::new(1.0, 4.75, 0.0)).length() < 1e-5);
+        let back_down = trajectory_point(start, velocity, GRAVITY, 1.0);
+        assert!((back_down - Vec3::new(2.0, 1.0, 0.0)).length() < 1e-5);
+    }
+}
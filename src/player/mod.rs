@@ -6,12 +6,36 @@ pub mod inputs;
 pub use inputs::*;
 pub mod grabbing;
 pub use grabbing::*;
+pub mod idle;
+pub use idle::*;
+pub mod respawn;
+pub use respawn::*;
+pub mod health;
+pub use health::*;
+pub mod fall_damage;
+pub use fall_damage::*;
+pub mod landing_prediction;
+pub use landing_prediction::*;
+pub mod knockdown;
+pub use knockdown::*;
+pub mod swinging;
+pub use swinging::*;
+pub mod rewind;
+pub use rewind::*;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(PlayerMovementPlugin)
-            .add_plugin(PlayerGrabbingPlugin);
+            .add_plugin(PlayerGrabbingPlugin)
+            .add_plugin(PlayerIdlePlugin)
+            .add_plugin(PlayerRespawnPlugin)
+            .add_plugin(PlayerHealthPlugin)
+            .add_plugin(PlayerFallDamagePlugin)
+            .add_plugin(PlayerLandingPredictionPlugin)
+            .add_plugin(PlayerKnockdownPlugin)
+            .add_plugin(PlayerSwingingPlugin)
+            .add_plugin(PlayerRewindPlugin);
     }
 }
@@ -1,4 +1,22 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::Movement;
+
+/// A double jump with the stick held further than this from the player's facing becomes a backflip
+const BACKFLIP_STICK_DOT: f32 = -0.5;
+const BACKFLIP_HEIGHT_BONUS: f32 = 5.0;
+/// A wall slide starts out this fast and speeds up over time until it reaches the top speed
+const WALL_SLIDE_START_SPEED: f32 = 1.5;
+const WALL_SLIDE_ACCELERATION: f32 = 3.0;
+const WALL_SLIDE_TOP_SPEED: f32 = 8.0;
+/// Holding crouch slides down this much faster, holding into the wall this much slower
+const WALL_SLIDE_FAST_MULTIPLIER: f32 = 2.0;
+const WALL_SLIDE_GRIP_MULTIPLIER: f32 = 0.5;
+/// Each wall jump in a row off the same wall keeps this much of the last one's height
+const WALL_JUMP_REPEAT_FALLOFF: f32 = 0.7;
+/// Walls facing this closely the same way as the last one jumped off count as the same wall
+const SAME_WALL_DOT: f32 = 0.95;
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
@@ -6,6 +24,9 @@ pub struct Jump {
     pub input_timer: Timer,
     pub jump_stage: JumpStage,
     pub jump_buffered: bool,
+    /// Which way the last wall jumped off faced, zero once back on the ground
+    pub last_wall_normal: Vec3,
+    pub same_wall_jumps: u32,
 }
 
 impl Jump {
@@ -54,11 +75,51 @@ impl Jump {
         }
     }
 
+    /// Whether the buffered jump is a double jump with the stick held back against `facing`
+    pub fn is_backflip(&self, movement: &Movement, facing: Vec3) -> bool {
+        self.jump_buffered
+            && self.jump_stage == JumpStage::Double
+            && movement.is_moving()
+            && movement.0.normalize().dot(facing.normalize_or_zero()) < BACKFLIP_STICK_DOT
+    }
+
+    /// A double jump with extra height, it still counts as the second jump of the chain
+    pub fn get_backflip_force(&mut self) -> Option<f32> {
+        self.get_jump_force()
+            .map(|force| force + BACKFLIP_HEIGHT_BONUS)
+    }
+
+    pub fn get_side_somersault_force(&mut self) -> Option<f32> {
+        if self.jump_buffered {
+            self.reset();
+            Some(18.0)
+        } else {
+            None
+        }
+    }
+
     pub fn get_wall_jump_force(&mut self) -> f32 {
         self.reset_input();
         15.0
     }
 
+    /// How high a wall jump off the wall facing `normal` goes, lower for each jump in a row off
+    /// the same wall while alternating between walls keeps the full height
+    pub fn get_wall_jump_height(&mut self, normal: Vec3) -> f32 {
+        if self.last_wall_normal.dot(normal) > SAME_WALL_DOT {
+            self.same_wall_jumps += 1;
+        } else {
+            self.same_wall_jumps = 0;
+        }
+        self.last_wall_normal = normal;
+        self.get_wall_jump_force() * WALL_JUMP_REPEAT_FALLOFF.powi(self.same_wall_jumps as i32)
+    }
+
+    pub fn reset_wall_chain(&mut self) {
+        self.last_wall_normal = Vec3::ZERO;
+        self.same_wall_jumps = 0;
+    }
+
     pub fn buffer_jump(&mut self) {
         self.jump_buffered = true;
         self.input_timer.reset();
@@ -71,11 +132,13 @@ impl Default for Jump {
             input_timer: Timer::from_seconds(0.2, TimerMode::Once),
             jump_stage: JumpStage::Single,
             jump_buffered: false,
+            last_wall_normal: Vec3::ZERO,
+            same_wall_jumps: 0,
         }
     }
 }
 
-#[derive(Default, Reflect)]
+#[derive(Default, Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub enum JumpStage {
     #[default]
     Single,
@@ -87,8 +150,8 @@ pub enum JumpStage {
 pub struct Coyote(Timer);
 
 impl Coyote {
-    pub fn new() -> Self {
-        Coyote(Timer::from_seconds(0.2, TimerMode::Once))
+    pub fn new(seconds: f32) -> Self {
+        Coyote(Timer::from_seconds(seconds, TimerMode::Once))
     }
     pub fn tick(&mut self, delta: std::time::Duration) {
         self.0.tick(delta);
@@ -97,16 +160,38 @@ impl Coyote {
     pub fn finished(&self) -> bool {
         self.0.finished()
     }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.0.remaining_secs()
+    }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
 pub struct Grounded;
 
+/// The solid entity directly under a grounded player
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub struct StandingOn(pub Entity);
+
 #[derive(Component, Default)]
 pub struct Walljump(pub Vec3);
 
 #[derive(Component, Default)]
-pub struct LedgeGrab(pub Vec3);
+pub struct LedgeGrab {
+    /// Points into the wall the ledge is on
+    pub direction: Vec3,
+    /// Where the player hangs from, eased toward after the grab
+    pub hang_position: Vec3,
+}
+
+impl LedgeGrab {
+    /// The rotation facing the wall
+    pub fn facing(&self) -> Quat {
+        let direction = Vec3::new(self.direction.x, 0.0, self.direction.z);
+        Transform::IDENTITY.looking_at(direction, Vec3::Y).rotation
+    }
+}
 
 #[derive(Component)]
 pub struct PlayerWallSensor;
@@ -114,7 +199,8 @@ pub struct PlayerWallSensor;
 #[derive(Component)]
 pub struct PlayerLedgeSensor;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
 pub struct Drift(pub Vec3);
 
 impl Drift {
@@ -134,3 +220,195 @@ impl Drift {
         self.0 += drift;
     }
 }
+
+#[derive(Component)]
+pub struct Stamina {
+    current: f32,
+    max: f32,
+    sprinting: bool,
+    exhaustion: Option<Timer>,
+}
+
+impl Stamina {
+    pub fn percent(&self) -> f32 {
+        if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current >= self.max
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.exhaustion.is_some()
+    }
+
+    pub fn is_sprinting(&self) -> bool {
+        self.sprinting
+    }
+
+    pub fn set_max(&mut self, max: f32) {
+        self.max = max;
+        self.current = self.current.min(max);
+    }
+
+    pub fn start_sprinting(&mut self) {
+        self.sprinting = true;
+    }
+
+    pub fn stop_sprinting(&mut self) {
+        self.sprinting = false;
+    }
+
+    /// Drains stamina, forcing an exhaustion cooldown once it runs out
+    pub fn drain(&mut self, amount: f32, exhaustion_seconds: f32) {
+        self.current = (self.current - amount).max(0.0);
+        if self.current == 0.0 {
+            self.exhaustion = Some(Timer::from_seconds(exhaustion_seconds, TimerMode::Once));
+        }
+    }
+
+    /// Regenerates stamina, regeneration is paused until any exhaustion cooldown has finished
+    pub fn recover(&mut self, delta: std::time::Duration, amount: f32) {
+        if let Some(exhaustion) = self.exhaustion.as_mut() {
+            exhaustion.tick(delta);
+            if exhaustion.finished() {
+                self.exhaustion = None;
+            }
+        } else {
+            self.current = (self.current + amount).min(self.max);
+        }
+    }
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina {
+            current: 100.0,
+            max: 100.0,
+            sprinting: false,
+            exhaustion: None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Skidding {
+    timer: Timer,
+    dust_timer: Timer,
+}
+
+impl Skidding {
+    pub fn new() -> Self {
+        Skidding {
+            timer: Timer::from_seconds(0.25, TimerMode::Once),
+            dust_timer: Timer::from_seconds(0.05, TimerMode::Repeating),
+        }
+    }
+
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        self.timer.tick(delta);
+        self.dust_timer.tick(delta);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.timer.finished()
+    }
+
+    pub fn should_spawn_dust(&self) -> bool {
+        self.dust_timer.just_finished()
+    }
+}
+
+/// Falling against a wall, the slide speeds up the longer it lasts
+#[derive(Component)]
+pub struct WallSliding {
+    seconds: f32,
+    dust_timer: Timer,
+}
+
+impl Default for WallSliding {
+    fn default() -> Self {
+        WallSliding {
+            seconds: 0.0,
+            dust_timer: Timer::from_seconds(0.08, TimerMode::Repeating),
+        }
+    }
+}
+
+impl WallSliding {
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        self.seconds += delta.as_secs_f32();
+        self.dust_timer.tick(delta);
+    }
+
+    /// The fastest the player may fall right now, `fast` while holding crouch and `grip`
+    /// while pushing into the wall
+    pub fn speed(&self, fast: bool, grip: bool) -> f32 {
+        let mut speed = (WALL_SLIDE_START_SPEED + WALL_SLIDE_ACCELERATION * self.seconds)
+            .min(WALL_SLIDE_TOP_SPEED);
+        if fast {
+            speed *= WALL_SLIDE_FAST_MULTIPLIER;
+        } else if grip {
+            speed *= WALL_SLIDE_GRIP_MULTIPLIER;
+        }
+        speed
+    }
+
+    pub fn should_spawn_dust(&self) -> bool {
+        self.dust_timer.just_finished()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_alternating_walls_keep_full_wall_jump_height() {
+        let mut jump = Jump::default();
+        let full = jump.get_wall_jump_height(Vec3::X);
+        let repeat = jump.get_wall_jump_height(Vec3::X);
+        assert!(repeat < full);
+        assert!(jump.get_wall_jump_height(Vec3::X) < repeat);
+
+        assert_eq!(jump.get_wall_jump_height(Vec3::NEG_X), full);
+        assert_eq!(jump.get_wall_jump_height(Vec3::X), full);
+
+        jump.get_wall_jump_height(Vec3::X);
+        jump.reset_wall_chain();
+        assert_eq!(jump.get_wall_jump_height(Vec3::X), full);
+    }
+
+    #[test]
+    fn wall_slides_speed_up_over_time() {
+        let mut sliding = WallSliding::default();
+        assert_eq!(sliding.speed(false, false), WALL_SLIDE_START_SPEED);
+        sliding.tick(std::time::Duration::from_secs(1));
+        assert!(sliding.speed(false, false) > WALL_SLIDE_START_SPEED);
+        assert!(sliding.speed(true, false) > sliding.speed(false, false));
+        assert!(sliding.speed(false, true) < sliding.speed(false, false));
+        sliding.tick(std::time::Duration::from_secs(10));
+        assert_eq!(sliding.speed(false, false), WALL_SLIDE_TOP_SPEED);
+    }
+
+    #[test]
+    fn only_a_double_jump_against_facing_is_a_backflip() {
+        let mut jump = Jump::default();
+        let back = Movement(Vec3::Z);
+        let facing = Vec3::NEG_Z;
+        jump.buffer_jump();
+        assert!(!jump.is_backflip(&back, facing));
+
+        jump.get_jump_force();
+        jump.buffer_jump();
+        assert!(!jump.is_backflip(&Movement(Vec3::X), facing));
+        assert!(!jump.is_backflip(&Movement::default(), facing));
+        assert!(jump.is_backflip(&back, facing));
+        assert_eq!(jump.get_backflip_force(), Some(20.0));
+        assert_eq!(jump.jump_stage, JumpStage::Triple);
+    }
+}
@@ -3,11 +3,42 @@ use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
 
 use crate::{
-    apply_momentum, get_direction_in_camera_space, Coyote, Crouching, Drift, Grounded, Jump,
-    Landing, Ledge, LedgeGrab, MainCamera, Momentum, Player, PlayerAction, PlayerLedgeSensor,
-    PlayerSpeed, PlayerWallSensor, Wall, Walljump,
+    apply_momentum, get_direction_in_camera_space, spawn_dust, Busy, Climbing, CostumeModifiers,
+    Coyote, Crouching, DebugProbes, Drift, EntityPools, GameSettings, Grinding, GroundSurface,
+    Grounded, Jump, KnockedDown, Landing, Ledge, LedgeGrab, MainCamera, Momentum, Movement,
+    ParticleAssets, Player, PlayerAction, PlayerLedgeSensor, PlayerSpeed, PlayerWallSensor, Probe,
+    Riding, Room, Skidding, StandingOn, SurfaceType, Swinging, TimeScale, Walkable, Wall,
+    WallSliding, Walljump, Ziplining, GRAVITY, PLAYER_HALF_HEIGHT,
 };
 
+/// Catching a ledge briefly slows the game down so the grab lands with some weight
+const LEDGE_CATCH_TIME_SCALE: f32 = 0.35;
+const LEDGE_CATCH_SLOWDOWN: f32 = 0.15;
+/// Ledges are only caught once the hands are at most this far below the lip
+const LEDGE_REACH: f32 = 0.3;
+/// Rising faster than this the player passes ledges by, slower counts as the top of the jump
+const LEDGE_MAX_RISE_SPEED: f32 = 2.0;
+/// The top of the player's capsule, ceilings are looked for from just under it
+const PLAYER_HEAD_OFFSET: f32 = 0.5;
+const CEILING_PROBE_RADIUS: f32 = 0.45;
+const CEILING_MARGIN: f32 = 0.1;
+/// How long a jump is still allowed after walking off an edge, before the accessibility multiplier
+const COYOTE_SECONDS: f32 = 0.2;
+/// A backflip carries the player a little way back the way the stick points
+const BACKFLIP_MOMENTUM: f32 = 4.0;
+/// Pushing the stick this directly into a wall grips it and slows the slide
+const WALL_SLIDE_GRIP_DOT: f32 = 0.5;
+/// Where the player hangs relative to the lip of a grabbed ledge, out from the wall and below
+const LEDGE_HANG_DISTANCE: f32 = 0.55;
+const LEDGE_HANG_DROP: f32 = 1.0;
+/// How far onto the ledge climbing up carries the player from where they hang
+const LEDGE_CLIMB_DISTANCE: f32 = 1.5;
+/// The player never hangs closer than this to either end of a ledge
+const LEDGE_HANG_MARGIN: f32 = 0.3;
+/// How quickly the player eases into the hang, in fractions of the remaining distance per second
+const LEDGE_ALIGN_RATE: f32 = 12.0;
+const LEDGE_ALIGN_SNAP: f32 = 0.01;
+
 pub fn handle_jump_buffer(time: Res<Time>, mut query: Query<&mut Jump>) {
     for mut jump in &mut query {
         jump.update(time.delta());
@@ -18,12 +49,22 @@ pub fn aerial_drift(
     time: Res<Time>,
     mut query: Query<
         (&mut Drift, &ActionState<PlayerAction>),
-        (With<Player>, Without<Grounded>, Without<LedgeGrab>),
+        (
+            With<Player>,
+            Without<Grounded>,
+            Without<LedgeGrab>,
+            Without<Grinding>,
+            Without<Swinging>,
+            Without<Ziplining>,
+            Without<Climbing>,
+            Without<Riding>,
+            Without<KnockedDown>,
+        ),
     >,
 
     camera_query: Query<&Transform, With<MainCamera>>,
 ) {
-    let camera_transform = camera_query.single();
+    let Ok(camera_transform) = camera_query.get_single() else {return;};
 
     for (mut drift, action) in &mut query {
         drift.add(
@@ -41,22 +82,64 @@ pub fn handle_grounded(
             &mut Drift,
             Option<&Grounded>,
             Option<&Walljump>,
+            Option<&GroundSurface>,
+            Option<&StandingOn>,
         ),
         With<Player>,
     >,
+    surface_query: Query<&SurfaceType>,
+    walkable_query: Query<(), With<Walkable>>,
+    disabled_query: Query<(), With<ColliderDisabled>>,
     rapier_context: Res<RapierContext>,
+    settings: Option<Res<GameSettings>>,
+    mut probes: Option<ResMut<DebugProbes>>,
 ) {
-    for (entity, transform, mut drift, grounded, walljump) in &mut query {
+    let coyote_seconds =
+        COYOTE_SECONDS * settings.map_or(1.0, |settings| settings.coyote_time_multiplier);
+    for (entity, transform, mut drift, grounded, walljump, ground_surface, standing_on) in
+        &mut query
+    {
         let is_grounded = grounded.is_some();
         let ray_pos = transform.translation;
         let ray_dir = Vec3::Y * -1.0;
         let max_distance = 1.1;
         let solid = true;
-        let filter = QueryFilter::exclude_dynamic().exclude_sensors();
+        // The player's own body is kinematic under the kinematic backend
+        let filter = QueryFilter::exclude_dynamic()
+            .exclude_sensors()
+            .exclude_collider(entity);
+
+        // A collider disabled this frame is still in the query pipeline until the next step
+        let ground = rapier_context
+            .cast_ray(ray_pos, ray_dir, max_distance, solid, filter)
+            .filter(|(hit_entity, _)| !disabled_query.contains(*hit_entity))
+            .or_else(|| {
+                let moving_filter = QueryFilter::only_dynamic()
+                    .exclude_sensors()
+                    .exclude_collider(entity);
+                rapier_context
+                    .cast_ray(ray_pos, ray_dir, max_distance, solid, moving_filter)
+                    .filter(|(hit_entity, _)| walkable_query.contains(*hit_entity))
+            });
+        if let Some(probes) = &mut probes {
+            let hit = ground.map(|(_, toi)| toi);
+            probes.grounded = Some(Probe::new(ray_pos, ray_dir, max_distance, hit));
+        }
+
+        if let Some((ground_entity, _intersection)) = ground {
+            let surface = GroundSurface(
+                surface_query
+                    .get(ground_entity)
+                    .copied()
+                    .unwrap_or_default(),
+            );
+            if ground_surface != Some(&surface) {
+                commands.entity(entity).insert(surface);
+            }
+            if standing_on != Some(&StandingOn(ground_entity)) {
+                commands.entity(entity).insert(StandingOn(ground_entity));
+            }
 
-        if let Some((_entity, _intersection)) =
-            rapier_context.cast_ray(ray_pos, ray_dir, max_distance, solid, filter)
-        {
             if !is_grounded {
                 drift.reset();
                 commands
@@ -72,14 +155,27 @@ pub fn handle_grounded(
             if is_grounded {
                 commands
                     .entity(entity)
-                    .insert(Coyote::new())
+                    .insert(Coyote::new(coyote_seconds))
                     .remove::<Grounded>();
             }
+            if standing_on.is_some() {
+                commands.entity(entity).remove::<StandingOn>();
+            }
         }
     }
 }
 
-pub fn buffer_jump(mut query: Query<(&mut Jump, &ActionState<PlayerAction>), With<Player>>) {
+pub fn buffer_jump(
+    mut query: Query<
+        (&mut Jump, &ActionState<PlayerAction>),
+        (
+            With<Player>,
+            Without<KnockedDown>,
+            Without<Riding>,
+            Without<Busy>,
+        ),
+    >,
+) {
     for (mut jump, action) in &mut query {
         if action.just_pressed(PlayerAction::Jump) {
             jump.buffer_jump();
@@ -87,31 +183,192 @@ pub fn buffer_jump(mut query: Query<(&mut Jump, &ActionState<PlayerAction>), Wit
     }
 }
 
+pub fn handle_side_somersault(
+    mut commands: Commands,
+    mut player_speed: ResMut<PlayerSpeed>,
+    mut jump_events: EventWriter<Jumped>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut Momentum,
+            &mut Jump,
+            &mut Friction,
+            &Movement,
+        ),
+        (With<Player>, With<Grounded>, With<Skidding>),
+    >,
+) {
+    for (entity, mut transform, mut velocity, mut momentum, mut jump, mut friction, movement) in
+        &mut query
+    {
+        if let Some(force) = jump.get_side_somersault_force() {
+            if movement.is_moving() {
+                let position = transform.translation;
+                transform.look_at(position + movement.0, Vec3::Y);
+            }
+            velocity.linvel.y = force;
+            jump_events.send(Jumped::SideSomersault);
+            momentum.set(5.0);
+            player_speed.reset();
+            friction.coefficient = 1.0;
+            commands
+                .entity(entity)
+                .remove::<Skidding>()
+                .remove::<Grounded>();
+        }
+    }
+}
+
+/// Sent whenever the player jumps off the ground or a wall
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Jumped {
+    Stage(JumpStage),
+    Wall,
+    SideSomersault,
+    Backflip,
+    Long,
+}
+
 pub fn handle_jumping(
     mut commands: Commands,
+    mut player_speed: ResMut<PlayerSpeed>,
+    mut jump_events: EventWriter<Jumped>,
     mut query: Query<
         (
             Entity,
+            &mut Transform,
             &mut Velocity,
+            &mut Momentum,
             &mut Jump,
+            &Movement,
             Option<&Grounded>,
             Option<&Coyote>,
+            Option<&GroundSurface>,
+            Option<&CostumeModifiers>,
         ),
-        With<Player>,
+        (With<Player>, Without<Climbing>),
     >,
 ) {
-    for (entity, mut velocity, mut jump, grounded, coyote) in &mut query {
+    for (
+        entity,
+        mut transform,
+        mut velocity,
+        mut momentum,
+        mut jump,
+        movement,
+        grounded,
+        coyote,
+        ground_surface,
+        costume,
+    ) in &mut query
+    {
+        let jump_multiplier = costume.map_or(1.0, |costume| costume.jump);
         if grounded.is_some() || coyote.is_some() {
-            if let Some(force) = jump.get_jump_force() {
-                velocity.linvel.y = force;
+            // No footing for a jump chain on ice, every jump is a single jump
+            if ground_surface.map_or(false, |ground| ground.0 == SurfaceType::Ice) {
+                jump.reset_jump_stage();
+            }
 
-                if grounded.is_some() {
-                    commands.entity(entity).remove::<Grounded>();
-                }
-                if coyote.is_some() {
-                    commands.entity(entity).remove::<Coyote>();
-                }
+            // The stick has to be read before the jump is spent and the stage moves on
+            if jump.is_backflip(movement, transform.forward()) {
+                let Some(force) = jump.get_backflip_force() else {continue;};
+                let position = transform.translation;
+                transform.look_at(position + movement.0, Vec3::Y);
+                velocity.linvel.y = force * jump_multiplier;
+                momentum.set(BACKFLIP_MOMENTUM);
+                player_speed.reset();
+                jump_events.send(Jumped::Backflip);
+            } else {
+                let stage = jump.jump_stage;
+                let Some(force) = jump.get_jump_force() else {continue;};
+                velocity.linvel.y = force * jump_multiplier;
+                jump_events.send(Jumped::Stage(stage));
             }
+
+            if grounded.is_some() {
+                commands.entity(entity).remove::<Grounded>();
+            }
+            if coyote.is_some() {
+                commands.entity(entity).remove::<Coyote>();
+            }
+        }
+    }
+}
+
+/// Sent when the player's head hits a ceiling on the way up
+pub struct CeilingBonk {
+    pub position: Vec3,
+    /// How fast the player was rising when they hit
+    pub speed: f32,
+}
+
+/// Cuts a jump short the moment the player's head would meet a ceiling, rather than leaving
+/// them pressed against it until gravity wins
+pub fn detect_ceilings(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    mut bonks: EventWriter<CeilingBonk>,
+    mut query: Query<
+        (Entity, &Transform, &mut Velocity),
+        (
+            With<Player>,
+            Without<Grounded>,
+            Without<LedgeGrab>,
+            Without<Grinding>,
+            Without<Swinging>,
+            Without<Ziplining>,
+        ),
+    >,
+) {
+    for (entity, transform, mut velocity) in &mut query {
+        let rising = velocity.linvel.y;
+        if rising <= 0.0 {
+            continue;
+        }
+        let head = transform.translation + Vec3::Y * PLAYER_HEAD_OFFSET;
+        let filter = QueryFilter::exclude_dynamic()
+            .exclude_sensors()
+            .exclude_collider(entity);
+        let ceiling = rapier_context.cast_shape(
+            head,
+            Quat::IDENTITY,
+            Vec3::Y,
+            &Collider::ball(CEILING_PROBE_RADIUS),
+            rising * time.delta_seconds() + CEILING_MARGIN,
+            filter,
+        );
+        if let Some((_, hit)) = ceiling {
+            velocity.linvel.y = 0.0;
+            bonks.send(CeilingBonk {
+                position: head + Vec3::Y * (hit.toi + CEILING_PROBE_RADIUS),
+                speed: rising,
+            });
+        }
+    }
+}
+
+/// Holds back part of gravity while falling, for costumes that slow the player's fall
+pub fn apply_fall_modifier(
+    time: Res<Time>,
+    mut query: Query<
+        (&mut Velocity, &CostumeModifiers),
+        (
+            With<Player>,
+            Without<Grounded>,
+            Without<LedgeGrab>,
+            Without<Grinding>,
+            Without<Swinging>,
+            Without<Ziplining>,
+            Without<Climbing>,
+            Without<Riding>,
+        ),
+    >,
+) {
+    for (mut velocity, costume) in &mut query {
+        if velocity.linvel.y < 0.0 && costume.fall < 1.0 {
+            velocity.linvel.y -= GRAVITY.y * (1.0 - costume.fall) * time.delta_seconds();
         }
     }
 }
@@ -121,6 +378,7 @@ pub fn reset_jumps_after_landing(
 ) {
     for mut jump in &mut query {
         jump.reset_jump_stage();
+        jump.reset_wall_chain();
     }
 }
 
@@ -144,8 +402,9 @@ pub fn detect_walls(
     >,
     wall_sensor_query: Query<Entity, (With<PlayerWallSensor>, Without<Player>, Without<Wall>)>,
     wall_query: Query<(Entity, &Transform), With<Wall>>,
+    mut probes: Option<ResMut<DebugProbes>>,
 ) {
-    let sensor_entity = wall_sensor_query.single();
+    let Ok(sensor_entity) = wall_sensor_query.get_single() else {return;};
     for (player_entity, player_transform, mut friction, walljump) in &mut player_query {
         for collision_event in collision_events.iter() {
             match collision_event {
@@ -173,13 +432,18 @@ pub fn detect_walls(
                             .exclude_sensors()
                             .exclude_collider(player_entity);
 
-                        if let Some((_, intersection)) = rapier_context.cast_ray_and_get_normal(
+                        let wall = rapier_context.cast_ray_and_get_normal(
                             ray_pos,
                             ray_dir,
                             max_distance,
                             solid,
                             filter,
-                        ) {
+                        );
+                        if let Some(probes) = &mut probes {
+                            let hit = wall.map(|(_, intersection)| intersection.toi);
+                            probes.wall = Some(Probe::new(ray_pos, ray_dir, max_distance, hit));
+                        }
+                        if let Some((_, intersection)) = wall {
                             commands
                                 .entity(player_entity)
                                 .insert(Walljump(intersection.normal));
@@ -203,8 +467,53 @@ pub fn detect_walls(
     }
 }
 
+/// Holds the fall against a wall to the slide's speed, kicking up dust along the wall
+pub fn handle_wall_sliding(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pools: ResMut<EntityPools>,
+    particle_assets: Res<ParticleAssets>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Velocity,
+            &Walljump,
+            &Movement,
+            &ActionState<PlayerAction>,
+            Option<&mut WallSliding>,
+        ),
+        With<Player>,
+    >,
+    off_wall_query: Query<Entity, (With<WallSliding>, Without<Walljump>)>,
+) {
+    for entity in &off_wall_query {
+        commands.entity(entity).remove::<WallSliding>();
+    }
+
+    for (entity, transform, mut velocity, walljump, movement, action, sliding) in &mut query {
+        // Still rising from the jump that got them to the wall
+        if velocity.linvel.y >= 0.0 {
+            continue;
+        }
+        let Some(mut sliding) = sliding else {commands.entity(entity).insert(WallSliding::default()); continue;};
+
+        sliding.tick(time.delta());
+        let fast = action.pressed(PlayerAction::Crouch);
+        let grip = movement.0.dot(-walljump.0) > WALL_SLIDE_GRIP_DOT;
+        velocity.linvel.y = velocity.linvel.y.max(-sliding.speed(fast, grip));
+
+        if sliding.should_spawn_dust() {
+            let hands = transform.translation - walljump.0 * 0.5 + Vec3::Y * 0.5;
+            let kick = walljump.0 + Vec3::Y * 0.5;
+            spawn_dust(&mut commands, &mut pools, &particle_assets, hands, kick);
+        }
+    }
+}
+
 pub fn handle_wall_jumping(
     mut commands: Commands,
+    mut jump_events: EventWriter<Jumped>,
     mut query: Query<
         (
             Entity,
@@ -235,17 +544,20 @@ pub fn handle_wall_jumping(
             drift.reset();
             transform.look_at(position + walljump.0, Vec3::Y);
             momentum.set(jump.get_wall_jump_force());
-            velocity.linvel = Vec3::Y * jump.get_wall_jump_force();
+            velocity.linvel = Vec3::Y * jump.get_wall_jump_height(walljump.0);
+            jump_events.send(Jumped::Wall);
             commands.entity(entity).remove::<Walljump>();
         }
     }
 }
 
-enum LedgeDetectionStatus {
-    Hit(Entity),
-    NoHit,
+/// Whether hands at `hands_y` can latch onto a lip at `lip_y` while rising at `rise_speed`
+pub fn can_catch_ledge(hands_y: f32, lip_y: f32, rise_speed: f32) -> bool {
+    rise_speed <= LEDGE_MAX_RISE_SPEED && hands_y >= lip_y - LEDGE_REACH
 }
 
+/// Ledges touching the ledge sensor are caught once the player's hands are up at the lip and
+/// they're near the top of their jump, so brushing one on the way up doesn't snap into a hang
 pub fn detect_ledges(
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
@@ -253,7 +565,7 @@ pub fn detect_ledges(
     mut player_query: Query<
         (
             Entity,
-            &mut Transform,
+            &Transform,
             &mut Velocity,
             &mut GravityScale,
             Option<&LedgeGrab>,
@@ -264,76 +576,111 @@ pub fn detect_ledges(
             Without<Grounded>,
             Without<PlayerLedgeSensor>,
             Without<Wall>,
+            Without<Climbing>,
         ),
     >,
-    ledge_sensor_query: Query<Entity, (With<PlayerLedgeSensor>, Without<Player>)>,
-    ledge_query: Query<(Entity, &Transform), (With<Ledge>, Without<Player>)>,
+    ledge_sensor_query: Query<
+        (Entity, &GlobalTransform),
+        (With<PlayerLedgeSensor>, Without<Player>),
+    >,
+    ledge_query: Query<(&GlobalTransform, &Collider), (With<Ledge>, Without<Player>)>,
+    room_query: Query<(), With<Room>>,
+    mut ledges_in_reach: Local<Vec<Entity>>,
+    mut time_scale: Option<ResMut<TimeScale>>,
+    mut probes: Option<ResMut<DebugProbes>>,
 ) {
-    let sensor_entity = ledge_sensor_query.single();
+    let Ok((sensor_entity, sensor_transform)) = ledge_sensor_query.get_single() else {return;};
+    for collision_event in collision_events.iter() {
+        let (CollisionEvent::Started(e1, e2, _) | CollisionEvent::Stopped(e1, e2, _)) =
+            collision_event;
+        let ledge = if *e1 == sensor_entity {
+            *e2
+        } else if *e2 == sensor_entity {
+            *e1
+        } else {
+            continue;
+        };
+        if !ledge_query.contains(ledge) {
+            continue;
+        }
+        ledges_in_reach.retain(|entity| *entity != ledge);
+        if matches!(collision_event, CollisionEvent::Started(..)) {
+            ledges_in_reach.push(ledge);
+        }
+    }
+
     for (
         player_entity,
-        mut player_transform,
+        player_transform,
         mut player_velocity,
         mut player_gravity,
         ledgegrab,
         walljump,
     ) in &mut player_query
     {
-        for collision_event in collision_events.iter() {
-            match collision_event {
-                CollisionEvent::Started(e1, e2, _) => {
-                    let ledge_detection_status =
-                        if *e1 == sensor_entity && ledge_query.contains(*e2) && ledgegrab.is_none()
-                        {
-                            LedgeDetectionStatus::Hit(*e2)
-                        } else if *e2 == sensor_entity
-                            && ledge_query.contains(*e1)
-                            && ledgegrab.is_none()
-                        {
-                            LedgeDetectionStatus::Hit(*e1)
-                        } else {
-                            LedgeDetectionStatus::NoHit
-                        };
-
-                    if let LedgeDetectionStatus::Hit(ledge) = ledge_detection_status {
-                        let (_, ledge_transform) = ledge_query.get(ledge).unwrap();
-                        let mut ray_pos = player_transform.translation;
-                        ray_pos.y = ledge_transform.translation.y;
-                        let ray_dir =
-                            (ledge_transform.translation - ray_pos.clone()).normalize_or_zero();
-                        println!("Ray Origin: {:?}\nRay Direction: {:?}", ray_pos, ray_dir);
-                        let max_distance = ray_pos.distance(ledge_transform.translation);
-                        let solid = true;
-                        let filter = QueryFilter::new().exclude_collider(player_entity);
+        if ledgegrab.is_some() {
+            continue;
+        }
+        let hands_y = sensor_transform.translation().y;
+        let caught = ledges_in_reach.iter().copied().find(|ledge| {
+            ledge_query
+                .get(*ledge)
+                .map_or(false, |(ledge_transform, ledge_collider)| {
+                    let lip_y =
+                        ledge_transform.translation().y + ledge_half_extents(ledge_collider).y;
+                    can_catch_ledge(hands_y, lip_y, player_velocity.linvel.y)
+                })
+        });
+        let Some(ledge) = caught else {continue;};
 
-                        if let Some((_, intersection)) = rapier_context.cast_ray_and_get_normal(
-                            ray_pos,
-                            ray_dir,
-                            max_distance,
-                            solid,
-                            filter,
-                        ) {
-                            let mut look_target = ray_pos - intersection.normal;
-                            look_target.y = player_transform.translation.y;
-                            player_velocity.linvel = Vec3::ZERO;
-                            player_gravity.0 = 0.0;
-                            player_transform.look_at(look_target, Vec3::Y);
-                            commands
-                                .entity(player_entity)
-                                .insert(LedgeGrab(intersection.normal * -1.0));
+        let (ledge_transform, ledge_collider) = ledge_query.get(ledge).unwrap();
+        let ledge_center = ledge_transform.translation();
+        let mut ray_pos = player_transform.translation;
+        ray_pos.y = ledge_center.y;
+        let ray_dir = (ledge_center - ray_pos.clone()).normalize_or_zero();
+        let max_distance = ray_pos.distance(ledge_center);
+        let solid = true;
+        let filter = QueryFilter::new()
+            .exclude_collider(player_entity)
+            .predicate(&|entity| !room_query.contains(entity));
 
-                            if walljump.is_some() {
-                                commands.entity(player_entity).remove::<Walljump>();
-                            }
-                        }
-                    }
-                }
-                _ => (),
-            }
+        let hit =
+            rapier_context.cast_ray_and_get_normal(ray_pos, ray_dir, max_distance, solid, filter);
+        if let Some(probes) = &mut probes {
+            let toi = hit.map(|(_, intersection)| intersection.toi);
+            probes.ledge = Some(Probe::new(ray_pos, ray_dir, max_distance, toi));
+        }
+        let Some((_, intersection)) = hit else {continue;};
+
+        // Caught, it takes leaving the ledge and reaching it again to catch it after letting go
+        ledges_in_reach.clear();
+        player_velocity.linvel = Vec3::ZERO;
+        player_gravity.0 = 0.0;
+        commands.entity(player_entity).insert(LedgeGrab {
+            direction: intersection.normal * -1.0,
+            hang_position: ledge_hang_position(
+                ledge_transform,
+                ledge_half_extents(ledge_collider),
+                intersection.point,
+                intersection.normal,
+            ),
+        });
+        if let Some(time_scale) = &mut time_scale {
+            time_scale.slow_down(LEDGE_CATCH_TIME_SCALE, LEDGE_CATCH_SLOWDOWN);
+        }
+
+        if walljump.is_some() {
+            commands.entity(player_entity).remove::<Walljump>();
         }
     }
 }
 
+fn ledge_half_extents(collider: &Collider) -> Vec3 {
+    collider
+        .as_cuboid()
+        .map_or(Vec3::ZERO, |cuboid| cuboid.half_extents())
+}
+
 pub fn handle_ledge_grab(
     mut commands: Commands,
     mut query: Query<
@@ -346,35 +693,95 @@ pub fn handle_ledge_grab(
         ),
         With<Player>,
     >,
+    settings: Option<Res<GameSettings>>,
 ) {
+    let auto_climb = settings.map_or(false, |settings| settings.auto_ledge_climb);
     for (entity, mut transform, mut gravity_scale, action, ledgegrab) in &mut query {
         if action.just_pressed(PlayerAction::Grab) {
             println!("Dropping from ledge");
         }
 
-        if action.just_pressed(PlayerAction::Jump) {
+        let climbing = action.just_pressed(PlayerAction::Jump) || auto_climb;
+        if climbing {
             println!("Climbing a ledge");
-            let new_position = transform.translation + (ledgegrab.0 * 1.5) + (Vec3::Y * 1.8);
-            transform.translation = new_position;
+            transform.translation = ledge_climb_position(ledgegrab);
         }
 
-        if action.just_pressed(PlayerAction::Grab) || action.just_pressed(PlayerAction::Jump) {
+        if action.just_pressed(PlayerAction::Grab) || climbing {
             commands.entity(entity).remove::<LedgeGrab>();
             gravity_scale.0 = 1.0;
         }
     }
 }
 
+/// Where the player hangs from a ledge grabbed at `point`, on the face of it with `normal`,
+/// held back from the ledge's ends so they don't dangle off past them
+pub fn ledge_hang_position(
+    ledge_transform: &GlobalTransform,
+    half_extents: Vec3,
+    point: Vec3,
+    normal: Vec3,
+) -> Vec3 {
+    let to_local = ledge_transform.affine().inverse();
+    let mut lip = to_local.transform_point3(point);
+    let local_normal = to_local.transform_vector3(normal);
+    // Only along the face, across it would pull the player into the wall
+    if local_normal.x.abs() < 0.5 {
+        let limit = (half_extents.x - LEDGE_HANG_MARGIN).max(0.0);
+        lip.x = lip.x.clamp(-limit, limit);
+    }
+    if local_normal.z.abs() < 0.5 {
+        let limit = (half_extents.z - LEDGE_HANG_MARGIN).max(0.0);
+        lip.z = lip.z.clamp(-limit, limit);
+    }
+    lip.y = half_extents.y;
+
+    let out = Vec3::new(normal.x, 0.0, normal.z).normalize_or_zero();
+    ledge_transform.transform_point(lip) + out * LEDGE_HANG_DISTANCE - Vec3::Y * LEDGE_HANG_DROP
+}
+
+/// Where climbing up from a hang leaves the player, feet on top of the ledge
+pub fn ledge_climb_position(ledgegrab: &LedgeGrab) -> Vec3 {
+    ledgegrab.hang_position
+        + ledgegrab.direction * LEDGE_CLIMB_DISTANCE
+        + Vec3::Y * (LEDGE_HANG_DROP + PLAYER_HALF_HEIGHT)
+}
+
+/// Eases a freshly grabbed player into the hang, facing the wall
+pub fn align_to_ledge(
+    time: Res<Time>,
+    mut query: Query<(&mut Transform, &LedgeGrab), With<Player>>,
+) {
+    let t = (LEDGE_ALIGN_RATE * time.delta_seconds()).min(1.0);
+    for (mut transform, ledgegrab) in &mut query {
+        let facing = ledgegrab.facing();
+        if transform.translation == ledgegrab.hang_position && transform.rotation == facing {
+            continue;
+        }
+        if transform.translation.distance(ledgegrab.hang_position) < LEDGE_ALIGN_SNAP {
+            transform.translation = ledgegrab.hang_position;
+            transform.rotation = facing;
+        } else {
+            transform.translation = transform.translation.lerp(ledgegrab.hang_position, t);
+            transform.rotation = transform.rotation.slerp(facing, t);
+        }
+    }
+}
+
 pub fn add_friction_when_landing(
-    mut player_query: Query<&mut Friction, (With<Player>, Added<Grounded>)>,
+    mut player_query: Query<
+        (&mut Friction, &GroundSurface),
+        (With<Player>, Or<(Added<Grounded>, Changed<GroundSurface>)>),
+    >,
 ) {
-    for mut friction in &mut player_query {
-        friction.coefficient = 1.0;
+    for (mut friction, ground_surface) in &mut player_query {
+        friction.coefficient = ground_surface.0.friction();
     }
 }
 
 pub fn handle_long_jump(
     mut player_speed: ResMut<PlayerSpeed>,
+    mut jump_events: EventWriter<Jumped>,
     mut player_query: Query<
         (&mut Momentum, &mut Velocity, &ActionState<PlayerAction>),
         (With<Player>, With<Grounded>, Without<Crouching>),
@@ -386,7 +793,53 @@ pub fn handle_long_jump(
                 player_speed.set(20.0);
                 velocity.linvel.y = 10.0;
                 momentum.set(20.0);
+                jump_events.send(Jumped::Long);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ledges_are_caught_at_the_lip_near_the_apex() {
+        assert!(can_catch_ledge(4.0, 4.2, -3.0));
+        assert!(can_catch_ledge(4.0, 4.2, 1.0));
+        assert!(!can_catch_ledge(4.0, 4.2, 8.0));
+        assert!(!can_catch_ledge(3.5, 4.2, 0.0));
+    }
+
+    #[test]
+    fn hang_position_stays_on_the_ledge() {
+        let ledge = GlobalTransform::from(Transform::from_xyz(0.0, 4.0, 0.0));
+        let half_extents = Vec3::new(2.0, 0.25, 1.0);
+
+        let middle = ledge_hang_position(&ledge, half_extents, Vec3::new(0.5, 4.0, 1.0), Vec3::Z);
+        assert!(middle.distance(Vec3::new(0.5, 3.25, 1.55)) < 0.001);
+
+        let past_end = ledge_hang_position(&ledge, half_extents, Vec3::new(5.0, 4.0, 1.0), Vec3::Z);
+        assert!((past_end.x - (2.0 - LEDGE_HANG_MARGIN)).abs() < 0.001);
+        assert!((past_end.z - 1.55).abs() < 0.001);
+    }
+
+    #[test]
+    fn climbing_up_stands_the_player_on_the_ledge() {
+        let ledge = GlobalTransform::from(Transform::from_xyz(0.0, 4.0, 0.0));
+        let half_extents = Vec3::new(2.0, 0.25, 1.0);
+        let ledgegrab = LedgeGrab {
+            direction: Vec3::NEG_Z,
+            hang_position: ledge_hang_position(
+                &ledge,
+                half_extents,
+                Vec3::new(0.5, 4.0, 1.0),
+                Vec3::Z,
+            ),
+        };
+
+        let feet = ledge_climb_position(&ledgegrab) - Vec3::Y * PLAYER_HALF_HEIGHT;
+        assert!((feet.y - 4.25).abs() < 0.001);
+        assert!(feet.z < 1.0);
+    }
+}
@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{GameConfig, Player};
+
+const KINEMATIC_OFFSET: f32 = 0.05;
+const KINEMATIC_SNAP_TO_GROUND: f32 = 0.2;
+const MAX_SLOPE_CLIMB_ANGLE: f32 = 45.0;
+const MIN_SLOPE_SLIDE_ANGLE: f32 = 30.0;
+const BLOCKED_EPSILON: f32 = 0.001;
+
+/// Which of Rapier's bodies moves the player, set with `movement_backend` in the game config
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MovementBackend {
+    /// A dynamic body whose velocity is overwritten every frame
+    #[default]
+    Dynamic,
+    /// A kinematic body moved by Rapier's `KinematicCharacterController`, the same `Velocity`
+    /// is integrated by hand so every movement system works unchanged
+    Kinematic,
+}
+
+/// Swaps the player's body whenever the configured backend changes, so both can be compared
+/// by editing the config while playing
+pub fn sync_movement_backend(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    query: Query<(Entity, &RigidBody), With<Player>>,
+) {
+    for (entity, body) in &query {
+        match (game_config.movement_backend, body) {
+            (MovementBackend::Kinematic, RigidBody::Dynamic) => {
+                commands
+                    .entity(entity)
+                    .insert(RigidBody::KinematicPositionBased)
+                    .insert(KinematicCharacterController {
+                        offset: CharacterLength::Absolute(KINEMATIC_OFFSET),
+                        max_slope_climb_angle: MAX_SLOPE_CLIMB_ANGLE.to_radians(),
+                        min_slope_slide_angle: MIN_SLOPE_SLIDE_ANGLE.to_radians(),
+                        snap_to_ground: Some(CharacterLength::Absolute(KINEMATIC_SNAP_TO_GROUND)),
+                        filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                        ..default()
+                    });
+            }
+            (MovementBackend::Dynamic, RigidBody::KinematicPositionBased) => {
+                commands
+                    .entity(entity)
+                    .insert(RigidBody::Dynamic)
+                    .remove::<KinematicCharacterController>()
+                    .remove::<KinematicCharacterControllerOutput>();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Applies gravity to the player's velocity and hands this frame's movement to the controller
+pub fn drive_kinematic_controller(
+    time: Res<Time>,
+    rapier_config: Option<Res<RapierConfiguration>>,
+    mut query: Query<
+        (
+            &mut Velocity,
+            &mut KinematicCharacterController,
+            Option<&GravityScale>,
+        ),
+        With<Player>,
+    >,
+) {
+    let gravity = rapier_config.map_or(Vec3::ZERO, |config| config.gravity);
+    let delta = time.delta_seconds();
+    for (mut velocity, mut controller, gravity_scale) in &mut query {
+        let scale = gravity_scale.map_or(1.0, |gravity_scale| gravity_scale.0);
+        velocity.linvel += gravity * scale * delta;
+        controller.translation = Some(velocity.linvel * delta);
+    }
+}
+
+/// Takes away whatever velocity the controller couldn't use, the way the solver would
+pub fn settle_kinematic_velocity(
+    time: Res<Time>,
+    mut query: Query<(&mut Velocity, &KinematicCharacterControllerOutput), With<Player>>,
+) {
+    let delta = time.delta_seconds();
+    if delta <= 0.0 {
+        return;
+    }
+    for (mut velocity, output) in &mut query {
+        velocity.linvel = settle_velocity(
+            velocity.linvel,
+            output.desired_translation,
+            output.effective_translation,
+            output.grounded,
+            delta,
+        );
+    }
+}
+
+/// Clamps each axis that was blocked down to the distance actually covered, and stops falling
+/// once grounded
+pub fn settle_velocity(
+    velocity: Vec3,
+    desired: Vec3,
+    effective: Vec3,
+    grounded: bool,
+    delta: f32,
+) -> Vec3 {
+    let blocked = |desired: f32, effective: f32| {
+        desired.abs() > BLOCKED_EPSILON && effective.abs() < desired.abs() - BLOCKED_EPSILON
+    };
+    let mut settled = velocity;
+    if blocked(desired.x, effective.x) {
+        settled.x = effective.x / delta;
+    }
+    if blocked(desired.z, effective.z) {
+        settled.z = effective.z / delta;
+    }
+    if desired.y > 0.0 && blocked(desired.y, effective.y) {
+        settled.y = 0.0;
+    }
+    if grounded && settled.y < 0.0 {
+        settled.y = 0.0;
+    }
+    settled
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn free_movement_keeps_velocity() {
+        let velocity = Vec3::new(3.0, -2.0, 1.0);
+        let step = velocity * 0.1;
+        assert_eq!(settle_velocity(velocity, step, step, false, 0.1), velocity);
+    }
+
+    #[test]
+    fn walls_and_ceilings_take_blocked_velocity() {
+        let velocity = Vec3::new(10.0, 5.0, 4.0);
+        let desired = velocity * 0.1;
+        let effective = Vec3::new(0.5, 0.0, 0.4);
+        let settled = settle_velocity(velocity, desired, effective, false, 0.1);
+        assert!((settled - Vec3::new(5.0, 0.0, 4.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn landing_stops_the_fall() {
+        let velocity = Vec3::new(0.0, -12.0, 0.0);
+        let settled = settle_velocity(velocity, velocity * 0.1, Vec3::ZERO, true, 0.1);
+        assert_eq!(settled, Vec3::ZERO);
+    }
+}
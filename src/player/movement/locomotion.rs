@@ -1,17 +1,29 @@
 use crate::{
-    DebugBall, Drift, Grounded, Landing, LedgeGrab, MainCamera, Momentum, Movement, OutsideForce,
-    Player, PlayerAction,
+    spawn_dust, Busy, Climbing, CostumeModifiers, DebugBall, Drift, EntityPools, ExternalForces,
+    GameConfig, Grinding, GroundSurface, Grounded, KnockedDown, Landing, LedgeGrab, LookingAround,
+    MainCamera, Momentum, Movement, ParticleAssets, Player, PlayerAction, Riding, Skidding,
+    Stamina, SurfaceType, Swinging, Ziplining,
 };
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
 
-const PLAYER_ROTATION_SPEED: f32 = 10.0;
+// Turn rates are in radians per second, the player turns slower the more momentum they carry
+const PLAYER_MAX_TURN_RATE: f32 = 4.0 * std::f32::consts::PI;
+const PLAYER_MIN_TURN_RATE: f32 = 1.25 * std::f32::consts::PI;
+const PLAYER_MOMENTUM_FOR_MIN_TURN_RATE: f32 = 20.0;
+const SKID_MIN_MOMENTUM: f32 = 8.0;
+const SKID_MIN_ANGLE: f32 = 2.0 * std::f32::consts::FRAC_PI_3;
+const SKID_DECELERATION: f32 = 60.0;
+const SKID_FRICTION: f32 = 3.0;
+const ICE_STEERING: f32 = 2.0;
+const ICE_DECELERATION: f32 = 4.0;
 
 #[derive(Component)]
 pub struct Crouching;
 
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct PlayerSpeed {
     accel_timer: Timer,
     decel_timer: Timer,
@@ -62,6 +74,14 @@ impl PlayerSpeed {
         self.top_speed = speed;
         self.current_speed = speed;
     }
+
+    pub fn set_top_speed(&mut self, speed: f32) {
+        self.top_speed = speed;
+    }
+
+    pub fn reset_top_speed(&mut self) {
+        self.top_speed = self.base_top_speed;
+    }
 }
 
 impl Default for PlayerSpeed {
@@ -82,19 +102,27 @@ impl Default for PlayerSpeed {
 
 pub fn set_player_direction(
     mut player_query: Query<
-        (&mut Movement, Option<&Grounded>, &ActionState<PlayerAction>),
-        With<Player>,
+        (
+            &mut Movement,
+            Option<&Grounded>,
+            Option<&Busy>,
+            &ActionState<PlayerAction>,
+        ),
+        (
+            With<Player>,
+            Without<KnockedDown>,
+            Without<LookingAround>,
+            Without<Riding>,
+        ),
     >,
     camera_query: Query<&Transform, With<MainCamera>>,
 ) {
-    let camera_transform = camera_query.single();
-    for (mut movement, grounded, action) in &mut player_query {
-        if grounded.is_some() {
+    let Ok(camera_transform) = camera_query.get_single() else {return;};
+    for (mut movement, grounded, busy, action) in &mut player_query {
+        if grounded.is_some() && busy.is_none() {
             movement.0 = get_direction_in_camera_space(camera_transform, action);
-        } else {
-            if movement.is_moving() {
-                movement.0 = Vec3::ZERO;
-            }
+        } else if movement.is_moving() {
+            movement.0 = Vec3::ZERO;
         }
     }
 }
@@ -108,6 +136,11 @@ pub fn get_direction_in_camera_space(
 
     let mut forward = camera_transform.forward();
     forward.y = 0.0;
+    // A camera looking straight down has no forward along the ground, up the screen stands in
+    if forward.length_squared() < 0.001 {
+        forward = camera_transform.up();
+        forward.y = 0.0;
+    }
     forward = forward.normalize();
 
     let mut right = camera_transform.right();
@@ -142,27 +175,161 @@ pub fn get_direction_in_camera_space(
     (right_vec + forward_vec).normalize_or_zero()
 }
 
+pub fn get_turn_rate(momentum: f32) -> f32 {
+    let speed_ratio = (momentum / PLAYER_MOMENTUM_FOR_MIN_TURN_RATE).clamp(0.0, 1.0);
+    PLAYER_MAX_TURN_RATE + (PLAYER_MIN_TURN_RATE - PLAYER_MAX_TURN_RATE) * speed_ratio
+}
+
 pub fn rotate_to_direction(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &Movement, Option<&Landing>), (With<Player>, With<Grounded>)>,
+    mut query: Query<
+        (&mut Transform, &Movement, &Momentum, Option<&Landing>),
+        (
+            With<Player>,
+            With<Grounded>,
+            Without<Skidding>,
+            Without<KnockedDown>,
+            Without<Climbing>,
+        ),
+    >,
     mut rotation_target: Local<Transform>,
 ) {
-    for (mut transform, direction, is_landing) in &mut query {
+    for (mut transform, direction, momentum, is_landing) in &mut query {
         rotation_target.translation = transform.translation;
         let flat_velo_direction = Vec3::new(direction.0.x, 0.0, direction.0.z).normalize_or_zero();
         if flat_velo_direction != Vec3::ZERO {
             let target_position = rotation_target.translation + flat_velo_direction;
 
             rotation_target.look_at(target_position, Vec3::Y);
-            let turn_speed = if is_landing.is_some() {
-                PLAYER_ROTATION_SPEED * 2.0
+            let turn_rate = if is_landing.is_some() {
+                get_turn_rate(momentum.get()) * 2.0
             } else {
-                PLAYER_ROTATION_SPEED
+                get_turn_rate(momentum.get())
             };
 
-            transform.rotation = transform
-                .rotation
-                .slerp(rotation_target.rotation, time.delta_seconds() * turn_speed);
+            let remaining_angle = transform.rotation.angle_between(rotation_target.rotation);
+            if remaining_angle > f32::EPSILON {
+                let max_step = turn_rate * time.delta_seconds();
+                transform.rotation = transform.rotation.slerp(
+                    rotation_target.rotation,
+                    (max_step / remaining_angle).min(1.0),
+                );
+            }
+        }
+    }
+}
+
+pub fn handle_sprinting(
+    time: Res<Time>,
+    game_config: Res<GameConfig>,
+    mut player_speed: ResMut<PlayerSpeed>,
+    mut query: Query<
+        (
+            &mut Stamina,
+            &Movement,
+            &ActionState<PlayerAction>,
+            Option<&Grounded>,
+        ),
+        With<Player>,
+    >,
+) {
+    let sprint = &game_config.sprint;
+    for (mut stamina, movement, action, grounded) in &mut query {
+        stamina.set_max(sprint.max_stamina);
+
+        let wants_to_sprint = action.pressed(PlayerAction::Sprint)
+            && !action.pressed(PlayerAction::Crouch)
+            && movement.is_moving()
+            && grounded.is_some();
+
+        if wants_to_sprint && !stamina.is_exhausted() {
+            stamina.start_sprinting();
+            player_speed.set_top_speed(sprint.top_speed);
+            if sprint.use_stamina {
+                stamina.drain(
+                    sprint.drain_per_second * time.delta_seconds(),
+                    sprint.exhaustion_cooldown,
+                );
+            }
+        } else if stamina.is_sprinting() {
+            stamina.stop_sprinting();
+            player_speed.reset_top_speed();
+        }
+
+        if !stamina.is_sprinting() {
+            stamina.recover(time.delta(), sprint.regen_per_second * time.delta_seconds());
+        }
+    }
+}
+
+pub fn start_skidding(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &Transform, &Movement, &Momentum, &mut Friction),
+        (With<Player>, With<Grounded>, Without<Skidding>),
+    >,
+) {
+    for (entity, transform, movement, momentum, mut friction) in &mut query {
+        if movement.is_moving() && momentum.get() >= SKID_MIN_MOMENTUM {
+            let mut facing = transform.forward();
+            facing.y = 0.0;
+            if facing.angle_between(movement.0) >= SKID_MIN_ANGLE {
+                friction.coefficient = SKID_FRICTION;
+                commands.entity(entity).insert(Skidding::new());
+            }
+        }
+    }
+}
+
+pub fn handle_skidding(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pools: ResMut<EntityPools>,
+    particle_assets: Res<ParticleAssets>,
+    mut player_speed: ResMut<PlayerSpeed>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Momentum,
+            &mut Friction,
+            &mut Skidding,
+            &Movement,
+            Option<&GroundSurface>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (
+        entity,
+        mut transform,
+        mut momentum,
+        mut friction,
+        mut skidding,
+        movement,
+        ground_surface,
+    ) in &mut query
+    {
+        skidding.tick(time.delta());
+        momentum.set((momentum.get() - SKID_DECELERATION * time.delta_seconds()).max(0.0));
+
+        if skidding.should_spawn_dust() {
+            let feet = transform.translation - Vec3::Y;
+            let kick = transform.forward() * momentum.get() * 0.25 + Vec3::Y;
+            spawn_dust(&mut commands, &mut pools, &particle_assets, feet, kick);
+        }
+
+        if skidding.finished() {
+            if movement.is_moving() {
+                let position = transform.translation;
+                transform.look_at(position + movement.0, Vec3::Y);
+            }
+            momentum.reset();
+            player_speed.reset();
+            friction.coefficient = ground_surface
+                .map(|ground| ground.0.friction())
+                .unwrap_or(1.0);
+            commands.entity(entity).remove::<Skidding>();
         }
     }
 }
@@ -171,18 +338,34 @@ pub fn handle_player_speed(
     time: Res<Time>,
     mut player_speed: ResMut<PlayerSpeed>,
     mut query: Query<
-        (&mut Momentum, &Movement, &ActionState<PlayerAction>),
-        (With<Player>, With<Grounded>, Without<Crouching>),
+        (
+            &mut Momentum,
+            &Movement,
+            &ActionState<PlayerAction>,
+            Option<&GroundSurface>,
+            Option<&CostumeModifiers>,
+        ),
+        (
+            With<Player>,
+            With<Grounded>,
+            Without<Crouching>,
+            Without<Skidding>,
+        ),
     >,
 ) {
-    for (mut momentum, movement, action) in &mut query {
+    for (mut momentum, movement, action, ground_surface, costume) in &mut query {
+        let surface = ground_surface.map(|ground| ground.0).unwrap_or_default();
+        let costume_speed = costume.map_or(1.0, |costume| costume.top_speed);
         if movement.is_moving() {
             if action.pressed(PlayerAction::Crouch) {
                 player_speed.decelerate(time.delta(), time.delta_seconds());
             } else {
-                player_speed.accelerate(time.delta(), time.delta_seconds());
+                player_speed.accelerate(
+                    time.delta(),
+                    time.delta_seconds() * surface.acceleration_multiplier(),
+                );
             }
-            momentum.set(player_speed.current_speed);
+            momentum.set(player_speed.current_speed * surface.speed_multiplier() * costume_speed);
         } else {
             momentum.reset();
             player_speed.reset();
@@ -190,26 +373,99 @@ pub fn handle_player_speed(
     }
 }
 
+/// Sent every time the player's foot hits the ground while running, for audio and particles to
+/// react to
+pub struct Footstep {
+    pub surface: SurfaceType,
+    pub position: Vec3,
+}
+
+pub fn emit_footsteps(
+    time: Res<Time>,
+    mut footsteps: EventWriter<Footstep>,
+    query: Query<(&Transform, &Momentum, Option<&GroundSurface>), (With<Player>, With<Grounded>)>,
+    mut since_last_step: Local<f32>,
+) {
+    for (transform, momentum, ground_surface) in &query {
+        if !momentum.has_momentum() {
+            *since_last_step = 0.0;
+            continue;
+        }
+
+        let surface = ground_surface.map(|ground| ground.0).unwrap_or_default();
+        *since_last_step += time.delta_seconds();
+        if *since_last_step >= surface.footstep_interval() {
+            *since_last_step = 0.0;
+            footsteps.send(Footstep {
+                surface,
+                position: transform.translation - Vec3::Y,
+            });
+        }
+    }
+}
+
+/// Blends the horizontal part of a velocity toward a target, leaving vertical velocity untouched
+pub fn steer_horizontal_velocity(current: Vec3, target: Vec3, blend: f32) -> Vec3 {
+    let blend = blend.clamp(0.0, 1.0);
+    Vec3::new(
+        current.x + (target.x - current.x) * blend,
+        current.y,
+        current.z + (target.z - current.z) * blend,
+    )
+}
+
+/// Slows the horizontal part of a velocity by a fixed amount without reversing it
+pub fn decelerate_horizontal_velocity(current: Vec3, amount: f32) -> Vec3 {
+    let horizontal = Vec3::new(current.x, 0.0, current.z);
+    let speed = horizontal.length();
+    if speed <= amount {
+        Vec3::new(0.0, current.y, 0.0)
+    } else {
+        let slowed = horizontal * ((speed - amount) / speed);
+        Vec3::new(slowed.x, current.y, slowed.z)
+    }
+}
+
 pub fn apply_momentum(
+    time: Res<Time>,
     mut query: Query<
         (
             &mut Velocity,
             &Transform,
             &Momentum,
             &Drift,
-            Option<&OutsideForce>,
+            Option<&ExternalForces>,
+            Option<&GroundSurface>,
+            Option<&Grounded>,
+        ),
+        (
+            Without<LedgeGrab>,
+            Without<Grinding>,
+            Without<Swinging>,
+            Without<Ziplining>,
+            Without<Climbing>,
+            Without<Riding>,
+            Without<KnockedDown>,
         ),
-        Without<LedgeGrab>,
     >,
 ) {
-    for (mut velocity, transform, momentum, drift, has_force) in &mut query {
+    for (mut velocity, transform, momentum, drift, external_forces, ground_surface, grounded) in
+        &mut query
+    {
+        let on_ice = grounded.is_some()
+            && ground_surface.map_or(false, |ground| ground.0 == SurfaceType::Ice);
         let mut speed_to_apply = Vec3::ZERO;
         let mut should_change_velocity: bool = false;
 
-        if let Some(outside_force) = has_force {
+        if let Some(forces) = external_forces.filter(|forces| !forces.is_empty()) {
+            let outside_force = forces.total();
             should_change_velocity = true;
-            speed_to_apply.x += outside_force.0.x;
-            speed_to_apply.z += outside_force.0.z;
+            speed_to_apply.x += outside_force.x;
+            speed_to_apply.z += outside_force.z;
+            // Updrafts carry the player up at their own speed rather than fighting gravity
+            if outside_force.y > 0.0 {
+                velocity.linvel.y = velocity.linvel.y.max(outside_force.y);
+            }
         }
 
         if momentum.has_momentum() {
@@ -223,9 +479,56 @@ pub fn apply_momentum(
             speed_to_apply += drift.0;
         }
 
-        if should_change_velocity {
+        if on_ice {
+            velocity.linvel = if should_change_velocity {
+                steer_horizontal_velocity(
+                    velocity.linvel,
+                    speed_to_apply,
+                    ICE_STEERING * time.delta_seconds(),
+                )
+            } else {
+                decelerate_horizontal_velocity(
+                    velocity.linvel,
+                    ICE_DECELERATION * time.delta_seconds(),
+                )
+            };
+        } else if should_change_velocity {
             velocity.linvel.x = speed_to_apply.x;
             velocity.linvel.z = speed_to_apply.z;
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn steering_blends_horizontal_velocity() {
+        let steered = steer_horizontal_velocity(Vec3::new(10.0, 5.0, 0.0), Vec3::Z * 10.0, 0.25);
+        assert_eq!(steered, Vec3::new(7.5, 5.0, 2.5));
+    }
+
+    #[test]
+    fn steering_clamps_blend() {
+        let current = Vec3::new(4.0, -2.0, 4.0);
+        let target = Vec3::new(-4.0, 0.0, 8.0);
+        assert_eq!(
+            steer_horizontal_velocity(current, target, 3.0),
+            Vec3::new(-4.0, -2.0, 8.0)
+        );
+        assert_eq!(steer_horizontal_velocity(current, target, -1.0), current);
+    }
+
+    #[test]
+    fn deceleration_keeps_direction() {
+        let slowed = decelerate_horizontal_velocity(Vec3::new(3.0, 1.0, 4.0), 2.5);
+        assert!((slowed - Vec3::new(1.5, 1.0, 2.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn deceleration_stops_without_reversing() {
+        let stopped = decelerate_horizontal_velocity(Vec3::new(1.0, -3.0, 0.0), 2.0);
+        assert_eq!(stopped, Vec3::new(0.0, -3.0, 0.0));
+    }
+}
@@ -1,7 +1,10 @@
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::PhysicsSet;
 use paste::paste;
 use std::time::Duration;
 
+use crate::Momentum;
+
 pub mod components;
 pub use components::*;
 
@@ -11,6 +14,12 @@ pub use locomotion::*;
 pub mod jumping;
 pub use jumping::*;
 
+pub mod kinematic;
+pub use kinematic::*;
+
+pub mod step_up;
+pub use step_up::*;
+
 pub struct PlayerMovementPlugin;
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
@@ -22,26 +31,68 @@ enum PlayerPhysicsSet {
 
 impl Plugin for PlayerMovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(apply_momentum.in_set(PlayerPhysicsSet::ApplyForces))
+        app.register_type::<Jump>()
+            .register_type::<JumpStage>()
+            .register_type::<Grounded>()
+            .register_type::<Momentum>()
+            .register_type::<Drift>()
+            .register_type::<PlayerSpeed>()
+            .add_event::<Footstep>()
+            .add_event::<CeilingBonk>()
+            .add_event::<Jumped>()
+            .add_system(apply_momentum.in_set(PlayerPhysicsSet::ApplyForces))
+            .add_system(emit_footsteps)
+            .add_systems((sync_movement_backend, sync_autostep).chain())
+            .add_systems(
+                (detect_steps, handle_stepping_up)
+                    .chain()
+                    .in_set(PlayerPhysicsSet::ApplyForces),
+            )
+            .add_system(
+                drive_kinematic_controller
+                    .in_base_set(CoreSet::PostUpdate)
+                    .before(PhysicsSet::SyncBackend),
+            )
+            .add_system(
+                settle_kinematic_velocity
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(PhysicsSet::Writeback),
+            )
             .add_system(handle_self_removing_components.in_set(PlayerPhysicsSet::Cleanup))
             .add_systems(
                 (
                     set_player_direction,
+                    start_skidding,
+                    handle_skidding,
+                    handle_sprinting,
                     handle_player_speed,
                     rotate_to_direction,
                 )
                     .chain()
                     .in_set(PlayerPhysicsSet::SetForces),
             )
-            .add_systems((buffer_jump, handle_jumping).chain())
+            .add_systems(
+                (
+                    buffer_jump,
+                    handle_side_somersault,
+                    handle_jumping,
+                    detect_ceilings,
+                )
+                    .chain(),
+            )
             .add_systems(
                 (
                     handle_grounded,
                     detect_walls,
                     detect_ledges,
+                    handle_wall_sliding.after(detect_walls),
                     handle_wall_jumping,
                     aerial_drift,
+                    apply_fall_modifier,
                     handle_ledge_grab,
+                    align_to_ledge
+                        .after(detect_ledges)
+                        .before(handle_ledge_grab),
                     reset_jumps_after_landing,
                     add_friction_when_landing,
                     handle_jump_buffer,
@@ -52,6 +103,11 @@ impl Plugin for PlayerMovementPlugin {
     }
 }
 
+/// Distance from the player's origin up to their eyes
+pub const PLAYER_HEAD_HEIGHT: f32 = 1.0;
+/// Half the height of the player's capsule, their feet are this far below their origin
+pub const PLAYER_HALF_HEIGHT: f32 = 1.0;
+
 #[derive(Component)]
 pub struct Player;
 
@@ -63,6 +119,11 @@ impl Busy {
         Busy(Timer::from_seconds(seconds, TimerMode::Once))
     }
 
+    /// Lasts until whatever added it takes it off again
+    pub fn indefinitely() -> Self {
+        Busy(Timer::new(Duration::MAX, TimerMode::Once))
+    }
+
     pub fn tick(&mut self, duration: Duration) {
         self.0.tick(duration);
     }
@@ -77,7 +138,15 @@ pub struct Landing(Timer);
 
 impl Landing {
     pub fn new() -> Self {
-        Landing(Timer::from_seconds(0.15, TimerMode::Once))
+        Landing::lasting(0.15)
+    }
+
+    pub fn lasting(seconds: f32) -> Self {
+        Landing(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.0.remaining_secs()
     }
 
     pub fn tick(&mut self, duration: Duration) {
@@ -133,3 +202,60 @@ pub fn handle_self_removing_components(
 ) {
     SelfRemoving!(time, commands, for Busy, busy_query, Landing, landing_query, Coyote, coyote_query);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        CameraControlPlugin, Drift, EntityPools, GameConfig, Momentum, Movement, ParticleAssets,
+        PlayerGrabbingPlugin, PlayerSpeed,
+    };
+    use bevy_rapier3d::prelude::*;
+
+    fn app_without_world() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<CollisionEvent>()
+            .insert_resource(RapierContext::default())
+            .insert_resource(PlayerSpeed::default())
+            .insert_resource(GameConfig::default())
+            .insert_resource(ParticleAssets::default())
+            .insert_resource(EntityPools::default())
+            .add_plugin(PlayerMovementPlugin)
+            .add_plugin(PlayerGrabbingPlugin);
+        app
+    }
+
+    #[test]
+    fn systems_run_in_menu_without_player_or_camera() {
+        let mut app = app_without_world();
+        app.update();
+        app.update();
+    }
+
+    #[test]
+    fn systems_run_after_player_dies() {
+        let mut app = app_without_world();
+        app.add_plugin(CameraControlPlugin);
+
+        let player = app
+            .world
+            .spawn((
+                Player,
+                TransformBundle::default(),
+                Velocity::default(),
+                Movement::default(),
+                Momentum::default(),
+                Drift::default(),
+                Jump::default(),
+                GravityScale(1.0),
+                Friction::default(),
+            ))
+            .id();
+        app.update();
+
+        app.world.despawn(player);
+        app.update();
+        app.update();
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{GameConfig, Grounded, KnockedDown, LedgeGrab, Player, PLAYER_HALF_HEIGHT};
+
+const STEP_PROBE_RADIUS: f32 = 0.2;
+/// How far ahead of the capsule's center a step is looked for
+const STEP_PROBE_DISTANCE: f32 = 0.7;
+const STEP_MIN_HEIGHT: f32 = 0.05;
+const STEP_MIN_SPEED: f32 = 0.5;
+/// Room the player needs above a step to stand on it
+const STEP_HEADROOM: f32 = 2.0;
+
+/// Present while the player is being lifted onto a step, `target` is their origin's final height
+#[derive(Component)]
+pub struct SteppingUp {
+    pub target: f32,
+}
+
+/// Whether a step `height` above the feet is tall enough to need lifting and low enough to allow it
+pub fn can_step_up(height: f32, max_height: f32) -> bool {
+    height > STEP_MIN_HEIGHT && height <= max_height
+}
+
+pub fn detect_steps(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    rapier_context: Res<RapierContext>,
+    query: Query<
+        (Entity, &Transform, &Velocity),
+        (
+            With<Player>,
+            With<Grounded>,
+            Without<SteppingUp>,
+            Without<LedgeGrab>,
+            Without<KnockedDown>,
+            Without<KinematicCharacterController>,
+        ),
+    >,
+) {
+    let max_height = game_config.step_up.max_height;
+    for (entity, transform, velocity) in &query {
+        let horizontal = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+        if horizontal.length() < STEP_MIN_SPEED {
+            continue;
+        }
+        let direction = horizontal.normalize();
+        let feet = transform.translation.y - PLAYER_HALF_HEIGHT;
+        let filter = QueryFilter::exclude_dynamic()
+            .exclude_sensors()
+            .exclude_collider(entity);
+
+        // Something at ankle height in the way
+        let probe = Vec3::new(
+            transform.translation.x,
+            feet + STEP_PROBE_RADIUS + STEP_MIN_HEIGHT,
+            transform.translation.z,
+        );
+        let hit = rapier_context.cast_shape(
+            probe,
+            Quat::IDENTITY,
+            direction,
+            &Collider::ball(STEP_PROBE_RADIUS),
+            STEP_PROBE_DISTANCE,
+            filter,
+        );
+        let Some((_, hit)) = hit else {continue;};
+
+        // Look down onto it from just above the highest step allowed
+        let over_step = probe + direction * (hit.toi + STEP_PROBE_RADIUS * 2.0);
+        let ray_start = Vec3::new(
+            over_step.x,
+            feet + max_height + STEP_MIN_HEIGHT,
+            over_step.z,
+        );
+        let ground = rapier_context.cast_ray(
+            ray_start,
+            Vec3::NEG_Y,
+            max_height + STEP_MIN_HEIGHT,
+            true,
+            filter,
+        );
+        let Some((_, toi)) = ground else {continue;};
+        let height = ray_start.y - toi - feet;
+        if !can_step_up(height, max_height) {
+            continue;
+        }
+
+        let blocked_above = rapier_context
+            .cast_ray(ray_start, Vec3::Y, STEP_HEADROOM, true, filter)
+            .is_some();
+        if !blocked_above {
+            commands.entity(entity).insert(SteppingUp {
+                target: transform.translation.y + height,
+            });
+        }
+    }
+}
+
+/// Lifts the player onto the step over a few frames instead of snapping them up
+pub fn handle_stepping_up(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_config: Res<GameConfig>,
+    mut query: Query<(Entity, &mut Transform, &mut Velocity, &SteppingUp), With<Player>>,
+) {
+    for (entity, mut transform, mut velocity, stepping_up) in &mut query {
+        let lift = game_config.step_up.speed * time.delta_seconds();
+        let remaining = stepping_up.target - transform.translation.y;
+        velocity.linvel.y = velocity.linvel.y.max(0.0);
+        if remaining <= lift {
+            transform.translation.y = stepping_up.target;
+            commands.entity(entity).remove::<SteppingUp>();
+        } else {
+            transform.translation.y += lift;
+        }
+    }
+}
+
+/// The kinematic backend steps up on its own, this keeps its limits in line with the config
+pub fn sync_autostep(
+    game_config: Res<GameConfig>,
+    mut query: Query<&mut KinematicCharacterController, With<Player>>,
+) {
+    for mut controller in &mut query {
+        if game_config.is_changed() || controller.is_added() {
+            controller.autostep = Some(CharacterAutostep {
+                max_height: CharacterLength::Absolute(game_config.step_up.max_height),
+                min_width: CharacterLength::Absolute(STEP_PROBE_RADIUS),
+                include_dynamic_bodies: false,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_low_steps_are_climbed() {
+        assert!(can_step_up(0.3, 0.4));
+        assert!(can_step_up(0.4, 0.4));
+        assert!(!can_step_up(0.5, 0.4));
+        assert!(!can_step_up(0.01, 0.4));
+    }
+}
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    Climbing, Drift, HardLanding, KnockedDown, LadderTransition, LedgeGrab, Momentum, Player,
+    PlayerSpeed, Riding, Sinking, Skidding, Swinging, WallSliding, Walljump, Ziplining,
+};
+
+pub struct PlayerRespawnPlugin;
+
+impl Plugin for PlayerRespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RespawnPlayer>()
+            .insert_resource(RespawnPoint::default())
+            .add_system(respawn_player);
+    }
+}
+
+/// Where the player is placed after falling out of the level or being defeated
+#[derive(Resource)]
+pub struct RespawnPoint(pub Vec3);
+
+impl Default for RespawnPoint {
+    fn default() -> Self {
+        RespawnPoint(Vec3::new(-1.0, 2.0, 0.0))
+    }
+}
+
+/// Send to put the player back at the current `RespawnPoint` with all movement state cleared
+pub struct RespawnPlayer;
+
+pub fn respawn_player(
+    mut commands: Commands,
+    mut respawn_events: EventReader<RespawnPlayer>,
+    respawn_point: Res<RespawnPoint>,
+    mut player_speed: ResMut<PlayerSpeed>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut Momentum,
+            &mut Drift,
+            &mut GravityScale,
+        ),
+        With<Player>,
+    >,
+) {
+    if respawn_events.iter().count() == 0 {
+        return;
+    }
+
+    for (entity, mut transform, mut velocity, mut momentum, mut drift, mut gravity_scale) in
+        &mut query
+    {
+        transform.translation = respawn_point.0;
+        transform.rotation = Quat::IDENTITY;
+        velocity.linvel = Vec3::ZERO;
+        velocity.angvel = Vec3::ZERO;
+        momentum.reset();
+        drift.reset();
+        gravity_scale.0 = 1.0;
+        player_speed.reset();
+        commands
+            .entity(entity)
+            .remove::<Walljump>()
+            .remove::<WallSliding>()
+            .remove::<LedgeGrab>()
+            .remove::<Skidding>()
+            .remove::<Sinking>()
+            .remove::<Swinging>()
+            .remove::<Ziplining>()
+            .remove::<Climbing>()
+            .remove::<LadderTransition>()
+            .remove::<Riding>()
+            .remove::<ColliderDisabled>()
+            .remove::<ImpulseJoint>()
+            .remove::<KnockedDown>()
+            .remove::<HardLanding>()
+            .insert(LockedAxes::ROTATION_LOCKED);
+    }
+}
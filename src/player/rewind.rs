@@ -0,0 +1,272 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use bevy_rapier3d::prelude::PhysicsSet;
+use leafwing_input_manager::prelude::ActionState;
+use std::collections::VecDeque;
+
+use crate::{AppState, GameConfig, Player, PlayerAction, WorldSnapshot};
+
+/// Every this many recorded frames leaves a ghost along the rewind trail
+const GHOST_SPACING: usize = 6;
+const GHOST_COLOR: Color = Color::rgba(0.3, 0.9, 0.85, 0.25);
+
+pub struct PlayerRewindPlugin;
+
+impl Plugin for PlayerRewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RewindHistory::default())
+            .add_systems(
+                (handle_rewind_input, draw_rewind_ghosts).in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(
+                record_or_rewind
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(PhysicsSet::Writeback),
+            );
+    }
+}
+
+/// Seconds of rewind the player has left, drained while rewinding and slowly recharged after
+#[derive(Component)]
+pub struct RewindMeter {
+    current: f32,
+    max: f32,
+}
+
+impl RewindMeter {
+    pub fn percent(&self) -> f32 {
+        if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current >= self.max
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn set_max(&mut self, max: f32) {
+        self.max = max;
+        self.current = self.current.min(max);
+    }
+
+    pub fn drain(&mut self, seconds: f32) {
+        self.current = (self.current - seconds).max(0.0);
+    }
+
+    pub fn recharge(&mut self, seconds: f32) {
+        self.current = (self.current + seconds).min(self.max);
+    }
+}
+
+impl Default for RewindMeter {
+    fn default() -> Self {
+        RewindMeter {
+            current: 5.0,
+            max: 5.0,
+        }
+    }
+}
+
+/// The player is being played backwards through the `RewindHistory`
+#[derive(Component)]
+pub struct Rewinding;
+
+/// A faded copy of the player marking where the rewind is headed, `0` is the oldest frame
+#[derive(Component)]
+pub struct RewindGhost(pub usize);
+
+/// A snapshot of every frame, oldest first, going back as far as a full meter can rewind
+#[derive(Resource, Default)]
+pub struct RewindHistory {
+    frames: VecDeque<(f32, WorldSnapshot)>,
+    seconds: f32,
+}
+
+impl RewindHistory {
+    /// Records a frame that lasted `delta` seconds, forgetting whatever is older than `max_seconds`
+    pub fn push(&mut self, delta: f32, snapshot: WorldSnapshot, max_seconds: f32) {
+        self.frames.push_back((delta, snapshot));
+        self.seconds += delta;
+        while self.seconds > max_seconds {
+            let Some((oldest, _)) = self.frames.pop_front() else {break;};
+            self.seconds -= oldest;
+        }
+    }
+
+    /// Takes back the newest frame
+    pub fn pop(&mut self) -> Option<WorldSnapshot> {
+        let (delta, snapshot) = self.frames.pop_back()?;
+        self.seconds = (self.seconds - delta).max(0.0);
+        Some(snapshot)
+    }
+
+    pub fn seconds(&self) -> f32 {
+        self.seconds
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Where the player was in each recorded frame, with the frame's index
+    pub fn player_poses(&self) -> impl Iterator<Item = (usize, Vec3, Quat)> + '_ {
+        self.frames
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, snapshot))| {
+                let body = snapshot.player.as_ref()?.body;
+                Some((index, body.translation, body.rotation))
+            })
+    }
+}
+
+fn handle_rewind_input(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_config: Res<GameConfig>,
+    history: Res<RewindHistory>,
+    mut player_query: Query<
+        (
+            Entity,
+            &ActionState<PlayerAction>,
+            &mut RewindMeter,
+            Option<&Rewinding>,
+        ),
+        With<Player>,
+    >,
+) {
+    let rewind = &game_config.rewind;
+    let Ok((entity, action, mut meter, rewinding)) = player_query.get_single_mut() else {return;};
+    meter.set_max(rewind.seconds);
+
+    let wants_to_rewind =
+        action.pressed(PlayerAction::Rewind) && !meter.is_empty() && !history.is_empty();
+    if wants_to_rewind {
+        meter.drain(time.delta_seconds());
+        if rewinding.is_none() {
+            commands.entity(entity).insert(Rewinding);
+        }
+    } else {
+        if rewinding.is_some() {
+            commands.entity(entity).remove::<Rewinding>();
+        }
+        if !meter.is_full() {
+            meter.recharge(rewind.recharge_per_second * time.delta_seconds());
+        }
+    }
+}
+
+/// Runs once physics has settled each frame, either recording it or stepping a frame back
+fn record_or_rewind(world: &mut World) {
+    let mut player_query = world.query_filtered::<Option<&Rewinding>, With<Player>>();
+    let Ok(rewinding) = player_query.get_single(world) else {return;};
+
+    if rewinding.is_some() {
+        let Some(snapshot) = world.resource_mut::<RewindHistory>().pop() else {return;};
+        // Only fails if the player is gone, which ends the rewind anyway
+        let _ = snapshot.restore(world);
+        return;
+    }
+
+    // Nothing moved while paused, no point filling the history with copies
+    let delta = world.resource::<Time>().delta_seconds();
+    if delta == 0.0 {
+        return;
+    }
+    let rewind = world.resource::<GameConfig>().rewind.clone();
+    let mut snapshot = WorldSnapshot::capture(world);
+    if !rewind.include_items {
+        snapshot.items.clear();
+    }
+    world
+        .resource_mut::<RewindHistory>()
+        .push(delta, snapshot, rewind.seconds);
+}
+
+fn draw_rewind_ghosts(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    history: Res<RewindHistory>,
+    player_query: Query<Option<&Rewinding>, With<Player>>,
+    started_query: Query<(), (With<Player>, Added<Rewinding>)>,
+    ghost_query: Query<(Entity, &RewindGhost)>,
+    mut ghost_assets: Local<Option<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+) {
+    let rewinding = player_query
+        .get_single()
+        .map_or(false, |rewinding| rewinding.is_some());
+
+    // Ghosts the rewind has already passed through, or all of them once it stops
+    for (entity, ghost) in &ghost_query {
+        if !rewinding || ghost.0 >= history.len() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    if started_query.is_empty() {
+        return;
+    }
+
+    let (mesh, material) = ghost_assets
+        .get_or_insert_with(|| {
+            (
+                meshes.add(Mesh::from(shape::Capsule::default())),
+                materials.add(StandardMaterial {
+                    base_color: GHOST_COLOR,
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true,
+                    ..default()
+                }),
+            )
+        })
+        .clone();
+    for (index, translation, rotation) in history.player_poses().step_by(GHOST_SPACING) {
+        commands
+            .spawn(PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_translation(translation).with_rotation(rotation),
+                ..default()
+            })
+            .insert(NotShadowCaster)
+            .insert(RewindGhost(index));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn history_forgets_frames_past_its_length() {
+        let mut history = RewindHistory::default();
+        for _ in 0..10 {
+            history.push(0.5, WorldSnapshot::default(), 2.0);
+        }
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.seconds(), 2.0);
+
+        assert!(history.pop().is_some());
+        assert_eq!(history.seconds(), 1.5);
+    }
+
+    #[test]
+    fn meter_drains_and_recharges_within_its_limits() {
+        let mut meter = RewindMeter::default();
+        meter.drain(10.0);
+        assert!(meter.is_empty());
+        meter.recharge(2.5);
+        assert_eq!(meter.percent(), 0.5);
+        meter.recharge(10.0);
+        assert!(meter.is_full());
+    }
+}
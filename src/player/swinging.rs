@@ -0,0 +1,216 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    get_direction_in_camera_space, ChainAnchor, ChainLink, Drift, Grounded, HeldItem, MainCamera,
+    Momentum, Player, PlayerAction, PlayerGrabSensor,
+};
+
+const SWING_HAND_OFFSET: f32 = 1.0;
+const SWING_PUMP_FORCE: f32 = 40.0;
+const SWING_RELEASE_BOOST: f32 = 6.0;
+
+pub struct PlayerSwingingPlugin;
+
+impl Plugin for PlayerSwingingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LinksInRange::default()).add_systems(
+            (
+                detect_chain_links,
+                grab_chain,
+                pump_swing,
+                climb_chain,
+                release_chain,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct LinksInRange(HashSet<Entity>);
+
+/// The player is hanging from `link` by a joint at their hands
+#[derive(Component)]
+pub struct Swinging {
+    pub link: Entity,
+}
+
+fn hand_joint() -> SphericalJointBuilder {
+    SphericalJointBuilder::new()
+        .local_anchor1(Vec3::ZERO)
+        .local_anchor2(Vec3::Y * SWING_HAND_OFFSET)
+}
+
+pub fn detect_chain_links(
+    mut links_in_range: ResMut<LinksInRange>,
+    mut collision_events: EventReader<CollisionEvent>,
+    grab_sensor_query: Query<Entity, With<PlayerGrabSensor>>,
+    link_query: Query<(), With<ChainLink>>,
+) {
+    let Ok(sensor_entity) = grab_sensor_query.get_single() else {return;};
+    for collision_event in collision_events.iter() {
+        let (e1, e2, started) = match collision_event {
+            CollisionEvent::Started(e1, e2, _) => (*e1, *e2, true),
+            CollisionEvent::Stopped(e1, e2, _) => (*e1, *e2, false),
+        };
+
+        let link_entity = if e1 == sensor_entity && link_query.contains(e2) {
+            e2
+        } else if e2 == sensor_entity && link_query.contains(e1) {
+            e1
+        } else {
+            continue;
+        };
+
+        if started {
+            links_in_range.0.insert(link_entity);
+        } else {
+            links_in_range.0.remove(&link_entity);
+        }
+    }
+}
+
+pub fn grab_chain(
+    mut commands: Commands,
+    links_in_range: Res<LinksInRange>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Momentum,
+            &mut Drift,
+            &ActionState<PlayerAction>,
+        ),
+        (
+            With<Player>,
+            Without<Grounded>,
+            Without<Swinging>,
+            Without<HeldItem>,
+        ),
+    >,
+    link_query: Query<&GlobalTransform, With<ChainLink>>,
+) {
+    for (player_entity, transform, mut momentum, mut drift, action) in &mut player_query {
+        if !action.just_pressed(PlayerAction::Grab) {
+            continue;
+        }
+
+        let hands = transform.translation + Vec3::Y * SWING_HAND_OFFSET;
+        let closest_link = links_in_range
+            .0
+            .iter()
+            .filter_map(|entity| {
+                link_query
+                    .get(*entity)
+                    .ok()
+                    .map(|link_transform| (*entity, link_transform.translation().distance(hands)))
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(entity, _)| entity);
+
+        if let Some(link) = closest_link {
+            momentum.reset();
+            drift.reset();
+            commands
+                .entity(player_entity)
+                .insert(ImpulseJoint::new(link, hand_joint()))
+                .insert(Swinging { link });
+        }
+    }
+}
+
+pub fn pump_swing(
+    mut commands: Commands,
+    time: Res<Time>,
+    player_query: Query<(Entity, &ActionState<PlayerAction>), (With<Player>, With<Swinging>)>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    let Ok(camera_transform) = camera_query.get_single() else {return;};
+    for (player_entity, action) in &player_query {
+        if action.pressed(PlayerAction::Crouch) {
+            continue;
+        }
+
+        let direction = get_direction_in_camera_space(camera_transform, action);
+        if direction != Vec3::ZERO {
+            commands.entity(player_entity).insert(ExternalImpulse {
+                impulse: direction * SWING_PUMP_FORCE * time.delta_seconds(),
+                ..default()
+            });
+        }
+    }
+}
+
+/// Holding crouch turns up and down into climbing along the chain instead of pumping
+pub fn climb_chain(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Swinging, &ActionState<PlayerAction>), With<Player>>,
+    link_query: Query<&ChainLink>,
+    anchor_query: Query<&ChainAnchor>,
+) {
+    for (player_entity, mut swinging, action) in &mut player_query {
+        if !action.pressed(PlayerAction::Crouch) {
+            continue;
+        }
+
+        let step: isize = if action.just_pressed(PlayerAction::Up) {
+            -1
+        } else if action.just_pressed(PlayerAction::Down) {
+            1
+        } else {
+            continue;
+        };
+
+        let Ok(link) = link_query.get(swinging.link) else {continue;};
+        let Ok(anchor) = anchor_query.get(link.chain) else {continue;};
+        let next_index = link.index as isize + step;
+        if next_index < 0 {
+            continue;
+        }
+
+        if let Some(next_link) = anchor.links.get(next_index as usize) {
+            swinging.link = *next_link;
+            commands
+                .entity(player_entity)
+                .insert(ImpulseJoint::new(*next_link, hand_joint()));
+        }
+    }
+}
+
+pub fn release_chain(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &Swinging,
+            &mut Transform,
+            &mut Velocity,
+            &ActionState<PlayerAction>,
+        ),
+        With<Player>,
+    >,
+    link_query: Query<(), With<ChainLink>>,
+) {
+    for (player_entity, swinging, mut transform, mut velocity, action) in &mut player_query {
+        let lost_link = !link_query.contains(swinging.link);
+        if action.just_pressed(PlayerAction::Jump)
+            || action.just_pressed(PlayerAction::Grab)
+            || lost_link
+        {
+            velocity.linvel.y += SWING_RELEASE_BOOST;
+
+            let flat_velocity = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+            if flat_velocity != Vec3::ZERO {
+                let position = transform.translation;
+                transform.look_at(position + flat_velocity, Vec3::Y);
+            }
+
+            commands
+                .entity(player_entity)
+                .remove::<ImpulseJoint>()
+                .remove::<Swinging>();
+        }
+    }
+}
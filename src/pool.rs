@@ -0,0 +1,218 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::ColliderDisabled;
+
+/// Parked entities past this many of one kind are despawned for real
+const POOL_CAPACITY: usize = 256;
+
+pub struct EntityPoolPlugin;
+
+impl Plugin for EntityPoolPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EntityPools::default());
+    }
+}
+
+/// Things spawned and thrown away often enough to be worth keeping around
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PoolKind {
+    Dust,
+    Raindrop,
+    Coin,
+    Boulder,
+}
+
+impl PoolKind {
+    pub const ALL: [PoolKind; 4] = [
+        PoolKind::Dust,
+        PoolKind::Raindrop,
+        PoolKind::Coin,
+        PoolKind::Boulder,
+    ];
+}
+
+/// Hidden and out of the physics world until it's taken out of its pool again
+#[derive(Component)]
+pub struct Pooled;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct PoolStats {
+    /// Times a parked entity was there to reuse
+    pub hits: u32,
+    /// Times a new entity had to be spawned instead
+    pub misses: u32,
+}
+
+impl PoolStats {
+    pub fn hit_rate(&self) -> f32 {
+        let takes = self.hits + self.misses;
+        if takes == 0 {
+            0.0
+        } else {
+            self.hits as f32 / takes as f32
+        }
+    }
+}
+
+/// Entities that are put away instead of despawned, so spawning another doesn't allocate a new
+/// one or add a new body to the physics broad phase
+#[derive(Resource, Default)]
+pub struct EntityPools {
+    parked: HashMap<PoolKind, Vec<Entity>>,
+    stats: HashMap<PoolKind, PoolStats>,
+}
+
+impl EntityPools {
+    /// Brings back a parked entity of `kind` as it was put away, the caller resets whatever state
+    /// it needs. `None` means a new one has to be spawned
+    pub fn take(&mut self, commands: &mut Commands, kind: PoolKind) -> Option<Entity> {
+        let parked = self.parked.entry(kind).or_default();
+        let stats = self.stats.entry(kind).or_default();
+        // Anything despawned while parked, say with the room it was in, is skipped over
+        while let Some(entity) = parked.pop() {
+            if commands.get_entity(entity).is_none() {
+                continue;
+            }
+            commands.add(sync_parked(kind, entity));
+            stats.hits += 1;
+            return Some(entity);
+        }
+        stats.misses += 1;
+        None
+    }
+
+    /// Parks `entity` to be taken again later, or despawns it if the pool is already full
+    pub fn release(&mut self, commands: &mut Commands, kind: PoolKind, entity: Entity) {
+        let parked = self.parked.entry(kind).or_default();
+        if parked.contains(&entity) {
+            return;
+        }
+        if parked.len() >= POOL_CAPACITY {
+            commands.entity(entity).despawn_recursive();
+            return;
+        }
+        parked.push(entity);
+        commands.add(sync_parked(kind, entity));
+    }
+
+    pub fn parked(&self, kind: PoolKind) -> usize {
+        self.parked.get(&kind).map_or(0, |parked| parked.len())
+    }
+
+    pub fn is_parked(&self, kind: PoolKind, entity: Entity) -> bool {
+        self.parked
+            .get(&kind)
+            .map_or(false, |parked| parked.contains(&entity))
+    }
+
+    pub fn stats(&self, kind: PoolKind) -> PoolStats {
+        self.stats.get(&kind).copied().unwrap_or_default()
+    }
+
+    /// One line per kind that has been taken from, for the debug overlay
+    pub fn summary(&self) -> String {
+        PoolKind::ALL
+            .iter()
+            .filter(|kind| self.stats.contains_key(kind))
+            .map(|kind| {
+                let stats = self.stats(*kind);
+                format!(
+                    "{kind:?}: {:.0}% reused, {} parked",
+                    stats.hit_rate() * 100.0,
+                    self.parked(*kind)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Hides or shows `entity` by whether it's still parked once commands are applied. A take and a
+/// release of the same entity from two systems in one frame can be applied in either order, so
+/// the pools are the only record of which one came last
+fn sync_parked(kind: PoolKind, entity: Entity) -> impl FnOnce(&mut World) + Send + 'static {
+    move |world: &mut World| {
+        let parked = world
+            .get_resource::<EntityPools>()
+            .map_or(false, |pools| pools.is_parked(kind, entity));
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {return;};
+        if parked {
+            entity_mut.insert((Pooled, Visibility::Hidden, ColliderDisabled));
+        } else {
+            entity_mut
+                .remove::<(Pooled, ColliderDisabled)>()
+                .insert(Visibility::Inherited);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy::ecs::system::CommandQueue;
+
+    /// Applies `queue` with `pools` in the world, the way it is between systems
+    fn apply(world: &mut World, queue: &mut CommandQueue, pools: &mut EntityPools) {
+        world.insert_resource(std::mem::take(pools));
+        queue.apply(world);
+        *pools = world.remove_resource::<EntityPools>().unwrap();
+    }
+
+    #[test]
+    fn released_entities_are_taken_again() {
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut pools = EntityPools::default();
+        let entity = world.spawn_empty().id();
+
+        let mut commands = Commands::new(&mut queue, &world);
+        assert_eq!(pools.take(&mut commands, PoolKind::Dust), None);
+        pools.release(&mut commands, PoolKind::Dust, entity);
+        pools.release(&mut commands, PoolKind::Dust, entity);
+        assert_eq!(pools.parked(PoolKind::Dust), 1);
+        apply(&mut world, &mut queue, &mut pools);
+        assert!(world.entity(entity).contains::<Pooled>());
+
+        let mut commands = Commands::new(&mut queue, &world);
+        assert_eq!(pools.take(&mut commands, PoolKind::Dust), Some(entity));
+        apply(&mut world, &mut queue, &mut pools);
+        assert!(!world.entity(entity).contains::<Pooled>());
+        assert_eq!(pools.stats(PoolKind::Dust).hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn taking_in_the_same_frame_as_releasing_never_leaks() {
+        let mut world = World::new();
+        let mut release_queue = CommandQueue::default();
+        let mut take_queue = CommandQueue::default();
+        let mut pools = EntityPools::default();
+        let entity = world.spawn_empty().id();
+
+        let mut commands = Commands::new(&mut release_queue, &world);
+        pools.release(&mut commands, PoolKind::Dust, entity);
+        let mut commands = Commands::new(&mut take_queue, &world);
+        assert_eq!(pools.take(&mut commands, PoolKind::Dust), Some(entity));
+
+        // The take's commands landing first mustn't leave it parked with nothing to take it again
+        apply(&mut world, &mut take_queue, &mut pools);
+        apply(&mut world, &mut release_queue, &mut pools);
+        assert!(!world.entity(entity).contains::<Pooled>());
+        assert_eq!(pools.parked(PoolKind::Dust), 0);
+    }
+
+    #[test]
+    fn despawned_entities_are_not_taken() {
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut pools = EntityPools::default();
+        let entity = world.spawn_empty().id();
+
+        let mut commands = Commands::new(&mut queue, &world);
+        pools.release(&mut commands, PoolKind::Coin, entity);
+        apply(&mut world, &mut queue, &mut pools);
+        world.despawn(entity);
+
+        let mut commands = Commands::new(&mut queue, &world);
+        assert_eq!(pools.take(&mut commands, PoolKind::Coin), None);
+        assert_eq!(pools.stats(PoolKind::Coin).misses, 1);
+    }
+}
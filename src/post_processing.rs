@@ -0,0 +1,271 @@
+use bevy::{
+    core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping},
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        view::ColorGrading,
+    },
+};
+use serde::Deserialize;
+
+use crate::{ActiveRoom, CurrentLevel, LevelData, MainCamera, Room};
+
+const VIGNETTE_TEXTURE_SIZE: u32 = 128;
+const MOOD_BLEND_SPEED: f32 = 1.5;
+/// A blend this close to its target snaps the rest of the way, easing alone never quite gets there
+const MOOD_BLEND_SNAP: f32 = 0.001;
+
+pub struct PostProcessingPlugin;
+
+impl Plugin for PostProcessingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ColorGradingLut::default())
+            .add_startup_system(spawn_vignette)
+            .add_systems(
+                (
+                    pick_mood,
+                    blend_mood,
+                    apply_camera_post_processing,
+                    apply_vignette,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The tonemapping lookup tables that ship with bevy, named so level files can pick one
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TonemappingLut {
+    None,
+    AcesFitted,
+    AgX,
+    #[default]
+    TonyMcMapface,
+    BlenderFilmic,
+}
+
+impl TonemappingLut {
+    pub fn tonemapping(&self) -> Tonemapping {
+        match self {
+            TonemappingLut::None => Tonemapping::None,
+            TonemappingLut::AcesFitted => Tonemapping::AcesFitted,
+            TonemappingLut::AgX => Tonemapping::AgX,
+            TonemappingLut::TonyMcMapface => Tonemapping::TonyMcMapface,
+            TonemappingLut::BlenderFilmic => Tonemapping::BlenderFilmic,
+        }
+    }
+}
+
+/// How an area of a level is graded, set for the whole level and optionally overridden per room
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct Mood {
+    pub lut: TonemappingLut,
+    pub bloom: f32,
+    pub vignette: f32,
+    pub exposure: f32,
+    pub gamma: f32,
+    pub saturation: f32,
+    pub tint: [f32; 3],
+}
+
+impl Default for Mood {
+    fn default() -> Self {
+        Mood {
+            lut: TonemappingLut::default(),
+            bloom: 0.15,
+            vignette: 0.2,
+            exposure: 0.0,
+            gamma: 1.0,
+            saturation: 1.0,
+            tint: [0.0, 0.0, 0.0],
+        }
+    }
+}
+
+impl Mood {
+    /// The largest gap between any one of this mood's values and `other`'s
+    pub fn difference(&self, other: &Mood) -> f32 {
+        let tint = (Vec3::from(self.tint) - Vec3::from(other.tint)).abs().max_element();
+        [
+            self.bloom - other.bloom,
+            self.vignette - other.vignette,
+            self.exposure - other.exposure,
+            self.gamma - other.gamma,
+            self.saturation - other.saturation,
+        ]
+        .into_iter()
+        .fold(tint, |largest, gap| largest.max(gap.abs()))
+    }
+
+    /// Eases every value towards `target`, the lut switches straight over
+    pub fn blend_towards(&mut self, target: &Mood, t: f32) {
+        let t = t.clamp(0.0, 1.0);
+        self.lut = target.lut;
+        self.bloom += (target.bloom - self.bloom) * t;
+        self.vignette += (target.vignette - self.vignette) * t;
+        self.exposure += (target.exposure - self.exposure) * t;
+        self.gamma += (target.gamma - self.gamma) * t;
+        self.saturation += (target.saturation - self.saturation) * t;
+        self.tint = Vec3::from(self.tint)
+            .lerp(Vec3::from(target.tint), t)
+            .into();
+        if self.difference(target) < MOOD_BLEND_SNAP {
+            *self = *target;
+        }
+    }
+}
+
+/// The grade on screen right now and the one it is blending towards
+#[derive(Resource, Default)]
+pub struct ColorGradingLut {
+    pub current: Mood,
+    pub target: Mood,
+}
+
+/// Full screen overlay shading the edges of the view in the mood's tint
+#[derive(Component)]
+pub struct Vignette;
+
+pub fn vignette_image() -> Image {
+    let size = VIGNETTE_TEXTURE_SIZE;
+    let center = (size as f32 - 1.0) / 2.0;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let offset = Vec2::new(x as f32 - center, y as f32 - center) / center;
+            let alpha = ((offset.length() - 0.5) / 0.9).clamp(0.0, 1.0).powf(1.5);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn spawn_vignette(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands
+        .spawn(ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..default()
+            },
+            image: images.add(vignette_image()).into(),
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.0).into(),
+            z_index: ZIndex::Global(-1),
+            ..default()
+        })
+        .insert(Vignette);
+}
+
+pub fn pick_mood(
+    mut grading: ResMut<ColorGradingLut>,
+    mut level_events: EventReader<AssetEvent<LevelData>>,
+    active_room: Res<ActiveRoom>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+    room_query: Query<&Room>,
+) {
+    let level_changed = level_events.iter().count() > 0;
+    if !level_changed && !active_room.is_changed() {
+        return;
+    }
+    let Some(current_level) = current_level else {return;};
+    let Some(level) = levels.get(&current_level.0) else {return;};
+
+    let room_mood = active_room
+        .0
+        .and_then(|room_entity| room_query.get(room_entity).ok())
+        .and_then(|room| level.rooms.get(room.index))
+        .and_then(|room| room.mood);
+    grading.target = room_mood.unwrap_or(level.mood);
+}
+
+pub fn blend_mood(time: Res<Time>, mut grading: ResMut<ColorGradingLut>) {
+    let settled = grading.current.lut == grading.target.lut
+        && grading.current.difference(&grading.target) < MOOD_BLEND_SNAP;
+    if settled {
+        return;
+    }
+    let target = grading.target;
+    grading
+        .current
+        .blend_towards(&target, time.delta_seconds() * MOOD_BLEND_SPEED);
+}
+
+pub fn apply_camera_post_processing(
+    grading: Res<ColorGradingLut>,
+    mut camera_query: Query<
+        (&mut BloomSettings, &mut Tonemapping, &mut ColorGrading),
+        With<MainCamera>,
+    >,
+) {
+    if !grading.is_changed() {
+        return;
+    }
+    let mood = grading.current;
+    for (mut bloom, mut tonemapping, mut color_grading) in &mut camera_query {
+        bloom.intensity = mood.bloom;
+        *tonemapping = mood.lut.tonemapping();
+        color_grading.exposure = mood.exposure;
+        color_grading.gamma = mood.gamma;
+        color_grading.post_saturation = mood.saturation;
+    }
+}
+
+pub fn apply_vignette(
+    grading: Res<ColorGradingLut>,
+    mut vignette_query: Query<&mut BackgroundColor, With<Vignette>>,
+) {
+    if !grading.is_changed() {
+        return;
+    }
+    let mood = grading.current;
+    let [r, g, b] = mood.tint;
+    for mut color in &mut vignette_query {
+        color.0 = Color::rgba(r, g, b, mood.vignette);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mood_blend_reaches_target() {
+        let mut mood = Mood::default();
+        let target = Mood {
+            lut: TonemappingLut::BlenderFilmic,
+            bloom: 0.6,
+            exposure: -1.0,
+            ..default()
+        };
+        mood.blend_towards(&target, 0.5);
+        assert_eq!(mood.lut, TonemappingLut::BlenderFilmic);
+        assert!((mood.bloom - 0.375).abs() < 0.001);
+        mood.blend_towards(&target, 1.0);
+        assert_eq!(mood, target);
+    }
+
+    #[test]
+    fn mood_blend_settles_exactly_on_target() {
+        let mut mood = Mood::default();
+        let target = Mood {
+            bloom: 0.6,
+            tint: [0.3, 0.1, 0.0],
+            ..default()
+        };
+        for _ in 0..1000 {
+            mood.blend_towards(&target, 1.0 / 60.0 * MOOD_BLEND_SPEED);
+        }
+        assert_eq!(mood, target);
+    }
+}
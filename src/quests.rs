@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    CreationBuilt, CreationType, CurrentLevel, LevelData, LevelSignal, Pickup, PickupCollected,
+};
+
+pub struct QuestPlugin;
+
+impl Plugin for QuestPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(QuestLog::default()).add_systems(
+            (
+                load_level_quests,
+                track_coin_quests,
+                track_area_quests,
+                track_build_quests,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// What has to happen for a quest to be done
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub enum QuestGoal {
+    CollectCoins(u32),
+    /// Reached by setting off the `Trigger` with this name
+    ReachArea(String),
+    Build(CreationType),
+}
+
+/// A quest as it is written in a level file
+#[derive(Deserialize, Clone, Debug)]
+pub struct QuestData {
+    pub name: String,
+    pub goal: QuestGoal,
+}
+
+#[derive(Clone, Debug)]
+pub struct Quest {
+    pub name: String,
+    pub goal: QuestGoal,
+    pub progress: u32,
+}
+
+impl Quest {
+    pub fn new(data: &QuestData) -> Self {
+        Quest {
+            name: data.name.clone(),
+            goal: data.goal.clone(),
+            progress: 0,
+        }
+    }
+
+    pub fn target(&self) -> u32 {
+        match self.goal {
+            QuestGoal::CollectCoins(amount) => amount,
+            QuestGoal::ReachArea(_) | QuestGoal::Build(_) => 1,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.progress >= self.target()
+    }
+
+    fn advance(&mut self, amount: u32) {
+        self.progress = (self.progress + amount).min(self.target());
+    }
+}
+
+/// The current level's quests, fed by the gameplay events each goal listens to
+#[derive(Resource, Default)]
+pub struct QuestLog {
+    pub quests: Vec<Quest>,
+}
+
+impl QuestLog {
+    pub fn new(quests: &[QuestData]) -> Self {
+        QuestLog {
+            quests: quests.iter().map(Quest::new).collect(),
+        }
+    }
+
+    pub fn completed(&self) -> usize {
+        self.quests
+            .iter()
+            .filter(|quest| quest.is_complete())
+            .count()
+    }
+
+    pub fn collect_coins(&mut self, amount: u32) {
+        for quest in &mut self.quests {
+            if matches!(quest.goal, QuestGoal::CollectCoins(_)) {
+                quest.advance(amount);
+            }
+        }
+    }
+
+    pub fn reach_area(&mut self, name: &str) {
+        for quest in &mut self.quests {
+            if quest.goal == QuestGoal::ReachArea(name.to_string()) {
+                quest.advance(1);
+            }
+        }
+    }
+
+    pub fn build(&mut self, creation: CreationType) {
+        for quest in &mut self.quests {
+            if quest.goal == QuestGoal::Build(creation) {
+                quest.advance(1);
+            }
+        }
+    }
+}
+
+pub fn load_level_quests(
+    mut quest_log: ResMut<QuestLog>,
+    mut level_events: EventReader<AssetEvent<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+) {
+    let Some(current_level) = current_level else {return;};
+    // Picking a level starts its quests over, even the one that was just played
+    let mut reload = current_level.is_changed();
+    for event in level_events.iter() {
+        let AssetEvent::Created { handle } = event else {continue;};
+        reload |= *handle == current_level.0;
+    }
+    if !reload {
+        return;
+    }
+    if let Some(level) = levels.get(&current_level.0) {
+        *quest_log = QuestLog::new(&level.quests);
+    }
+}
+
+fn track_coin_quests(mut quest_log: ResMut<QuestLog>, mut pickups: EventReader<PickupCollected>) {
+    for PickupCollected(pickup) in pickups.iter() {
+        if let Pickup::Coin(amount) = pickup {
+            quest_log.collect_coins(*amount as u32);
+        }
+    }
+}
+
+fn track_area_quests(mut quest_log: ResMut<QuestLog>, mut signals: EventReader<LevelSignal>) {
+    for signal in signals.iter().filter(|signal| signal.active) {
+        quest_log.reach_area(&signal.name);
+    }
+}
+
+fn track_build_quests(mut quest_log: ResMut<QuestLog>, mut built: EventReader<CreationBuilt>) {
+    for CreationBuilt(creation) in built.iter() {
+        quest_log.build(*creation);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn quest_log() -> QuestLog {
+        QuestLog::new(&[
+            QuestData {
+                name: "Pocket Money".to_string(),
+                goal: QuestGoal::CollectCoins(5),
+            },
+            QuestData {
+                name: "East Side".to_string(),
+                goal: QuestGoal::ReachArea("east_gate".to_string()),
+            },
+            QuestData {
+                name: "Lift Off".to_string(),
+                goal: QuestGoal::Build(CreationType::Launcher),
+            },
+        ])
+    }
+
+    #[test]
+    fn coin_quests_count_up_to_their_target() {
+        let mut quest_log = quest_log();
+        quest_log.collect_coins(3);
+        assert!(!quest_log.quests[0].is_complete());
+        quest_log.collect_coins(4);
+        assert!(quest_log.quests[0].is_complete());
+        assert_eq!(quest_log.quests[0].progress, 5);
+        assert_eq!(quest_log.completed(), 1);
+    }
+
+    #[test]
+    fn only_matching_goals_advance() {
+        let mut quest_log = quest_log();
+        quest_log.reach_area("ambush");
+        quest_log.build(CreationType::Crate);
+        assert_eq!(quest_log.completed(), 0);
+        quest_log.reach_area("east_gate");
+        quest_log.build(CreationType::Launcher);
+        assert_eq!(quest_log.completed(), 2);
+    }
+}
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    build_rope, BuildCreation, Creation, CreationBuilt, CreationType, IdeaChanged, MainCamera,
+    Player, PlayerAction, PlayerIdeas, RopeEnd, SpentIdeas,
+};
+
+/// How far away an anchor can be picked
+const ROPE_AIM_REACH: f32 = 25.0;
+const MAX_ROPE_LENGTH: f32 = 20.0;
+const ROPE_SEGMENT_LENGTH: f32 = 0.5;
+const MIN_ROPE_SEGMENTS: usize = 2;
+const ANCHOR_MARKER_RADIUS: f32 = 0.2;
+
+pub struct RopePlugin;
+
+impl Plugin for RopePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                start_rope_placement,
+                pick_rope_anchors,
+                cancel_rope_placement,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The player is aiming to pick where a rope is tied, Interact picks an anchor and
+/// unloading ideas gives up
+#[derive(Component, Default)]
+pub struct PlacingRope {
+    pub first: Option<RopeEnd>,
+    markers: Vec<Entity>,
+    /// Become part of the rope once it's built
+    pegs: Vec<Entity>,
+}
+
+/// A fixed point a rope is tied to where the aim hit something without a body of its own
+#[derive(Component)]
+pub struct RopePeg;
+
+/// The rigid body a collider belongs to, walking up from the collider itself
+fn owning_body<'a>(
+    entity: Entity,
+    body_query: &'a Query<(Option<&RigidBody>, Option<&Parent>, &GlobalTransform)>,
+) -> Option<(Entity, &'a GlobalTransform)> {
+    let mut current = entity;
+    loop {
+        let (rigid_body, parent, transform) = body_query.get(current).ok()?;
+        if rigid_body.is_some() {
+            return Some((current, transform));
+        }
+        current = parent?.get();
+    }
+}
+
+fn rope_segments(length: f32) -> usize {
+    ((length / ROPE_SEGMENT_LENGTH).ceil() as usize).max(MIN_ROPE_SEGMENTS)
+}
+
+fn start_rope_placement(
+    mut commands: Commands,
+    mut build_events: EventReader<BuildCreation>,
+    player_query: Query<Entity, (With<Player>, Without<PlacingRope>)>,
+) {
+    let Ok(player_entity) = player_query.get_single() else {return;};
+    if build_events
+        .iter()
+        .any(|BuildCreation(creation)| *creation == CreationType::Rope)
+    {
+        commands
+            .entity(player_entity)
+            .insert(PlacingRope::default());
+    }
+}
+
+fn pick_rope_anchors(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut player_ideas: ResMut<PlayerIdeas>,
+    mut built_events: EventWriter<CreationBuilt>,
+    mut idea_events: EventWriter<IdeaChanged>,
+    rapier_context: Res<RapierContext>,
+    mut player_query: Query<(Entity, &ActionState<PlayerAction>, &mut PlacingRope), With<Player>>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    body_query: Query<(Option<&RigidBody>, Option<&Parent>, &GlobalTransform)>,
+) {
+    let Ok((player_entity, action, mut placing)) = player_query.get_single_mut() else {return;};
+    let Ok(camera_transform) = camera_query.get_single() else {return;};
+    if !action.just_pressed(PlayerAction::Interact) {
+        return;
+    }
+
+    let filter = QueryFilter::new()
+        .exclude_sensors()
+        .exclude_rigid_body(player_entity);
+    let origin = camera_transform.translation();
+    let direction = camera_transform.forward();
+    let hit = rapier_context.cast_ray(origin, direction, ROPE_AIM_REACH, true, filter);
+    let Some((hit_entity, toi)) = hit else {return;};
+    let point = origin + direction * toi;
+    if let Some(first) = placing.first {
+        if first.point.distance(point) > MAX_ROPE_LENGTH {
+            return;
+        }
+    }
+
+    // Tie onto whatever body was hit, or a peg where the level has no body of its own
+    let end = match owning_body(hit_entity, &body_query) {
+        Some((body, transform)) => RopeEnd::tied(body, transform, point),
+        None => {
+            let peg = commands
+                .spawn(TransformBundle::from_transform(
+                    Transform::from_translation(point),
+                ))
+                .insert(RigidBody::Fixed)
+                .insert(RopePeg)
+                .id();
+            placing.pegs.push(peg);
+            RopeEnd {
+                body: Some(peg),
+                point,
+                local_anchor: Vec3::ZERO,
+            }
+        }
+    };
+
+    let first = match placing.first {
+        Some(first) => first,
+        None => {
+            let marker = commands
+                .spawn(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::UVSphere {
+                        radius: ANCHOR_MARKER_RADIUS,
+                        ..default()
+                    })),
+                    material: materials.add(Color::YELLOW.into()),
+                    transform: Transform::from_translation(point),
+                    ..default()
+                })
+                .id();
+            placing.markers.push(marker);
+            placing.first = Some(end);
+            return;
+        }
+    };
+
+    let segments = rope_segments(first.point.distance(end.point));
+    let rope = build_rope(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        first,
+        end,
+        segments,
+    );
+    let spent = player_ideas.spend_loaded_for(CreationType::Rope);
+    if !spent.is_empty() {
+        idea_events.send(IdeaChanged::Spent);
+    }
+    commands
+        .entity(rope)
+        .insert(CreationType::Rope)
+        .insert(Creation)
+        .insert(SpentIdeas(spent))
+        .push_children(&placing.pegs);
+    for marker in &placing.markers {
+        commands.entity(*marker).despawn_recursive();
+    }
+    commands.entity(player_entity).remove::<PlacingRope>();
+    built_events.send(CreationBuilt(CreationType::Rope));
+}
+
+fn cancel_rope_placement(
+    mut commands: Commands,
+    player_query: Query<(Entity, &ActionState<PlayerAction>, &PlacingRope), With<Player>>,
+) {
+    let Ok((player_entity, action, placing)) = player_query.get_single() else {return;};
+    if !action.just_pressed(PlayerAction::UnloadIdeas) {
+        return;
+    }
+    for entity in placing.markers.iter().chain(&placing.pegs) {
+        commands.entity(*entity).despawn_recursive();
+    }
+    commands.entity(player_entity).remove::<PlacingRope>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ropes_get_enough_segments_to_sag() {
+        assert_eq!(rope_segments(0.1), MIN_ROPE_SEGMENTS);
+        assert_eq!(rope_segments(5.0), 10);
+        assert_eq!(rope_segments(5.2), 11);
+    }
+}
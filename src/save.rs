@@ -0,0 +1,306 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{Costume, GameSettings, LevelProgress, ProfileStats};
+
+pub const PROFILE_SLOTS: usize = 3;
+const SAVE_DIRECTORY: &str = "saves";
+/// Where progress and settings were kept before there were profiles, the first slot picks them up
+const LEGACY_SAVE_PATH: &str = "save.ron";
+const LEGACY_SETTINGS_PATH: &str = "settings.ron";
+/// First line of every save file, followed by the checksum of the rest in hex
+const CHECKSUM_PREFIX: &str = "// checksum: ";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SaveData::default())
+            .add_system(write_save_data);
+    }
+}
+
+/// The save slot being played, its folder holds the player's progress and settings. Nothing is
+/// written until one is picked
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActiveProfile(pub usize);
+
+impl ActiveProfile {
+    pub fn folder(&self) -> PathBuf {
+        Path::new(SAVE_DIRECTORY).join(format!("slot{}", self.0))
+    }
+
+    pub fn save_path(&self) -> PathBuf {
+        self.folder().join("save.ron")
+    }
+
+    pub fn settings_path(&self) -> PathBuf {
+        self.folder().join("settings.ron")
+    }
+
+    /// The slot's progress, or `None` if nothing was ever saved in it
+    pub fn load_save(&self) -> Option<SaveData> {
+        read_save_file(&self.save_path()).or_else(|| {
+            (self.0 == 0)
+                .then(|| read_save_file(Path::new(LEGACY_SAVE_PATH)))
+                .flatten()
+        })
+    }
+
+    pub fn load_settings(&self) -> Option<GameSettings> {
+        read_save_file(&self.settings_path()).or_else(|| {
+            (self.0 == 0)
+                .then(|| read_save_file(Path::new(LEGACY_SETTINGS_PATH)))
+                .flatten()
+        })
+    }
+
+    /// Empties the slot, backups and all
+    pub fn delete(&self) -> Result<(), String> {
+        match std::fs::remove_dir_all(self.folder()) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// The slot saved to most recently, its settings are used until a profile is picked
+    pub fn last_played() -> Option<ActiveProfile> {
+        (0..PROFILE_SLOTS)
+            .map(ActiveProfile)
+            .filter_map(|profile| {
+                let modified = std::fs::metadata(profile.save_path())
+                    .ok()?
+                    .modified()
+                    .ok()?;
+                Some((modified, profile))
+            })
+            .max_by_key(|(modified, _)| *modified)
+            .map(|(_, profile)| profile)
+    }
+}
+
+/// Writes `contents` beside `path` and swaps it in, so a crash mid-write never leaves half a file.
+/// The version it replaces is kept as a backup, as long as that one was still sound
+pub fn write_save_file(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(folder) = path.parent() {
+        std::fs::create_dir_all(folder).map_err(|error| error.to_string())?;
+    }
+    let temporary = path.with_extension("ron.tmp");
+    let mut file = std::fs::File::create(&temporary).map_err(|error| error.to_string())?;
+    file.write_all(seal(contents).as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|error| error.to_string())?;
+    if read_sealed(path).is_some() {
+        std::fs::rename(path, backup_path(path)).map_err(|error| error.to_string())?;
+    }
+    std::fs::rename(&temporary, path).map_err(|error| error.to_string())
+}
+
+/// Reads a save file, falling back on its backup if it's missing, damaged or won't parse
+pub fn read_save_file<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let parse = |path: &Path| ron::from_str(&read_sealed(path)?).ok();
+    parse(path).or_else(|| {
+        if path.exists() {
+            println!("{} is damaged, loading its backup", path.display());
+        }
+        parse(&backup_path(path))
+    })
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("ron.bak")
+}
+
+fn read_sealed(path: &Path) -> Option<String> {
+    let file = std::fs::read_to_string(path).ok()?;
+    unseal(&file).map(str::to_string)
+}
+
+/// Puts the checksum of `contents` in front of them
+pub fn seal(contents: &str) -> String {
+    format!("{CHECKSUM_PREFIX}{:016x}\n{contents}", checksum(contents))
+}
+
+/// What was sealed in `file`, or `None` if it no longer matches its checksum. Files written
+/// before there were checksums are taken as they are
+pub fn unseal(file: &str) -> Option<&str> {
+    let Some(sealed) = file.strip_prefix(CHECKSUM_PREFIX) else {return Some(file);};
+    let (sum, contents) = sealed.split_once('\n')?;
+    let sum = u64::from_str_radix(sum.trim(), 16).ok()?;
+    (sum == checksum(contents)).then_some(contents)
+}
+
+/// FNV-1a, enough to notice a truncated or scrambled file
+fn checksum(contents: &str) -> u64 {
+    contents.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// A lore collectible as it is shown in the gallery
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MemoryRecord {
+    pub id: String,
+    pub title: String,
+    pub text: String,
+    /// Path of an image under `assets/`
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+/// Progress that outlives a play session, written to the `ActiveProfile`'s `save.ron` whenever
+/// it changes
+#[derive(Resource, Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct SaveData {
+    /// What the profile select screen calls this slot
+    pub name: String,
+    pub memories: Vec<MemoryRecord>,
+    /// Ids of the tutorial hints already shown
+    pub seen_hints: Vec<String>,
+    /// Costumes found so far, in the order they were found
+    pub costumes: Vec<Costume>,
+    pub equipped_costume: Option<Costume>,
+    pub stats: ProfileStats,
+    /// Reached the ending and rolled the credits at least once
+    pub finished: bool,
+    /// Best result in each campaign level played, by level id
+    pub levels: BTreeMap<String, LevelProgress>,
+}
+
+impl SaveData {
+    pub fn has_memory(&self, id: &str) -> bool {
+        self.memories.iter().any(|memory| memory.id == id)
+    }
+
+    /// Returns false if the memory was already found
+    pub fn add_memory(&mut self, memory: MemoryRecord) -> bool {
+        if self.has_memory(&memory.id) {
+            return false;
+        }
+        self.memories.push(memory);
+        true
+    }
+
+    pub fn has_costume(&self, costume: Costume) -> bool {
+        self.costumes.contains(&costume)
+    }
+
+    /// Returns false if the costume was already found
+    pub fn add_costume(&mut self, costume: Costume) -> bool {
+        if self.has_costume(costume) {
+            return false;
+        }
+        self.costumes.push(costume);
+        true
+    }
+
+    /// Puts on a costume from the wardrobe, or takes it off if it's already on
+    pub fn toggle_costume(&mut self, costume: Costume) {
+        if !self.has_costume(costume) {
+            return;
+        }
+        self.equipped_costume = if self.equipped_costume == Some(costume) {
+            None
+        } else {
+            Some(costume)
+        };
+    }
+
+    /// Writes this to `profile`'s save file right away, rather than waiting for the next change
+    pub fn write(&self, profile: &ActiveProfile) -> Result<(), String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| write_save_file(&profile.save_path(), &contents))
+    }
+
+    /// Returns false if the hint was already seen
+    pub fn see_hint(&mut self, id: &str) -> bool {
+        if self.seen_hints.iter().any(|seen| seen == id) {
+            return false;
+        }
+        self.seen_hints.push(id.to_string());
+        true
+    }
+}
+
+fn write_save_data(save_data: Res<SaveData>, profile: Option<Res<ActiveProfile>>) {
+    let Some(profile) = profile else {return;};
+    // A newly picked profile gets a save file straight away, before anything is collected
+    if save_data.is_added() || !(save_data.is_changed() || profile.is_changed()) {
+        return;
+    }
+    let written = save_data.write(&profile);
+    if let Err(error) = written {
+        println!("Couldn't write save data: {error}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn memory(id: &str) -> MemoryRecord {
+        MemoryRecord {
+            id: id.to_string(),
+            title: "A Title".to_string(),
+            text: "Some text".to_string(),
+            image: None,
+        }
+    }
+
+    #[test]
+    fn memories_are_only_found_once() {
+        let mut save_data = SaveData::default();
+        assert!(save_data.add_memory(memory("first")));
+        assert!(!save_data.add_memory(memory("first")));
+        assert!(save_data.has_memory("first"));
+        assert_eq!(save_data.memories.len(), 1);
+    }
+
+    #[test]
+    fn hints_are_only_seen_once() {
+        let mut save_data = SaveData::default();
+        assert!(save_data.see_hint("wall_slide"));
+        assert!(!save_data.see_hint("wall_slide"));
+        assert!(save_data.see_hint("idea_pickup"));
+    }
+
+    #[test]
+    fn only_found_costumes_can_be_worn() {
+        let mut save_data = SaveData::default();
+        save_data.toggle_costume(Costume::Umbrella);
+        assert_eq!(save_data.equipped_costume, None);
+
+        assert!(save_data.add_costume(Costume::Umbrella));
+        assert!(!save_data.add_costume(Costume::Umbrella));
+        save_data.toggle_costume(Costume::Umbrella);
+        assert_eq!(save_data.equipped_costume, Some(Costume::Umbrella));
+        save_data.toggle_costume(Costume::Umbrella);
+        assert_eq!(save_data.equipped_costume, None);
+    }
+
+    #[test]
+    fn save_data_round_trips() {
+        let mut save_data = SaveData::default();
+        save_data.add_memory(memory("first"));
+        save_data.add_costume(Costume::SpringHat);
+        save_data.toggle_costume(Costume::SpringHat);
+        let contents = ron::to_string(&save_data).unwrap();
+        assert_eq!(ron::from_str::<SaveData>(&contents).unwrap(), save_data);
+    }
+
+    #[test]
+    fn damaged_files_fail_their_checksum() {
+        let sealed = seal("(name: \"Pip\")");
+        assert_eq!(unseal(&sealed), Some("(name: \"Pip\")"));
+        assert_eq!(unseal(&sealed.replace("Pip", "Pop")), None);
+        assert_eq!(unseal(&sealed[..sealed.len() - 3]), None);
+        assert_eq!(unseal("(name: \"Old\")"), Some("(name: \"Old\")"));
+    }
+}
@@ -0,0 +1,297 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    buttonlike::ButtonState,
+    plugin::InputManagerSystem,
+    prelude::{ActionState, InputMap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    write_save_file, ActiveProfile, HeldItem, InputListenerBundle, LaunchOptions, Localization,
+    Player, PlayerAction, Rebind, UiPalette, LANGUAGES,
+};
+
+const COYOTE_TIME_MULTIPLIERS: [f32; 4] = [1.0, 1.5, 2.0, 3.0];
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameSettings::load_last_played())
+            .insert_resource(CrouchLatch::default())
+            .add_system(
+                apply_action_modes
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputManagerSystem::Update),
+            )
+            .add_systems((apply_control_preset, write_game_settings));
+    }
+}
+
+/// Which buttons do what, swapped moves jump and grab onto each other's buttons
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ControlPreset {
+    #[default]
+    Standard,
+    Swapped,
+}
+
+/// Whether an action lasts while its button is held or flips on and off with each press
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ActionMode {
+    Hold,
+    Toggle,
+}
+
+/// Player facing options, kept per profile in the `ActiveProfile`'s `settings.ron`
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GameSettings {
+    pub preset: ControlPreset,
+    /// Toggle picks up with one press and throws with the next, hold throws on release
+    pub grab_mode: ActionMode,
+    pub crouch_mode: ActionMode,
+    /// Pulls the player up as soon as they catch a ledge
+    pub auto_ledge_climb: bool,
+    pub coyote_time_multiplier: f32,
+    /// Holds the camera at a fixed distance instead of pulling back with momentum
+    pub reduced_camera_motion: bool,
+    pub palette: UiPalette,
+    /// Code of a file in `assets/lang/`
+    pub language: String,
+    /// Bindings picked in place of the defaults, applied on top of `preset`
+    pub rebinds: Vec<Rebind>,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        GameSettings {
+            preset: ControlPreset::Standard,
+            grab_mode: ActionMode::Toggle,
+            crouch_mode: ActionMode::Hold,
+            auto_ledge_climb: false,
+            coyote_time_multiplier: 1.0,
+            reduced_camera_motion: false,
+            palette: UiPalette::Classic,
+            language: LANGUAGES[0].to_string(),
+            rebinds: Vec::new(),
+        }
+    }
+}
+
+impl GameSettings {
+    /// What the game boots with, so the profile select screen is already in the player's language
+    pub fn load_last_played() -> Self {
+        ActiveProfile::last_played()
+            .and_then(|profile| profile.load_settings())
+            .unwrap_or_default()
+    }
+}
+
+/// A line on the accessibility page of the pause menu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SettingsRow {
+    Preset,
+    GrabMode,
+    CrouchMode,
+    AutoLedgeClimb,
+    CoyoteTime,
+    ReducedCameraMotion,
+    Palette,
+    Language,
+}
+
+impl SettingsRow {
+    pub const ALL: [SettingsRow; 8] = [
+        SettingsRow::Preset,
+        SettingsRow::GrabMode,
+        SettingsRow::CrouchMode,
+        SettingsRow::AutoLedgeClimb,
+        SettingsRow::CoyoteTime,
+        SettingsRow::ReducedCameraMotion,
+        SettingsRow::Palette,
+        SettingsRow::Language,
+    ];
+
+    pub fn label(&self, settings: &GameSettings, localization: &Localization) -> String {
+        let on_off = |on: bool| localization.text(if on { "settings.on" } else { "settings.off" });
+        let value = |name: String| localization.text(&format!("settings.{name}"));
+        let (key, shown) = match self {
+            SettingsRow::Preset => ("settings.controls", value(format!("{:?}", settings.preset))),
+            SettingsRow::GrabMode => ("settings.grab", value(format!("{:?}", settings.grab_mode))),
+            SettingsRow::CrouchMode => (
+                "settings.crouch",
+                value(format!("{:?}", settings.crouch_mode)),
+            ),
+            SettingsRow::AutoLedgeClimb => (
+                "settings.auto_ledge_climb",
+                on_off(settings.auto_ledge_climb),
+            ),
+            SettingsRow::CoyoteTime => (
+                "settings.coyote_time",
+                format!("{:.1}", settings.coyote_time_multiplier),
+            ),
+            SettingsRow::ReducedCameraMotion => (
+                "settings.reduced_camera_motion",
+                on_off(settings.reduced_camera_motion),
+            ),
+            SettingsRow::Palette => ("settings.palette", value(format!("{:?}", settings.palette))),
+            SettingsRow::Language => (
+                "settings.language",
+                localization.language_name(&settings.language),
+            ),
+        };
+        localization.format(key, &[&shown])
+    }
+
+    /// Moves this row's option on to its next value, wrapping around
+    pub fn cycle(&self, settings: &mut GameSettings) {
+        let flip = |mode: ActionMode| match mode {
+            ActionMode::Hold => ActionMode::Toggle,
+            ActionMode::Toggle => ActionMode::Hold,
+        };
+        match self {
+            SettingsRow::Preset => {
+                settings.preset = match settings.preset {
+                    ControlPreset::Standard => ControlPreset::Swapped,
+                    ControlPreset::Swapped => ControlPreset::Standard,
+                }
+            }
+            SettingsRow::GrabMode => settings.grab_mode = flip(settings.grab_mode),
+            SettingsRow::CrouchMode => settings.crouch_mode = flip(settings.crouch_mode),
+            SettingsRow::AutoLedgeClimb => settings.auto_ledge_climb = !settings.auto_ledge_climb,
+            SettingsRow::CoyoteTime => {
+                let next = COYOTE_TIME_MULTIPLIERS
+                    .iter()
+                    .position(|multiplier| *multiplier == settings.coyote_time_multiplier)
+                    .map_or(0, |index| (index + 1) % COYOTE_TIME_MULTIPLIERS.len());
+                settings.coyote_time_multiplier = COYOTE_TIME_MULTIPLIERS[next];
+            }
+            SettingsRow::ReducedCameraMotion => {
+                settings.reduced_camera_motion = !settings.reduced_camera_motion
+            }
+            SettingsRow::Language => {
+                let next = LANGUAGES
+                    .iter()
+                    .position(|code| *code == settings.language)
+                    .map_or(0, |index| (index + 1) % LANGUAGES.len());
+                settings.language = LANGUAGES[next].to_string();
+            }
+            SettingsRow::Palette => {
+                settings.palette = match settings.palette {
+                    UiPalette::Classic => UiPalette::ColorblindSafe,
+                    UiPalette::ColorblindSafe => UiPalette::HighContrast,
+                    UiPalette::HighContrast => UiPalette::Classic,
+                }
+            }
+        }
+    }
+}
+
+/// Whether crouch is stuck down while it is in toggle mode
+#[derive(Resource, Default)]
+pub struct CrouchLatch {
+    pub latched: bool,
+    /// The button as it really was last frame, the action state itself gets overwritten
+    pub held: bool,
+}
+
+/// Turns a held button into the state a toggled one would have, or the other way around
+pub fn apply_action_modes(
+    settings: Res<GameSettings>,
+    mut crouch_latch: ResMut<CrouchLatch>,
+    mut player_query: Query<(&mut ActionState<PlayerAction>, Option<&HeldItem>), With<Player>>,
+) {
+    for (mut action, held_item) in &mut player_query {
+        if settings.grab_mode == ActionMode::Hold
+            && held_item.is_some()
+            && action.just_released(PlayerAction::Grab)
+        {
+            action.action_data_mut(PlayerAction::Grab).state = ButtonState::JustPressed;
+        }
+
+        let held = action.pressed(PlayerAction::Crouch);
+        let just_pressed = held && !crouch_latch.held;
+        crouch_latch.held = held;
+        if settings.crouch_mode == ActionMode::Toggle {
+            let was_latched = crouch_latch.latched;
+            if just_pressed {
+                crouch_latch.latched = !crouch_latch.latched;
+            }
+            action.action_data_mut(PlayerAction::Crouch).state =
+                latched_state(was_latched, crouch_latch.latched);
+        } else {
+            crouch_latch.latched = false;
+        }
+    }
+}
+
+pub fn latched_state(was_latched: bool, latched: bool) -> ButtonState {
+    match (was_latched, latched) {
+        (false, true) => ButtonState::JustPressed,
+        (true, true) => ButtonState::Pressed,
+        (true, false) => ButtonState::JustReleased,
+        (false, false) => ButtonState::Released,
+    }
+}
+
+fn apply_control_preset(
+    settings: Res<GameSettings>,
+    options: Option<Res<LaunchOptions>>,
+    spawned_query: Query<(), (With<Player>, Added<InputMap<PlayerAction>>)>,
+    mut player_query: Query<&mut InputMap<PlayerAction>, With<Player>>,
+) {
+    if !settings.is_changed() && spawned_query.is_empty() {
+        return;
+    }
+    let gamepad = options.map_or(0, |options| options.gamepad);
+    for mut input_map in &mut player_query {
+        let mut bindings = InputListenerBundle::build_input_map(gamepad, settings.preset);
+        Rebind::apply_to_player(&settings.rebinds, &mut bindings);
+        *input_map = bindings;
+    }
+}
+
+fn write_game_settings(settings: Res<GameSettings>, profile: Option<Res<ActiveProfile>>) {
+    let Some(profile) = profile else {return;};
+    // Switching profiles counts too, so the new one starts out with its own settings file
+    if settings.is_added() || !(settings.is_changed() || profile.is_changed()) {
+        return;
+    }
+    let written = ron::ser::to_string_pretty(&*settings, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|contents| write_save_file(&profile.settings_path(), &contents));
+    if let Err(error) = written {
+        println!("Couldn't write settings: {error}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn coyote_time_cycles_through_multipliers() {
+        let mut settings = GameSettings::default();
+        for expected in [1.5, 2.0, 3.0, 1.0] {
+            SettingsRow::CoyoteTime.cycle(&mut settings);
+            assert_eq!(settings.coyote_time_multiplier, expected);
+        }
+    }
+
+    #[test]
+    fn latched_crouch_only_just_presses_once() {
+        assert_eq!(latched_state(false, true), ButtonState::JustPressed);
+        assert_eq!(latched_state(true, true), ButtonState::Pressed);
+        assert_eq!(latched_state(true, false), ButtonState::JustReleased);
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let mut settings = GameSettings::default();
+        SettingsRow::GrabMode.cycle(&mut settings);
+        SettingsRow::ReducedCameraMotion.cycle(&mut settings);
+        let contents = ron::to_string(&settings).unwrap();
+        assert_eq!(ron::from_str::<GameSettings>(&contents).unwrap(), settings);
+    }
+}
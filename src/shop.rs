@@ -0,0 +1,285 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+use serde::Deserialize;
+
+use crate::{
+    spawn_prop, AppState, AssetCollection, EntityPools, Idea, IdeaChanged, InputContext,
+    InputDevice, Localization, MenuAction, PickupsInventory, Player, PlayerAction, PlayerIdeas,
+    PropData, UiTheme,
+};
+
+const SHOP_RANGE: f32 = 3.0;
+/// Where bought items are dropped, relative to the shopkeeper
+const SHOP_DELIVERY_OFFSET: Vec3 = Vec3::new(0.0, 2.0, 2.5);
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ShopMenu::default())
+            .add_system(spawn_shop_menu.in_schedule(OnEnter(AppState::Playing)))
+            // Using before opening keeps the press that opens the shop from also closing it
+            .add_systems((use_shop, open_shop, draw_shop_menu).chain());
+    }
+}
+
+/// Something a shopkeeper sells
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ShopItem {
+    Idea(Idea),
+    Crate,
+}
+
+impl ShopItem {
+    pub fn name(&self, localization: &Localization) -> String {
+        match self {
+            ShopItem::Idea(idea) => localization.format("shop.idea", &[&idea.to_string()]),
+            ShopItem::Crate => localization.text("shop.crate"),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct ShopStock {
+    pub item: ShopItem,
+    /// In coins
+    pub price: u8,
+}
+
+/// An NPC the player can walk up to and trade coins with
+#[derive(Component)]
+pub struct Shopkeeper {
+    pub stock: Vec<ShopStock>,
+}
+
+/// The shopkeeper being traded with, if any
+#[derive(Resource, Default)]
+pub struct ShopMenu {
+    pub shop: Option<Entity>,
+    pub selected: usize,
+}
+
+#[derive(Component)]
+pub struct ShopMenuRoot;
+
+#[derive(Component)]
+pub struct ShopMenuText;
+
+pub fn spawn_shopkeeper(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    stock: Vec<ShopStock>,
+) -> Entity {
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Capsule::default())),
+            material: materials.add(Color::PINK.into()),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(Collider::capsule_y(0.5, 0.5))
+        .insert(RigidBody::Fixed)
+        .insert(Shopkeeper { stock })
+        .id()
+}
+
+/// Ideas can only be bought once
+pub fn is_sold_out(item: ShopItem, player_ideas: &PlayerIdeas) -> bool {
+    match item {
+        ShopItem::Idea(idea) => player_ideas.ideas.contains(&idea),
+        ShopItem::Crate => false,
+    }
+}
+
+fn spawn_shop_menu(mut commands: Commands, assets: Res<AssetCollection>, theme: Res<UiTheme>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(40.0),
+                    top: Val::Px(80.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(16.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.1, 0.05, 0.1, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(3),
+            ..default()
+        })
+        .insert(ShopMenuRoot)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    theme.text_style(&assets.font, 26.0, theme.text),
+                ))
+                .insert(ShopMenuText);
+        });
+}
+
+pub fn open_shop(
+    mut shop_menu: ResMut<ShopMenu>,
+    mut input_context: ResMut<InputContext>,
+    player_query: Query<(&Transform, &ActionState<PlayerAction>), With<Player>>,
+    shop_query: Query<(Entity, &Transform), With<Shopkeeper>>,
+    mut root_query: Query<&mut Visibility, With<ShopMenuRoot>>,
+) {
+    if shop_menu.shop.is_some() {
+        return;
+    }
+    let Ok((player_transform, action)) = player_query.get_single() else {return;};
+    if !action.just_pressed(PlayerAction::Interact) {
+        return;
+    }
+    let nearby = shop_query.iter().find(|(_, shop_transform)| {
+        shop_transform
+            .translation
+            .distance(player_transform.translation)
+            <= SHOP_RANGE
+    });
+    let Some((shop, _)) = nearby else {return;};
+
+    shop_menu.shop = Some(shop);
+    shop_menu.selected = 0;
+    input_context.open_menu("shop");
+    for mut visibility in &mut root_query {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+pub fn use_shop(
+    mut commands: Commands,
+    mut pools: ResMut<EntityPools>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut shop_menu: ResMut<ShopMenu>,
+    mut inventory: ResMut<PickupsInventory>,
+    mut player_ideas: ResMut<PlayerIdeas>,
+    mut idea_events: EventWriter<IdeaChanged>,
+    mut input_context: ResMut<InputContext>,
+    shop_query: Query<(&Transform, &Shopkeeper)>,
+    mut root_query: Query<&mut Visibility, With<ShopMenuRoot>>,
+) {
+    let Some(shop) = shop_menu.shop else {return;};
+    let Ok((shop_transform, shopkeeper)) = shop_query.get(shop) else {
+        shop_menu.shop = None;
+        input_context.close_menu("shop");
+        return;
+    };
+    let last = shopkeeper.stock.len().saturating_sub(1);
+    if menu_actions.just_pressed(MenuAction::Up) {
+        shop_menu.selected = shop_menu.selected.saturating_sub(1);
+    }
+    if menu_actions.just_pressed(MenuAction::Down) {
+        shop_menu.selected = (shop_menu.selected + 1).min(last);
+    }
+
+    if menu_actions.just_pressed(MenuAction::Confirm) {
+        if let Some(stock) = shopkeeper.stock.get(shop_menu.selected) {
+            if !is_sold_out(stock.item, &player_ideas) && inventory.spend("Coins", stock.price) {
+                match stock.item {
+                    ShopItem::Idea(idea) => {
+                        player_ideas.get_idea(idea);
+                        idea_events.send(IdeaChanged::Gained(idea));
+                    }
+                    ShopItem::Crate => {
+                        let position = shop_transform.translation + SHOP_DELIVERY_OFFSET;
+                        spawn_prop(
+                            &mut commands,
+                            &mut pools,
+                            &mut meshes,
+                            &mut materials,
+                            &PropData::Crate {
+                                position: position.into(),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    if menu_actions.just_pressed(MenuAction::Back) {
+        shop_menu.shop = None;
+        input_context.close_menu("shop");
+        for mut visibility in &mut root_query {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+pub fn draw_shop_menu(
+    shop_menu: Res<ShopMenu>,
+    inventory: Res<PickupsInventory>,
+    player_ideas: Res<PlayerIdeas>,
+    mut idea_events: EventReader<IdeaChanged>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    device: Res<InputDevice>,
+    menu_bindings: Option<Res<InputMap<MenuAction>>>,
+    assets: Res<AssetCollection>,
+    shop_query: Query<&Shopkeeper>,
+    mut text_query: Query<&mut Text, With<ShopMenuText>>,
+) {
+    let ideas_changed = idea_events.iter().count() > 0;
+    let Some(shop) = shop_menu.shop else {return;};
+    let Ok(shopkeeper) = shop_query.get(shop) else {return;};
+    let changed = shop_menu.is_changed()
+        || inventory.is_changed()
+        || ideas_changed
+        || theme.is_changed()
+        || localization.is_changed()
+        || device.is_changed();
+    if !changed {
+        return;
+    }
+
+    let coins = inventory.get("Coins").to_string();
+    let mut lines = vec![localization.format("shop.coins", &[&coins])];
+    for (index, stock) in shopkeeper.stock.iter().enumerate() {
+        let cursor = if index == shop_menu.selected {
+            ">"
+        } else {
+            " "
+        };
+        let price = if is_sold_out(stock.item, &player_ideas) {
+            localization.text("shop.sold_out")
+        } else {
+            localization.format("shop.price", &[&stock.price.to_string()])
+        };
+        lines.push(format!(
+            "{cursor} {}  {price}",
+            stock.item.name(&localization)
+        ));
+    }
+    let Some(menu_bindings) = menu_bindings else {return;};
+    let buy = device.menu_label(&menu_bindings, MenuAction::Confirm);
+    let leave = device.menu_label(&menu_bindings, MenuAction::Back);
+    lines.push(localization.format("shop.hint", &[&buy, &leave]));
+
+    for mut text in &mut text_query {
+        text.sections[0].value = lines.join("\n");
+        text.sections[0].style.color = theme.text;
+        text.sections[0].style.font = localization.font(&assets.font);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn owned_ideas_are_sold_out() {
+        let player_ideas = PlayerIdeas::with_ideas(vec![Idea::Cube]);
+        assert!(is_sold_out(ShopItem::Idea(Idea::Cube), &player_ideas));
+        assert!(!is_sold_out(ShopItem::Idea(Idea::Wheel), &player_ideas));
+        assert!(!is_sold_out(ShopItem::Crate, &player_ideas));
+    }
+}
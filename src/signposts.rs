@@ -0,0 +1,309 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+
+use crate::{
+    spawn_action_glyph, AppState, AssetCollection, Busy, HeldItem, InputContext, InputDevice,
+    Localization, Localized, MenuAction, Player, PlayerAction, UiTheme,
+};
+
+const SIGNPOST_RANGE: f32 = 2.5;
+const SIGNPOST_PANEL_WIDTH: f32 = 520.0;
+
+pub struct SignpostPlugin;
+
+impl Plugin for SignpostPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SignpostReader::default())
+            .add_system(spawn_signpost_ui.in_schedule(OnEnter(AppState::Playing)))
+            // Closing before opening keeps the press that opens a sign from also closing it
+            .add_systems(
+                (
+                    close_signpost,
+                    open_signpost,
+                    show_signpost_prompt,
+                    draw_signpost,
+                )
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            );
+    }
+}
+
+/// A sign the player can walk up to and read with Grab
+#[derive(Component)]
+pub struct Signpost {
+    pub text: String,
+}
+
+/// The sign being read, if any
+#[derive(Resource, Default)]
+pub struct SignpostReader {
+    pub open: Option<Entity>,
+}
+
+/// Tells the player they can read the sign they're standing next to
+#[derive(Component)]
+pub struct SignpostPrompt;
+
+#[derive(Component)]
+pub struct SignpostPanel;
+
+#[derive(Component)]
+pub struct SignpostText;
+
+pub fn spawn_signpost(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    text: String,
+) -> Entity {
+    let wood = materials.add(Color::rgb(0.55, 0.38, 0.22).into());
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(0.15, 1.4, 0.15))),
+            material: wood.clone(),
+            transform: Transform::from_translation(position + Vec3::Y * 0.7),
+            ..default()
+        })
+        .insert(Collider::cuboid(0.075, 0.7, 0.075))
+        .insert(RigidBody::Fixed)
+        .insert(Signpost { text })
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(1.0, 0.6, 0.08))),
+                material: wood,
+                transform: Transform::from_xyz(0.0, 0.5, 0.0),
+                ..default()
+            });
+        })
+        .id()
+}
+
+/// The closest sign to `position` that's in reading range
+fn nearest_signpost<'a>(
+    position: Vec3,
+    signposts: impl Iterator<Item = (Entity, &'a GlobalTransform)>,
+) -> Option<Entity> {
+    signposts
+        .map(|(entity, transform)| (entity, transform.translation().distance(position)))
+        .filter(|(_, distance)| *distance <= SIGNPOST_RANGE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+fn spawn_signpost_ui(
+    mut commands: Commands,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+) {
+    let font = localization.font(&assets.font);
+    let style = |size: f32, color: Color| theme.text_style(&font, size, color);
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Percent(50.0),
+                    bottom: Val::Px(120.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(8.0)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: theme.panel.into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(SignpostPrompt)
+        .with_children(|parent| {
+            spawn_action_glyph(parent, PlayerAction::Grab, style(20.0, theme.text));
+            parent
+                .spawn(TextBundle::from_section(
+                    localization.text("signpost.read"),
+                    style(20.0, theme.text),
+                ))
+                .insert(Localized::new("signpost.read", 0));
+        });
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(3),
+            ..default()
+        })
+        .insert(SignpostPanel)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        max_size: Size::width(Val::Px(SIGNPOST_PANEL_WIDTH)),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: theme.panel.into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    panel
+                        .spawn(TextBundle::from_sections([
+                            TextSection::new("", style(24.0, theme.text)),
+                            TextSection::new("", style(18.0, theme.muted)),
+                        ]))
+                        .insert(SignpostText);
+                });
+        });
+}
+
+/// Reading holds the player still until the sign is closed again
+fn open_signpost(
+    mut commands: Commands,
+    mut reader: ResMut<SignpostReader>,
+    mut input_context: ResMut<InputContext>,
+    player_query: Query<
+        (Entity, &GlobalTransform, &ActionState<PlayerAction>),
+        (With<Player>, Without<HeldItem>),
+    >,
+    signpost_query: Query<(Entity, &GlobalTransform), With<Signpost>>,
+) {
+    if reader.open.is_some() {
+        return;
+    }
+    let Ok((player, player_transform, action)) = player_query.get_single() else {return;};
+    if !action.just_pressed(PlayerAction::Grab) {
+        return;
+    }
+    let nearest = nearest_signpost(player_transform.translation(), signpost_query.iter());
+    let Some(signpost) = nearest else {return;};
+
+    reader.open = Some(signpost);
+    input_context.open_menu("signpost");
+    commands.entity(player).insert(Busy::indefinitely());
+}
+
+fn close_signpost(
+    mut commands: Commands,
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut reader: ResMut<SignpostReader>,
+    mut input_context: ResMut<InputContext>,
+    player_query: Query<Entity, With<Player>>,
+    signpost_query: Query<(), With<Signpost>>,
+) {
+    let Some(signpost) = reader.open else {return;};
+    let closed = menu_actions.just_pressed(MenuAction::Confirm)
+        || menu_actions.just_pressed(MenuAction::Back)
+        || signpost_query.get(signpost).is_err();
+    if !closed {
+        return;
+    }
+    reader.open = None;
+    input_context.close_menu("signpost");
+    for player in &player_query {
+        commands.entity(player).remove::<Busy>();
+    }
+}
+
+fn show_signpost_prompt(
+    reader: Res<SignpostReader>,
+    player_query: Query<&GlobalTransform, (With<Player>, Without<HeldItem>)>,
+    signpost_query: Query<(Entity, &GlobalTransform), With<Signpost>>,
+    mut prompt_query: Query<&mut Visibility, With<SignpostPrompt>>,
+) {
+    let nearby = player_query.get_single().map_or(false, |player_transform| {
+        nearest_signpost(player_transform.translation(), signpost_query.iter()).is_some()
+    });
+    let visibility = if nearby && reader.open.is_none() {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut prompt_visibility in &mut prompt_query {
+        if *prompt_visibility != visibility {
+            *prompt_visibility = visibility;
+        }
+    }
+}
+
+fn draw_signpost(
+    reader: Res<SignpostReader>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    device: Res<InputDevice>,
+    menu_bindings: Option<Res<InputMap<MenuAction>>>,
+    assets: Res<AssetCollection>,
+    signpost_query: Query<&Signpost>,
+    mut panel_query: Query<&mut Visibility, With<SignpostPanel>>,
+    mut text_query: Query<&mut Text, With<SignpostText>>,
+) {
+    let changed = reader.is_changed()
+        || theme.is_changed()
+        || localization.is_changed()
+        || device.is_changed();
+    if !changed {
+        return;
+    }
+    let signpost = reader
+        .open
+        .and_then(|entity| signpost_query.get(entity).ok());
+    for mut visibility in &mut panel_query {
+        *visibility = if signpost.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    let Some(signpost) = signpost else {return;};
+    let Some(menu_bindings) = menu_bindings else {return;};
+    let close = device.menu_label(&menu_bindings, MenuAction::Confirm);
+    let hint = localization.format("signpost.close", &[&close]);
+
+    for mut text in &mut text_query {
+        text.sections[0].value = format!("{}\n\n", signpost.text);
+        text.sections[1].value = hint.clone();
+        for section in &mut text.sections {
+            section.style.font = localization.font(&assets.font);
+        }
+        text.sections[0].style.color = theme.text;
+        text.sections[1].style.color = theme.muted;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nearest_signpost_in_range_is_picked() {
+        let mut world = World::new();
+        let near = world.spawn_empty().id();
+        let nearer = world.spawn_empty().id();
+        let far = world.spawn_empty().id();
+        let signposts = [
+            (near, GlobalTransform::from_xyz(2.0, 0.0, 0.0)),
+            (nearer, GlobalTransform::from_xyz(0.0, 0.0, 1.0)),
+            (far, GlobalTransform::from_xyz(0.0, 0.0, 10.0)),
+        ];
+        let iter = || {
+            signposts
+                .iter()
+                .map(|(entity, transform)| (*entity, transform))
+        };
+
+        assert_eq!(nearest_signpost(Vec3::ZERO, iter()), Some(nearer));
+        assert_eq!(
+            nearest_signpost(Vec3::new(0.0, 0.0, 9.0), iter()),
+            Some(far)
+        );
+        assert_eq!(nearest_signpost(Vec3::new(0.0, 50.0, 0.0), iter()), None);
+    }
+}
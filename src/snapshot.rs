@@ -0,0 +1,310 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{
+    AddConsoleCommand, Coyote, Drift, Grounded, Item, Jump, JumpStage, Landing, LaunchOptions,
+    Momentum, Player, Walljump,
+};
+
+const PLAYER_GONE: &str = "the player in this snapshot is gone";
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(QuickSnapshot::default())
+            .add_console_command("snapshot", "snapshot [path]", take_snapshot_command)
+            .add_console_command("restore", "restore [path]", restore_snapshot_command);
+    }
+}
+
+/// Where a rigid body was and how it was moving
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct BodySnapshot {
+    /// `Entity::to_bits`, snapshots only restore onto the run they were taken in
+    pub entity: u64,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub linvel: Vec3,
+    pub angvel: Vec3,
+}
+
+impl BodySnapshot {
+    fn capture(entity: Entity, transform: &Transform, velocity: &Velocity) -> Self {
+        BodySnapshot {
+            entity: entity.to_bits(),
+            translation: transform.translation,
+            rotation: transform.rotation,
+            linvel: velocity.linvel,
+            angvel: velocity.angvel,
+        }
+    }
+
+    fn apply(&self, transform: &mut Transform, velocity: &mut Velocity) {
+        transform.translation = self.translation;
+        transform.rotation = self.rotation;
+        velocity.linvel = self.linvel;
+        velocity.angvel = self.angvel;
+    }
+}
+
+/// The player's body plus the movement state that decides what their next input does
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerSnapshot {
+    pub body: BodySnapshot,
+    pub momentum: f32,
+    pub drift: Vec3,
+    pub jump_stage: JumpStage,
+    pub jump_buffered: bool,
+    pub jump_input_elapsed: f32,
+    pub grounded: bool,
+    /// Seconds left on each timer, `None` when it wasn't running
+    pub coyote_remaining: Option<f32>,
+    pub landing_remaining: Option<f32>,
+    pub walljump_normal: Option<Vec3>,
+}
+
+/// Dynamic state of the player and loose items, enough to put a run back exactly where it was.
+/// Held items and anything the level spawned later are left as they are on restore
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct WorldSnapshot {
+    /// Generated level the snapshot was taken in, it can't be restored into another one
+    pub seed: Option<u64>,
+    pub player: Option<PlayerSnapshot>,
+    pub items: Vec<BodySnapshot>,
+}
+
+impl WorldSnapshot {
+    pub fn capture(world: &mut World) -> Self {
+        let seed = world
+            .get_resource::<LaunchOptions>()
+            .and_then(|options| options.seed);
+
+        let mut player_query = world.query_filtered::<(
+            Entity,
+            &Transform,
+            &Velocity,
+            &Momentum,
+            &Drift,
+            &Jump,
+            Option<&Grounded>,
+            Option<&Coyote>,
+            Option<&Landing>,
+            Option<&Walljump>,
+        ), With<Player>>();
+        let player = player_query.iter(world).next().map(
+            |(
+                entity,
+                transform,
+                velocity,
+                momentum,
+                drift,
+                jump,
+                grounded,
+                coyote,
+                landing,
+                walljump,
+            )| {
+                PlayerSnapshot {
+                    body: BodySnapshot::capture(entity, transform, velocity),
+                    momentum: momentum.get(),
+                    drift: drift.0,
+                    jump_stage: jump.jump_stage,
+                    jump_buffered: jump.jump_buffered,
+                    jump_input_elapsed: jump.input_timer.elapsed_secs(),
+                    grounded: grounded.is_some(),
+                    coyote_remaining: coyote.map(Coyote::remaining_secs),
+                    landing_remaining: landing.map(Landing::remaining_secs),
+                    walljump_normal: walljump.map(|walljump| walljump.0),
+                }
+            },
+        );
+
+        let mut item_query = world.query_filtered::<(Entity, &Transform, &Velocity), With<Item>>();
+        let items = item_query
+            .iter(world)
+            .map(|(entity, transform, velocity)| BodySnapshot::capture(entity, transform, velocity))
+            .collect();
+
+        WorldSnapshot {
+            seed,
+            player,
+            items,
+        }
+    }
+
+    /// Puts every body that still exists back, returning how many were restored
+    pub fn restore(&self, world: &mut World) -> Result<usize, String> {
+        let seed = world
+            .get_resource::<LaunchOptions>()
+            .and_then(|options| options.seed);
+        if seed != self.seed {
+            return Err("snapshot was taken in a different level".to_string());
+        }
+
+        let mut restored = 0;
+        if let Some(player) = &self.player {
+            let entity = Entity::from_bits(player.body.entity);
+            let Some(mut player_entity) = world.get_entity_mut(entity) else {return Err(PLAYER_GONE.to_string());};
+            if !player_entity.contains::<Player>() {
+                return Err(PLAYER_GONE.to_string());
+            }
+            if let Some(mut transform) = player_entity.get_mut::<Transform>() {
+                transform.translation = player.body.translation;
+                transform.rotation = player.body.rotation;
+            }
+            if let Some(mut velocity) = player_entity.get_mut::<Velocity>() {
+                velocity.linvel = player.body.linvel;
+                velocity.angvel = player.body.angvel;
+            }
+            if let Some(mut momentum) = player_entity.get_mut::<Momentum>() {
+                momentum.set(player.momentum);
+            }
+            if let Some(mut drift) = player_entity.get_mut::<Drift>() {
+                drift.set(player.drift);
+            }
+            if let Some(mut jump) = player_entity.get_mut::<Jump>() {
+                jump.jump_stage = player.jump_stage;
+                jump.jump_buffered = player.jump_buffered;
+                jump.input_timer
+                    .set_elapsed(Duration::from_secs_f32(player.jump_input_elapsed));
+            }
+
+            if player.grounded {
+                player_entity.insert(Grounded);
+            } else {
+                player_entity.remove::<Grounded>();
+            }
+            match player.coyote_remaining {
+                Some(seconds) => player_entity.insert(Coyote::new(seconds)),
+                None => player_entity.remove::<Coyote>(),
+            };
+            match player.landing_remaining {
+                Some(seconds) => player_entity.insert(Landing::lasting(seconds)),
+                None => player_entity.remove::<Landing>(),
+            };
+            match player.walljump_normal {
+                Some(normal) => player_entity.insert(Walljump(normal)),
+                None => player_entity.remove::<Walljump>(),
+            };
+            restored += 1;
+        }
+
+        let mut item_query = world.query_filtered::<(&mut Transform, &mut Velocity), With<Item>>();
+        for item in &self.items {
+            let Ok((mut transform, mut velocity)) =
+                item_query.get_mut(world, Entity::from_bits(item.entity))
+            else {
+                continue;
+            };
+            item.apply(&mut transform, &mut velocity);
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+    }
+
+    pub fn from_ron(contents: &str) -> Result<Self, String> {
+        ron::from_str(contents).map_err(|error| error.to_string())
+    }
+}
+
+/// The snapshot the `snapshot` console command took without a path
+#[derive(Resource, Default)]
+pub struct QuickSnapshot(pub Option<WorldSnapshot>);
+
+fn take_snapshot_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let snapshot = WorldSnapshot::capture(world);
+    let bodies = snapshot.items.len() + snapshot.player.iter().count();
+    match args.first() {
+        Some(path) => {
+            let contents = snapshot.to_ron()?;
+            std::fs::write(path, contents).map_err(|error| error.to_string())?;
+            Ok(format!("saved {bodies} bodies to {path}"))
+        }
+        None => {
+            world.resource_mut::<QuickSnapshot>().0 = Some(snapshot);
+            Ok(format!("snapshot of {bodies} bodies taken"))
+        }
+    }
+}
+
+fn restore_snapshot_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let snapshot = match args.first() {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+            WorldSnapshot::from_ron(&contents)?
+        }
+        None => world
+            .resource::<QuickSnapshot>()
+            .0
+            .clone()
+            .ok_or_else(|| "no snapshot taken yet".to_string())?,
+    };
+    let restored = snapshot.restore(world)?;
+    Ok(format!("restored {restored} bodies"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::headless::*;
+
+    #[test]
+    fn snapshots_survive_ron() {
+        let snapshot = WorldSnapshot {
+            seed: Some(7),
+            player: None,
+            items: vec![BodySnapshot {
+                entity: 3,
+                translation: Vec3::new(1.0, 2.0, 3.0),
+                rotation: Quat::from_rotation_y(0.5),
+                linvel: Vec3::Y,
+                angvel: Vec3::ZERO,
+            }],
+        };
+        let contents = snapshot.to_ron().unwrap();
+        assert_eq!(WorldSnapshot::from_ron(&contents).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn restoring_replays_the_same_fall() {
+        let mut app = headless_app();
+        let player = spawn_headless_player(&mut app, Vec3::new(0.0, 20.0, 0.0));
+        idle(&mut app, player, 5);
+
+        let snapshot = WorldSnapshot::capture(&mut app.world);
+        idle(&mut app, player, 10);
+        let first_run = WorldSnapshot::capture(&mut app.world);
+
+        idle(&mut app, player, 20);
+        assert_eq!(snapshot.restore(&mut app.world), Ok(1));
+        idle(&mut app, player, 10);
+        let second_run = WorldSnapshot::capture(&mut app.world);
+
+        let first = first_run.player.unwrap().body;
+        let second = second_run.player.unwrap().body;
+        assert!(
+            first.translation.distance(second.translation) < 0.01,
+            "{} vs {}",
+            first.translation,
+            second.translation
+        );
+        assert!(first.linvel.distance(second.linvel) < 0.01);
+    }
+
+    #[test]
+    fn snapshots_from_another_level_are_refused() {
+        let mut app = headless_app();
+        spawn_headless_player(&mut app, Vec3::Y * 5.0);
+        let mut snapshot = WorldSnapshot::capture(&mut app.world);
+        snapshot.seed = Some(42);
+        assert!(snapshot.restore(&mut app.world).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use bevy::{
+    core_pipeline::{bloom::BloomSettings, clear_color::ClearColorConfig},
+    prelude::*,
+    render::camera::Viewport,
+    window::PrimaryWindow,
+};
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{AppState, LaunchOptions, MainCamera};
+
+const SPECTATOR_SPEED: f32 = 12.0;
+/// Holding Boost flies this many times faster, for crossing a level between shots
+const SPECTATOR_BOOST: f32 = 3.0;
+/// Degrees per second at full stick
+const SPECTATOR_LOOK_SPEED: f32 = 90.0;
+const SPECTATOR_MAX_PITCH: f32 = 85.0;
+
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(InputManagerPlugin::<SpectatorAction>::default())
+            .init_resource::<ActionState<SpectatorAction>>()
+            .add_startup_system(bind_spectator_gamepad)
+            .add_systems(
+                (toggle_spectator, fly_spectator)
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            // Also puts the main camera back after the spectator goes with the rest of the level
+            .add_system(lay_out_views);
+    }
+}
+
+/// What the second controller does, it never touches the player
+#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Serialize, Deserialize)]
+pub enum SpectatorAction {
+    /// Starts and stops spectating
+    Toggle,
+    /// Swaps between sharing the screen with player one and taking all of it
+    FullScreen,
+    Move,
+    Look,
+    Rise,
+    Sink,
+    Boost,
+}
+
+impl SpectatorAction {
+    pub fn build_input_map(gamepad: usize) -> InputMap<SpectatorAction> {
+        use SpectatorAction::*;
+
+        let mut input_map = InputMap::new([
+            (GamepadButtonType::Start, Toggle),
+            (GamepadButtonType::Select, FullScreen),
+            (GamepadButtonType::RightTrigger, Rise),
+            (GamepadButtonType::LeftTrigger, Sink),
+            (GamepadButtonType::South, Boost),
+        ])
+        .set_gamepad(Gamepad { id: gamepad })
+        .build();
+
+        input_map
+            .insert(DualAxis::left_stick(), Move)
+            .insert(DualAxis::right_stick(), Look);
+
+        input_map
+    }
+}
+
+/// A free flying camera for a second controller, beside player one's view or over all of it
+#[derive(Component)]
+pub struct SpectatorCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub full_screen: bool,
+}
+
+/// Player one's view on the left half of the window and the spectator's on the right
+pub fn split_viewports(window_size: UVec2) -> (Viewport, Viewport) {
+    let left_width = window_size.x / 2;
+    let left = Viewport {
+        physical_position: UVec2::ZERO,
+        physical_size: UVec2::new(left_width, window_size.y),
+        ..default()
+    };
+    let right = Viewport {
+        physical_position: UVec2::new(left_width, 0),
+        physical_size: UVec2::new(window_size.x - left_width, window_size.y),
+        ..default()
+    };
+    (left, right)
+}
+
+/// The spectator uses the controller after player one's
+fn bind_spectator_gamepad(mut commands: Commands, options: Option<Res<LaunchOptions>>) {
+    let gamepad = options.map_or(0, |options| options.gamepad) + 1;
+    commands.insert_resource(SpectatorAction::build_input_map(gamepad));
+}
+
+/// Starts where the main camera is looking from, so the first shot picks up where play is
+fn toggle_spectator(
+    mut commands: Commands,
+    actions: Res<ActionState<SpectatorAction>>,
+    main_camera_query: Query<&Transform, With<MainCamera>>,
+    mut spectator_query: Query<(Entity, &mut SpectatorCamera)>,
+) {
+    if let Ok((entity, mut spectator)) = spectator_query.get_single_mut() {
+        if actions.just_pressed(SpectatorAction::Toggle) {
+            commands.entity(entity).despawn_recursive();
+        } else if actions.just_pressed(SpectatorAction::FullScreen) {
+            spectator.full_screen = !spectator.full_screen;
+        }
+        return;
+    }
+    if !actions.just_pressed(SpectatorAction::Toggle) {
+        return;
+    }
+
+    let transform = main_camera_query.get_single().copied().unwrap_or_default();
+    let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+    commands
+        .spawn(Camera3dBundle {
+            camera_3d: Camera3d {
+                // Player one's camera already cleared the window, unless it's full screen
+                clear_color: ClearColorConfig::None,
+                ..default()
+            },
+            camera: Camera {
+                // After the main and UI cameras, so it draws over both when full screen
+                order: 2,
+                hdr: true,
+                ..default()
+            },
+            transform,
+            ..default()
+        })
+        .insert(BloomSettings::default())
+        .insert(UiCameraConfig { show_ui: false })
+        .insert(Name::new("Spectator Camera"))
+        .insert(SpectatorCamera {
+            yaw: yaw.to_degrees(),
+            pitch: pitch.to_degrees(),
+            full_screen: false,
+        });
+}
+
+fn fly_spectator(
+    time: Res<Time>,
+    actions: Res<ActionState<SpectatorAction>>,
+    mut spectator_query: Query<(&mut Transform, &mut SpectatorCamera)>,
+) {
+    let Ok((mut transform, mut spectator)) = spectator_query.get_single_mut() else {return;};
+    let delta_seconds = time.delta_seconds();
+
+    if let Some(look) = actions.clamped_axis_pair(SpectatorAction::Look) {
+        let step = SPECTATOR_LOOK_SPEED * delta_seconds;
+        spectator.yaw -= look.x() * step;
+        spectator.pitch =
+            (spectator.pitch + look.y() * step).clamp(-SPECTATOR_MAX_PITCH, SPECTATOR_MAX_PITCH);
+    }
+    transform.rotation = Quat::from_euler(
+        EulerRot::YXZ,
+        spectator.yaw.to_radians(),
+        spectator.pitch.to_radians(),
+        0.0,
+    );
+
+    let mut direction = Vec3::ZERO;
+    if let Some(stick) = actions.clamped_axis_pair(SpectatorAction::Move) {
+        direction += transform.forward() * stick.y() + transform.right() * stick.x();
+    }
+    direction +=
+        Vec3::Y * (actions.value(SpectatorAction::Rise) - actions.value(SpectatorAction::Sink));
+    let boost = if actions.pressed(SpectatorAction::Boost) {
+        SPECTATOR_BOOST
+    } else {
+        1.0
+    };
+    transform.translation +=
+        direction.clamp_length_max(1.0) * SPECTATOR_SPEED * boost * delta_seconds;
+}
+
+/// Splits the window while spectating, hands all of it to one camera otherwise
+fn lay_out_views(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut main_camera_query: Query<&mut Camera, (With<MainCamera>, Without<SpectatorCamera>)>,
+    mut spectator_query: Query<(&mut Camera, &mut Camera3d, &SpectatorCamera)>,
+) {
+    let Ok(window) = window_query.get_single() else {return;};
+    let Ok(mut main_camera) = main_camera_query.get_single_mut() else {return;};
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    let (left, right) = split_viewports(window_size);
+
+    let (main_viewport, main_active) = match spectator_query.get_single_mut() {
+        Ok((mut camera, mut camera_3d, spectator)) if spectator.full_screen => {
+            camera.viewport = None;
+            // Nothing else clears the window with player one's camera switched off
+            set_clear_color(&mut camera_3d, ClearColorConfig::Default);
+            (None, false)
+        }
+        Ok((mut camera, mut camera_3d, _)) => {
+            camera.viewport = Some(right);
+            set_clear_color(&mut camera_3d, ClearColorConfig::None);
+            (Some(left), true)
+        }
+        Err(_) => (None, true),
+    };
+    main_camera.viewport = main_viewport;
+    if main_camera.is_active != main_active {
+        main_camera.is_active = main_active;
+    }
+}
+
+fn set_clear_color(camera_3d: &mut Mut<Camera3d>, clear_color: ClearColorConfig) {
+    if !matches!(
+        (&camera_3d.clear_color, &clear_color),
+        (ClearColorConfig::Default, ClearColorConfig::Default)
+            | (ClearColorConfig::None, ClearColorConfig::None)
+    ) {
+        camera_3d.clear_color = clear_color;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_viewports_cover_the_window_between_them() {
+        let (left, right) = split_viewports(UVec2::new(1281, 720));
+        assert_eq!(left.physical_position, UVec2::ZERO);
+        assert_eq!(left.physical_size, UVec2::new(640, 720));
+        assert_eq!(right.physical_position, UVec2::new(640, 0));
+        assert_eq!(right.physical_size, UVec2::new(641, 720));
+    }
+}
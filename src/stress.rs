@@ -0,0 +1,220 @@
+use bevy::{
+    diagnostic::{Diagnostics, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::{spawn_prop, AppState, AssetCollection, EntityPools, LaunchOptions, PropData};
+
+const STRESS_SPACING: f32 = 3.0;
+const STRESS_HEIGHT: f32 = 12.0;
+
+pub struct StressPlugin;
+
+impl Plugin for StressPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StressConfig::from_args(std::env::args()))
+            .add_plugin(FrameTimeDiagnosticsPlugin::default())
+            .add_plugin(EntityCountDiagnosticsPlugin::default())
+            .add_systems(
+                (spawn_stress_scene, spawn_performance_overlay)
+                    .in_schedule(OnEnter(AppState::Playing)),
+            )
+            .add_system(update_performance_overlay);
+    }
+}
+
+/// Set with `--stress`, counts can be changed with `--stress-items=N`, `--stress-chains=N`
+/// and `--stress-wind=N`
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct StressConfig {
+    pub enabled: bool,
+    pub items: usize,
+    pub chains: usize,
+    pub wind_zones: usize,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        StressConfig {
+            enabled: false,
+            items: 200,
+            chains: 10,
+            wind_zones: 6,
+        }
+    }
+}
+
+impl StressConfig {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut config = StressConfig::default();
+        for arg in args {
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), value.parse::<usize>().ok()),
+                None => (arg, None),
+            };
+            match (flag.as_str(), value) {
+                ("--stress", _) => config.enabled = true,
+                ("--stress-items", Some(count)) => config.items = count,
+                ("--stress-chains", Some(count)) => config.chains = count,
+                ("--stress-wind", Some(count)) => config.wind_zones = count,
+                _ => (),
+            }
+        }
+        config
+    }
+}
+
+#[derive(Component)]
+pub struct PerformanceOverlay;
+
+/// Lays `count` positions out on a square grid centered over the origin
+fn grid(count: usize, spacing: f32, height: f32) -> impl Iterator<Item = [f32; 3]> {
+    let side = (count as f32).sqrt().ceil().max(1.0) as usize;
+    let offset = (side as f32 - 1.0) * spacing / 2.0;
+    (0..count).map(move |i| {
+        [
+            (i % side) as f32 * spacing - offset,
+            height,
+            (i / side) as f32 * spacing - offset,
+        ]
+    })
+}
+
+pub fn spawn_stress_scene(
+    mut commands: Commands,
+    mut pools: ResMut<EntityPools>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<StressConfig>,
+) {
+    if !config.enabled {
+        return;
+    }
+
+    let mut props = Vec::new();
+    props.extend(
+        grid(config.items, STRESS_SPACING, STRESS_HEIGHT)
+            .map(|position| PropData::Crate { position }),
+    );
+    props.extend(
+        grid(config.chains, STRESS_SPACING * 4.0, STRESS_HEIGHT * 1.5)
+            .map(|anchor| PropData::Chain { anchor, links: 12 }),
+    );
+    props.extend(
+        grid(config.wind_zones, STRESS_SPACING * 6.0, 2.0).map(|position| PropData::WindZone {
+            position,
+            half_extents: [3.0, 2.0, 3.0],
+            force: [0.0, 0.0, 6.0],
+        }),
+    );
+
+    for prop in &props {
+        spawn_prop(&mut commands, &mut pools, &mut meshes, &mut materials, prop);
+    }
+}
+
+fn spawn_performance_overlay(
+    mut commands: Commands,
+    assets: Res<AssetCollection>,
+    config: Res<StressConfig>,
+    options: Res<LaunchOptions>,
+) {
+    let style = TextStyle {
+        font: assets.font.clone(),
+        font_size: 18.0,
+        color: Color::YELLOW,
+    };
+    commands
+        .spawn(
+            TextBundle::from_sections([
+                TextSection::new("Frame: ", style.clone()),
+                TextSection::from_style(style.clone()),
+                TextSection::new("\nPhysics: ", style.clone()),
+                TextSection::from_style(style.clone()),
+                TextSection::new("\nEntities: ", style.clone()),
+                TextSection::from_style(style.clone()),
+                TextSection::new("\nPools:\n", style.clone()),
+                TextSection::from_style(style),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(if config.enabled || options.debug_overlay {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        })
+        .insert(PerformanceOverlay);
+}
+
+pub fn update_performance_overlay(
+    diagnostics: Res<Diagnostics>,
+    mut rapier_context: ResMut<RapierContext>,
+    pools: Res<EntityPools>,
+    mut overlay_query: Query<(&mut Text, &Visibility), With<PerformanceOverlay>>,
+) {
+    let Ok((mut text, visibility)) = overlay_query.get_single_mut() else {return;};
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let counters = &mut rapier_context.pipeline.counters;
+    if !counters.enabled() {
+        counters.enable();
+    }
+
+    let frame_time = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed());
+    let entities = diagnostics
+        .get(EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+        .and_then(|count| count.value());
+
+    text.sections[1].value = frame_time.map_or("-".to_string(), |ms| format!("{ms:.2}ms"));
+    text.sections[3].value = format!("{:.2}ms", counters.step_time());
+    text.sections[5].value = entities.map_or("-".to_string(), |count| format!("{count:.0}"));
+    text.sections[7].value = pools.summary();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn stress_is_off_without_the_flag() {
+        let config = StressConfig::from_args(args(&["paintbrush", "--stress-items=5"]));
+        assert!(!config.enabled);
+        assert_eq!(config.items, 5);
+    }
+
+    #[test]
+    fn stress_counts_are_read_from_flags() {
+        let config = StressConfig::from_args(args(&[
+            "paintbrush",
+            "--stress",
+            "--stress-chains=3",
+            "--stress-wind=bogus",
+        ]));
+        assert!(config.enabled);
+        assert_eq!(config.chains, 3);
+        assert_eq!(config.wind_zones, StressConfig::default().wind_zones);
+    }
+
+    #[test]
+    fn grid_yields_every_position() {
+        assert_eq!(grid(10, 2.0, 1.0).count(), 10);
+        assert_eq!(grid(0, 2.0, 1.0).count(), 0);
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::{
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        mesh::MeshVertexBufferLayout,
+        render_resource::{
+            AsBindGroup, Face, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+    },
+};
+
+use crate::{Creation, Item, Player};
+
+const DEFAULT_OUTLINE_WIDTH: f32 = 0.04;
+const DEFAULT_BANDS: f32 = 3.0;
+
+pub struct StylizedPlugin;
+
+impl Plugin for StylizedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(MaterialPlugin::<ToonMaterial>::default())
+            .add_plugin(MaterialPlugin::<OutlineMaterial>::default())
+            .add_systems((stylize_characters_and_items, apply_stylized).chain());
+    }
+}
+
+/// Opts an entity into cel shading and an outline, its standard material is swapped out on insert
+#[derive(Component, Clone, Copy)]
+pub struct Stylized {
+    pub outline_width: f32,
+    pub outline_color: Color,
+    pub bands: f32,
+}
+
+impl Default for Stylized {
+    fn default() -> Self {
+        Stylized {
+            outline_width: DEFAULT_OUTLINE_WIDTH,
+            outline_color: Color::BLACK,
+            bands: DEFAULT_BANDS,
+        }
+    }
+}
+
+/// Lighting snapped to a few flat bands between `shadow_color` and `color`
+#[derive(AsBindGroup, TypeUuid, Clone)]
+#[uuid = "3c6f1a2e-92d4-4b7a-a0e5-5d8b7c14f2a9"]
+pub struct ToonMaterial {
+    #[uniform(0)]
+    pub color: Color,
+    #[uniform(0)]
+    pub shadow_color: Color,
+    #[uniform(0)]
+    pub bands: f32,
+}
+
+impl Material for ToonMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/toon.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        if self.color.a() < 1.0 {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        }
+    }
+}
+
+/// Drawn on a copy of the mesh with its back faces pushed out, leaving a solid edge around it
+#[derive(AsBindGroup, TypeUuid, Clone)]
+#[uuid = "b81e4d07-5f3a-4c29-8e6d-27a9c0f3e5b1"]
+pub struct OutlineMaterial {
+    #[uniform(0)]
+    pub color: Color,
+    #[uniform(0)]
+    pub width: f32,
+}
+
+impl Material for OutlineMaterial {
+    fn vertex_shader() -> ShaderRef {
+        "shaders/outline.wgsl".into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        "shaders/outline.wgsl".into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayout,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout = layout.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+        ])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+        descriptor.primitive.cull_mode = Some(Face::Front);
+        Ok(())
+    }
+}
+
+/// The player, items and creations are always drawn stylized
+pub fn stylize_characters_and_items(
+    mut commands: Commands,
+    query: Query<
+        Entity,
+        (
+            Or<(With<Player>, With<Item>, With<Creation>)>,
+            With<Handle<StandardMaterial>>,
+            Without<Stylized>,
+        ),
+    >,
+) {
+    for entity in &query {
+        commands.entity(entity).insert(Stylized::default());
+    }
+}
+
+pub fn apply_stylized(
+    mut commands: Commands,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut toon_materials: ResMut<Assets<ToonMaterial>>,
+    mut outline_materials: ResMut<Assets<OutlineMaterial>>,
+    query: Query<(Entity, &Stylized, &Handle<Mesh>, &Handle<StandardMaterial>), Added<Stylized>>,
+) {
+    for (entity, stylized, mesh, material) in &query {
+        let color = standard_materials
+            .get(material)
+            .map_or(Color::WHITE, |material| material.base_color);
+        let [r, g, b, a] = color.as_rgba_f32();
+        let toon = toon_materials.add(ToonMaterial {
+            color,
+            shadow_color: Color::rgba(r * 0.45, g * 0.4, b * 0.55, a),
+            bands: stylized.bands,
+        });
+        let outline = outline_materials.add(OutlineMaterial {
+            color: stylized.outline_color,
+            width: stylized.outline_width,
+        });
+
+        commands
+            .entity(entity)
+            .remove::<Handle<StandardMaterial>>()
+            .insert(toon)
+            .with_children(|parent| {
+                parent.spawn(MaterialMeshBundle {
+                    mesh: mesh.clone(),
+                    material: outline,
+                    ..default()
+                });
+            });
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::LaunchOptions;
+
+pub struct TimeScalePlugin;
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TimeScale::default()).add_systems(
+            (step_frames, apply_time_scale)
+                .chain()
+                .in_base_set(CoreSet::PreUpdate),
+        );
+    }
+}
+
+/// A temporary change of speed, counted down in real seconds so it isn't stretched by itself
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Slowdown {
+    scale: f32,
+    remaining: f32,
+}
+
+/// How fast gameplay runs. Every timer, animation and the Rapier step read the scaled `Time`,
+/// so slowing this slows the whole game together
+#[derive(Resource, Debug)]
+pub struct TimeScale {
+    base: f32,
+    slowdowns: Vec<Slowdown>,
+    holds: Vec<(&'static str, f32)>,
+    paused: bool,
+    step: bool,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale {
+            base: 1.0,
+            slowdowns: Vec::new(),
+            holds: Vec::new(),
+            paused: false,
+            step: false,
+        }
+    }
+}
+
+impl TimeScale {
+    pub fn set(&mut self, scale: f32) {
+        self.base = scale.max(0.0);
+    }
+
+    pub fn base(&self) -> f32 {
+        self.base
+    }
+
+    /// Slows the game to `scale` for `seconds` of real time, e.g. a dramatic ledge catch
+    pub fn slow_down(&mut self, scale: f32, seconds: f32) {
+        self.slowdowns.push(Slowdown {
+            scale: scale.max(0.0),
+            remaining: seconds,
+        });
+    }
+
+    /// Slows the game to `scale` until `release` is called with the same `source`,
+    /// for things like menus that stay open as long as the player wants
+    pub fn hold(&mut self, source: &'static str, scale: f32) {
+        self.release(source);
+        self.holds.push((source, scale.max(0.0)));
+    }
+
+    pub fn release(&mut self, source: &'static str) {
+        self.holds.retain(|(held_by, _)| *held_by != source);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.step = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Lets a single frame through while paused
+    pub fn step(&mut self) {
+        if self.paused {
+            self.step = true;
+        }
+    }
+
+    /// The slowest of everything currently asking for a change, zero while paused
+    pub fn current(&self) -> f32 {
+        if self.paused && !self.step {
+            return 0.0;
+        }
+        self.slowdowns
+            .iter()
+            .map(|slowdown| slowdown.scale)
+            .chain(self.holds.iter().map(|(_, scale)| *scale))
+            .fold(self.base, f32::min)
+    }
+
+    pub fn tick(&mut self, real_seconds: f32) {
+        for slowdown in &mut self.slowdowns {
+            slowdown.remaining -= real_seconds;
+        }
+        self.slowdowns.retain(|slowdown| slowdown.remaining > 0.0);
+    }
+}
+
+/// F9 freezes the game and F10 advances it a frame at a time, with the `--debug` flag
+pub fn step_frames(
+    keyboard: Res<Input<KeyCode>>,
+    options: Option<Res<LaunchOptions>>,
+    mut time_scale: ResMut<TimeScale>,
+) {
+    if time_scale.step {
+        time_scale.step = false;
+    }
+    if !options.map_or(false, |options| options.debug_overlay) {
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::F9) {
+        time_scale.toggle_pause();
+    }
+    if keyboard.just_pressed(KeyCode::F10) {
+        time_scale.step();
+    }
+}
+
+/// Runs after `Time` has updated, so the new speed takes hold from the next frame
+pub fn apply_time_scale(
+    mut time: ResMut<Time>,
+    mut time_scale: ResMut<TimeScale>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    // This frame's delta was measured at the old speed, physics has to agree with it
+    rapier_config.physics_pipeline_active = time.relative_speed() > 0.0;
+    time_scale.tick(time.raw_delta_seconds());
+    let scale = time_scale.current();
+    if time.relative_speed() != scale {
+        time.set_relative_speed(scale);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn slowest_request_wins() {
+        let mut time_scale = TimeScale::default();
+        time_scale.set(0.8);
+        time_scale.slow_down(0.5, 1.0);
+        time_scale.hold("menu", 0.2);
+        assert_eq!(time_scale.current(), 0.2);
+        time_scale.release("menu");
+        assert_eq!(time_scale.current(), 0.5);
+    }
+
+    #[test]
+    fn slowdowns_wear_off() {
+        let mut time_scale = TimeScale::default();
+        time_scale.slow_down(0.25, 0.5);
+        time_scale.tick(0.3);
+        assert_eq!(time_scale.current(), 0.25);
+        time_scale.tick(0.3);
+        assert_eq!(time_scale.current(), 1.0);
+    }
+
+    #[test]
+    fn paused_time_only_moves_when_stepped() {
+        let mut time_scale = TimeScale::default();
+        time_scale.toggle_pause();
+        assert_eq!(time_scale.current(), 0.0);
+        time_scale.step();
+        assert_eq!(time_scale.current(), 1.0);
+        time_scale.toggle_pause();
+        time_scale.step();
+        assert_eq!(time_scale.current(), 1.0);
+    }
+}
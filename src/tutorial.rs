@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    AppState, CreationBuilt, CurrentLevel, Grounded, JumpStage, Jumped, LedgeGrab, LevelData,
+    LevelSignal, Player, ThrownItem,
+};
+
+/// Further than this in one frame is a respawn or a teleport, not walking
+const TUTORIAL_MAX_STEP: f32 = 2.0;
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TutorialProgress>()
+            .insert_resource(Tutorial::default())
+            .add_system(load_level_tutorial)
+            .add_systems(
+                (
+                    track_tutorial_movement,
+                    track_tutorial_jumps,
+                    track_tutorial_ledges,
+                    track_tutorial_throws,
+                    track_tutorial_builds,
+                    advance_tutorial,
+                )
+                    .chain()
+                    .after(load_level_tutorial)
+                    .in_set(OnUpdate(AppState::Playing)),
+            );
+    }
+}
+
+/// The mechanics a tutorial step can ask for
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TutorialObjective {
+    /// Metres covered on the ground
+    Move,
+    /// Triple jumps landed, chained off a single and a double
+    JumpChain,
+    WallJump,
+    LedgeGrab,
+    Throw,
+    /// Creations built out of ideas
+    BuildIdea,
+}
+
+/// One step of a level's tutorial as written in the level file, `unlocks` is sent as a
+/// `LevelSignal` once it's done, opening whichever doors gate the next section
+#[derive(Deserialize, Clone, Debug)]
+pub struct TutorialStep {
+    pub objective: TutorialObjective,
+    #[serde(default = "default_tutorial_amount")]
+    pub amount: f32,
+    pub unlocks: String,
+}
+
+fn default_tutorial_amount() -> f32 {
+    1.0
+}
+
+/// Sent whenever the player does something a tutorial step could be waiting for
+#[derive(Clone, Copy, Debug)]
+pub struct TutorialProgress {
+    pub objective: TutorialObjective,
+    pub amount: f32,
+}
+
+impl TutorialProgress {
+    pub fn once(objective: TutorialObjective) -> Self {
+        TutorialProgress {
+            objective,
+            amount: 1.0,
+        }
+    }
+}
+
+/// The current level's tutorial steps, taken strictly in order
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    pub steps: Vec<TutorialStep>,
+    pub current: usize,
+    pub progress: f32,
+}
+
+impl Tutorial {
+    pub fn new(steps: &[TutorialStep]) -> Self {
+        Tutorial {
+            steps: steps.to_vec(),
+            current: 0,
+            progress: 0.0,
+        }
+    }
+
+    pub fn current_step(&self) -> Option<&TutorialStep> {
+        self.steps.get(self.current)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    /// Counts `progress` towards the current step, anything a later step asks for is ignored until
+    /// it's reached. Returns the signal to send when this finished the step
+    pub fn record(&mut self, progress: TutorialProgress) -> Option<String> {
+        let step = self.current_step()?;
+        if step.objective != progress.objective {
+            return None;
+        }
+        self.progress += progress.amount;
+        if self.progress < step.amount {
+            return None;
+        }
+        let unlocks = step.unlocks.clone();
+        self.current += 1;
+        self.progress = 0.0;
+        Some(unlocks)
+    }
+}
+
+pub fn load_level_tutorial(
+    mut tutorial: ResMut<Tutorial>,
+    mut level_events: EventReader<AssetEvent<LevelData>>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<LevelData>>,
+) {
+    let Some(current_level) = current_level else {return;};
+    // Picking a level starts its tutorial over with every door shut again
+    let mut reload = current_level.is_changed();
+    for event in level_events.iter() {
+        let AssetEvent::Created { handle } = event else {continue;};
+        reload |= *handle == current_level.0;
+    }
+    if !reload {
+        return;
+    }
+    if let Some(level) = levels.get(&current_level.0) {
+        *tutorial = Tutorial::new(&level.tutorial);
+    }
+}
+
+fn track_tutorial_movement(
+    mut progress_events: EventWriter<TutorialProgress>,
+    mut last_position: Local<Option<Vec3>>,
+    player_query: Query<(&Transform, Option<&Grounded>), With<Player>>,
+) {
+    let Ok((transform, grounded)) = player_query.get_single() else {return;};
+    let position = transform.translation;
+    let step = last_position.replace(position).map_or(0.0, |last| {
+        Vec2::new(position.x - last.x, position.z - last.z).length()
+    });
+    if grounded.is_some() && step > 0.0 && step < TUTORIAL_MAX_STEP {
+        progress_events.send(TutorialProgress {
+            objective: TutorialObjective::Move,
+            amount: step,
+        });
+    }
+}
+
+fn track_tutorial_jumps(
+    mut progress_events: EventWriter<TutorialProgress>,
+    mut jump_events: EventReader<Jumped>,
+) {
+    for jumped in jump_events.iter() {
+        match jumped {
+            Jumped::Stage(JumpStage::Triple) => {
+                progress_events.send(TutorialProgress::once(TutorialObjective::JumpChain));
+            }
+            Jumped::Wall => {
+                progress_events.send(TutorialProgress::once(TutorialObjective::WallJump));
+            }
+            _ => (),
+        }
+    }
+}
+
+fn track_tutorial_ledges(
+    mut progress_events: EventWriter<TutorialProgress>,
+    grab_query: Query<(), (With<Player>, Added<LedgeGrab>)>,
+) {
+    for _ in &grab_query {
+        progress_events.send(TutorialProgress::once(TutorialObjective::LedgeGrab));
+    }
+}
+
+fn track_tutorial_throws(
+    mut progress_events: EventWriter<TutorialProgress>,
+    thrown_query: Query<(), Added<ThrownItem>>,
+) {
+    for _ in &thrown_query {
+        progress_events.send(TutorialProgress::once(TutorialObjective::Throw));
+    }
+}
+
+fn track_tutorial_builds(
+    mut progress_events: EventWriter<TutorialProgress>,
+    mut built_events: EventReader<CreationBuilt>,
+) {
+    for _ in built_events.iter() {
+        progress_events.send(TutorialProgress::once(TutorialObjective::BuildIdea));
+    }
+}
+
+fn advance_tutorial(
+    mut tutorial: ResMut<Tutorial>,
+    mut progress_events: EventReader<TutorialProgress>,
+    mut signals: EventWriter<LevelSignal>,
+) {
+    for progress in progress_events.iter() {
+        let Some(unlocks) = tutorial.record(*progress) else {continue;};
+        signals.send(LevelSignal {
+            name: unlocks,
+            active: true,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn step(objective: TutorialObjective, amount: f32, unlocks: &str) -> TutorialStep {
+        TutorialStep {
+            objective,
+            amount,
+            unlocks: unlocks.to_string(),
+        }
+    }
+
+    #[test]
+    fn steps_unlock_in_order() {
+        let mut tutorial = Tutorial::new(&[
+            step(TutorialObjective::Move, 5.0, "moved"),
+            step(TutorialObjective::WallJump, 1.0, "wall_jumped"),
+        ]);
+        // Running ahead doesn't count towards a later step
+        assert!(tutorial
+            .record(TutorialProgress::once(TutorialObjective::WallJump))
+            .is_none());
+        let walk = TutorialProgress {
+            objective: TutorialObjective::Move,
+            amount: 3.0,
+        };
+        assert!(tutorial.record(walk).is_none());
+        assert_eq!(tutorial.record(walk).as_deref(), Some("moved"));
+        assert_eq!(
+            tutorial
+                .record(TutorialProgress::once(TutorialObjective::WallJump))
+                .as_deref(),
+            Some("wall_jumped")
+        );
+        assert!(tutorial.is_complete());
+        assert!(tutorial.record(walk).is_none());
+    }
+}
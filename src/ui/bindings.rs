@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+
+use crate::{IdeaChanged, Localization, PlayerIdeas};
+
+pub struct UiBindingPlugin;
+
+impl Plugin for UiBindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(update_bound_text);
+    }
+}
+
+/// Something about the player's ideas a HUD widget can show
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HudValue {
+    /// The idea ready to load
+    SelectedIdea,
+    /// The idea in a loaded slot, in load order
+    LoadedIdea(usize),
+}
+
+impl HudValue {
+    pub fn read(&self, player_ideas: &PlayerIdeas) -> Option<String> {
+        match self {
+            HudValue::SelectedIdea => player_ideas.get_current_idea_tag(),
+            HudValue::LoadedIdea(index) => player_ideas.get_loaded_idea_at(*index),
+        }
+    }
+}
+
+/// Keeps a section of this entity's `Text` showing `value`, or the localized "hud.empty" without
+/// one
+#[derive(Component)]
+pub struct BoundText {
+    pub value: HudValue,
+    pub section: usize,
+}
+
+impl BoundText {
+    pub fn new(value: HudValue, section: usize) -> Self {
+        BoundText { value, section }
+    }
+}
+
+pub fn update_bound_text(
+    player_ideas: Res<PlayerIdeas>,
+    localization: Res<Localization>,
+    mut idea_events: EventReader<IdeaChanged>,
+    added_query: Query<(), Added<BoundText>>,
+    mut query: Query<(&BoundText, &mut Text)>,
+) {
+    let ideas_changed = idea_events.iter().count() > 0;
+    if !(ideas_changed || localization.is_changed() || !added_query.is_empty()) {
+        return;
+    }
+    for (bound, mut text) in &mut query {
+        let Some(section) = text.sections.get_mut(bound.section) else {continue;};
+        section.value = bound
+            .value
+            .read(&player_ideas)
+            .unwrap_or_else(|| localization.text("hud.empty"));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Idea;
+
+    #[test]
+    fn values_read_from_player_ideas() {
+        let mut player_ideas = PlayerIdeas::with_ideas(vec![Idea::Cube, Idea::Spring]);
+        player_ideas.load_idea();
+        assert_eq!(
+            HudValue::SelectedIdea.read(&player_ideas),
+            Some(Idea::Spring.to_string())
+        );
+        assert_eq!(
+            HudValue::LoadedIdea(0).read(&player_ideas),
+            Some(Idea::Cube.to_string())
+        );
+        assert_eq!(HudValue::LoadedIdea(1).read(&player_ideas), None);
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{axislike::AxisType, prelude::*, user_input::InputKind};
+
+use crate::{is_gamepad_input, MenuAction, Player, PlayerAction};
+
+/// Pads whose name contains one of these get PlayStation glyphs, every other pad gets Xbox ones
+const PLAYSTATION_NAMES: [&str; 6] = [
+    "playstation",
+    "sony",
+    "dualshock",
+    "dualsense",
+    "ps4",
+    "ps5",
+];
+
+pub struct GlyphPlugin;
+
+impl Plugin for GlyphPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputDevice::default())
+            .add_systems((detect_input_device, update_action_glyphs).chain());
+    }
+}
+
+/// The kind of device the player last pressed something on, prompts name its buttons
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Xbox,
+    PlayStation,
+}
+
+impl InputDevice {
+    pub fn from_gamepad_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if PLAYSTATION_NAMES.iter().any(|hint| name.contains(hint)) {
+            InputDevice::PlayStation
+        } else {
+            InputDevice::Xbox
+        }
+    }
+
+    pub fn is_gamepad(&self) -> bool {
+        *self != InputDevice::Keyboard
+    }
+
+    /// Whether `input` is something this device can press
+    fn reads(&self, input: &InputKind) -> bool {
+        is_gamepad_input(input) == self.is_gamepad()
+    }
+
+    /// The first of `action`'s bindings this device can press
+    pub fn binding<A: Actionlike>(&self, input_map: &InputMap<A>, action: A) -> Option<InputKind> {
+        input_map.get(action).iter().find_map(|input| match input {
+            UserInput::Single(kind) if self.reads(kind) => Some(kind.clone()),
+            _ => None,
+        })
+    }
+
+    /// Names `input` the way this device prints it
+    pub fn glyph_name(&self, input: &InputKind) -> String {
+        match input {
+            InputKind::Keyboard(key) => format!("{key:?}"),
+            InputKind::Mouse(button) => format!("Mouse{button:?}"),
+            InputKind::GamepadButton(button) => self.button_name(*button).to_string(),
+            InputKind::DualAxis(axis) => match axis.x.axis_type {
+                AxisType::Gamepad(GamepadAxisType::LeftStickX | GamepadAxisType::LeftStickY) => {
+                    "LeftStick".to_string()
+                }
+                AxisType::Gamepad(_) => "RightStick".to_string(),
+                _ => "Mouse".to_string(),
+            },
+            other => format!("{other:?}"),
+        }
+    }
+
+    /// How a prompt shows `input`, e.g. "[LB]"
+    pub fn glyph_label(&self, input: &InputKind) -> String {
+        format!("[{}]", self.glyph_name(input))
+    }
+
+    /// Names what this device presses for a menu `action`
+    pub fn menu_label(&self, input_map: &InputMap<MenuAction>, action: MenuAction) -> String {
+        self.binding(input_map, action)
+            .map(|input| self.glyph_name(&input))
+            .unwrap_or_else(|| format!("{action:?}"))
+    }
+
+    fn button_name(&self, button: GamepadButtonType) -> &'static str {
+        let (xbox, playstation) = match button {
+            GamepadButtonType::South => ("A", "Cross"),
+            GamepadButtonType::East => ("B", "Circle"),
+            GamepadButtonType::West => ("X", "Square"),
+            GamepadButtonType::North => ("Y", "Triangle"),
+            GamepadButtonType::LeftTrigger => ("LB", "L1"),
+            GamepadButtonType::RightTrigger => ("RB", "R1"),
+            GamepadButtonType::LeftTrigger2 => ("LT", "L2"),
+            GamepadButtonType::RightTrigger2 => ("RT", "R2"),
+            GamepadButtonType::LeftThumb => ("LS", "L3"),
+            GamepadButtonType::RightThumb => ("RS", "R3"),
+            GamepadButtonType::Select => ("View", "Share"),
+            GamepadButtonType::Start => ("Menu", "Options"),
+            GamepadButtonType::DPadUp => ("DPadUp", "DPadUp"),
+            GamepadButtonType::DPadDown => ("DPadDown", "DPadDown"),
+            GamepadButtonType::DPadLeft => ("DPadLeft", "DPadLeft"),
+            GamepadButtonType::DPadRight => ("DPadRight", "DPadRight"),
+            _ => ("Button", "Button"),
+        };
+        if *self == InputDevice::PlayStation {
+            playstation
+        } else {
+            xbox
+        }
+    }
+}
+
+/// Shows what `PlayerAction` is bound to on the current `InputDevice`
+#[derive(Component)]
+pub struct ActionGlyph(pub PlayerAction);
+
+/// Adds a glyph for `action` to a UI node, in a line of text in `style`
+pub fn spawn_action_glyph(
+    parent: &mut ChildBuilder,
+    action: PlayerAction,
+    style: TextStyle,
+) -> Entity {
+    parent
+        .spawn(TextBundle::from_section("", style).with_style(Style {
+            margin: UiRect::horizontal(Val::Px(4.0)),
+            ..default()
+        }))
+        .insert(ActionGlyph(action))
+        .id()
+}
+
+/// Watches for presses rather than stick or mouse motion, which drift without the player touching
+/// anything
+fn detect_input_device(
+    mut device: ResMut<InputDevice>,
+    gamepads: Res<Gamepads>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    let detected = if let Some(button) = buttons.get_just_pressed().next() {
+        InputDevice::from_gamepad_name(gamepads.name(button.gamepad).unwrap_or_default())
+    } else if keyboard.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+    {
+        InputDevice::Keyboard
+    } else {
+        return;
+    };
+    if *device != detected {
+        *device = detected;
+    }
+}
+
+fn update_action_glyphs(
+    device: Res<InputDevice>,
+    input_query: Query<&InputMap<PlayerAction>, With<Player>>,
+    mut glyph_query: Query<(&ActionGlyph, &mut Text, &mut Visibility)>,
+) {
+    let Ok(input_map) = input_query.get_single() else {return;};
+    for (glyph, mut text, mut visibility) in &mut glyph_query {
+        // Nothing on this device does the action, so there's nothing to show
+        let Some(input) = device.binding(input_map, glyph.0) else {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+            continue;
+        };
+        if *visibility != Visibility::Inherited {
+            *visibility = Visibility::Inherited;
+        }
+
+        let label = device.glyph_label(&input);
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gamepads_pick_glyphs_by_name() {
+        assert_eq!(
+            InputDevice::from_gamepad_name("Sony DualSense Wireless Controller"),
+            InputDevice::PlayStation
+        );
+        assert_eq!(
+            InputDevice::from_gamepad_name("PS4 Controller"),
+            InputDevice::PlayStation
+        );
+        assert_eq!(
+            InputDevice::from_gamepad_name("Xbox Wireless Controller"),
+            InputDevice::Xbox
+        );
+        assert_eq!(
+            InputDevice::from_gamepad_name("Generic USB Joystick"),
+            InputDevice::Xbox
+        );
+    }
+
+    #[test]
+    fn devices_only_show_bindings_they_can_press() {
+        let mut input_map = InputMap::default();
+        input_map.insert(KeyCode::Space, PlayerAction::Jump);
+        input_map.insert(GamepadButtonType::South, PlayerAction::Jump);
+
+        let keyboard = InputDevice::Keyboard;
+        let key = keyboard.binding(&input_map, PlayerAction::Jump).unwrap();
+        assert_eq!(keyboard.glyph_label(&key), "[Space]");
+
+        let playstation = InputDevice::PlayStation;
+        let button = playstation.binding(&input_map, PlayerAction::Jump).unwrap();
+        assert_eq!(playstation.glyph_name(&button), "Cross");
+        assert_eq!(InputDevice::Xbox.glyph_label(&button), "[A]");
+
+        assert!(keyboard.binding(&input_map, PlayerAction::Grab).is_none());
+    }
+}
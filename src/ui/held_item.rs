@@ -0,0 +1,170 @@
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use crate::{
+    spawn_action_glyph, ActionMode, AppState, AssetCollection, GameSettings, HeldItem,
+    HeldItemSlot, ItemId, Localization, LookingAround, Player, PlayerAction, UiTheme, UI_LAYER,
+};
+
+const HELD_ICON_SPIN_SPEED: f32 = 0.8;
+
+pub struct HeldItemHudPlugin;
+
+impl Plugin for HeldItemHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_held_item_hud.in_schedule(OnEnter(AppState::Playing)))
+            .add_systems(
+                (
+                    sync_held_item_icon,
+                    spin_held_item_icon,
+                    draw_held_item_text,
+                )
+                    .in_set(OnUpdate(AppState::Playing)),
+            );
+    }
+}
+
+/// The icon in the `HeldItemSlot`, for the item the player is carrying
+#[derive(Component)]
+pub struct HeldItemIcon(pub ItemId);
+
+#[derive(Component)]
+pub struct HeldItemPanel;
+
+/// Name and weight of the held item, then what pressing Grab will do with it
+#[derive(Component)]
+pub struct HeldItemText;
+
+fn spawn_held_item_hud(mut commands: Commands, assets: Res<AssetCollection>, theme: Res<UiTheme>) {
+    let style = |size: f32, color: Color| theme.text_style(&assets.font, size, color);
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(20.0),
+                    bottom: Val::Px(20.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            background_color: theme.panel.into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(HeldItemPanel)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_sections([
+                    TextSection::new("", style(26.0, theme.text)),
+                    TextSection::new("", style(20.0, theme.muted)),
+                    TextSection::new("", style(20.0, theme.muted)),
+                ]))
+                .insert(HeldItemText);
+            spawn_action_glyph(parent, PlayerAction::Grab, style(20.0, theme.muted));
+        });
+}
+
+fn sync_held_item_icon(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_query: Query<Option<&HeldItem>, With<Player>>,
+    slot_query: Query<Entity, With<HeldItemSlot>>,
+    icon_query: Query<(Entity, &HeldItemIcon)>,
+) {
+    let Ok(held_item) = player_query.get_single() else {return;};
+    let Ok(slot_entity) = slot_query.get_single() else {return;};
+    let held = held_item.map(|held_item| held_item.item);
+    let showing = icon_query.iter().next();
+    if showing.map(|(_, icon)| icon.0) == held {
+        return;
+    }
+
+    if let Some((icon_entity, _)) = showing {
+        commands.entity(icon_entity).despawn_recursive();
+    }
+    let Some(item) = held else {return;};
+    let (mesh, color) = item.icon();
+    commands.entity(slot_entity).with_children(|parent| {
+        parent
+            .spawn(PbrBundle {
+                mesh: meshes.add(mesh),
+                material: materials.add(color.into()),
+                ..default()
+            })
+            .insert(RenderLayers::layer(UI_LAYER))
+            .insert(HeldItemIcon(item));
+    });
+}
+
+fn spin_held_item_icon(time: Res<Time>, mut icon_query: Query<&mut Transform, With<HeldItemIcon>>) {
+    for mut transform in &mut icon_query {
+        transform.rotate_y(HELD_ICON_SPIN_SPEED * time.raw_delta_seconds());
+    }
+}
+
+fn draw_held_item_text(
+    settings: Res<GameSettings>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    assets: Res<AssetCollection>,
+    player_query: Query<(Option<&HeldItem>, Option<&LookingAround>), With<Player>>,
+    mut panel_query: Query<&mut Visibility, With<HeldItemPanel>>,
+    mut text_query: Query<&mut Text, With<HeldItemText>>,
+) {
+    let Ok((held_item, looking_around)) = player_query.get_single() else {return;};
+    for mut visibility in &mut panel_query {
+        let shown = if held_item.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != shown {
+            *visibility = shown;
+        }
+    }
+    let Some(held_item) = held_item else {return;};
+
+    // Aiming through the player's eyes, Grab throws wherever they look
+    let aiming = looking_around.is_some();
+    let prompt = if aiming {
+        "held.throw_ready"
+    } else if settings.grab_mode == ActionMode::Hold {
+        "held.release_to_throw"
+    } else {
+        "held.throw"
+    };
+    let weight = format!("{:?}", held_item.item.get_weight());
+    let values = [
+        format!(
+            "{}\n",
+            localization.text(&format!("held.{:?}", held_item.item))
+        ),
+        format!("{}\n", localization.text(&format!("held.{weight}"))),
+        localization.text(prompt),
+    ];
+    let prompt_color = if aiming { theme.highlight } else { theme.muted };
+    let colors = [theme.text, theme.muted, prompt_color];
+    let font = localization.font(&assets.font);
+
+    for mut text in &mut text_query {
+        let unchanged =
+            text.sections
+                .iter()
+                .zip(values.iter().zip(colors))
+                .all(|(section, (value, color))| {
+                    section.value == *value
+                        && section.style.color == color
+                        && section.style.font == font
+                });
+        if unchanged {
+            continue;
+        }
+        for (section, (value, color)) in text.sections.iter_mut().zip(values.iter().zip(colors)) {
+            section.value = value.clone();
+            section.style.color = color;
+            section.style.font = font.clone();
+        }
+    }
+}
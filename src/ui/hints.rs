@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use std::collections::VecDeque;
+
+use crate::{
+    spawn_action_glyph, AppState, AssetCollection, IdeaChanged, Localization, Player, PlayerAction,
+    SaveData, UiTheme, Walljump,
+};
+
+/// Hints shown the first time something happens rather than by a volume in the level
+pub const WALL_SLIDE_HINT: &str = "wall_slide";
+pub const IDEA_PICKUP_HINT: &str = "idea_pickup";
+/// A card nobody acts on goes away by itself after this long
+const HINT_SECONDS: f32 = 8.0;
+
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShowHint>()
+            .insert_resource(HintQueue::default())
+            .add_systems(
+                (
+                    trigger_hint_volumes,
+                    hint_first_times,
+                    queue_unseen_hints,
+                    show_next_hint,
+                    dismiss_hints,
+                )
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            );
+    }
+}
+
+/// Asks for the hint `id` to be shown, the card's text is the localized "hint.<id>"
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShowHint {
+    pub id: String,
+    /// What the hint teaches, its binding is shown on the card and pressing it dismisses the card
+    pub action: PlayerAction,
+}
+
+impl ShowHint {
+    pub fn new(id: &str, action: PlayerAction) -> Self {
+        ShowHint {
+            id: id.to_string(),
+            action,
+        }
+    }
+}
+
+/// A volume that shows its hint the first time the player walks into it
+#[derive(Component)]
+pub struct HintTrigger(pub ShowHint);
+
+/// Hints waiting for the card on screen to go away
+#[derive(Resource, Default)]
+pub struct HintQueue(VecDeque<ShowHint>);
+
+#[derive(Component)]
+pub struct HintCard {
+    pub action: PlayerAction,
+    pub timer: Timer,
+}
+
+fn trigger_hint_volumes(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut hint_events: EventWriter<ShowHint>,
+    player_query: Query<(), With<Player>>,
+    trigger_query: Query<&HintTrigger>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {continue;};
+        let trigger_entity = if player_query.contains(*e1) {
+            *e2
+        } else if player_query.contains(*e2) {
+            *e1
+        } else {
+            continue;
+        };
+
+        if let Ok(HintTrigger(hint)) = trigger_query.get(trigger_entity) {
+            hint_events.send(hint.clone());
+        }
+    }
+}
+
+fn hint_first_times(
+    mut hint_events: EventWriter<ShowHint>,
+    mut idea_events: EventReader<IdeaChanged>,
+    wall_query: Query<(), (With<Player>, Added<Walljump>)>,
+) {
+    if !wall_query.is_empty() {
+        hint_events.send(ShowHint::new(WALL_SLIDE_HINT, PlayerAction::Jump));
+    }
+    let gained_idea = idea_events
+        .iter()
+        .any(|event| matches!(event, IdeaChanged::Gained(_)));
+    if gained_idea {
+        hint_events.send(ShowHint::new(IDEA_PICKUP_HINT, PlayerAction::LoadIdea));
+    }
+}
+
+/// Counts a hint as seen once it's queued, so it never shows twice even if it's never dismissed
+fn queue_unseen_hints(
+    mut hint_events: EventReader<ShowHint>,
+    mut save_data: ResMut<SaveData>,
+    mut queue: ResMut<HintQueue>,
+) {
+    for hint in hint_events.iter() {
+        if save_data.see_hint(&hint.id) {
+            queue.0.push_back(hint.clone());
+        }
+    }
+}
+
+fn show_next_hint(
+    mut commands: Commands,
+    mut queue: ResMut<HintQueue>,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    card_query: Query<(), With<HintCard>>,
+) {
+    if !card_query.is_empty() {
+        return;
+    }
+    let Some(hint) = queue.0.pop_front() else {return;};
+    let font = localization.font(&assets.font);
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Percent(30.0),
+                    bottom: Val::Px(120.0),
+                    ..default()
+                },
+                size: Size::width(Val::Percent(40.0)),
+                padding: UiRect::all(Val::Px(12.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: theme.panel.into(),
+            ..default()
+        })
+        .insert(HintCard {
+            action: hint.action,
+            timer: Timer::from_seconds(HINT_SECONDS, TimerMode::Once),
+        })
+        .with_children(|card| {
+            spawn_action_glyph(
+                card,
+                hint.action,
+                theme.text_style(&font, 26.0, theme.accent),
+            );
+            card.spawn(TextBundle::from_section(
+                localization.text(&format!("hint.{}", hint.id)),
+                theme.text_style(&font, 26.0, theme.text),
+            ));
+        });
+}
+
+fn dismiss_hints(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut card_query: Query<(Entity, &mut HintCard)>,
+    player_query: Query<&ActionState<PlayerAction>, With<Player>>,
+) {
+    let action = player_query.get_single().ok();
+    for (entity, mut card) in &mut card_query {
+        card.timer.tick(time.delta());
+        let acted = action.map_or(false, |action| action.just_pressed(card.action));
+        if acted || card.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use crate::{
+    spawn_ui_camera, AppState, AssetCollection, Idea, IdeaChanged, IdeaSlot, PlayerIdeas, UiCamera,
+    UiTheme, UI_LAYER,
+};
+
+const ICON_SPIN_SPEED: f32 = 1.2;
+/// How fast icons grow in when loaded and shrink away when unloaded, in scale per second
+const ICON_POP_SPEED: f32 = 6.0;
+/// Icons never shrink all the way to nothing, a zero scale can't be inverted for lighting
+const ICON_MIN_SCALE: f32 = 0.01;
+const SLOT_MARKER_FONT_SIZE: f32 = 14.0;
+/// Markers are centered in a box this wide, just under their icon
+const SLOT_MARKER_WIDTH: f32 = 80.0;
+const SLOT_MARKER_DROP: f32 = 40.0;
+
+pub struct IdeaIconsPlugin;
+
+impl Plugin for IdeaIconsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (setup_idea_icon_assets, spawn_ui_camera, spawn_slot_markers)
+                .in_schedule(OnEnter(AppState::Playing)),
+        )
+        .add_systems(
+            (
+                tint_idea_icons,
+                sync_idea_icons,
+                animate_idea_icons,
+                update_slot_markers,
+            )
+                .chain()
+                .in_set(OnUpdate(AppState::Playing)),
+        );
+    }
+}
+
+/// A loaded idea's icon sitting in an `IdeaSlot`, `leaving` once it has been unloaded
+#[derive(Component)]
+pub struct IdeaIcon {
+    pub idea: Idea,
+    pub leaving: bool,
+}
+
+/// Labels the icon in the loaded slot at this index with its marker, for palettes that don't tell
+/// slots apart by color alone
+#[derive(Component)]
+pub struct SlotMarker(pub usize);
+
+/// Shared meshes for each idea's icon and a material per slot, colored by the `UiTheme`
+#[derive(Resource)]
+pub struct IdeaIconAssets {
+    pub cube: Handle<Mesh>,
+    pub coil: Handle<Mesh>,
+    pub wheel: Handle<Mesh>,
+    pub rope: Handle<Mesh>,
+    pub balloon: Handle<Mesh>,
+    pub slots: Vec<Handle<StandardMaterial>>,
+}
+
+impl IdeaIconAssets {
+    /// Mesh and offset of each piece the icon for `idea` is built from
+    fn parts(&self, idea: Idea) -> Vec<(Handle<Mesh>, Transform)> {
+        match idea {
+            Idea::Cube => vec![(self.cube.clone(), Transform::IDENTITY)],
+            Idea::Spring => (0..3)
+                .map(|turn| {
+                    let height = (turn as f32 - 1.0) * 0.07;
+                    (self.coil.clone(), Transform::from_xyz(0.0, height, 0.0))
+                })
+                .collect(),
+            Idea::Wheel => vec![(
+                self.wheel.clone(),
+                Transform::from_rotation(Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            )],
+            Idea::Rope => vec![(self.rope.clone(), Transform::IDENTITY)],
+            Idea::Balloon => vec![(self.balloon.clone(), Transform::IDENTITY)],
+        }
+    }
+}
+
+fn setup_idea_icon_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    theme: Res<UiTheme>,
+) {
+    commands.insert_resource(IdeaIconAssets {
+        cube: meshes.add(Mesh::from(shape::Cube { size: 0.2 })),
+        coil: meshes.add(Mesh::from(shape::Torus {
+            radius: 0.1,
+            ring_radius: 0.02,
+            ..default()
+        })),
+        wheel: meshes.add(Mesh::from(shape::Cylinder {
+            radius: 0.13,
+            height: 0.06,
+            ..default()
+        })),
+        rope: meshes.add(Mesh::from(shape::Capsule {
+            radius: 0.03,
+            depth: 0.22,
+            ..default()
+        })),
+        balloon: meshes.add(Mesh::from(shape::UVSphere {
+            radius: 0.12,
+            ..default()
+        })),
+        slots: theme
+            .slots
+            .iter()
+            .map(|color| materials.add((*color).into()))
+            .collect(),
+    });
+}
+
+fn spawn_slot_markers(mut commands: Commands, assets: Res<AssetCollection>, theme: Res<UiTheme>) {
+    for (index, color) in theme.slots.iter().enumerate() {
+        commands
+            .spawn(
+                TextBundle::from_section(
+                    "",
+                    theme.text_style(&assets.font, SLOT_MARKER_FONT_SIZE, *color),
+                )
+                .with_text_alignment(TextAlignment::Center)
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::width(Val::Px(SLOT_MARKER_WIDTH)),
+                    ..default()
+                }),
+            )
+            .insert(SlotMarker(index));
+    }
+}
+
+fn tint_idea_icons(
+    theme: Res<UiTheme>,
+    icon_assets: Option<Res<IdeaIconAssets>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(icon_assets) = icon_assets else {return;};
+    if !theme.is_changed() {
+        return;
+    }
+    for (handle, color) in icon_assets.slots.iter().zip(theme.slots) {
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color = color;
+        }
+    }
+}
+
+/// Sends away icons whose slot no longer holds their idea and brings in icons for new ones
+pub fn sync_idea_icons(
+    mut commands: Commands,
+    player_ideas: Res<PlayerIdeas>,
+    mut idea_events: EventReader<IdeaChanged>,
+    icon_assets: Option<Res<IdeaIconAssets>>,
+    slot_query: Query<(Entity, &IdeaSlot, Option<&Children>)>,
+    mut icon_query: Query<&mut IdeaIcon>,
+) {
+    let ideas_changed = idea_events.iter().count() > 0;
+    let Some(icon_assets) = icon_assets else {return;};
+    if !(ideas_changed || icon_assets.is_added()) {
+        return;
+    }
+
+    for (slot_entity, slot, children) in &slot_query {
+        let loaded = player_ideas.loaded_ideas.get(slot.0).copied();
+        let mut showing = None;
+        for child in children.iter().flat_map(|children| children.iter()) {
+            let Ok(mut icon) = icon_query.get_mut(*child) else {continue;};
+            if icon.leaving {
+                continue;
+            }
+            if Some(icon.idea) == loaded {
+                showing = Some(icon.idea);
+            } else {
+                icon.leaving = true;
+            }
+        }
+
+        let Some(idea) = loaded else {continue;};
+        if showing.is_some() {
+            continue;
+        }
+        let Some(material) = icon_assets.slots.get(slot.0) else {continue;};
+        commands.entity(slot_entity).with_children(|parent| {
+            parent
+                .spawn(SpatialBundle {
+                    transform: Transform::from_scale(Vec3::splat(ICON_MIN_SCALE)),
+                    ..default()
+                })
+                .insert(IdeaIcon {
+                    idea,
+                    leaving: false,
+                })
+                .with_children(|icon| {
+                    for (mesh, transform) in icon_assets.parts(idea) {
+                        icon.spawn(PbrBundle {
+                            mesh,
+                            material: material.clone(),
+                            transform,
+                            ..default()
+                        })
+                        .insert(RenderLayers::layer(UI_LAYER));
+                    }
+                });
+        });
+    }
+}
+
+fn animate_idea_icons(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut icon_query: Query<(Entity, &IdeaIcon, &mut Transform)>,
+) {
+    // Real time, so icons still pop while the game is slowed or paused
+    let delta = time.raw_delta_seconds();
+    for (entity, icon, mut transform) in &mut icon_query {
+        transform.rotate_y(ICON_SPIN_SPEED * delta);
+        let target = if icon.leaving { ICON_MIN_SCALE } else { 1.0 };
+        let scale = move_towards(transform.scale.x, target, ICON_POP_SPEED * delta);
+        transform.scale = Vec3::splat(scale);
+        if icon.leaving && scale <= ICON_MIN_SCALE {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Keeps each marker under its slot on screen, written the way the theme numbers slots
+fn update_slot_markers(
+    theme: Res<UiTheme>,
+    player_ideas: Res<PlayerIdeas>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<UiCamera>>,
+    slot_query: Query<(&IdeaSlot, &GlobalTransform)>,
+    mut marker_query: Query<(&SlotMarker, &mut Text, &mut Style)>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {return;};
+    for (marker, mut text, mut style) in &mut marker_query {
+        let Some((_, slot)) = slot_query.iter().find(|(slot, _)| slot.0 == marker.0) else {continue;};
+        let Some(screen) = camera.world_to_viewport(camera_transform, slot.translation()) else {continue;};
+        // Viewport coordinates start from the bottom left, the same as `bottom` and `left`
+        let position = UiRect {
+            left: Val::Px(screen.x - SLOT_MARKER_WIDTH / 2.0),
+            bottom: Val::Px(screen.y - SLOT_MARKER_DROP),
+            ..default()
+        };
+        if style.position != position {
+            style.position = position;
+        }
+
+        let value = match player_ideas.get_loaded_idea_at(marker.0) {
+            Some(idea_tag) if theme.slot_markers => theme.slot_text(marker.0, &idea_tag),
+            _ => String::new(),
+        };
+        let color = theme.slots.get(marker.0).copied().unwrap_or(theme.text);
+        let section = &text.sections[0];
+        if section.value != value || section.style.color != color {
+            let section = &mut text.sections[0];
+            section.value = value;
+            section.style.color = color;
+        }
+    }
+}
+
+fn move_towards(current: f32, target: f32, max_step: f32) -> f32 {
+    current + (target - current).clamp(-max_step, max_step)
+}
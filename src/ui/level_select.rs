@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+
+use crate::{
+    asset_path, load_level_quests, spawn_rooms, AppState, AssetCollection, CampaignData,
+    CampaignHandle, CurrentLevel, InputDevice, LaunchOptions, LevelData, Localization, MenuAction,
+    PlayingLevel, SaveData, UiTheme,
+};
+
+pub struct LevelSelectPlugin;
+
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LevelSelect::default())
+            .add_system(spawn_level_select.in_schedule(OnEnter(AppState::LevelSelect)))
+            // Picking a level swaps the rooms and quests over before play starts
+            .add_systems(
+                (navigate_levels, draw_levels)
+                    .chain()
+                    .before(spawn_rooms)
+                    .before(load_level_quests)
+                    .in_set(OnUpdate(AppState::LevelSelect)),
+            )
+            .add_system(despawn_level_select.in_schedule(OnExit(AppState::LevelSelect)));
+    }
+}
+
+/// The campaign's levels, Up and Down pick one and Confirm plays it if it's unlocked
+#[derive(Resource, Default)]
+pub struct LevelSelect {
+    pub selected: usize,
+    /// Each level's file, loaded on the way in so the number of stars in it can be shown
+    pub levels: Vec<Handle<LevelData>>,
+}
+
+#[derive(Component)]
+pub struct LevelSelectRoot;
+
+/// A generated level, or one from `--level` that isn't part of the campaign, is played straight
+/// away
+fn spawn_level_select(
+    mut commands: Commands,
+    mut select: ResMut<LevelSelect>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut playing_level: ResMut<PlayingLevel>,
+    asset_server: Res<AssetServer>,
+    options: Res<LaunchOptions>,
+    campaign: Res<CampaignHandle>,
+    campaigns: Res<Assets<CampaignData>>,
+) {
+    let Some(campaign) = campaigns.get(&campaign.0) else {return;};
+    let in_campaign = campaign
+        .levels
+        .iter()
+        .any(|level| asset_path(&level.path) == asset_path(&options.level));
+    if options.seed.is_some() || !in_campaign {
+        playing_level.0 = None;
+        next_state.set(AppState::Playing);
+        return;
+    }
+
+    select.levels = campaign
+        .levels
+        .iter()
+        .map(|level| asset_server.load(asset_path(&level.path)))
+        .collect();
+    select.selected = select.selected.min(campaign.levels.len().saturating_sub(1));
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::rgb(0.1, 0.08, 0.15).into(),
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .insert(LevelSelectRoot);
+}
+
+fn despawn_level_select(mut commands: Commands, query: Query<Entity, With<LevelSelectRoot>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn navigate_levels(
+    menu_actions: Res<ActionState<MenuAction>>,
+    save_data: Res<SaveData>,
+    campaign: Res<CampaignHandle>,
+    campaigns: Res<Assets<CampaignData>>,
+    mut select: ResMut<LevelSelect>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut playing_level: ResMut<PlayingLevel>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(campaign) = campaigns.get(&campaign.0) else {return;};
+    if menu_actions.just_pressed(MenuAction::Back) {
+        next_state.set(AppState::ProfileSelect);
+        return;
+    }
+
+    let last = campaign.levels.len().saturating_sub(1);
+    if menu_actions.just_pressed(MenuAction::Up) {
+        select.selected = select.selected.saturating_sub(1);
+    }
+    if menu_actions.just_pressed(MenuAction::Down) {
+        select.selected = (select.selected + 1).min(last);
+    }
+
+    let index = select.selected;
+    let unlocked = campaign.is_unlocked(index, &save_data.levels);
+    if !menu_actions.just_pressed(MenuAction::Confirm) || !unlocked {
+        return;
+    }
+    let Some(level) = campaign.levels.get(index) else {return;};
+    let Some(handle) = select.levels.get(index) else {return;};
+    current_level.0 = handle.clone();
+    playing_level.0 = Some(level.id.clone());
+    next_state.set(AppState::Playing);
+}
+
+fn draw_levels(
+    mut commands: Commands,
+    select: Res<LevelSelect>,
+    save_data: Res<SaveData>,
+    campaign: Res<CampaignHandle>,
+    campaigns: Res<Assets<CampaignData>>,
+    levels: Res<Assets<LevelData>>,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    device: Res<InputDevice>,
+    menu_bindings: Option<Res<InputMap<MenuAction>>>,
+    root_query: Query<Entity, With<LevelSelectRoot>>,
+    mut level_events: EventReader<AssetEvent<LevelData>>,
+) {
+    // Star totals show up as each level file finishes loading
+    let loaded = level_events.iter().count() > 0;
+    let changed = select.is_changed()
+        || loaded
+        || theme.is_changed()
+        || localization.is_changed()
+        || device.is_changed();
+    if !changed {
+        return;
+    }
+    let Some(campaign) = campaigns.get(&campaign.0) else {return;};
+    let Some(menu_bindings) = menu_bindings else {return;};
+    let font = localization.font(&assets.font);
+    let style = |font_size: f32, color: Color| theme.text_style(&font, font_size, color);
+    let hint = localization.format(
+        "levels.hint",
+        &[
+            &device.menu_label(&menu_bindings, MenuAction::Confirm),
+            &device.menu_label(&menu_bindings, MenuAction::Back),
+        ],
+    );
+
+    for root in &root_query {
+        commands.entity(root).despawn_descendants();
+        commands.entity(root).with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.text("levels.title"),
+                style(34.0, theme.text),
+            ));
+            for (index, level) in campaign.levels.iter().enumerate() {
+                let color = if index == select.selected {
+                    theme.highlight
+                } else if campaign.is_unlocked(index, &save_data.levels) {
+                    theme.text
+                } else {
+                    theme.muted
+                };
+                let details = if campaign.is_unlocked(index, &save_data.levels) {
+                    let stars = save_data
+                        .levels
+                        .get(&level.id)
+                        .map_or(0, |progress| progress.stars);
+                    let total = select
+                        .levels
+                        .get(index)
+                        .and_then(|handle| levels.get(handle))
+                        .map_or("?".to_string(), |data| data.quests.len().to_string());
+                    localization.format("levels.stars", &[&stars.to_string(), &total])
+                } else {
+                    let needed =
+                        level.stars_required - campaign.stars_before(index, &save_data.levels);
+                    localization.format("levels.locked", &[&needed.to_string()])
+                };
+                parent.spawn(TextBundle::from_sections([
+                    TextSection::new(level.name.clone(), style(28.0, color)),
+                    TextSection::new(format!("  {details}"), style(18.0, theme.muted)),
+                ]));
+            }
+            parent.spawn(TextBundle::from_section(hint, style(18.0, theme.muted)));
+        });
+    }
+}
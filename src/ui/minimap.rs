@@ -0,0 +1,241 @@
+use bevy::{
+    asset::HandleId,
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+    utils::HashMap,
+};
+
+use crate::{Particle, Player, ToonMaterial};
+
+/// Render layer only the minimap camera can see, holding flat copies of the level and the icons
+/// drawn above tracked entities. Layer 1 is the UI overlay's
+pub const MINIMAP_LAYER: u8 = 2;
+const MINIMAP_TEXTURE_SIZE: u32 = 256;
+const MINIMAP_WORLD_SIZE: f32 = 60.0;
+const MINIMAP_CAMERA_HEIGHT: f32 = 60.0;
+const MINIMAP_ICON_HEIGHT: f32 = 5.0;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_minimap)
+            .add_system(attach_minimap_icons)
+            .add_system(attach_minimap_shadows)
+            .add_system(follow_player_with_minimap_camera);
+    }
+}
+
+#[derive(Component)]
+pub struct MinimapCamera;
+
+#[derive(Component)]
+pub struct MinimapDisplay;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum MinimapIcon {
+    Player,
+    Checkpoint,
+    Star,
+    Idea,
+}
+
+impl MinimapIcon {
+    pub fn color(&self) -> Color {
+        use MinimapIcon::*;
+        match self {
+            Player => Color::TURQUOISE,
+            Checkpoint => Color::GREEN,
+            Star => Color::GOLD,
+            Idea => Color::FUCHSIA,
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        match self {
+            MinimapIcon::Player => 1.5,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MinimapIconMarker;
+
+/// An unlit, flat colored copy of a mesh, drawn by the minimap in place of its real material
+#[derive(Component)]
+pub struct MinimapShadow;
+
+fn spawn_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = Extent3d {
+        width: MINIMAP_TEXTURE_SIZE,
+        height: MINIMAP_TEXTURE_SIZE,
+        ..default()
+    };
+
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image_handle = images.add(image);
+
+    commands
+        .spawn(Camera3dBundle {
+            camera: Camera {
+                order: -1,
+                target: RenderTarget::Image(image_handle.clone()),
+                ..default()
+            },
+            projection: OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical(MINIMAP_WORLD_SIZE),
+                ..default()
+            }
+            .into(),
+            transform: Transform::from_xyz(0.0, MINIMAP_CAMERA_HEIGHT, 0.0)
+                .with_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+            ..default()
+        })
+        .insert(RenderLayers::layer(MINIMAP_LAYER))
+        .insert(MinimapCamera);
+
+    commands
+        .spawn(ImageBundle {
+            style: Style {
+                size: Size::all(Val::Px(200.0)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            image: image_handle.into(),
+            ..default()
+        })
+        .insert(MinimapDisplay);
+}
+
+fn attach_minimap_icons(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, &MinimapIcon), Added<MinimapIcon>>,
+) {
+    for (entity, icon) in &query {
+        let icon_entity = commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Cylinder {
+                    radius: icon.radius(),
+                    height: 0.1,
+                    ..default()
+                })),
+                material: materials.add(StandardMaterial {
+                    base_color: icon.color(),
+                    unlit: true,
+                    ..default()
+                }),
+                transform: Transform::from_xyz(0.0, MINIMAP_ICON_HEIGHT, 0.0),
+                ..default()
+            })
+            .insert(RenderLayers::layer(MINIMAP_LAYER))
+            .insert(MinimapIconMarker)
+            .id();
+
+        commands.entity(entity).add_child(icon_entity);
+    }
+}
+
+/// Gives every piece of level geometry a flat copy on the minimap's layer, so the minimap skips the
+/// lighting, toon shading and outlines. Copies are children, so they move and vanish with the mesh.
+/// Sky, decals, gizmos and ghosts don't cast shadows, which keeps them off the map too
+fn attach_minimap_shadows(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    toon_materials: Res<Assets<ToonMaterial>>,
+    mut flat_materials: Local<HashMap<HandleId, Handle<StandardMaterial>>>,
+    query: Query<
+        (
+            Entity,
+            &Handle<Mesh>,
+            Option<&Handle<StandardMaterial>>,
+            Option<&Handle<ToonMaterial>>,
+        ),
+        (
+            Added<Handle<Mesh>>,
+            Without<RenderLayers>,
+            Without<Particle>,
+            Without<MinimapShadow>,
+            Without<NotShadowCaster>,
+        ),
+    >,
+) {
+    for (entity, mesh, standard, toon) in &query {
+        let (source, color) = if let Some(handle) = standard {
+            let color = materials.get(handle).map(|material| material.base_color);
+            (handle.id(), color)
+        } else if let Some(handle) = toon {
+            let color = toon_materials.get(handle).map(|material| material.color);
+            (handle.id(), color)
+        } else {
+            continue;
+        };
+        let material = flat_materials
+            .entry(source)
+            .or_insert_with(|| {
+                let color = color.unwrap_or(Color::GRAY);
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    alpha_mode: if color.a() < 1.0 {
+                        AlphaMode::Blend
+                    } else {
+                        AlphaMode::Opaque
+                    },
+                    unlit: true,
+                    ..default()
+                })
+            })
+            .clone();
+
+        let shadow = commands
+            .spawn(PbrBundle {
+                mesh: mesh.clone(),
+                material,
+                ..default()
+            })
+            .insert(RenderLayers::layer(MINIMAP_LAYER))
+            .insert(MinimapShadow)
+            .id();
+        commands.entity(entity).add_child(shadow);
+    }
+}
+
+fn follow_player_with_minimap_camera(
+    player_query: Query<&Transform, (With<Player>, Without<MinimapCamera>)>,
+    mut camera_query: Query<&mut Transform, With<MinimapCamera>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {return;};
+    for mut camera_transform in &mut camera_query {
+        camera_transform.translation =
+            player_transform.translation + Vec3::Y * MINIMAP_CAMERA_HEIGHT;
+    }
+}
@@ -1,14 +1,68 @@
 use bevy::prelude::*;
 
-use crate::PlayerIdeas;
+use crate::{
+    ActiveChallenge, AppState, AssetCollection, Imagination, Localization, Localized, Player,
+    RewindMeter, Rewinding, Stamina,
+};
+
+pub mod bindings;
+pub use bindings::*;
+
+pub mod minimap;
+pub use minimap::*;
+
+pub mod pause;
+pub use pause::*;
+
+pub mod quests;
+pub use quests::*;
+
+pub mod theme;
+pub use theme::*;
+
+pub mod idea_icons;
+pub use idea_icons::*;
+
+pub mod held_item;
+pub use held_item::*;
+
+pub mod hints;
+pub use hints::*;
+
+pub mod glyphs;
+pub use glyphs::*;
+
+pub mod touch;
+pub use touch::*;
+
+pub mod profiles;
+pub use profiles::*;
+
+pub mod level_select;
+pub use level_select::*;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(spawn_hud)
-            .add_system(handle_selected_idea_text)
-            .add_system(handle_loaded_ideas_text);
+        app.add_plugin(UiThemePlugin)
+            .add_plugin(UiBindingPlugin)
+            .add_plugin(MinimapPlugin)
+            .add_plugin(IdeaIconsPlugin)
+            .add_plugin(HeldItemHudPlugin)
+            .add_plugin(PauseMenuPlugin)
+            .add_plugin(QuestTrackerPlugin)
+            .add_plugin(HintPlugin)
+            .add_plugin(GlyphPlugin)
+            .add_plugin(TouchControlsPlugin)
+            .add_plugin(ProfileSelectPlugin)
+            .add_plugin(LevelSelectPlugin)
+            .add_system(spawn_hud.in_schedule(OnEnter(AppState::Playing)))
+            .add_system(handle_current_idea_color)
+            .add_system(handle_stamina_bar)
+            .add_system(handle_rewind_bar)
+            .add_system(handle_imagination_bar)
+            .add_system(handle_challenge_text);
     }
 }
 
@@ -16,53 +70,157 @@ impl Plugin for UiPlugin {
 pub struct CurrentIdeaText;
 
 #[derive(Component)]
-pub struct LoadedIdeasText;
+pub struct LeftHud;
 
 #[derive(Component)]
-pub struct LeftHud;
+pub struct StaminaBar;
 
-fn handle_selected_idea_text(
-    player_ideas: Res<PlayerIdeas>,
-    mut query: Query<&mut Text, With<CurrentIdeaText>>,
+#[derive(Component)]
+pub struct StaminaBarFill;
+
+#[derive(Component)]
+pub struct RewindBar;
+
+#[derive(Component)]
+pub struct RewindBarFill;
+
+#[derive(Component)]
+pub struct ImaginationBar;
+
+#[derive(Component)]
+pub struct ImaginationBarFill;
+
+#[derive(Component)]
+pub struct ChallengeText;
+
+fn handle_challenge_text(
+    challenge_query: Query<&ActiveChallenge>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<ChallengeText>>,
 ) {
-    if player_ideas.is_changed() {
-        for mut text in &mut query {
-            if let Some(idea_tag) = player_ideas.get_current_idea_tag() {
-                text.sections[1].value = idea_tag;
-            } else {
-                text.sections[1].value = "".to_string();
-            }
+    let challenge = challenge_query.iter().next();
+    for (mut text, mut visibility) in &mut text_query {
+        if let Some(challenge) = challenge {
+            *visibility = Visibility::Inherited;
+            text.sections[1].value = format!("{}/{}", challenge.collected(), challenge.total);
+            text.sections[2].value = format!("  {:.1}", challenge.seconds_left());
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn handle_stamina_bar(
+    player_query: Query<&Stamina, (With<Player>, Changed<Stamina>)>,
+    mut bar_query: Query<&mut Visibility, With<StaminaBar>>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor), With<StaminaBarFill>>,
+) {
+    let Ok(stamina) = player_query.get_single() else {return;};
+    for mut visibility in &mut bar_query {
+        *visibility = if stamina.is_full() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+    for (mut style, mut color) in &mut fill_query {
+        style.size.width = Val::Percent(stamina.percent() * 100.0);
+        color.0 = if stamina.is_exhausted() {
+            Color::GRAY
+        } else {
+            Color::LIME_GREEN
+        };
+    }
+}
+
+fn handle_rewind_bar(
+    theme: Res<UiTheme>,
+    player_query: Query<(&RewindMeter, Option<&Rewinding>), With<Player>>,
+    mut bar_query: Query<&mut Visibility, With<RewindBar>>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor), With<RewindBarFill>>,
+) {
+    let Ok((meter, rewinding)) = player_query.get_single() else {return;};
+    for mut visibility in &mut bar_query {
+        let shown = if meter.is_full() && rewinding.is_none() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != shown {
+            *visibility = shown;
+        }
+    }
+    for (mut style, mut color) in &mut fill_query {
+        let width = Val::Percent(meter.percent() * 100.0);
+        if style.size.width != width {
+            style.size.width = width;
+        }
+        let fill = if rewinding.is_some() {
+            theme.highlight
+        } else {
+            theme.accent
+        };
+        if color.0 != fill {
+            color.0 = fill;
+        }
+    }
+}
+
+/// Shown while creations are holding imagination up or it's still refilling
+fn handle_imagination_bar(
+    theme: Res<UiTheme>,
+    imagination: Res<Imagination>,
+    mut bar_query: Query<&mut Visibility, With<ImaginationBar>>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor), With<ImaginationBarFill>>,
+) {
+    if !imagination.is_changed() && !theme.is_changed() {
+        return;
+    }
+    for mut visibility in &mut bar_query {
+        let shown = if imagination.is_full() && imagination.materialized() == 0 {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != shown {
+            *visibility = shown;
+        }
+    }
+    for (mut style, mut color) in &mut fill_query {
+        let width = Val::Percent(imagination.percent() * 100.0);
+        if style.size.width != width {
+            style.size.width = width;
+        }
+        let fill = if imagination.is_empty() {
+            theme.muted
+        } else {
+            theme.slots[1]
+        };
+        if color.0 != fill {
+            color.0 = fill;
         }
     }
 }
 
-fn handle_loaded_ideas_text(
-    player_ideas: Res<PlayerIdeas>,
-    mut query: Query<&mut Text, With<LoadedIdeasText>>,
+fn handle_current_idea_color(
+    theme: Res<UiTheme>,
+    mut query: Query<&mut Text, With<CurrentIdeaText>>,
 ) {
-    if player_ideas.is_changed() {
+    if theme.is_changed() {
         for mut text in &mut query {
-            if let Some(idea_tag) = player_ideas.get_loaded_idea_at(2) {
-                text.sections[3].value = idea_tag;
-            } else {
-                text.sections[3].value = String::default();
-            }
-            if let Some(idea_tag) = player_ideas.get_loaded_idea_at(1) {
-                text.sections[2].value = idea_tag;
-            } else {
-                text.sections[2].value = String::default();
-            }
-            if let Some(idea_tag) = player_ideas.get_loaded_idea_at(0) {
-                text.sections[1].value = idea_tag;
-            } else {
-                text.sections[1].value = String::default();
-            }
+            text.sections[0].style.color = theme.text;
+            text.sections[1].style.color = theme.accent;
         }
     }
 }
 
-fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("FiraSans-Bold.ttf");
+fn spawn_hud(
+    mut commands: Commands,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+) {
+    let font = assets.font.clone();
+    let style = |color: Color| theme.text_style(&font, 30.0, color);
     commands
         .spawn(NodeBundle {
             style: Style {
@@ -86,60 +244,93 @@ fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
                 .with_children(|parent_2| {
                     parent_2
                         .spawn(TextBundle::from_sections([
-                            TextSection::new(
-                                "Ideas: ",
-                                TextStyle {
-                                    font: font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::WHITE,
-                                },
-                            ),
-                            TextSection::new(
-                                "Empty",
-                                TextStyle {
-                                    font: font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::TEAL,
-                                },
-                            ),
+                            TextSection::new("", style(theme.text)),
+                            TextSection::new(localization.text("hud.empty"), style(theme.accent)),
                         ]))
+                        .insert(Localized::new("hud.ideas", 0))
+                        .insert(BoundText::new(HudValue::SelectedIdea, 1))
                         .insert(CurrentIdeaText);
                     parent_2
-                        .spawn(TextBundle::from_sections([
-                            TextSection::new(
-                                "Loaded Ideas: ",
-                                TextStyle {
-                                    font: font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::WHITE,
+                        .spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.0), Val::Px(12.0)),
+                                margin: UiRect::top(Val::Px(8.0)),
+                                ..default()
+                            },
+                            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        })
+                        .insert(StaminaBar)
+                        .with_children(|bar| {
+                            bar.spawn(NodeBundle {
+                                style: Style {
+                                    size: Size::height(Val::Percent(100.0)),
+                                    ..default()
                                 },
-                            ),
-                            TextSection::new(
-                                " ",
-                                TextStyle {
-                                    font: font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::RED,
-                                },
-                            ),
-                            TextSection::new(
-                                " ",
-                                TextStyle {
-                                    font: font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::ORANGE,
+                                background_color: Color::LIME_GREEN.into(),
+                                ..default()
+                            })
+                            .insert(StaminaBarFill);
+                        });
+                    parent_2
+                        .spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.0), Val::Px(8.0)),
+                                margin: UiRect::top(Val::Px(4.0)),
+                                ..default()
+                            },
+                            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        })
+                        .insert(RewindBar)
+                        .with_children(|bar| {
+                            bar.spawn(NodeBundle {
+                                style: Style {
+                                    size: Size::height(Val::Percent(100.0)),
+                                    ..default()
                                 },
-                            ),
-                            TextSection::new(
-                                " ",
-                                TextStyle {
-                                    font: font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::YELLOW,
+                                background_color: theme.accent.into(),
+                                ..default()
+                            })
+                            .insert(RewindBarFill);
+                        });
+                    parent_2
+                        .spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.0), Val::Px(8.0)),
+                                margin: UiRect::top(Val::Px(4.0)),
+                                ..default()
+                            },
+                            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        })
+                        .insert(ImaginationBar)
+                        .with_children(|bar| {
+                            bar.spawn(NodeBundle {
+                                style: Style {
+                                    size: Size::height(Val::Percent(100.0)),
+                                    ..default()
                                 },
-                            ),
-                        ]))
-                        .insert(LoadedIdeasText);
+                                background_color: theme.slots[1].into(),
+                                ..default()
+                            })
+                            .insert(ImaginationBarFill);
+                        });
+                    parent_2
+                        .spawn(TextBundle {
+                            text: Text::from_sections([
+                                TextSection::new("", style(theme.text)),
+                                TextSection::new(" ", style(theme.slots[0])),
+                                TextSection::new(" ", style(theme.slots[2])),
+                            ]),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        })
+                        .insert(Localized::new("hud.coins", 0))
+                        .insert(ChallengeText);
                 });
         });
 }
@@ -0,0 +1,553 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_path, format_playtime, AppState, AssetCollection, Costume, GameSettings, InputContext,
+    Localization, MenuAction, Player, PlayerAction, SaveData, SavedSessionMetrics, SessionMetrics,
+    SettingsRow, TimeScale, UiTheme,
+};
+
+const GALLERY_IMAGE_SIZE: f32 = 256.0;
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PauseMenu::default())
+            .add_system(spawn_pause_menu.in_schedule(OnEnter(AppState::Playing)))
+            .add_systems(
+                (
+                    toggle_pause_menu,
+                    switch_pause_page,
+                    navigate_gallery,
+                    navigate_settings,
+                    navigate_wardrobe,
+                    draw_gallery,
+                    draw_settings,
+                    draw_wardrobe,
+                    draw_stats,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Pause stops the game and opens the memory gallery, Up and Down pick a memory to read
+#[derive(Resource, Default)]
+pub struct PauseMenu {
+    pub open: bool,
+    pub page: PausePage,
+    pub selected: usize,
+    pub selected_setting: usize,
+    pub selected_costume: usize,
+}
+
+/// `MenuAction::NextPage` flips between the pages of the pause menu, each page's node carries
+/// the one it shows
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PausePage {
+    #[default]
+    Gallery,
+    Accessibility,
+    Wardrobe,
+    Stats,
+}
+
+#[derive(Component)]
+pub struct PauseMenuRoot;
+
+#[derive(Component)]
+pub struct MemoryGallery;
+
+#[derive(Component)]
+pub struct SettingsPage;
+
+#[derive(Component)]
+pub struct WardrobePage;
+
+#[derive(Component)]
+pub struct StatsPage;
+
+fn spawn_pause_menu(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::rgba(0.05, 0.03, 0.1, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(4),
+            ..default()
+        })
+        .insert(PauseMenuRoot)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(80.0), Val::Percent(70.0)),
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(MemoryGallery)
+                .insert(PausePage::Gallery);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(80.0), Val::Percent(70.0)),
+                        flex_direction: FlexDirection::Column,
+                        display: Display::None,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(SettingsPage)
+                .insert(PausePage::Accessibility);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(80.0), Val::Percent(70.0)),
+                        flex_direction: FlexDirection::Column,
+                        display: Display::None,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(WardrobePage)
+                .insert(PausePage::Wardrobe);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(80.0), Val::Percent(70.0)),
+                        flex_direction: FlexDirection::Column,
+                        display: Display::None,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(StatsPage)
+                .insert(PausePage::Stats);
+        });
+}
+
+/// Opens on the player's pause and closes on the menu's, only one of them is listening at a time
+pub fn toggle_pause_menu(
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut pause_menu: ResMut<PauseMenu>,
+    mut time_scale: ResMut<TimeScale>,
+    mut input_context: ResMut<InputContext>,
+    player_query: Query<&ActionState<PlayerAction>, With<Player>>,
+    mut root_query: Query<&mut Visibility, With<PauseMenuRoot>>,
+) {
+    let pressed = if pause_menu.open {
+        menu_actions.just_pressed(MenuAction::Pause)
+    } else {
+        player_query
+            .get_single()
+            .map_or(false, |action| action.just_pressed(PlayerAction::Pause))
+    };
+    if !pressed {
+        return;
+    }
+    pause_menu.open = !pause_menu.open;
+    if pause_menu.open {
+        time_scale.hold("pause", 0.0);
+        input_context.open_menu("pause");
+    } else {
+        time_scale.release("pause");
+        input_context.close_menu("pause");
+    }
+    for mut visibility in &mut root_query {
+        *visibility = if pause_menu.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+pub fn switch_pause_page(
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut pause_menu: ResMut<PauseMenu>,
+    mut page_query: Query<(&mut Style, &PausePage)>,
+) {
+    if !pause_menu.open || !menu_actions.just_pressed(MenuAction::NextPage) {
+        return;
+    }
+    pause_menu.page = match pause_menu.page {
+        PausePage::Gallery => PausePage::Accessibility,
+        PausePage::Accessibility => PausePage::Wardrobe,
+        PausePage::Wardrobe => PausePage::Stats,
+        PausePage::Stats => PausePage::Gallery,
+    };
+    for (mut style, page) in &mut page_query {
+        style.display = if *page == pause_menu.page {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+pub fn navigate_gallery(
+    menu_actions: Res<ActionState<MenuAction>>,
+    save_data: Res<SaveData>,
+    mut pause_menu: ResMut<PauseMenu>,
+) {
+    if !pause_menu.open || pause_menu.page != PausePage::Gallery {
+        return;
+    }
+    let last = save_data.memories.len().saturating_sub(1);
+    if menu_actions.just_pressed(MenuAction::Up) {
+        pause_menu.selected = pause_menu.selected.saturating_sub(1);
+    }
+    if menu_actions.just_pressed(MenuAction::Down) {
+        pause_menu.selected = (pause_menu.selected + 1).min(last);
+    }
+}
+
+/// Up and Down pick an option, Confirm, Left or Right change it
+pub fn navigate_settings(
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut settings: ResMut<GameSettings>,
+    mut pause_menu: ResMut<PauseMenu>,
+) {
+    if !pause_menu.open || pause_menu.page != PausePage::Accessibility {
+        return;
+    }
+    let last = SettingsRow::ALL.len() - 1;
+    if menu_actions.just_pressed(MenuAction::Up) {
+        pause_menu.selected_setting = pause_menu.selected_setting.saturating_sub(1);
+    }
+    if menu_actions.just_pressed(MenuAction::Down) {
+        pause_menu.selected_setting = (pause_menu.selected_setting + 1).min(last);
+    }
+    let change = [MenuAction::Confirm, MenuAction::Left, MenuAction::Right];
+    if change
+        .into_iter()
+        .any(|action| menu_actions.just_pressed(action))
+    {
+        SettingsRow::ALL[pause_menu.selected_setting].cycle(&mut settings);
+    }
+}
+
+/// Up and Down pick a costume, Confirm puts it on or takes it off
+pub fn navigate_wardrobe(
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut save_data: ResMut<SaveData>,
+    mut pause_menu: ResMut<PauseMenu>,
+) {
+    if !pause_menu.open || pause_menu.page != PausePage::Wardrobe {
+        return;
+    }
+    let last = save_data.costumes.len().saturating_sub(1);
+    if menu_actions.just_pressed(MenuAction::Up) {
+        pause_menu.selected_costume = pause_menu.selected_costume.saturating_sub(1);
+    }
+    if menu_actions.just_pressed(MenuAction::Down) {
+        pause_menu.selected_costume = (pause_menu.selected_costume + 1).min(last);
+    }
+    if menu_actions.just_pressed(MenuAction::Confirm) {
+        if let Some(costume) = save_data.costumes.get(pause_menu.selected_costume).copied() {
+            save_data.toggle_costume(costume);
+        }
+    }
+}
+
+/// Rebuilds the list of found memories and the open one's page whenever either changes
+pub fn draw_gallery(
+    mut commands: Commands,
+    pause_menu: Res<PauseMenu>,
+    save_data: Res<SaveData>,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    asset_server: Res<AssetServer>,
+    gallery_query: Query<Entity, With<MemoryGallery>>,
+) {
+    if !pause_menu.open || pause_menu.page != PausePage::Gallery {
+        return;
+    }
+    if !(pause_menu.is_changed()
+        || save_data.is_changed()
+        || theme.is_changed()
+        || localization.is_changed())
+    {
+        return;
+    }
+    let font = localization.font(&assets.font);
+    let style = |font_size: f32, color: Color| theme.text_style(&font, font_size, color);
+    let count = save_data.memories.len().to_string();
+
+    for gallery in &gallery_query {
+        commands.entity(gallery).despawn_descendants();
+        commands.entity(gallery).with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::width(Val::Percent(35.0)),
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|list| {
+                    list.spawn(TextBundle::from_section(
+                        localization.format("pause.memories", &[&count]),
+                        style(34.0, theme.text),
+                    ));
+                    list.spawn(TextBundle::from_section(
+                        localization.text("pause.to_settings"),
+                        style(18.0, theme.muted),
+                    ));
+                    if save_data.memories.is_empty() {
+                        list.spawn(TextBundle::from_section(
+                            localization.text("pause.nothing_remembered"),
+                            style(24.0, theme.muted),
+                        ));
+                    }
+                    for (index, memory) in save_data.memories.iter().enumerate() {
+                        let color = if index == pause_menu.selected {
+                            theme.highlight
+                        } else {
+                            theme.text
+                        };
+                        list.spawn(TextBundle::from_section(
+                            memory.title.clone(),
+                            style(24.0, color),
+                        ));
+                    }
+                });
+
+            let Some(memory) = save_data.memories.get(pause_menu.selected) else {return;};
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::width(Val::Percent(60.0)),
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|page| {
+                    page.spawn(TextBundle::from_section(
+                        memory.title.clone(),
+                        style(30.0, theme.highlight),
+                    ));
+                    if let Some(image) = &memory.image {
+                        page.spawn(ImageBundle {
+                            style: Style {
+                                size: Size::new(
+                                    Val::Px(GALLERY_IMAGE_SIZE),
+                                    Val::Px(GALLERY_IMAGE_SIZE),
+                                ),
+                                margin: UiRect::vertical(Val::Px(8.0)),
+                                ..default()
+                            },
+                            image: UiImage {
+                                texture: asset_server.load(asset_path(image)),
+                                ..default()
+                            },
+                            ..default()
+                        });
+                    }
+                    page.spawn(
+                        TextBundle::from_section(memory.text.clone(), style(22.0, theme.text))
+                            .with_style(Style {
+                                max_size: Size::width(Val::Percent(100.0)),
+                                ..default()
+                            }),
+                    );
+                });
+        });
+    }
+}
+
+pub fn draw_settings(
+    mut commands: Commands,
+    pause_menu: Res<PauseMenu>,
+    settings: Res<GameSettings>,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    page_query: Query<Entity, With<SettingsPage>>,
+) {
+    if !pause_menu.open || pause_menu.page != PausePage::Accessibility {
+        return;
+    }
+    if !(pause_menu.is_changed()
+        || settings.is_changed()
+        || theme.is_changed()
+        || localization.is_changed())
+    {
+        return;
+    }
+    let font = localization.font(&assets.font);
+    let style = |font_size: f32, color: Color| theme.text_style(&font, font_size, color);
+
+    for page in &page_query {
+        commands.entity(page).despawn_descendants();
+        commands.entity(page).with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.text("settings.title"),
+                style(34.0, theme.text),
+            ));
+            for (index, row) in SettingsRow::ALL.iter().enumerate() {
+                let color = if index == pause_menu.selected_setting {
+                    theme.highlight
+                } else {
+                    theme.text
+                };
+                parent.spawn(TextBundle::from_section(
+                    row.label(&settings, &localization),
+                    style(24.0, color),
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                localization.text("pause.to_wardrobe"),
+                style(18.0, theme.muted),
+            ));
+        });
+    }
+}
+
+pub fn draw_wardrobe(
+    mut commands: Commands,
+    pause_menu: Res<PauseMenu>,
+    save_data: Res<SaveData>,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    page_query: Query<Entity, With<WardrobePage>>,
+) {
+    if !pause_menu.open || pause_menu.page != PausePage::Wardrobe {
+        return;
+    }
+    if !(pause_menu.is_changed()
+        || save_data.is_changed()
+        || theme.is_changed()
+        || localization.is_changed())
+    {
+        return;
+    }
+    let font = localization.font(&assets.font);
+    let style = |font_size: f32, color: Color| theme.text_style(&font, font_size, color);
+    let count = format!("{}/{}", save_data.costumes.len(), Costume::ALL.len());
+
+    for page in &page_query {
+        commands.entity(page).despawn_descendants();
+        commands.entity(page).with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.format("wardrobe.title", &[&count]),
+                style(34.0, theme.text),
+            ));
+            if save_data.costumes.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    localization.text("wardrobe.empty"),
+                    style(24.0, theme.muted),
+                ));
+            }
+            for (index, costume) in save_data.costumes.iter().enumerate() {
+                let color = if index == pause_menu.selected_costume {
+                    theme.highlight
+                } else {
+                    theme.text
+                };
+                let wearing = if save_data.equipped_costume == Some(*costume) {
+                    localization.text("wardrobe.wearing")
+                } else {
+                    String::new()
+                };
+                parent.spawn(TextBundle::from_sections([
+                    TextSection::new(
+                        format!("{} {wearing}", costume.name(&localization)),
+                        style(24.0, color),
+                    ),
+                    TextSection::new(
+                        format!("  {}", costume_effects(*costume, &localization)),
+                        style(18.0, theme.muted),
+                    ),
+                ]));
+            }
+            parent.spawn(TextBundle::from_section(
+                localization.text("wardrobe.hint"),
+                style(18.0, theme.muted),
+            ));
+        });
+    }
+}
+
+/// The profile's saved stats, with this session's so far added on
+pub fn draw_stats(
+    mut commands: Commands,
+    pause_menu: Res<PauseMenu>,
+    save_data: Res<SaveData>,
+    metrics: Res<SessionMetrics>,
+    saved_metrics: Res<SavedSessionMetrics>,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    page_query: Query<Entity, With<StatsPage>>,
+) {
+    if !pause_menu.open || pause_menu.page != PausePage::Stats {
+        return;
+    }
+    if !(pause_menu.is_changed()
+        || save_data.is_changed()
+        || theme.is_changed()
+        || localization.is_changed())
+    {
+        return;
+    }
+    let font = localization.font(&assets.font);
+    let style = |font_size: f32, color: Color| theme.text_style(&font, font_size, color);
+    let stats = save_data.stats.with_session(&saved_metrics.0, &metrics);
+    let rows = [
+        ("stats.playtime", format_playtime(stats.seconds_played)),
+        ("stats.deaths", stats.deaths.to_string()),
+        ("stats.jumps", stats.jumps.to_string()),
+        ("stats.creations", stats.creations_built.to_string()),
+        ("stats.thrown", stats.items_thrown.to_string()),
+        ("stats.furthest_fall", format!("{:.1}", stats.furthest_fall)),
+    ];
+
+    for page in &page_query {
+        commands.entity(page).despawn_descendants();
+        commands.entity(page).with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.text("stats.title"),
+                style(34.0, theme.text),
+            ));
+            for (key, value) in &rows {
+                parent.spawn(TextBundle::from_section(
+                    localization.format(key, &[value.as_str()]),
+                    style(24.0, theme.text),
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                localization.text("stats.hint"),
+                style(18.0, theme.muted),
+            ));
+        });
+    }
+}
+
+/// Lists only the modifiers a costume actually changes
+fn costume_effects(costume: Costume, localization: &Localization) -> String {
+    let modifiers = costume.modifiers();
+    [
+        ("costume.jump", modifiers.jump),
+        ("costume.top_speed", modifiers.top_speed),
+        ("costume.fall", modifiers.fall),
+    ]
+    .into_iter()
+    .filter(|(_, value)| *value != 1.0)
+    .map(|(key, value)| localization.format(key, &[&value.to_string()]))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
@@ -0,0 +1,237 @@
+use bevy::{prelude::*, window::ReceivedCharacter};
+use leafwing_input_manager::prelude::{ActionState, InputMap};
+
+use crate::{
+    format_playtime, ActiveProfile, AppState, AssetCollection, GameSettings, InputDevice,
+    Localization, MenuAction, SaveData, UiTheme, PROFILE_SLOTS,
+};
+
+const MAX_PROFILE_NAME: usize = 16;
+
+pub struct ProfileSelectPlugin;
+
+impl Plugin for ProfileSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ProfileSelect::default())
+            .add_system(spawn_profile_select.in_schedule(OnEnter(AppState::ProfileSelect)))
+            .add_systems(
+                (navigate_profiles, draw_profiles)
+                    .chain()
+                    .in_set(OnUpdate(AppState::ProfileSelect)),
+            )
+            .add_system(despawn_profile_select.in_schedule(OnExit(AppState::ProfileSelect)));
+    }
+}
+
+/// The save slots shown on boot, Up and Down pick one and Confirm plays it or starts a new one
+#[derive(Resource, Default)]
+pub struct ProfileSelect {
+    pub selected: usize,
+    /// What's saved in each slot, `None` where there's nothing yet
+    pub slots: Vec<Option<SaveData>>,
+    /// The name being typed for a new profile in the selected slot
+    pub naming: Option<String>,
+    /// Delete was pressed once on the selected slot, pressing it again empties the slot
+    pub confirming_delete: bool,
+}
+
+impl ProfileSelect {
+    pub fn refresh(&mut self) {
+        self.slots = (0..PROFILE_SLOTS)
+            .map(|slot| ActiveProfile(slot).load_save())
+            .collect();
+    }
+}
+
+#[derive(Component)]
+pub struct ProfileSelectRoot;
+
+/// What a slot is called when its profile was never named, or was saved before profiles had names
+pub fn default_profile_name(slot: usize, localization: &Localization) -> String {
+    localization.format("profiles.default_name", &[&(slot + 1).to_string()])
+}
+
+fn spawn_profile_select(mut commands: Commands, mut select: ResMut<ProfileSelect>) {
+    select.refresh();
+    select.naming = None;
+    select.confirming_delete = false;
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::rgb(0.1, 0.08, 0.15).into(),
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .insert(ProfileSelectRoot);
+}
+
+fn despawn_profile_select(mut commands: Commands, query: Query<Entity, With<ProfileSelectRoot>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// A new profile keeps the settings the game booted with, an existing one brings back its own
+fn navigate_profiles(
+    mut commands: Commands,
+    menu_actions: Res<ActionState<MenuAction>>,
+    keyboard: Res<Input<KeyCode>>,
+    localization: Res<Localization>,
+    mut typed: EventReader<ReceivedCharacter>,
+    mut select: ResMut<ProfileSelect>,
+    mut save_data: ResMut<SaveData>,
+    mut settings: ResMut<GameSettings>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let slot = select.selected;
+    let mut play = |commands: &mut Commands| {
+        commands.insert_resource(ActiveProfile(slot));
+        next_state.set(AppState::LevelSelect);
+    };
+
+    // Typing owns the keyboard, only Confirm and Pause still do anything
+    if let Some(mut name) = select.naming.clone() {
+        for character in typed.iter() {
+            if !character.char.is_control() && name.chars().count() < MAX_PROFILE_NAME {
+                name.push(character.char);
+            }
+        }
+        if keyboard.just_pressed(KeyCode::Back) {
+            name.pop();
+        }
+        if menu_actions.just_pressed(MenuAction::Pause) {
+            select.naming = None;
+        } else if menu_actions.just_pressed(MenuAction::Confirm) {
+            let name = match name.trim() {
+                "" => default_profile_name(slot, &localization),
+                name => name.to_string(),
+            };
+            *save_data = SaveData { name, ..default() };
+            play(&mut commands);
+        } else if select.naming.as_ref() != Some(&name) {
+            select.naming = Some(name);
+        }
+        return;
+    }
+    typed.clear();
+
+    let last = PROFILE_SLOTS - 1;
+    if menu_actions.just_pressed(MenuAction::Up) {
+        select.selected = slot.saturating_sub(1);
+    }
+    if menu_actions.just_pressed(MenuAction::Down) {
+        select.selected = (slot + 1).min(last);
+    }
+    if select.selected != slot {
+        select.confirming_delete = false;
+        return;
+    }
+
+    let saved = select.slots.get(slot).cloned().flatten();
+    if menu_actions.just_pressed(MenuAction::Delete) && saved.is_some() {
+        if select.confirming_delete {
+            if let Err(error) = ActiveProfile(slot).delete() {
+                println!("Couldn't delete profile: {error}");
+            }
+            select.refresh();
+        }
+        select.confirming_delete = !select.confirming_delete;
+    }
+    if menu_actions.just_pressed(MenuAction::Confirm) {
+        match saved {
+            Some(saved) => {
+                *save_data = saved;
+                if let Some(saved_settings) = ActiveProfile(slot).load_settings() {
+                    *settings = saved_settings;
+                }
+                play(&mut commands);
+            }
+            None => select.naming = Some(String::new()),
+        }
+    }
+}
+
+fn draw_profiles(
+    mut commands: Commands,
+    select: Res<ProfileSelect>,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    device: Res<InputDevice>,
+    menu_bindings: Option<Res<InputMap<MenuAction>>>,
+    root_query: Query<Entity, With<ProfileSelectRoot>>,
+) {
+    let changed = select.is_changed()
+        || theme.is_changed()
+        || localization.is_changed()
+        || device.is_changed();
+    if !changed {
+        return;
+    }
+    let Some(menu_bindings) = menu_bindings else {return;};
+    let font = localization.font(&assets.font);
+    let style = |font_size: f32, color: Color| theme.text_style(&font, font_size, color);
+    let label = |action: MenuAction| device.menu_label(&menu_bindings, action);
+
+    let hint = if select.naming.is_some() {
+        localization.format(
+            "profiles.naming",
+            &[&label(MenuAction::Confirm), &label(MenuAction::Pause)],
+        )
+    } else if select.confirming_delete {
+        localization.format("profiles.confirm_delete", &[&label(MenuAction::Delete)])
+    } else {
+        localization.format(
+            "profiles.hint",
+            &[&label(MenuAction::Confirm), &label(MenuAction::Delete)],
+        )
+    };
+
+    for root in &root_query {
+        commands.entity(root).despawn_descendants();
+        commands.entity(root).with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.text("profiles.title"),
+                style(34.0, theme.text),
+            ));
+            for (slot, saved) in select.slots.iter().enumerate() {
+                let selected = slot == select.selected;
+                let color = if selected {
+                    theme.highlight
+                } else {
+                    theme.text
+                };
+                let (name, details) = match (saved, &select.naming) {
+                    (_, Some(name)) if selected => (format!("{name}_"), String::new()),
+                    (Some(saved), _) => (
+                        match saved.name.as_str() {
+                            "" => default_profile_name(slot, &localization),
+                            name => name.to_string(),
+                        },
+                        localization.format(
+                            "profiles.details",
+                            &[
+                                &format_playtime(saved.stats.seconds_played),
+                                &saved.memories.len().to_string(),
+                                &saved.costumes.len().to_string(),
+                            ],
+                        ),
+                    ),
+                    (None, _) => (localization.text("profiles.empty"), String::new()),
+                };
+                parent.spawn(TextBundle::from_sections([
+                    TextSection::new(name, style(28.0, color)),
+                    TextSection::new(format!("  {details}"), style(18.0, theme.muted)),
+                ]));
+            }
+            parent.spawn(TextBundle::from_section(hint, style(18.0, theme.muted)));
+        });
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+
+use crate::{AppState, AssetCollection, Localization, PauseMenu, QuestLog, UiTheme};
+
+pub struct QuestTrackerPlugin;
+
+impl Plugin for QuestTrackerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(QuestTracker::default())
+            .add_system(spawn_quest_tracker.in_schedule(OnEnter(AppState::Playing)))
+            .add_systems((toggle_quest_tracker, draw_quest_tracker).chain());
+    }
+}
+
+/// Tab expands the tracker from a completed count into the full list of quests
+#[derive(Resource, Default)]
+pub struct QuestTracker {
+    pub expanded: bool,
+}
+
+#[derive(Component)]
+pub struct QuestTrackerText;
+
+fn spawn_quest_tracker(mut commands: Commands, assets: Res<AssetCollection>, theme: Res<UiTheme>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(20.0),
+                    top: Val::Px(20.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            background_color: theme.panel.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    theme.text_style(&assets.font, 22.0, theme.text),
+                ))
+                .insert(QuestTrackerText);
+        });
+}
+
+pub fn toggle_quest_tracker(
+    keyboard: Res<Input<KeyCode>>,
+    pause_menu: Res<PauseMenu>,
+    mut tracker: ResMut<QuestTracker>,
+) {
+    // Tab turns pages in the pause menu instead
+    if keyboard.just_pressed(KeyCode::Tab) && !pause_menu.open {
+        tracker.expanded = !tracker.expanded;
+    }
+}
+
+pub fn draw_quest_tracker(
+    tracker: Res<QuestTracker>,
+    quest_log: Res<QuestLog>,
+    localization: Res<Localization>,
+    assets: Res<AssetCollection>,
+    spawned_query: Query<(), Added<QuestTrackerText>>,
+    mut text_query: Query<&mut Text, With<QuestTrackerText>>,
+) {
+    let changed = tracker.is_changed() || quest_log.is_changed() || localization.is_changed();
+    if !(changed || !spawned_query.is_empty()) {
+        return;
+    }
+
+    let completed = quest_log.completed().to_string();
+    let total = quest_log.quests.len().to_string();
+    let mut lines = vec![localization.format("quests.title", &[&completed, &total])];
+    if tracker.expanded {
+        for quest in &quest_log.quests {
+            let status = if quest.is_complete() {
+                localization.text("quests.done")
+            } else {
+                format!("{}/{}", quest.progress, quest.target())
+            };
+            lines.push(format!("{}  {status}", quest.name));
+        }
+    }
+
+    for mut text in &mut text_query {
+        text.sections[0].value = lines.join("\n");
+        text.sections[0].style.font = localization.font(&assets.font);
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::GameSettings;
+
+/// Bracket shapes that tell loaded idea slots apart without relying on color
+const SLOT_MARKERS: [(&str, &str); 3] = [("(", ")"), ("[", "]"), ("<", ">")];
+
+pub struct UiThemePlugin;
+
+impl Plugin for UiThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(UiTheme::default())
+            .add_system(sync_ui_theme.in_base_set(CoreSet::PreUpdate));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum UiPalette {
+    #[default]
+    Classic,
+    /// Colors from the Okabe-Ito set, plus slot markers
+    ColorblindSafe,
+    HighContrast,
+}
+
+/// Colors every HUD and menu should draw with instead of picking their own
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct UiTheme {
+    pub palette: UiPalette,
+    pub text: Color,
+    /// Dimmed text for hints and empty states
+    pub muted: Color,
+    /// The selected line in a menu
+    pub highlight: Color,
+    /// The idea currently ready to load
+    pub accent: Color,
+    pub panel: Color,
+    /// Loaded idea slots, in load order
+    pub slots: [Color; 3],
+    pub slot_markers: bool,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        UiTheme::new(UiPalette::default())
+    }
+}
+
+impl UiTheme {
+    pub fn new(palette: UiPalette) -> Self {
+        match palette {
+            UiPalette::Classic => UiTheme {
+                palette,
+                text: Color::WHITE,
+                muted: Color::GRAY,
+                highlight: Color::GOLD,
+                accent: Color::TEAL,
+                panel: Color::rgba(0.0, 0.0, 0.0, 0.4),
+                slots: [Color::RED, Color::ORANGE, Color::YELLOW],
+                slot_markers: false,
+            },
+            UiPalette::ColorblindSafe => UiTheme {
+                palette,
+                text: Color::WHITE,
+                muted: Color::GRAY,
+                highlight: Color::rgb(0.94, 0.89, 0.26),
+                accent: Color::rgb(0.0, 0.62, 0.45),
+                panel: Color::rgba(0.0, 0.0, 0.0, 0.4),
+                slots: [
+                    Color::rgb(0.0, 0.45, 0.7),
+                    Color::rgb(0.9, 0.6, 0.0),
+                    Color::rgb(0.8, 0.47, 0.65),
+                ],
+                slot_markers: true,
+            },
+            UiPalette::HighContrast => UiTheme {
+                palette,
+                text: Color::WHITE,
+                muted: Color::SILVER,
+                highlight: Color::YELLOW,
+                accent: Color::CYAN,
+                panel: Color::rgba(0.0, 0.0, 0.0, 0.9),
+                slots: [Color::WHITE, Color::YELLOW, Color::CYAN],
+                slot_markers: true,
+            },
+        }
+    }
+
+    /// How an idea in the loaded slot at `index` is written on the HUD
+    pub fn slot_text(&self, index: usize, idea_tag: &str) -> String {
+        match SLOT_MARKERS.get(index) {
+            Some((open, close)) if self.slot_markers => {
+                format!("{open}{}{close} {idea_tag} ", index + 1)
+            }
+            _ => idea_tag.to_string(),
+        }
+    }
+
+    pub fn text_style(&self, font: &Handle<Font>, font_size: f32, color: Color) -> TextStyle {
+        TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        }
+    }
+}
+
+fn sync_ui_theme(settings: Option<Res<GameSettings>>, mut theme: ResMut<UiTheme>) {
+    let Some(settings) = settings else {return;};
+    if settings.is_changed() && theme.palette != settings.palette {
+        *theme = UiTheme::new(settings.palette);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn markers_number_slots_only_when_the_palette_asks() {
+        let classic = UiTheme::new(UiPalette::Classic);
+        assert_eq!(classic.slot_text(1, "Cube"), "Cube");
+        let safe = UiTheme::new(UiPalette::ColorblindSafe);
+        assert_eq!(safe.slot_text(0, "Cube"), "(1) Cube ");
+        assert_eq!(safe.slot_text(2, "Spring"), "<3> Spring ");
+    }
+}
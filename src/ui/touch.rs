@@ -0,0 +1,320 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    axislike::DualAxisData,
+    plugin::InputManagerSystem,
+    prelude::{ActionState, ToggleActions},
+};
+
+use crate::{
+    apply_action_modes, latched_state, AppState, AssetCollection, Localization, Localized, Player,
+    PlayerAction, UiTheme,
+};
+
+const STICK_BASE_SIZE: f32 = 160.0;
+const STICK_KNOB_SIZE: f32 = 64.0;
+/// How far the knob travels from the middle of the base before the stick is pushed all the way
+const STICK_RADIUS: f32 = 60.0;
+const TOUCH_BUTTON_SIZE: f32 = 84.0;
+/// Down the right edge of the screen, top first
+const TOUCH_BUTTONS: [PlayerAction; 4] = [
+    PlayerAction::Interact,
+    PlayerAction::Crouch,
+    PlayerAction::Grab,
+    PlayerAction::Jump,
+];
+
+pub struct TouchControlsPlugin;
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TouchControls::default())
+            .add_system(spawn_touch_controls.in_schedule(OnEnter(AppState::Playing)))
+            .add_system(
+                feed_touch_actions
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputManagerSystem::Update)
+                    .before(apply_action_modes),
+            )
+            .add_systems((detect_touch_input, draw_touch_controls).chain());
+    }
+}
+
+/// The on-screen stick and buttons, they come up with the first touch and go away again once a
+/// key or gamepad button is pressed
+#[derive(Resource, Default)]
+pub struct TouchControls {
+    pub shown: bool,
+    /// The finger steering the stick
+    stick_touch: Option<u64>,
+    /// How far the stick is pushed, up is positive y
+    stick: Vec2,
+    /// Actions the on-screen buttons held down last frame
+    held: Vec<PlayerAction>,
+}
+
+impl TouchControls {
+    pub fn stick(&self) -> Vec2 {
+        self.stick
+    }
+
+    pub fn is_held(&self, action: PlayerAction) -> bool {
+        self.held.contains(&action)
+    }
+}
+
+#[derive(Component)]
+pub struct TouchControlsRoot;
+
+#[derive(Component)]
+pub struct TouchStick;
+
+#[derive(Component)]
+pub struct TouchStickKnob;
+
+/// Holds `PlayerAction` down while a finger is on it
+#[derive(Component)]
+pub struct TouchButton(pub PlayerAction);
+
+/// Where a finger `offset` pixels from the middle of the stick pushes it, screen y points down
+pub fn stick_from_offset(offset: Vec2) -> Vec2 {
+    let pushed = offset.clamp_length_max(STICK_RADIUS) / STICK_RADIUS;
+    Vec2::new(pushed.x, -pushed.y)
+}
+
+/// Whether `position` lands on a UI node, both in the UI's top left based pixels
+fn node_contains(node: &Node, transform: &GlobalTransform, position: Vec2) -> bool {
+    let offset = position - transform.translation().truncate();
+    offset.abs().cmple(node.size() / 2.0).all()
+}
+
+fn spawn_touch_controls(
+    mut commands: Commands,
+    assets: Res<AssetCollection>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+) {
+    let font = localization.font(&assets.font);
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(2),
+            ..default()
+        })
+        .insert(TouchControlsRoot)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(40.0),
+                            bottom: Val::Px(40.0),
+                            ..default()
+                        },
+                        size: Size::all(Val::Px(STICK_BASE_SIZE)),
+                        ..default()
+                    },
+                    background_color: theme.panel.into(),
+                    ..default()
+                })
+                .insert(TouchStick)
+                .with_children(|stick| {
+                    stick
+                        .spawn(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                size: Size::all(Val::Px(STICK_KNOB_SIZE)),
+                                ..default()
+                            },
+                            background_color: theme.accent.into(),
+                            ..default()
+                        })
+                        .insert(TouchStickKnob);
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            right: Val::Px(40.0),
+                            bottom: Val::Px(40.0),
+                            ..default()
+                        },
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|buttons| {
+                    for action in TOUCH_BUTTONS {
+                        buttons
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    size: Size::all(Val::Px(TOUCH_BUTTON_SIZE)),
+                                    margin: UiRect::all(Val::Px(8.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: theme.panel.into(),
+                                ..default()
+                            })
+                            .insert(TouchButton(action))
+                            .with_children(|button| {
+                                let key = format!("touch.{action:?}");
+                                button
+                                    .spawn(TextBundle::from_section(
+                                        localization.text(&key),
+                                        theme.text_style(&font, 20.0, theme.text),
+                                    ))
+                                    .insert(Localized::new(&key, 0));
+                            });
+                    }
+                });
+        });
+}
+
+fn detect_touch_input(
+    touches: Res<Touches>,
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mut controls: ResMut<TouchControls>,
+    mut root_query: Query<&mut Visibility, With<TouchControlsRoot>>,
+) {
+    let shown = if touches.any_just_pressed() {
+        true
+    } else if keyboard.get_just_pressed().next().is_some()
+        || buttons.get_just_pressed().next().is_some()
+    {
+        false
+    } else {
+        controls.shown
+    };
+    if controls.shown != shown {
+        controls.shown = shown;
+    }
+    let visibility = if shown {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut root_visibility in &mut root_query {
+        if *root_visibility != visibility {
+            *root_visibility = visibility;
+        }
+    }
+}
+
+/// Overwrites the player's actions with what the fingers are doing, the same way the action
+/// modes overwrite held buttons
+fn feed_touch_actions(
+    touches: Res<Touches>,
+    player_actions: Res<ToggleActions<PlayerAction>>,
+    mut controls: ResMut<TouchControls>,
+    stick_query: Query<(&Node, &GlobalTransform), With<TouchStick>>,
+    button_query: Query<(&Node, &GlobalTransform, &TouchButton)>,
+    mut player_query: Query<&mut ActionState<PlayerAction>, With<Player>>,
+) {
+    let Ok(mut action) = player_query.get_single_mut() else {return;};
+    let active = controls.shown && player_actions.enabled;
+
+    // A finger that lands on the stick keeps steering it wherever it slides off to
+    let stick_touch = if active {
+        controls
+            .stick_touch
+            .and_then(|id| touches.get_pressed(id))
+            .or_else(|| {
+                let (node, transform) = stick_query.get_single().ok()?;
+                touches
+                    .iter_just_pressed()
+                    .find(|touch| node_contains(node, transform, touch.position()))
+            })
+    } else {
+        None
+    };
+    let was_steering = controls.stick != Vec2::ZERO;
+    controls.stick_touch = stick_touch.map(|touch| touch.id());
+    controls.stick = match (stick_touch, stick_query.get_single()) {
+        (Some(touch), Ok((_, transform))) => {
+            stick_from_offset(touch.position() - transform.translation().truncate())
+        }
+        _ => Vec2::ZERO,
+    };
+    let steering = controls.stick != Vec2::ZERO;
+    if steering || was_steering {
+        let stick = controls.stick;
+        let data = action.action_data_mut(PlayerAction::Move);
+        data.state = latched_state(was_steering, steering);
+        data.axis_pair = Some(DualAxisData::new(stick.x, stick.y));
+        data.value = stick.length();
+    }
+
+    let mut held = Vec::new();
+    for (node, transform, button) in &button_query {
+        let touched = active
+            && touches.iter().any(|touch| {
+                Some(touch.id()) != controls.stick_touch
+                    && node_contains(node, transform, touch.position())
+            });
+        let was_held = controls.is_held(button.0);
+        if touched || was_held {
+            action.action_data_mut(button.0).state = latched_state(was_held, touched);
+        }
+        if touched {
+            held.push(button.0);
+        }
+    }
+    controls.held = held;
+}
+
+fn draw_touch_controls(
+    theme: Res<UiTheme>,
+    controls: Res<TouchControls>,
+    mut knob_query: Query<&mut Style, With<TouchStickKnob>>,
+    mut button_query: Query<(&TouchButton, &mut BackgroundColor)>,
+) {
+    if !controls.is_changed() && !theme.is_changed() {
+        return;
+    }
+    let resting = (STICK_BASE_SIZE - STICK_KNOB_SIZE) / 2.0;
+    let pushed = controls.stick * STICK_RADIUS;
+    for mut style in &mut knob_query {
+        style.position = UiRect {
+            left: Val::Px(resting + pushed.x),
+            top: Val::Px(resting - pushed.y),
+            ..default()
+        };
+    }
+    for (button, mut color) in &mut button_query {
+        let fill = if controls.is_held(button.0) {
+            theme.highlight
+        } else {
+            theme.panel
+        };
+        if color.0 != fill {
+            color.0 = fill;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sticks_clamp_and_point_up_the_screen() {
+        assert_eq!(stick_from_offset(Vec2::ZERO), Vec2::ZERO);
+        assert_eq!(
+            stick_from_offset(Vec2::new(0.0, -STICK_RADIUS / 2.0)),
+            Vec2::new(0.0, 0.5)
+        );
+        let far = stick_from_offset(Vec2::new(STICK_RADIUS * 3.0, 0.0));
+        assert!((far - Vec2::X).length() < 0.001);
+    }
+}
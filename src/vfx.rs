@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use crate::{CeilingBonk, EntityPools, Footstep, PoolKind, Pooled, SurfaceType};
+
+pub struct VfxPlugin;
+
+impl Plugin for VfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_particle_assets)
+            .add_system(update_particles)
+            .add_system(spawn_footstep_particles)
+            .add_system(spawn_bonk_particles);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ParticleAssets {
+    pub mesh: Handle<Mesh>,
+    pub dust: Handle<StandardMaterial>,
+    pub rain_mesh: Handle<Mesh>,
+    pub rain: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec3,
+    pub lifetime: Timer,
+    /// Where it's parked once its lifetime runs out
+    pub pool: PoolKind,
+}
+
+impl Particle {
+    pub fn new(pool: PoolKind, velocity: Vec3, seconds: f32) -> Self {
+        Particle {
+            velocity,
+            lifetime: Timer::from_seconds(seconds, TimerMode::Once),
+            pool,
+        }
+    }
+}
+
+fn load_particle_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ParticleAssets {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 0.2 })),
+        dust: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.8, 0.7, 0.55, 0.8),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        rain_mesh: meshes.add(Mesh::from(shape::Box::new(0.03, 0.5, 0.03))),
+        rain: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.6, 0.7, 1.0, 0.6),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+pub fn spawn_dust(
+    commands: &mut Commands,
+    pools: &mut EntityPools,
+    particle_assets: &ParticleAssets,
+    position: Vec3,
+    velocity: Vec3,
+) {
+    let particle = Particle::new(PoolKind::Dust, velocity, 0.4);
+    let transform = Transform::from_translation(position);
+    if let Some(entity) = pools.take(commands, PoolKind::Dust) {
+        commands.entity(entity).insert(transform).insert(particle);
+        return;
+    }
+    commands
+        .spawn(PbrBundle {
+            mesh: particle_assets.mesh.clone(),
+            material: particle_assets.dust.clone(),
+            transform,
+            ..default()
+        })
+        .insert(particle);
+}
+
+pub fn spawn_raindrop(
+    commands: &mut Commands,
+    pools: &mut EntityPools,
+    particle_assets: &ParticleAssets,
+    position: Vec3,
+    wind: Vec3,
+) {
+    let particle = Particle::new(PoolKind::Raindrop, wind + Vec3::Y * -30.0, 0.6);
+    let transform = Transform::from_translation(position);
+    if let Some(entity) = pools.take(commands, PoolKind::Raindrop) {
+        commands.entity(entity).insert(transform).insert(particle);
+        return;
+    }
+    commands
+        .spawn(PbrBundle {
+            mesh: particle_assets.rain_mesh.clone(),
+            material: particle_assets.rain.clone(),
+            transform,
+            ..default()
+        })
+        .insert(particle);
+}
+
+fn spawn_footstep_particles(
+    mut commands: Commands,
+    mut pools: ResMut<EntityPools>,
+    particle_assets: Res<ParticleAssets>,
+    mut footsteps: EventReader<Footstep>,
+) {
+    for footstep in footsteps.iter() {
+        if let SurfaceType::Sand | SurfaceType::Grass = footstep.surface {
+            spawn_dust(
+                &mut commands,
+                &mut pools,
+                &particle_assets,
+                footstep.position,
+                Vec3::Y * 1.5,
+            );
+        }
+    }
+}
+
+fn spawn_bonk_particles(
+    mut commands: Commands,
+    mut pools: ResMut<EntityPools>,
+    particle_assets: Res<ParticleAssets>,
+    mut bonks: EventReader<CeilingBonk>,
+) {
+    for bonk in bonks.iter() {
+        for kick in [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z] {
+            spawn_dust(
+                &mut commands,
+                &mut pools,
+                &particle_assets,
+                bonk.position,
+                kick * 2.0 + Vec3::NEG_Y,
+            );
+        }
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pools: ResMut<EntityPools>,
+    mut query: Query<(Entity, &mut Transform, &mut Particle), Without<Pooled>>,
+) {
+    for (entity, mut transform, mut particle) in &mut query {
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.finished() {
+            pools.release(&mut commands, particle.pool, entity);
+        } else {
+            transform.translation += particle.velocity * time.delta_seconds();
+            transform.scale = Vec3::splat(particle.lifetime.percent_left());
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+#[cfg(target_arch = "wasm32")]
+use crate::AudioUnlocked;
+
+/// What the game needs on top of the usual plugins to run in a browser, empty on every other
+/// platform
+pub struct WebPlugins;
+
+impl PluginGroup for WebPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>();
+        #[cfg(target_arch = "wasm32")]
+        let group = group.add(AudioUnlockPlugin);
+        group
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct AudioUnlockPlugin;
+
+#[cfg(target_arch = "wasm32")]
+impl Plugin for AudioUnlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(unlock_audio);
+    }
+}
+
+/// The audio output made at startup belongs to a page nobody has touched yet, which the browser
+/// keeps muted for good. One made after the first press is allowed to play
+#[cfg(target_arch = "wasm32")]
+fn unlock_audio(
+    mut commands: Commands,
+    mut unlocked: ResMut<AudioUnlocked>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    buttons: Res<Input<GamepadButton>>,
+    touches: Res<Touches>,
+) {
+    if unlocked.0 {
+        return;
+    }
+    let pressed = keyboard.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || buttons.get_just_pressed().next().is_some()
+        || touches.any_just_pressed();
+    if !pressed {
+        return;
+    }
+    commands.insert_resource(AudioOutput::<AudioSource>::default());
+    unlocked.0 = true;
+}